@@ -0,0 +1,601 @@
+//! 命令行模式 - 不创建窗口和 GPU 设备，可以在脚本和 CI 中使用
+//!
+//! 不带参数启动时进入图形界面，带子命令时执行对应的命令后退出：
+//! - `info <FILE>`: 版本、节点、Message / Signal 数量等概要信息
+//! - `validate [--deny-warnings] <FILE>...`: 检查 DBC，有错误时返回非零退出码
+//! - `convert [--cluster=NAME] <INPUT> <OUTPUT>`: 格式转换，格式由扩展名决定（.dbc / .json / .kcd），
+//!   输入也可以是 .arxml，有多个 CAN 集群时用 --cluster 选择；转换中丢弃的内容作为警告输出到 stderr
+//! - `diff <OLD> <NEW>`: 按 ID 匹配 Message、按名称匹配 Signal 比较两个文件，有差异时返回 1
+//! - `fmt [--check] <FILE>...`: 按规范格式重新写回文件，会丢失内容或输出重新解析后与原内容不同时报错，不覆盖原文件
+//! - `merge <BASE> <OURS> <THEIRS> [OUTPUT]`: 三方合并，可以作为 git 合并驱动使用，有冲突时返回 1
+//! - `codegen c <FILE> <OUT_DIR>`: 生成 C99 的头文件和源文件，文件名和标识符前缀取自 DBC 的文件名
+//! - `codegen rust <FILE> <OUTPUT>`: 生成 Rust 模块
+
+use std::fs;
+use std::path::Path;
+
+use roxy_dbc::codegen::{generate_c, generate_rust_file};
+use roxy_dbc::editable_dbc::{
+    EditableDbc, FileFormat, MergeResolutions, Severity, is_arxml, load_arxml, load_file,
+    load_file_as, load_file_with_warnings, save_file_as, save_file_with_warnings,
+};
+
+/// 命令执行成功
+pub const EXIT_SUCCESS: i32 = 0;
+/// 检查未通过（validate 有错误、diff 有差异、fmt --check 需要格式化、merge 有冲突）
+pub const EXIT_FAILURE: i32 = 1;
+/// 参数错误或文件读写失败
+pub const EXIT_ERROR: i32 = 2;
+
+const USAGE: &str = "\
+Usage: roxy-dbc [COMMAND]
+
+Without a command the graphical editor is started.
+
+Commands:
+  info <FILE>                          Print a summary of the file
+  validate [--deny-warnings] <FILE>... Check files, exit with 1 if there are errors
+  convert [--cluster=NAME] <INPUT> <OUTPUT>
+                                       Convert between .dbc, .json and .kcd (chosen by extension),
+                                       or import a CAN cluster from an .arxml file
+  diff <OLD> <NEW>                     Compare messages and signals, exit with 1 if they differ
+  fmt [--check] <FILE>...              Rewrite files in canonical form
+  merge <BASE> <OURS> <THEIRS> [OUTPUT]
+                                       Three-way merge into OUTPUT (default OURS),
+                                       exit with 1 if there are conflicts
+  codegen c <FILE> <OUT_DIR>           Generate <NAME>.h and <NAME>.c, NAME is the file stem
+  codegen rust <FILE> <OUTPUT>         Generate a Rust module
+  help                                 Print this message";
+
+/// 执行命令行参数（不含程序名）对应的命令，返回进程退出码
+pub fn run(args: &[String]) -> i32 {
+    let Some((command, rest)) = args.split_first() else {
+        eprintln!("{USAGE}");
+        return EXIT_ERROR;
+    };
+    let result = match command.as_str() {
+        "info" => info(rest),
+        "validate" => validate(rest),
+        "convert" => convert(rest),
+        "diff" => diff(rest),
+        "fmt" => fmt(rest),
+        "merge" => merge(rest),
+        "codegen" => codegen(rest),
+        "help" | "-h" | "--help" => {
+            println!("{USAGE}");
+            Ok(EXIT_SUCCESS)
+        }
+        _ => Err(format!("Unknown command: {command}\n\n{USAGE}")),
+    };
+    result.unwrap_or_else(|e| {
+        eprintln!("error: {e}");
+        EXIT_ERROR
+    })
+}
+
+/// 分离 `--xxx` 形式的选项和位置参数，不认识的选项报错
+fn split_options<'a>(
+    args: &'a [String],
+    known: &[&str],
+) -> Result<(Vec<&'a str>, Vec<&'a str>), String> {
+    let mut options = Vec::new();
+    let mut positional = Vec::new();
+    for arg in args {
+        if arg.starts_with("--") {
+            if !known.contains(&arg.as_str()) {
+                return Err(format!("Unknown option: {arg}"));
+            }
+            options.push(arg.as_str());
+        } else {
+            positional.push(arg.as_str());
+        }
+    }
+    Ok((options, positional))
+}
+
+fn expect_files(positional: &[&str], count: usize, usage: &str) -> Result<(), String> {
+    if positional.len() != count {
+        return Err(format!("Usage: roxy-dbc {usage}"));
+    }
+    Ok(())
+}
+
+fn info(args: &[String]) -> Result<i32, String> {
+    let (_, files) = split_options(args, &[])?;
+    expect_files(&files, 1, "info <FILE>")?;
+    let dbc = load_file(Path::new(files[0]))?;
+    print!("{}", format_info(&dbc));
+    Ok(EXIT_SUCCESS)
+}
+
+/// info 命令的输出
+fn format_info(dbc: &EditableDbc) -> String {
+    let messages = dbc.messages();
+    let signal_count: usize = messages.iter().map(|m| m.signals_count()).sum();
+    let fd_count = messages.iter().filter(|m| m.is_fd()).count();
+
+    let mut messages_line = messages.len().to_string();
+    if fd_count > 0 {
+        messages_line += &format!(" ({fd_count} CAN FD)");
+    }
+
+    let mut out = String::new();
+    let mut line = |label: &str, value: String| out += &format!("{label:<23}{value}\n");
+    line("Version:", format!("\"{}\"", dbc.version()));
+    line("Nodes:", dbc.nodes().len().to_string());
+    for node in dbc.nodes() {
+        line("", node.clone());
+    }
+    line("Messages:", messages_line);
+    line("Signals:", signal_count.to_string());
+    line("Value tables:", dbc.value_tables().len().to_string());
+    line(
+        "Attribute definitions:",
+        dbc.attribute_definitions().len().to_string(),
+    );
+    line(
+        "Environment variables:",
+        dbc.environment_variables().len().to_string(),
+    );
+    out
+}
+
+fn validate(args: &[String]) -> Result<i32, String> {
+    let (options, files) = split_options(args, &["--deny-warnings"])?;
+    if files.is_empty() {
+        return Err("Usage: roxy-dbc validate [--deny-warnings] <FILE>...".to_string());
+    }
+    let deny_warnings = options.contains(&"--deny-warnings");
+
+    let mut failed = false;
+    for file in files {
+        let dbc = load_file(Path::new(file))?;
+        let diagnostics = dbc.validate();
+        for diagnostic in &diagnostics {
+            println!("{file}: {diagnostic}");
+        }
+        let errors = diagnostics
+            .iter()
+            .filter(|d| d.severity() == Severity::Error)
+            .count();
+        let warnings = diagnostics.len() - errors;
+        println!("{file}: {errors} error(s), {warnings} warning(s)");
+        failed |= errors > 0 || (deny_warnings && warnings > 0);
+    }
+    Ok(if failed { EXIT_FAILURE } else { EXIT_SUCCESS })
+}
+
+fn convert(args: &[String]) -> Result<i32, String> {
+    // --cluster 带值，单独处理
+    let mut cluster = None;
+    let mut rest = Vec::new();
+    for arg in args {
+        match arg.strip_prefix("--cluster=") {
+            Some(name) => cluster = Some(name),
+            None => rest.push(arg.clone()),
+        }
+    }
+    let (_, files) = split_options(&rest, &[])?;
+    expect_files(&files, 2, "convert [--cluster=NAME] <INPUT> <OUTPUT>")?;
+    let (input, output) = (Path::new(files[0]), Path::new(files[1]));
+    // 先检查输出格式，避免读取完大文件后才报错
+    FileFormat::from_path(output)?;
+    let (dbc, mut warnings) = if is_arxml(input) {
+        load_arxml(input, cluster)?
+    } else if cluster.is_some() {
+        return Err("--cluster can only be used with an .arxml input".to_string());
+    } else {
+        load_file_with_warnings(input)?
+    };
+    extend_warnings(&mut warnings, save_file_with_warnings(&dbc, output)?);
+    for warning in warnings {
+        eprintln!("warning: {warning}");
+    }
+    Ok(EXIT_SUCCESS)
+}
+
+// 保存时的警告包含读取时已经提示过的内容，不重复输出
+fn extend_warnings(warnings: &mut Vec<String>, more: Vec<String>) {
+    for warning in more {
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
+}
+
+fn diff(args: &[String]) -> Result<i32, String> {
+    let (_, files) = split_options(args, &[])?;
+    expect_files(&files, 2, "diff <OLD> <NEW>")?;
+    let old = load_file(Path::new(files[0]))?;
+    let new = load_file(Path::new(files[1]))?;
+    let diff = old.diff(&new);
+    print!("{diff}");
+    Ok(if diff.is_empty() {
+        EXIT_SUCCESS
+    } else {
+        EXIT_FAILURE
+    })
+}
+
+fn fmt(args: &[String]) -> Result<i32, String> {
+    let (options, files) = split_options(args, &["--check"])?;
+    if files.is_empty() {
+        return Err("Usage: roxy-dbc fmt [--check] <FILE>...".to_string());
+    }
+    let check = options.contains(&"--check");
+
+    let mut unformatted = false;
+    for file in files {
+        let path = Path::new(file);
+        let format = FileFormat::from_path(path)?;
+        let original = fs::read(path).map_err(|e| format!("Failed to open file: {file}: {e}"))?;
+        // 读取时无法解码的字节会被替换，写回会破坏原文件
+        let original = String::from_utf8(original)
+            .map_err(|_| format!("{file}: not valid UTF-8, file not rewritten"))?;
+        let (dbc, mut warnings) = format
+            .parse_with_warnings(&original)
+            .map_err(|e| format!("Failed to parse {format}: {file}: {e}"))?;
+        let (formatted, write_warnings) = format.write_with_warnings(&dbc);
+        extend_warnings(&mut warnings, write_warnings);
+        // 读取或写出时丢弃了内容，不覆盖原文件
+        if !warnings.is_empty() {
+            for warning in warnings {
+                eprintln!("warning: {file}: {warning}");
+            }
+            return Err(format!(
+                "{file}: contents would be lost by formatting, file not rewritten"
+            ));
+        }
+        if original == formatted {
+            continue;
+        }
+        // 重新解析的结果与原内容不同时说明写出的文件会丢失或改变内容，不覆盖原文件
+        format.check_round_trip(&original, &formatted).map_err(|e| {
+            format!("{file}: formatted output does not parse back to the same contents ({e}), file not rewritten")
+        })?;
+        if check {
+            println!("{file}: not formatted");
+            unformatted = true;
+        } else {
+            fs::write(path, formatted).map_err(|e| format!("Failed to save file: {file}: {e}"))?;
+            println!("{file}: formatted");
+        }
+    }
+    Ok(if unformatted {
+        EXIT_FAILURE
+    } else {
+        EXIT_SUCCESS
+    })
+}
+
+// git 合并驱动传入的是没有扩展名的临时文件，无法识别扩展名时按 DBC 处理
+fn merge_format(path: &Path) -> FileFormat {
+    FileFormat::from_path(path).unwrap_or(FileFormat::Dbc)
+}
+
+fn merge(args: &[String]) -> Result<i32, String> {
+    let (_, files) = split_options(args, &[])?;
+    if files.len() != 3 && files.len() != 4 {
+        return Err("Usage: roxy-dbc merge <BASE> <OURS> <THEIRS> [OUTPUT]".to_string());
+    }
+    let paths: Vec<&Path> = files.iter().map(Path::new).collect();
+    let base = load_file_as(paths[0], merge_format(paths[0]))?;
+    let ours = load_file_as(paths[1], merge_format(paths[1]))?;
+    let theirs = load_file_as(paths[2], merge_format(paths[2]))?;
+
+    let result = EditableDbc::merge(&base, &ours, &theirs, &MergeResolutions::new());
+    // 有冲突时也写出结果（冲突处采用 OURS），git 会把文件标记为冲突，之后可以在图形界面中解决
+    let output = paths.get(3).copied().unwrap_or(paths[1]);
+    save_file_as(&result.merged, output, merge_format(output))?;
+
+    for conflict in &result.conflicts {
+        eprintln!("CONFLICT: {}", conflict.location);
+        let side = |label: &str, value: &Option<String>| {
+            eprintln!("  {label:<7}{}", value.as_deref().unwrap_or("(deleted)"));
+        };
+        side("base:", &conflict.base);
+        side("ours:", &conflict.ours);
+        side("theirs:", &conflict.theirs);
+    }
+    if result.conflicts.is_empty() {
+        Ok(EXIT_SUCCESS)
+    } else {
+        eprintln!(
+            "{} conflict(s), kept OURS in {}",
+            result.conflicts.len(),
+            output.display()
+        );
+        Ok(EXIT_FAILURE)
+    }
+}
+
+fn codegen(args: &[String]) -> Result<i32, String> {
+    const USAGE: &str = "codegen c <FILE> <OUT_DIR> | codegen rust <FILE> <OUTPUT>";
+    let (_, positional) = split_options(args, &[])?;
+    expect_files(&positional, 3, USAGE)?;
+    let (input, output) = (Path::new(positional[1]), Path::new(positional[2]));
+    match positional[0] {
+        "c" => codegen_c(input, output)?,
+        "rust" => {
+            generate_rust_file(input, output)?;
+            println!("{}", output.display());
+        }
+        language => {
+            return Err(format!(
+                "Unknown language: {language}\nUsage: roxy-dbc {USAGE}"
+            ));
+        }
+    }
+    Ok(EXIT_SUCCESS)
+}
+
+fn codegen_c(input: &Path, out_dir: &Path) -> Result<(), String> {
+    let dbc = load_file(input)?;
+    let name = input
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| format!("Invalid file name: {}", input.display()))?;
+    let code = generate_c(&dbc, name)?;
+
+    fs::create_dir_all(out_dir)
+        .map_err(|e| format!("Failed to create directory: {}: {e}", out_dir.display()))?;
+    for (path, text) in [
+        (out_dir.join(format!("{name}.h")), &code.header),
+        (out_dir.join(format!("{name}.c")), &code.source),
+    ] {
+        fs::write(&path, text)
+            .map_err(|e| format!("Failed to save file: {}: {e}", path.display()))?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    const SAMPLE: &str = "dbc-sample/motbus.dbc";
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_usage_errors() {
+        assert_eq!(run(&[]), EXIT_ERROR);
+        assert_eq!(run(&args(&["unknown"])), EXIT_ERROR);
+        assert_eq!(run(&args(&["info"])), EXIT_ERROR);
+        assert_eq!(run(&args(&["validate", "--bogus", SAMPLE])), EXIT_ERROR);
+        assert_eq!(run(&args(&["info", "missing.dbc"])), EXIT_ERROR);
+        assert_eq!(run(&args(&["help"])), EXIT_SUCCESS);
+    }
+
+    #[test]
+    fn test_info() {
+        let dbc = load_file(Path::new(SAMPLE)).unwrap();
+        let info = format_info(&dbc);
+        assert!(info.contains(&format!("Messages:              {}\n", dbc.message_count())));
+        assert_eq!(run(&args(&["info", SAMPLE])), EXIT_SUCCESS);
+    }
+
+    #[test]
+    fn test_validate_exit_code() {
+        let dir = temp_dir("cli-validate");
+        let broken = dir.join("broken.dbc");
+        fs::write(
+            &broken,
+            "VERSION \"\"\n\nBU_: ECU\n\nBO_ 1 A: 8 ECU\n\nBO_ 1 B: 8 ECU\n",
+        )
+        .unwrap();
+        assert_eq!(
+            run(&args(&["validate", broken.to_str().unwrap()])),
+            EXIT_FAILURE
+        );
+        assert_eq!(run(&args(&["validate", SAMPLE])), EXIT_SUCCESS);
+    }
+
+    #[test]
+    fn test_convert_fmt_and_diff() {
+        let dir = temp_dir("cli-convert");
+        let copy = dir.join("copy.dbc");
+        let copy = copy.to_str().unwrap();
+        assert_eq!(run(&args(&["convert", SAMPLE, copy])), EXIT_SUCCESS);
+        // 转换输出已经是规范格式
+        assert_eq!(run(&args(&["fmt", "--check", copy])), EXIT_SUCCESS);
+        assert_eq!(run(&args(&["diff", SAMPLE, copy])), EXIT_SUCCESS);
+
+        // 破坏格式后 --check 失败，fmt 之后恢复
+        let text = fs::read_to_string(copy).unwrap();
+        fs::write(copy, text.replace("\n", "\r\n")).unwrap();
+        assert_eq!(run(&args(&["fmt", "--check", copy])), EXIT_FAILURE);
+        assert_eq!(run(&args(&["fmt", copy])), EXIT_SUCCESS);
+        assert_eq!(fs::read_to_string(copy).unwrap(), text);
+
+        // 超出 f64 范围的数值写出为 inf，无法重新解析，不覆盖原文件
+        let overflow = dir.join("overflow.dbc");
+        let overflow_text = "VERSION \"\"\n\nBU_: ECU\n\nBO_ 1 A: 8 ECU\n SG_ S : 0|8@1+ (1,0) [0|1e400] \"\" ECU\n";
+        fs::write(&overflow, overflow_text).unwrap();
+        let overflow = overflow.to_str().unwrap();
+        assert_eq!(run(&args(&["fmt", overflow])), EXIT_ERROR);
+        assert_eq!(run(&args(&["fmt", "--check", overflow])), EXIT_ERROR);
+        assert_eq!(fs::read_to_string(overflow).unwrap(), overflow_text);
+
+        // 经过 JSON 转换回来与原文件相同
+        let json = dir.join("copy.json");
+        let json = json.to_str().unwrap();
+        assert_eq!(run(&args(&["convert", copy, json])), EXIT_SUCCESS);
+        assert_eq!(run(&args(&["convert", json, copy])), EXIT_SUCCESS);
+        assert_eq!(fs::read_to_string(copy).unwrap(), text);
+
+        // KCD 不能表示 CycleTime 等属性，只输出警告
+        let kcd = dir.join("copy.kcd");
+        let kcd = kcd.to_str().unwrap();
+        assert_eq!(run(&args(&["convert", copy, kcd])), EXIT_SUCCESS);
+        let restored = load_file(Path::new(kcd)).unwrap();
+        assert_eq!(
+            restored.messages().len(),
+            load_file(Path::new(SAMPLE)).unwrap().messages().len()
+        );
+
+        assert_eq!(
+            run(&args(&[
+                "convert",
+                SAMPLE,
+                dir.join("out.txt").to_str().unwrap()
+            ])),
+            EXIT_ERROR
+        );
+    }
+
+    #[test]
+    fn test_fmt_refuses_lossy_files() {
+        let dir = temp_dir("cli-fmt");
+        let dbc =
+            "VERSION \"\"\n\nBU_: ECU\n\nBO_ 1 A: 8 ECU\n SG_ S : 0|8@1+ (1,0) [0|0] \"\" ECU\n";
+        let cases: [(&str, Vec<u8>); 5] = [
+            // 保存时会丢弃的段落
+            (
+                "groups.dbc",
+                format!("{dbc}\nSIG_GROUP_ 1 G 1 : S;\n").into_bytes(),
+            ),
+            (
+                "envvar.dbc",
+                format!(
+                    "{dbc}\nEV_ E: 0 [0|1] \"\" 0 1 DUMMY_NODE_VECTOR0 ECU;\n\nENVVAR_DATA_ E: 4;\n"
+                )
+                .into_bytes(),
+            ),
+            // 读取时会丢弃指向不存在的 Message 的注释，只有比较完整的解析结果才能发现
+            (
+                "dangling.dbc",
+                format!("{dbc}\nCM_ BO_ 2 \"Missing\";\n").into_bytes(),
+            ),
+            // Windows-1252 编码的 "°C"
+            (
+                "latin1.dbc",
+                dbc.replace("\"\" ECU\n", "\"#C\" ECU\n")
+                    .into_bytes()
+                    .into_iter()
+                    .map(|b| if b == b'#' { 0xB0 } else { b })
+                    .collect(),
+            ),
+            // KCD 读取时丢弃了 LabelGroup
+            (
+                "labels.kcd",
+                b"<NetworkDefinition><Bus name=\"B\"><Message id=\"0x1\" name=\"A\" length=\"8\">\
+                  <Signal name=\"S\" offset=\"0\" length=\"8\"><LabelSet>\
+                  <LabelGroup name=\"G\" from=\"0\" to=\"3\"/></LabelSet></Signal>\
+                  </Message></Bus></NetworkDefinition>"
+                    .to_vec(),
+            ),
+        ];
+        for (name, contents) in cases {
+            let path = dir.join(name);
+            fs::write(&path, &contents).unwrap();
+            let file = path.to_str().unwrap();
+            assert_eq!(run(&args(&["fmt", "--check", file])), EXIT_ERROR, "{name}");
+            assert_eq!(run(&args(&["fmt", file])), EXIT_ERROR, "{name}");
+            assert_eq!(fs::read(&path).unwrap(), contents, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_convert_arxml() {
+        let dir = temp_dir("cli-arxml");
+        let cluster = |name: &str| {
+            format!(
+                "<CAN-CLUSTER><SHORT-NAME>{name}</SHORT-NAME><CAN-FRAME-TRIGGERING>\
+                 <SHORT-NAME>T</SHORT-NAME><FRAME-REF>/Frames/F{name}</FRAME-REF>\
+                 <IDENTIFIER>16</IDENTIFIER></CAN-FRAME-TRIGGERING></CAN-CLUSTER>\
+                 <CAN-FRAME><SHORT-NAME>F{name}</SHORT-NAME><FRAME-LENGTH>8</FRAME-LENGTH></CAN-FRAME>"
+            )
+        };
+        let arxml = dir.join("system.arxml");
+        fs::write(
+            &arxml,
+            format!(
+                "<AUTOSAR><AR-PACKAGES><AR-PACKAGE><SHORT-NAME>Frames</SHORT-NAME>{}{}\
+                 </AR-PACKAGE></AR-PACKAGES></AUTOSAR>",
+                cluster("A"),
+                cluster("B")
+            ),
+        )
+        .unwrap();
+        let arxml = arxml.to_str().unwrap();
+        let output = dir.join("system.dbc");
+        let output = output.to_str().unwrap();
+
+        // 有两个集群时必须选择
+        assert_eq!(run(&args(&["convert", arxml, output])), EXIT_ERROR);
+        assert_eq!(
+            run(&args(&["convert", "--cluster=B", arxml, output])),
+            EXIT_SUCCESS
+        );
+        let dbc = load_file(Path::new(output)).unwrap();
+        assert_eq!(dbc.get_message(16).unwrap().message_name(), "FB");
+        assert_eq!(
+            run(&args(&["convert", "--cluster=B", SAMPLE, output])),
+            EXIT_ERROR
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let dir = temp_dir("cli-merge");
+        let write = |name: &str, text: &str| {
+            // 与 git 的临时文件一样没有扩展名
+            let path = dir.join(name);
+            fs::write(&path, text).unwrap();
+            path.to_str().unwrap().to_string()
+        };
+        let base_text = "VERSION \"\"\n\nBU_: ECU\n\nBO_ 1 A: 8 ECU\n";
+        let base = write("base", base_text);
+        let ours = write("ours", &base_text.replace("BO_ 1 A", "BO_ 1 Renamed"));
+        let theirs = write("theirs", &base_text.replace(": 8 ECU", ": 4 ECU"));
+        assert_eq!(run(&args(&["merge", &base, &ours, &theirs])), EXIT_SUCCESS);
+        let merged = load_file_as(Path::new(&ours), FileFormat::Dbc).unwrap();
+        let message = merged.get_message(1).unwrap();
+        assert_eq!(
+            (message.message_name(), message.message_size()),
+            ("Renamed", 4)
+        );
+
+        let theirs = write("theirs", &base_text.replace("BO_ 1 A", "BO_ 1 Other"));
+        let output = dir.join("out.dbc");
+        let output = output.to_str().unwrap();
+        assert_eq!(
+            run(&args(&["merge", &base, &ours, &theirs, output])),
+            EXIT_FAILURE
+        );
+        let merged = load_file(Path::new(output)).unwrap();
+        assert_eq!(merged.get_message(1).unwrap().message_name(), "Renamed");
+    }
+
+    #[test]
+    fn test_codegen() {
+        let dir = temp_dir("cli-codegen");
+        let out = dir.to_str().unwrap();
+        assert_eq!(run(&args(&["codegen", "c", SAMPLE, out])), EXIT_SUCCESS);
+        let header = fs::read_to_string(dir.join("motbus.h")).unwrap();
+        assert!(header.contains("#define MOTBUS_ENGINE_DATA_FRAME_ID (0x64u)"));
+        assert!(dir.join("motbus.c").exists());
+        let module = dir.join("motbus.rs");
+        assert_eq!(
+            run(&args(&[
+                "codegen",
+                "rust",
+                SAMPLE,
+                module.to_str().unwrap()
+            ])),
+            EXIT_SUCCESS
+        );
+        assert!(
+            fs::read_to_string(module)
+                .unwrap()
+                .contains("pub struct EngineData {")
+        );
+        assert_eq!(run(&args(&["codegen", "cobol", SAMPLE, out])), EXIT_ERROR);
+        assert_eq!(run(&args(&["codegen", "c", SAMPLE])), EXIT_ERROR);
+    }
+}
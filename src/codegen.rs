@@ -0,0 +1,162 @@
+//! 代码生成 - 从 `EditableDbc` 生成报文结构体和编解码代码
+//!
+//! - `generate_c`: C99 头文件和源文件，用于 ECU 固件
+//! - `generate_rust`: Rust 模块，可以在 build.rs 中用 `generate_rust_file` 从仓库中的 DBC 生成
+//!
+//! 生成的 pack / unpack 与 `EditableMessage::encode` / `decode` 使用同一套位布局。
+
+mod c;
+mod rust;
+
+pub use c::{CCode, generate_c};
+pub use rust::{generate_rust, generate_rust_file};
+
+use can_dbc::ValueType;
+
+use crate::editable_dbc::{EditableMessage, EditableSignal, check_signal_placement};
+
+/// 转换为 snake_case：在驼峰处断开，非字母数字的字符当作分隔符，连续的分隔符合并
+///
+/// `EngineData` -> `engine_data`，`ABSStatus` -> `abs_status`，`Speed_RPM` -> `speed_rpm`
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            out.push('_');
+            continue;
+        }
+        if c.is_ascii_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if previous.is_ascii_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_ascii_uppercase() && next_is_lower)
+            {
+                out.push('_');
+            }
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out.split('_')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// 转换后的名字不能为空，同一作用域中不能重复，例如 `EngineData` 和 `Engine_Data`
+///
+/// names 为 (转换后的名字, 原来的名字)
+fn check_unique<'a>(
+    names: impl IntoIterator<Item = (&'a str, &'a str)>,
+    scope: &str,
+) -> Result<(), String> {
+    let mut seen: Vec<(&str, &str)> = Vec::new();
+    for (generated, original) in names {
+        if generated.is_empty() {
+            return Err(format!(
+                "Cannot derive an identifier from \"{original}\" in {scope}"
+            ));
+        }
+        if let Some((_, other)) = seen.iter().find(|(g, _)| *g == generated) {
+            return Err(format!(
+                "\"{other}\" and \"{original}\" both map to \"{generated}\" in {scope}"
+            ));
+        }
+        seen.push((generated, original));
+    }
+    Ok(())
+}
+
+/// 转换为 UpperCamelCase，单词的划分与 snake_case 相同
+///
+/// `ABS_Status` -> `AbsStatus`，`gear 1` -> `Gear1`
+fn camel_case(name: &str) -> String {
+    snake_case(name)
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn is_signed(signal: &EditableSignal) -> bool {
+    *signal.value_type() == ValueType::Signed
+}
+
+/// 能放下原始值的最小整数类型的位宽
+fn raw_width(signal: &EditableSignal) -> u32 {
+    match signal.signal_size() {
+        0..=8 => 8,
+        9..=16 => 16,
+        17..=32 => 32,
+        _ => 64,
+    }
+}
+
+/// IEEE 浮点信号（SIG_VALTYPE_）的位宽，长度与类型不符的信号由 check_layout 报错
+fn float_width(signal: &EditableSignal) -> Option<u32> {
+    signal.float_size().ok().flatten().map(|size| size as u32)
+}
+
+/// 信号位宽能表示的原始值范围
+fn raw_bounds(signal: &EditableSignal) -> (i128, i128) {
+    let size = signal.signal_size().clamp(1, 64) as u32;
+    type_bounds(is_signed(signal), size)
+}
+
+/// width 位整数能表示的范围
+fn type_bounds(signed: bool, width: u32) -> (i128, i128) {
+    if signed {
+        (-(1i128 << (width - 1)), (1i128 << (width - 1)) - 1)
+    } else {
+        (0, (1i128 << width) - 1)
+    }
+}
+
+/// 生成的代码不检查下标，所有信号必须完整地位于负载中，浮点信号的长度必须与类型一致
+fn check_layout(message: &EditableMessage) -> Result<(), String> {
+    for signal in message.signals() {
+        check_signal_placement(signal, message.message_size())
+            .and_then(|_| signal.float_size())
+            .map_err(|e| format!("{e} in message {}", message.message_name()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(snake_case("EngineData"), "engine_data");
+        assert_eq!(snake_case("ABSStatus"), "abs_status");
+        assert_eq!(snake_case("Speed_RPM"), "speed_rpm");
+        assert_eq!(snake_case("Msg1Data"), "msg1_data");
+        assert_eq!(snake_case("__odd name!"), "odd_name");
+        assert_eq!(snake_case("---"), "");
+        assert_eq!(camel_case("ABS_Status"), "AbsStatus");
+        assert_eq!(camel_case("gear 1"), "Gear1");
+        assert_eq!(camel_case("engine_data"), "EngineData");
+
+        assert!(check_unique([("a", "A"), ("b", "B")], "test").is_ok());
+        let error = check_unique(
+            [
+                ("engine_data", "EngineData"),
+                ("engine_data", "Engine_Data"),
+            ],
+            "test",
+        )
+        .unwrap_err();
+        assert!(
+            error.contains("EngineData") && error.contains("Engine_Data"),
+            "{error}"
+        );
+        assert!(check_unique([("", "---")], "test").is_err());
+    }
+}
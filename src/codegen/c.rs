@@ -0,0 +1,1073 @@
+use std::fmt::Write as _;
+
+use can_dbc::MultiplexIndicator;
+
+use super::{
+    check_layout, check_unique, float_width, is_signed, raw_bounds, raw_width, snake_case,
+    type_bounds,
+};
+use crate::editable_dbc::{EditableDbc, EditableMessage, EditableSignal, FrameFormat, size_to_dlc};
+
+// C99 代码生成，命名方式与 cantools 相同：
+//   #define <NAME>_<MESSAGE>_FRAME_ID / _IS_EXTENDED / _IS_FD / _LENGTH / _DLC / _CYCLE_TIME_MS
+//   struct <name>_<message>_t               每个信号一个字段，保存原始值，IEEE 浮点信号为 float / double
+//   <name>_<message>_pack / _unpack         按 DBC 的位布局打包 / 解包
+//   <name>_<message>_<signal>_encode / _decode / _is_in_range   物理值和原始值的转换
+//   enum <name>_<message>_<signal>_e        信号的值描述（VAL_）
+//   enum <name>_<table>_e                   全局值表（VAL_TABLE_）
+//
+// pack / unpack 按字节展开成移位和掩码，每个字节中连续的位一次处理，浮点信号用 memcpy 取得位模式
+// 被复用的信号只在复用器的值匹配时打包 / 解包
+
+const CAN_ID_MASK: u32 = 0x1FFF_FFFF;
+
+const C_KEYWORDS: [&str; 37] = [
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else",
+    "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long", "register",
+    "restrict", "return", "short", "signed", "sizeof", "static", "struct", "switch", "typedef",
+    "union", "unsigned", "void", "volatile", "while", "bool", "true", "false",
+];
+
+/// 生成的 C 代码，header 保存为 `<name>.h`，source 保存为 `<name>.c`
+#[derive(Clone, Debug, PartialEq)]
+pub struct CCode {
+    pub header: String,
+    pub source: String,
+}
+
+/// 生成 C99 代码，name 为文件名（不含扩展名），同时作为所有标识符的前缀
+///
+/// 以数字开头的文件名在前缀前加上 `dbc_`，如 `2024_bus` -> `dbc_2024_bus_engine_data_t`
+///
+/// 转换后的 Message 名或同一 Message 中的信号名重复，或者信号超出负载时返回错误
+pub fn generate_c(dbc: &EditableDbc, name: &str) -> Result<CCode, String> {
+    let mut prefix = snake_case(name);
+    check_unique([(prefix.as_str(), name)], "the file name")?;
+    if prefix.starts_with(|c: char| c.is_ascii_digit()) {
+        prefix.insert_str(0, "dbc_");
+    }
+
+    let messages: Vec<CMessage> = dbc
+        .messages()
+        .iter()
+        .map(|m| CMessage::new(&prefix, dbc, m))
+        .collect();
+    check_unique(
+        messages
+            .iter()
+            .map(|m| (m.name.as_str(), m.message.message_name())),
+        "message names",
+    )?;
+    for message in &messages {
+        check_layout(message.message)?;
+        check_unique(
+            message
+                .signals
+                .iter()
+                .map(|s| (s.field.as_str(), s.signal.name())),
+            &format!("message {}", message.message.message_name()),
+        )?;
+    }
+
+    Ok(CCode {
+        header: header(dbc, name, &prefix, &messages),
+        source: source(name, &messages),
+    })
+}
+
+struct CMessage<'a> {
+    message: &'a EditableMessage,
+    name: String,
+    // 函数和类型名前缀，如 motbus_engine_data
+    prefix: String,
+    cycle_time: Option<u64>,
+    signals: Vec<CSignal<'a>>,
+}
+
+struct CSignal<'a> {
+    signal: &'a EditableSignal,
+    field: String,
+    // 函数名前缀，如 motbus_engine_data_speed
+    prefix: String,
+    raw_type: &'static str,
+    width: u32,
+}
+
+impl<'a> CMessage<'a> {
+    fn new(file_prefix: &str, dbc: &EditableDbc, message: &'a EditableMessage) -> Self {
+        let name = snake_case(message.message_name());
+        let prefix = format!("{file_prefix}_{name}");
+        let signals = message
+            .signals()
+            .iter()
+            .map(|signal| {
+                let mut field = snake_case(signal.name());
+                let (raw_type, width) = raw_type(signal);
+                let prefix = format!("{prefix}_{field}");
+                if field.starts_with(|c: char| c.is_ascii_digit()) {
+                    field.insert(0, '_');
+                }
+                if C_KEYWORDS.contains(&field.as_str()) {
+                    field.push('_');
+                }
+                CSignal {
+                    signal,
+                    field,
+                    prefix,
+                    raw_type,
+                    width,
+                }
+            })
+            .collect();
+        CMessage {
+            message,
+            cycle_time: dbc.message_cycle_time(message.message_id()),
+            name,
+            prefix,
+            signals,
+        }
+    }
+
+    fn upper(&self) -> String {
+        self.prefix.to_uppercase()
+    }
+
+    fn length(&self) -> u64 {
+        self.message.message_size()
+    }
+
+    fn multiplexor(&self) -> Option<&CSignal<'a>> {
+        let name = self.message.multiplexor()?.name();
+        self.signals.iter().find(|s| s.signal.name() == name)
+    }
+}
+
+impl CSignal<'_> {
+    fn is_signed(&self) -> bool {
+        is_signed(self.signal)
+    }
+
+    fn is_float(&self) -> bool {
+        float_width(self.signal).is_some()
+    }
+
+    fn raw_bounds(&self) -> (i128, i128) {
+        raw_bounds(self.signal)
+    }
+
+    /// C 类型能表示的范围
+    fn type_bounds(&self) -> (i128, i128) {
+        type_bounds(self.is_signed(), self.width)
+    }
+
+    fn literal(&self, value: i128) -> String {
+        int_literal(value, self.is_signed(), self.width)
+    }
+}
+
+fn raw_type(signal: &EditableSignal) -> (&'static str, u32) {
+    match float_width(signal) {
+        Some(32) => return ("float", 32),
+        Some(width) => return ("double", width),
+        None => {}
+    }
+    let width = raw_width(signal);
+    let signed = is_signed(signal);
+    let name = match (signed, width) {
+        (false, 8) => "uint8_t",
+        (false, 16) => "uint16_t",
+        (false, 32) => "uint32_t",
+        (false, _) => "uint64_t",
+        (true, 8) => "int8_t",
+        (true, 16) => "int16_t",
+        (true, 32) => "int32_t",
+        (true, _) => "int64_t",
+    };
+    (name, width)
+}
+
+fn int_literal(value: i128, signed: bool, width: u32) -> String {
+    // -2147483648 这样的字面量在 C 中是对正数取负，类型会被提升，使用标准库的宏
+    if signed && width >= 32 && value == -(1i128 << (width - 1)) {
+        format!("INT{width}_MIN")
+    } else if signed {
+        value.to_string()
+    } else {
+        format!("{value}u")
+    }
+}
+
+// C 的 double 字面量，总是带小数点或指数
+fn double_literal(value: f64) -> String {
+    format!("{value:?}")
+}
+
+// 注释中不能出现 "*/"，换行合并为空格
+fn comment_text(text: &str) -> String {
+    text.replace("*/", "* /")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 信号在一个字节中占用的连续位
+struct Segment {
+    byte: u64,
+    // 字节内的最低位
+    shift: u32,
+    // 对应原始值中的最低位
+    raw_shift: u64,
+    width: u32,
+}
+
+// signal.bits() 从最高位到最低位排列，同一字节中相邻的位在字节内和原始值中都是连续的
+fn segments(signal: &EditableSignal) -> Vec<Segment> {
+    let bits = signal.bits();
+    let size = bits.len() as u64;
+    let mut segments: Vec<Segment> = Vec::new();
+    for (i, bit) in bits.into_iter().enumerate() {
+        let raw_bit = size - 1 - i as u64;
+        let (byte, position) = (bit / 8, (bit % 8) as u32);
+        match segments.last_mut() {
+            Some(last)
+                if last.byte == byte
+                    && last.shift == position + 1
+                    && last.raw_shift == raw_bit + 1 =>
+            {
+                last.shift = position;
+                last.raw_shift = raw_bit;
+                last.width += 1;
+            }
+            _ => segments.push(Segment {
+                byte,
+                shift: position,
+                raw_shift: raw_bit,
+                width: 1,
+            }),
+        }
+    }
+    segments
+}
+
+fn shift_text(operator: &str, amount: u64) -> String {
+    if amount == 0 {
+        String::new()
+    } else {
+        format!(" {operator} {amount}")
+    }
+}
+
+/// 枚举成员名，描述文本无法转换时使用 VALUE_<raw>，重复时加上原始值
+fn enumerators(prefix: &str, descriptions: &[(i64, String)]) -> Vec<(i64, String)> {
+    let raw_text = |raw: i64| {
+        if raw < 0 {
+            format!("M{}", raw.unsigned_abs())
+        } else {
+            raw.to_string()
+        }
+    };
+    let mut result: Vec<(i64, String)> = Vec::new();
+    for (raw, text) in descriptions {
+        let mut label = snake_case(text).to_uppercase();
+        if label.is_empty() {
+            label = format!("VALUE_{}", raw_text(*raw));
+        }
+        let mut name = format!("{prefix}_{label}");
+        if result.iter().any(|(_, n)| *n == name) {
+            name = format!("{name}_{}", raw_text(*raw));
+        }
+        result.push((*raw, name));
+    }
+    result
+}
+
+fn write_enum(out: &mut String, title: &str, name: &str, descriptions: &[(i64, String)]) {
+    let _ = writeln!(out, "/* {} */", comment_text(title));
+    let _ = writeln!(out, "enum {name}_e {{");
+    for ((raw, enumerator), (_, text)) in enumerators(&name.to_uppercase(), descriptions)
+        .into_iter()
+        .zip(descriptions)
+    {
+        // 枚举常量必须在 int 范围内
+        if i32::try_from(raw).is_ok() {
+            let _ = writeln!(out, "    {enumerator} = {raw},");
+        } else {
+            let _ = writeln!(
+                out,
+                "    /* {raw}: {} (out of int range) */",
+                comment_text(text)
+            );
+        }
+    }
+    let _ = writeln!(out, "}};\n");
+}
+
+fn header(dbc: &EditableDbc, name: &str, prefix: &str, messages: &[CMessage]) -> String {
+    let guard = format!("{}_H", prefix.to_uppercase());
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "/**\n * Generated by roxy-dbc from {}. Do not edit.\n */\n",
+        comment_text(name)
+    );
+    let _ = writeln!(out, "#ifndef {guard}\n#define {guard}\n");
+    out.push_str("#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n");
+    out.push_str("#include <stdbool.h>\n#include <stddef.h>\n#include <stdint.h>\n\n");
+
+    if !messages.is_empty() {
+        out.push_str("/* Frame IDs, lengths and cycle times. */\n");
+    }
+    for message in messages {
+        let upper = message.upper();
+        let extended = message.message.frame_format() == FrameFormat::Extended;
+        let id = message.message.message_id() & CAN_ID_MASK;
+        let _ = writeln!(out, "#define {upper}_FRAME_ID (0x{id:X}u)");
+        let _ = writeln!(out, "#define {upper}_IS_EXTENDED ({})", extended as u8);
+        let _ = writeln!(
+            out,
+            "#define {upper}_IS_FD ({})",
+            message.message.is_fd() as u8
+        );
+        let _ = writeln!(out, "#define {upper}_LENGTH ({}u)", message.length());
+        let _ = writeln!(
+            out,
+            "#define {upper}_DLC ({}u)",
+            size_to_dlc(message.length())
+        );
+        if let Some(cycle_time) = message.cycle_time {
+            let _ = writeln!(out, "#define {upper}_CYCLE_TIME_MS ({cycle_time}u)");
+        }
+        out.push('\n');
+    }
+
+    for table in dbc.value_tables() {
+        let table_name = format!("{prefix}_{}", snake_case(table.name()));
+        let title = format!("Value table {}.", table.name());
+        write_enum(&mut out, &title, &table_name, table.descriptions());
+    }
+    for message in messages {
+        for signal in &message.signals {
+            if signal.signal.value_descriptions().is_empty() {
+                continue;
+            }
+            let title = format!(
+                "Values of signal {}.{}.",
+                message.message.message_name(),
+                signal.signal.name()
+            );
+            write_enum(
+                &mut out,
+                &title,
+                &signal.prefix,
+                signal.signal.value_descriptions(),
+            );
+        }
+    }
+
+    for message in messages {
+        write_struct(&mut out, message);
+    }
+    for message in messages {
+        write_prototypes(&mut out, message);
+    }
+
+    out.push_str("#ifdef __cplusplus\n}\n#endif\n\n");
+    let _ = writeln!(out, "#endif /* {guard} */");
+    out
+}
+
+fn write_struct(out: &mut String, message: &CMessage) {
+    let _ = writeln!(
+        out,
+        "/**\n * Signals in message {}.",
+        message.message.message_name()
+    );
+    if !message.message.comment().is_empty() {
+        let _ = writeln!(out, " *\n * {}", comment_text(message.message.comment()));
+    }
+    out.push_str(" *\n * All signal values are as on the CAN bus (raw).\n */\n");
+    let _ = writeln!(out, "struct {}_t {{", message.prefix);
+    for signal in &message.signals {
+        let s = signal.signal;
+        out.push_str("    /**\n");
+        if !s.comment().is_empty() {
+            let _ = writeln!(out, "     * {}\n     *", comment_text(s.comment()));
+        }
+        if let MultiplexIndicator::MultiplexedSignal(value) = s.multiplexer_indicator() {
+            let _ = writeln!(
+                out,
+                "     * Multiplexed: only valid when the multiplexor is {value}"
+            );
+        }
+        if s.min() < s.max() {
+            let _ = writeln!(
+                out,
+                "     * Range: {}..{} {}",
+                s.min(),
+                s.max(),
+                comment_text(s.unit())
+            );
+        }
+        if signal.is_float() {
+            let _ = writeln!(out, "     * IEEE 754 {}", signal.raw_type);
+        }
+        let _ = writeln!(out, "     * Scale: {}", s.factor());
+        let _ = writeln!(out, "     * Offset: {}", s.offset());
+        out.push_str("     */\n");
+        let _ = writeln!(out, "    {} {};", signal.raw_type, signal.field);
+    }
+    // C99 不允许空结构体
+    if message.signals.is_empty() {
+        out.push_str("    uint8_t dummy;\n");
+    }
+    out.push_str("};\n\n");
+}
+
+fn write_prototypes(out: &mut String, message: &CMessage) {
+    let name = message.message.message_name();
+    let prefix = &message.prefix;
+    let _ = writeln!(
+        out,
+        "/**\n * Pack message {name}.\n *\n * @param[out] dst_p Buffer to pack the message into.\n \
+         * @param[in] src_p Data to pack.\n * @param[in] size Size of dst_p.\n *\n \
+         * @return Size of packed data, or -1 if dst_p is too small.\n */"
+    );
+    let _ = writeln!(
+        out,
+        "int {prefix}_pack(uint8_t *dst_p, const struct {prefix}_t *src_p, size_t size);\n"
+    );
+    let _ = writeln!(
+        out,
+        "/**\n * Unpack message {name}.\n *\n * @param[out] dst_p Object to unpack the message into.\n \
+         * @param[in] src_p Message to unpack.\n * @param[in] size Size of src_p.\n *\n \
+         * @return 0 on success, or -1 if src_p is too small.\n */"
+    );
+    let _ = writeln!(
+        out,
+        "int {prefix}_unpack(struct {prefix}_t *dst_p, const uint8_t *src_p, size_t size);\n"
+    );
+    for signal in &message.signals {
+        let (ty, p) = (signal.raw_type, &signal.prefix);
+        let conversion = if signal.is_float() {
+            ""
+        } else {
+            " (rounded and clamped to the raw range)"
+        };
+        let _ = writeln!(
+            out,
+            "/* Signal {}: physical value to raw value{conversion}. */",
+            signal.signal.name()
+        );
+        let _ = writeln!(out, "{ty} {p}_encode(double value);\n");
+        let _ = writeln!(
+            out,
+            "/* Signal {}: raw value to physical value. */",
+            signal.signal.name()
+        );
+        let _ = writeln!(out, "double {p}_decode({ty} value);\n");
+        let _ = writeln!(
+            out,
+            "/* Signal {}: whether a raw value is within the signal's range. */",
+            signal.signal.name()
+        );
+        let _ = writeln!(out, "bool {p}_is_in_range({ty} value);\n");
+    }
+}
+
+fn source(name: &str, messages: &[CMessage]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "/**\n * Generated by roxy-dbc from {}. Do not edit.\n */\n",
+        comment_text(name)
+    );
+    out.push_str("#include <string.h>\n\n");
+    let _ = writeln!(out, "#include \"{name}.h\"\n");
+    for message in messages {
+        write_pack(&mut out, message);
+        write_unpack(&mut out, message);
+        for signal in &message.signals {
+            write_conversions(&mut out, signal);
+        }
+    }
+    out
+}
+
+// 被复用的信号包在 if 中，返回缩进
+fn open_multiplexed(out: &mut String, message: &CMessage, signal: &CSignal, access: &str) -> usize {
+    match (signal.signal.multiplexer_indicator(), message.multiplexor()) {
+        (MultiplexIndicator::MultiplexedSignal(value), Some(multiplexor)) => {
+            let _ = writeln!(
+                out,
+                "    if ({access}{} == {}) {{",
+                multiplexor.field,
+                multiplexor.literal(*value as i128)
+            );
+            8
+        }
+        _ => 4,
+    }
+}
+
+fn close_multiplexed(out: &mut String, indent: usize) {
+    if indent > 4 {
+        out.push_str("    }\n");
+    }
+}
+
+fn write_pack(out: &mut String, message: &CMessage) {
+    let prefix = &message.prefix;
+    let length = message.length();
+    let _ = writeln!(
+        out,
+        "int {prefix}_pack(uint8_t *dst_p, const struct {prefix}_t *src_p, size_t size)\n{{"
+    );
+    write_locals(out, message);
+    if length > 0 {
+        let _ = writeln!(
+            out,
+            "    if (size < {length}u) {{\n        return (-1);\n    }}\n"
+        );
+        let _ = writeln!(out, "    memset(&dst_p[0], 0, {length});\n");
+    } else {
+        out.push_str("    (void)dst_p;\n    (void)size;\n\n");
+    }
+    for signal in &message.signals {
+        let indent = open_multiplexed(out, message, signal, "src_p->");
+        let pad = " ".repeat(indent);
+        match signal.raw_type {
+            "float" => {
+                let _ = writeln!(
+                    out,
+                    "{pad}memcpy(&raw32, &src_p->{}, sizeof(raw32));\n{pad}raw = raw32;",
+                    signal.field
+                );
+            }
+            "double" => {
+                let _ = writeln!(
+                    out,
+                    "{pad}memcpy(&raw, &src_p->{}, sizeof(raw));",
+                    signal.field
+                );
+            }
+            _ => {
+                let _ = writeln!(out, "{pad}raw = (uint64_t)src_p->{};", signal.field);
+            }
+        }
+        for segment in segments(signal.signal) {
+            let mask = (1u32 << segment.width) - 1;
+            let _ = writeln!(
+                out,
+                "{pad}dst_p[{}] |= (uint8_t)(((raw{}) & 0x{mask:02x}u){});",
+                segment.byte,
+                shift_text(">>", segment.raw_shift),
+                shift_text("<<", segment.shift as u64)
+            );
+        }
+        close_multiplexed(out, indent);
+    }
+    if !message.signals.is_empty() {
+        out.push('\n');
+    }
+    let _ = writeln!(out, "    return ({length});\n}}\n");
+}
+
+// pack / unpack 的局部变量，32 位浮点信号经过 raw32 转换位模式
+fn write_locals(out: &mut String, message: &CMessage) {
+    if message.signals.is_empty() {
+        out.push_str("    (void)src_p;\n");
+    } else {
+        out.push_str("    uint64_t raw;\n");
+    }
+    if message.signals.iter().any(|s| s.raw_type == "float") {
+        out.push_str("    uint32_t raw32;\n");
+    }
+    out.push('\n');
+}
+
+fn write_unpack(out: &mut String, message: &CMessage) {
+    let prefix = &message.prefix;
+    let length = message.length();
+    let _ = writeln!(
+        out,
+        "int {prefix}_unpack(struct {prefix}_t *dst_p, const uint8_t *src_p, size_t size)\n{{"
+    );
+    write_locals(out, message);
+    if length > 0 {
+        let _ = writeln!(
+            out,
+            "    if (size < {length}u) {{\n        return (-1);\n    }}\n"
+        );
+    } else {
+        out.push_str("    (void)size;\n\n");
+    }
+    out.push_str("    memset(dst_p, 0, sizeof(*dst_p));\n\n");
+
+    // 先解出复用器，被复用的信号根据它的值决定是否解包
+    let multiplexor = message.multiplexor();
+    let ordered = multiplexor.into_iter().chain(
+        message
+            .signals
+            .iter()
+            .filter(|s| multiplexor.is_none_or(|m| m.signal.name() != s.signal.name())),
+    );
+    for signal in ordered {
+        let indent = open_multiplexed(out, message, signal, "dst_p->");
+        let pad = " ".repeat(indent);
+        let _ = writeln!(out, "{pad}raw = 0u;");
+        for segment in segments(signal.signal) {
+            let mask = (1u32 << segment.width) - 1;
+            let _ = writeln!(
+                out,
+                "{pad}raw |= (uint64_t)((src_p[{}]{}) & 0x{mask:02x}u){};",
+                segment.byte,
+                shift_text(">>", segment.shift as u64),
+                shift_text("<<", segment.raw_shift)
+            );
+        }
+        let size = signal.signal.signal_size();
+        match signal.raw_type {
+            "float" => {
+                let _ = writeln!(
+                    out,
+                    "{pad}raw32 = (uint32_t)raw;\n{pad}memcpy(&dst_p->{}, &raw32, sizeof(raw32));",
+                    signal.field
+                );
+            }
+            "double" => {
+                let _ = writeln!(
+                    out,
+                    "{pad}memcpy(&dst_p->{}, &raw, sizeof(raw));",
+                    signal.field
+                );
+            }
+            ty => {
+                if signal.is_signed() && size < 64 {
+                    let sign = 1u64 << (size - 1);
+                    let extension = !((1u64 << size) - 1);
+                    let _ = writeln!(
+                        out,
+                        "{pad}if ((raw & 0x{sign:x}ull) != 0u) {{\n{pad}    raw |= 0x{extension:x}ull;\n{pad}}}"
+                    );
+                }
+                let _ = writeln!(out, "{pad}dst_p->{} = ({ty})raw;", signal.field);
+            }
+        }
+        close_multiplexed(out, indent);
+    }
+    if !message.signals.is_empty() {
+        out.push('\n');
+    }
+    out.push_str("    return (0);\n}\n\n");
+}
+
+fn write_conversions(out: &mut String, signal: &CSignal) {
+    let s = signal.signal;
+    let (ty, p) = (signal.raw_type, &signal.prefix);
+    let (raw_min, raw_max) = signal.raw_bounds();
+
+    let _ = writeln!(out, "{ty} {p}_encode(double value)\n{{");
+    if s.factor() == 0.0 {
+        let _ = writeln!(out, "    (void)value;\n\n    return ({ty})0;\n}}\n");
+    } else if signal.is_float() {
+        // 浮点信号不取整也不限制范围
+        let _ = writeln!(
+            out,
+            "    return ({ty})((value - {}) / {});\n}}\n",
+            double_literal(s.offset()),
+            double_literal(s.factor())
+        );
+    } else {
+        let _ = writeln!(
+            out,
+            "    double raw = (value - {}) / {};\n",
+            double_literal(s.offset()),
+            double_literal(s.factor())
+        );
+        // 四舍五入到最近的整数（与 f64::round 一致），NaN 按下限处理
+        out.push_str("    raw = (raw < 0.0) ? (raw - 0.5) : (raw + 0.5);\n");
+        let _ = writeln!(
+            out,
+            "    if (!(raw > {})) {{\n        return {};\n    }}",
+            double_literal(raw_min as f64),
+            signal.literal(raw_min)
+        );
+        let _ = writeln!(
+            out,
+            "    if (raw >= {}) {{\n        return {};\n    }}\n",
+            double_literal(raw_max as f64),
+            signal.literal(raw_max)
+        );
+        let _ = writeln!(out, "    return ({ty})raw;\n}}\n");
+    }
+
+    let _ = writeln!(
+        out,
+        "double {p}_decode({ty} value)\n{{\n    return ((double)value * {}) + {};\n}}\n",
+        double_literal(s.factor()),
+        double_literal(s.offset())
+    );
+
+    if signal.is_float() {
+        write_float_range(out, signal);
+        return;
+    }
+
+    // 物理范围换算成原始值范围，没有给出范围时使用信号位宽能表示的范围
+    let (mut low, mut high) = (raw_min, raw_max);
+    if s.min() < s.max() && s.factor() != 0.0 {
+        let a = ((s.min() - s.offset()) / s.factor()).round();
+        let b = ((s.max() - s.offset()) / s.factor()).round();
+        let (a, b) = if a <= b { (a, b) } else { (b, a) };
+        low = (a as i128).clamp(raw_min, raw_max);
+        high = (b as i128).clamp(raw_min, raw_max);
+    }
+    // 与 C 类型的边界比较总是成立，省略以免 -Wtype-limits 警告
+    let (type_min, type_max) = signal.type_bounds();
+    let mut conditions = Vec::new();
+    if low > type_min {
+        conditions.push(format!("(value >= {})", signal.literal(low)));
+    }
+    if high < type_max {
+        conditions.push(format!("(value <= {})", signal.literal(high)));
+    }
+    let _ = writeln!(out, "bool {p}_is_in_range({ty} value)\n{{");
+    if conditions.is_empty() {
+        out.push_str("    (void)value;\n\n    return (true);\n}\n\n");
+    } else {
+        let _ = writeln!(out, "    return ({});\n}}\n", conditions.join(" && "));
+    }
+}
+
+// 浮点信号按物理范围换算出的原始值范围比较，不取整，没有给出范围时总是成立
+fn write_float_range(out: &mut String, signal: &CSignal) {
+    let s = signal.signal;
+    let (ty, p) = (signal.raw_type, &signal.prefix);
+    let _ = writeln!(out, "bool {p}_is_in_range({ty} value)\n{{");
+    if s.min() < s.max() && s.factor() != 0.0 {
+        let a = (s.min() - s.offset()) / s.factor();
+        let b = (s.max() - s.offset()) / s.factor();
+        let (low, high) = if a <= b { (a, b) } else { (b, a) };
+        let _ = writeln!(
+            out,
+            "    return (((double)value >= {}) && ((double)value <= {}));\n}}\n",
+            double_literal(low),
+            double_literal(high)
+        );
+    } else {
+        out.push_str("    (void)value;\n\n    return (true);\n}\n\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use can_dbc::Dbc;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    const DBC: &str = r#"VERSION ""
+
+BU_: ECU
+
+BO_ 256 EngineData: 8 ECU
+ SG_ Speed : 0|16@1+ (0.5,0) [0|30000] "rpm" Vector__XXX
+ SG_ Temp : 16|8@1- (1,0) [-40|87] "degC" Vector__XXX
+ SG_ Pressure : 31|12@0+ (0.1,0) [0|409.5] "kPa" Vector__XXX
+ SG_ State : 32|3@1+ (1,0) [0|2] "" Vector__XXX
+ SG_ Torque : 47|13@0- (0.25,-100) [-1000|1000] "Nm" Vector__XXX
+
+BO_ 2566844672 Extended: 8 ECU
+ SG_ Counter : 0|4@1+ (1,0) [0|15] "" Vector__XXX
+ SG_ Big : 8|40@1+ (1,0) [0|0] "" Vector__XXX
+ SG_ Wide : 63|8@0- (1,0) [0|0] "" Vector__XXX
+
+BO_ 512 Muxed: 4 ECU
+ SG_ Mux M : 0|4@1+ (1,0) [0|1] "" Vector__XXX
+ SG_ A m0 : 15|16@0- (1,0) [0|0] "" Vector__XXX
+ SG_ B m1 : 8|8@1+ (2,1) [1|511] "" Vector__XXX
+
+BO_ 768 FdFrame: 64 ECU
+ SG_ Tail : 504|8@1+ (1,0) [0|0] "" Vector__XXX
+ SG_ Head : 7|16@0+ (1,0) [0|0] "" Vector__XXX
+
+BO_ 1024 Empty: 0 ECU
+
+BO_ 1280 Floats: 12 ECU
+ SG_ Ratio : 0|32@1- (1,0) [0|0] "" Vector__XXX
+ SG_ Precise : 39|64@0- (0.5,10) [0|100] "" Vector__XXX
+
+CM_ SG_ 256 Speed "Engine speed */ not a comment end";
+BA_DEF_ BO_  "VFrameFormat" ENUM  "StandardCAN","ExtendedCAN","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","StandardCAN_FD","ExtendedCAN_FD";
+BA_DEF_ BO_  "GenMsgCycleTime" INT 0 65535;
+BA_DEF_DEF_  "VFrameFormat" "StandardCAN";
+BA_DEF_DEF_  "GenMsgCycleTime" 0;
+BA_ "GenMsgCycleTime" BO_ 256 100;
+BA_ "VFrameFormat" BO_ 768 14;
+BA_ "VFrameFormat" BO_ 1280 14;
+VAL_ 256 State 0 "Off" 1 "Running" 2 "Fault" 3 "fault" ;
+SIG_VALTYPE_ 1280 Ratio : 1;
+SIG_VALTYPE_ 1280 Precise : 2;
+"#;
+
+    fn test_dbc() -> EditableDbc {
+        EditableDbc::from_dbc(&Dbc::try_from(DBC).unwrap())
+    }
+
+    // 主机上没有 C 编译器时跳过
+    fn compile(dir: &Path, sources: &[&str], output: &str) -> Option<PathBuf> {
+        let executable = dir.join(output);
+        let result = Command::new("cc")
+            .current_dir(dir)
+            .args([
+                "-std=c99",
+                "-Wall",
+                "-Wextra",
+                "-Wconversion",
+                "-pedantic",
+                "-Werror",
+                "-o",
+            ])
+            .arg(&executable)
+            .args(sources)
+            .output();
+        let output = match result {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("skipping C compilation, cc not available: {e}");
+                return None;
+            }
+        };
+        assert!(
+            output.status.success(),
+            "cc failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Some(executable)
+    }
+
+    #[test]
+    fn test_generate_names() {
+        let code = generate_c(&test_dbc(), "test_bus").unwrap();
+        let header = &code.header;
+        assert!(header.contains("#define TEST_BUS_ENGINE_DATA_FRAME_ID (0x100u)"));
+        assert!(header.contains("#define TEST_BUS_ENGINE_DATA_CYCLE_TIME_MS (100u)"));
+        assert!(header.contains("#define TEST_BUS_EXTENDED_FRAME_ID (0x18FEF100u)"));
+        assert!(header.contains("#define TEST_BUS_EXTENDED_IS_EXTENDED (1)"));
+        assert!(header.contains("#define TEST_BUS_FD_FRAME_DLC (15u)"));
+        assert!(!header.contains("TEST_BUS_MUXED_CYCLE_TIME_MS"));
+        assert!(header.contains("    TEST_BUS_ENGINE_DATA_STATE_RUNNING = 1,"));
+        // 转换后重复的枚举成员名加上原始值
+        assert!(header.contains("    TEST_BUS_ENGINE_DATA_STATE_FAULT_3 = 3,"));
+        assert!(header.contains("Engine speed * / not a comment end"));
+        assert!(header.contains("    uint64_t big;"));
+        assert!(header.contains("    int16_t torque;"));
+        assert!(header.contains("    float ratio;"));
+        assert!(header.contains("    double precise;"));
+        assert!(code.source.contains("#include \"test_bus.h\""));
+
+        let mut dbc = test_dbc();
+        dbc.set_message_name(512, "Engine_Data");
+        let error = generate_c(&dbc, "test_bus").unwrap_err();
+        assert!(error.contains("Engine_Data"), "{error}");
+
+        // 超出负载的信号会让 pack / unpack 越界访问
+        let mut dbc = test_dbc();
+        dbc.set_message_size(512, 1);
+        assert!(generate_c(&dbc, "test_bus").is_err());
+
+        // 长度与浮点类型不符的信号无法按 IEEE 浮点解释
+        let text = DBC.replace("Ratio : 0|32@1-", "Ratio : 0|16@1-");
+        let dbc = EditableDbc::from_dbc(&Dbc::try_from(text.as_str()).unwrap());
+        let error = generate_c(&dbc, "test_bus").unwrap_err();
+        assert!(error.contains("Ratio"), "{error}");
+    }
+
+    #[test]
+    fn test_round_trip_with_host_compiler() {
+        let dbc = test_dbc();
+        let code = generate_c(&dbc, "rt").unwrap();
+        let dir = temp_dir("codegen-c-round-trip");
+        fs::write(dir.join("rt.h"), &code.header).unwrap();
+        fs::write(dir.join("rt.c"), &code.source).unwrap();
+
+        // 每个用例：Message 名、C 前缀、信号的物理值
+        let cases = [
+            (
+                "EngineData",
+                "rt_engine_data",
+                vec![
+                    ("Speed", 1234.5),
+                    ("Temp", -40.0),
+                    ("Pressure", 301.7),
+                    ("State", 2.0),
+                    ("Torque", -87.25),
+                ],
+            ),
+            (
+                "Extended",
+                "rt_extended",
+                vec![("Counter", 9.0), ("Big", 1099511627775.0), ("Wide", -2.0)],
+            ),
+            ("Muxed", "rt_muxed", vec![("Mux", 0.0), ("A", -12345.0)]),
+            ("Muxed", "rt_muxed", vec![("Mux", 1.0), ("B", 201.0)]),
+            (
+                "FdFrame",
+                "rt_fd_frame",
+                vec![("Tail", 170.0), ("Head", 4660.0)],
+            ),
+            ("Empty", "rt_empty", Vec::new()),
+            (
+                "Floats",
+                "rt_floats",
+                vec![("Ratio", -0.75), ("Precise", 42.5)],
+            ),
+        ];
+
+        let mut main = String::from(
+            "#include <stdio.h>\n#include <string.h>\n#include \"rt.h\"\n\n\
+             static int failures = 0;\n\
+             #define CHECK(cond) do { if (!(cond)) { printf(\"line %d: %s\\n\", __LINE__, #cond); failures++; } } while (0)\n\
+             static int near(double a, double b) { double d = a - b; return d < 1e-6 && d > -1e-6; }\n\n\
+             int main(void)\n{\n",
+        );
+        for (message_name, prefix, values) in &cases {
+            let message = dbc
+                .messages()
+                .iter()
+                .find(|m| m.message_name() == *message_name)
+                .unwrap();
+            let expected = message.encode(values).unwrap();
+            let length = expected.len();
+            let bytes: Vec<String> = expected.iter().map(|b| format!("0x{b:02x}")).collect();
+            main += "    {\n";
+            let _ = writeln!(main, "        struct {prefix}_t msg;");
+            let _ = writeln!(main, "        struct {prefix}_t unpacked;");
+            let _ = writeln!(
+                main,
+                "        const uint8_t expected[{}] = {{ {} }};",
+                length.max(1),
+                if bytes.is_empty() {
+                    "0".to_string()
+                } else {
+                    bytes.join(", ")
+                }
+            );
+            let _ = writeln!(main, "        uint8_t buffer[{}];", length.max(1));
+            main += "        memset(&msg, 0, sizeof(msg));\n";
+            for (signal_name, value) in values {
+                let signal = message
+                    .signals()
+                    .iter()
+                    .find(|s| s.name() == *signal_name)
+                    .unwrap();
+                let field = snake_case(signal_name);
+                // 浮点信号的字段保存换算后的浮点数，而不是位模式
+                let scaled = (value - signal.offset()) / signal.factor();
+                let raw = match float_width(signal) {
+                    Some(32) => format!("{:?}f", scaled as f32),
+                    Some(_) => format!("{scaled:?}"),
+                    None => signal.physical_to_raw(*value).unwrap().to_string(),
+                };
+                let _ = writeln!(main, "        msg.{field} = {raw};");
+                let _ = writeln!(
+                    main,
+                    "        CHECK({prefix}_{field}_encode({value:?}) == {raw});"
+                );
+                let _ = writeln!(
+                    main,
+                    "        CHECK(near({prefix}_{field}_decode({raw}), {value:?}));"
+                );
+            }
+            let _ = writeln!(
+                main,
+                "        CHECK({prefix}_pack(buffer, &msg, sizeof(buffer)) == {length});"
+            );
+            let _ = writeln!(
+                main,
+                "        CHECK(memcmp(buffer, expected, {length}) == 0);"
+            );
+            let _ = writeln!(
+                main,
+                "        CHECK({prefix}_unpack(&unpacked, expected, sizeof(expected)) == 0);"
+            );
+            let _ = writeln!(
+                main,
+                "        CHECK(memcmp(&unpacked, &msg, sizeof(msg)) == 0);"
+            );
+            if length > 0 {
+                let _ = writeln!(
+                    main,
+                    "        CHECK({prefix}_pack(buffer, &msg, {}) == -1);",
+                    length - 1
+                );
+            }
+            main += "    }\n";
+        }
+        // 编码时超出原始值范围的物理值被限制在范围内，is_in_range 按 DBC 中的物理范围检查
+        main += "    CHECK(rt_engine_data_temp_encode(-1000.0) == -128);\n\
+                 \x20   CHECK(rt_engine_data_speed_encode(1e9) == 65535u);\n\
+                 \x20   CHECK(rt_engine_data_speed_is_in_range(60000u));\n\
+                 \x20   CHECK(!rt_engine_data_speed_is_in_range(60001u));\n\
+                 \x20   CHECK(!rt_engine_data_temp_is_in_range(-41));\n\
+                 \x20   CHECK(rt_engine_data_state_is_in_range(RT_ENGINE_DATA_STATE_FAULT));\n\
+                 \x20   CHECK(rt_extended_big_is_in_range(1099511627775u));\n\
+                 \x20   CHECK(!rt_extended_big_is_in_range(1099511627776u));\n\
+                 \x20   CHECK(rt_floats_precise_is_in_range(180.0));\n\
+                 \x20   CHECK(!rt_floats_precise_is_in_range(180.5));\n\
+                 \x20   CHECK(rt_floats_ratio_is_in_range(1e30f));\n\
+                 \x20   return failures == 0 ? 0 : 1;\n}\n";
+        fs::write(dir.join("main.c"), main).unwrap();
+
+        let Some(executable) = compile(&dir, &["main.c", "rt.c"], "round_trip") else {
+            return;
+        };
+        let output = Command::new(&executable).output().unwrap();
+        assert!(
+            output.status.success(),
+            "round trip failed:\n{}",
+            String::from_utf8_lossy(&output.stdout)
+        );
+    }
+
+    #[test]
+    fn test_sample_files_compile() {
+        let dir = temp_dir("codegen-c-samples");
+        for sample in [
+            "motbus",
+            "motbus_ext",
+            "motbus_ext_2",
+            "can_data_ev1",
+            "empty",
+        ] {
+            let path = Path::new("dbc-sample").join(format!("{sample}.dbc"));
+            let dbc = crate::editable_dbc::load_file(&path).unwrap();
+            let code = generate_c(&dbc, sample).unwrap();
+            fs::write(dir.join(format!("{sample}.h")), &code.header).unwrap();
+            fs::write(dir.join(format!("{sample}.c")), &code.source).unwrap();
+            let main = format!("#include \"{sample}.h\"\nint main(void)\n{{\n    return 0;\n}}\n");
+            let main_file = format!("{sample}_main.c");
+            fs::write(dir.join(&main_file), main).unwrap();
+            let source_file = format!("{sample}.c");
+            if compile(&dir, &[&main_file, &source_file], sample).is_none() {
+                return;
+            }
+        }
+
+        // 以数字开头的文件名不能直接作为 C 标识符的前缀
+        let dbc = crate::editable_dbc::load_file(Path::new("dbc-sample/motbus.dbc")).unwrap();
+        let code = generate_c(&dbc, "2024_bus").unwrap();
+        assert!(code.header.contains("#ifndef DBC_2024_BUS_H"));
+        assert!(code.source.contains("#include \"2024_bus.h\""));
+        fs::write(dir.join("2024_bus.h"), &code.header).unwrap();
+        fs::write(dir.join("2024_bus.c"), &code.source).unwrap();
+        fs::write(
+            dir.join("2024_bus_main.c"),
+            "#include \"2024_bus.h\"\nint main(void)\n{\n    return 0;\n}\n",
+        )
+        .unwrap();
+        compile(&dir, &["2024_bus_main.c", "2024_bus.c"], "2024_bus");
+    }
+}
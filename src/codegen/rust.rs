@@ -0,0 +1,1508 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use can_dbc::{ByteOrder, MultiplexIndicator};
+
+use super::{
+    camel_case, check_layout, check_unique, float_width, is_signed, raw_bounds, raw_width,
+    snake_case, type_bounds,
+};
+use crate::editable_dbc::{EditableDbc, EditableMessage, EditableSignal, FrameFormat, load_file};
+
+// Rust 代码生成，生成的模块只依赖标准库：
+//   struct <Message>               负载的字节数组，MESSAGE_ID / IS_EXTENDED / IS_FD / LENGTH 等常量
+//                                  TryFrom<&[u8]> / into_bytes，每个信号一组 getter / setter
+//   enum <Message><Signal>         信号的值描述（VAL_），未描述的值为 _Other(raw)
+//   enum <ValueTable>              全局值表（VAL_TABLE_）
+//   enum <Message><Mux>            复用器的每个值对应一个变体，变体中是该值下的信号
+//   enum Messages                  按 CAN ID 解码任意 Message
+//   enum CanError                  错误类型
+//
+// 信号的物理值类型：有值描述时为枚举，1 位无符号且 factor = 1、offset = 0 时为 bool，
+// factor = 1、offset = 0 时为原始值的整数类型，其他为 f64
+// IEEE 浮点信号（SIG_VALTYPE_）的原始值为 f32 / f64，物理值总是 f64
+// 标识符都用完整路径引用 core 中的类型，Message 名与 Result、Default 等相同时也能编译
+
+const CAN_ID_MASK: u32 = 0x1FFF_FFFF;
+
+const RUST_KEYWORDS: [&str; 51] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+// 每个 Message 结构体都有的方法
+const BUILTIN_MEMBERS: [&str; 3] = ["new", "raw", "into_bytes"];
+
+const RESULT: &str = "core::result::Result";
+
+/// 生成 Rust 模块，name 只用于文件头的注释
+///
+/// 生成的代码通过 `include!` 放进一个模块中使用：
+///
+/// ```ignore
+/// mod network {
+///     include!(concat!(env!("OUT_DIR"), "/network.rs"));
+/// }
+/// ```
+///
+/// 转换后的类型名或同一类型中的方法名重复，信号超出负载，或者复用器是浮点信号时返回错误
+pub fn generate_rust(dbc: &EditableDbc, name: &str) -> Result<String, String> {
+    let messages: Vec<RustMessage> = dbc
+        .messages()
+        .iter()
+        .map(|m| RustMessage::new(dbc, m))
+        .collect();
+    let tables: Vec<(String, &Vec<(i64, String)>)> = dbc
+        .value_tables()
+        .iter()
+        .map(|t| (type_name(t.name()), t.descriptions()))
+        .collect();
+
+    let mut types: Vec<(&str, &str)> = vec![("CanError", "CanError"), ("Messages", "Messages")];
+    for message in &messages {
+        check_layout(message.message)?;
+        let original = message.message.message_name();
+        types.push((&message.name, original));
+        for signal in message.all_signals() {
+            if let Kind::Enum(name) = &signal.kind {
+                types.push((name, signal.signal.name()));
+            }
+        }
+        if let Some(mux) = &message.mux {
+            // 复用器的值按整数匹配
+            if mux.signal.is_float() {
+                return Err(format!(
+                    "Multiplexor {} is a float signal in message {original}",
+                    mux.signal.signal.name()
+                ));
+            }
+            types.push((&mux.name, mux.signal.signal.name()));
+            types.extend(mux.groups.iter().map(|g| (g.name.as_str(), original)));
+        }
+        message.check_members()?;
+    }
+    types.extend(tables.iter().map(|(n, _)| (n.as_str(), n.as_str())));
+    check_unique(types, "type names")?;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "// Generated by roxy-dbc from {}. Do not edit.\n\
+         //\n\
+         // Include this file in a module:\n\
+         //     mod network {{ include!(concat!(env!(\"OUT_DIR\"), \"/network.rs\")); }}\n",
+        name.replace(['\r', '\n'], " ")
+    );
+    write_messages_enum(&mut out, &messages);
+    for message in &messages {
+        write_message(&mut out, message);
+    }
+    for (table, descriptions) in &tables {
+        let title = format!("Value table {table}.");
+        write_value_enum(
+            &mut out,
+            &title,
+            table,
+            "i64",
+            (i64::MIN as i128, i64::MAX as i128),
+            descriptions,
+        );
+    }
+    write_error(&mut out);
+    if messages.iter().any(|m| !m.message.signals().is_empty()) {
+        out.push_str(BIT_HELPERS);
+    }
+    Ok(out)
+}
+
+/// 读取 DBC 文件（任意支持的格式），生成 Rust 模块写入 output，供 build.rs 使用
+///
+/// ```ignore
+/// // build.rs
+/// fn main() {
+///     println!("cargo:rerun-if-changed=network.dbc");
+///     let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("network.rs");
+///     roxy_dbc::codegen::generate_rust_file("network.dbc".as_ref(), &out).unwrap();
+/// }
+/// ```
+pub fn generate_rust_file(input: &Path, output: &Path) -> Result<(), String> {
+    let dbc = load_file(input)?;
+    let name = input.file_name().map_or_else(
+        || input.display().to_string(),
+        |n| n.to_string_lossy().into_owned(),
+    );
+    let code = generate_rust(&dbc, &name)?;
+    fs::write(output, code).map_err(|e| format!("Failed to save file: {}: {e}", output.display()))
+}
+
+// 蛇形命名的方法名前缀，以数字开头时前面加 signal_
+// 不能以 _ 开头，否则 set__1 这样的方法名不符合 snake_case
+fn member_name(name: &str) -> String {
+    let name = snake_case(name);
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("signal_{name}")
+    } else {
+        name
+    }
+}
+
+// 物理值 getter 的名字，关键字使用原始标识符，不能作为原始标识符的关键字和内置方法名后面加 _
+fn getter_name(name: &str) -> String {
+    if ["self", "super", "crate"].contains(&name) || BUILTIN_MEMBERS.contains(&name) {
+        format!("{name}_")
+    } else if RUST_KEYWORDS.contains(&name) {
+        format!("r#{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+fn type_name(name: &str) -> String {
+    let mut name = camel_case(name);
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    // 关键字都是小写，只有 Self 会冲突
+    if name == "Self" {
+        name.push('_');
+    }
+    name
+}
+
+enum Kind {
+    Float,
+    Integer,
+    Bool,
+    Enum(String),
+}
+
+struct RustSignal<'a> {
+    signal: &'a EditableSignal,
+    // 方法名前缀，如 speed_raw、set_speed 中的 speed
+    name: String,
+    // 物理值 getter 的名字
+    getter: String,
+    raw_type: &'static str,
+    width: u32,
+    kind: Kind,
+}
+
+struct MuxGroup<'a> {
+    value: u64,
+    // 变体的结构体名，如 MuxedMuxM0
+    name: String,
+    signals: Vec<RustSignal<'a>>,
+}
+
+struct RustMux<'a> {
+    signal: RustSignal<'a>,
+    // 枚举名，如 MuxedMux
+    name: String,
+    groups: Vec<MuxGroup<'a>>,
+}
+
+struct RustMessage<'a> {
+    message: &'a EditableMessage,
+    name: String,
+    signals: Vec<RustSignal<'a>>,
+    mux: Option<RustMux<'a>>,
+    cycle_time: Option<u64>,
+}
+
+impl<'a> RustSignal<'a> {
+    fn new(message_type: &str, signal: &'a EditableSignal) -> Self {
+        let width = raw_width(signal);
+        let signed = is_signed(signal);
+        let raw_type = match (float_width(signal), signed, width) {
+            (Some(32), _, _) => "f32",
+            (Some(_), _, _) => "f64",
+            (None, false, 8) => "u8",
+            (None, false, 16) => "u16",
+            (None, false, 32) => "u32",
+            (None, false, _) => "u64",
+            (None, true, 8) => "i8",
+            (None, true, 16) => "i16",
+            (None, true, 32) => "i32",
+            (None, true, _) => "i64",
+        };
+        let identity = signal.factor() == 1.0 && signal.offset() == 0.0;
+        // 浮点信号的值描述无法按整数匹配，物理值也用 f64
+        let kind = if float_width(signal).is_some() {
+            Kind::Float
+        } else if !signal.value_descriptions().is_empty() {
+            Kind::Enum(format!("{message_type}{}", type_name(signal.name())))
+        } else if identity && !signed && signal.signal_size() == 1 {
+            Kind::Bool
+        } else if identity {
+            Kind::Integer
+        } else {
+            Kind::Float
+        };
+        let name = member_name(signal.name());
+        RustSignal {
+            signal,
+            getter: getter_name(&name),
+            name,
+            raw_type,
+            width,
+            kind,
+        }
+    }
+
+    fn is_signed(&self) -> bool {
+        is_signed(self.signal)
+    }
+
+    fn is_float(&self) -> bool {
+        float_width(self.signal).is_some()
+    }
+
+    fn physical_type(&self) -> &str {
+        match &self.kind {
+            Kind::Float => "f64",
+            Kind::Integer => self.raw_type,
+            Kind::Bool => "bool",
+            Kind::Enum(name) => name,
+        }
+    }
+
+    fn upper(&self) -> String {
+        self.name.trim_matches('_').to_uppercase()
+    }
+
+    fn bits_args(&self) -> String {
+        let little_endian = *self.signal.byte_order() == ByteOrder::LittleEndian;
+        format!(
+            "{}, {}, {little_endian}",
+            self.signal.start_bit(),
+            self.signal.signal_size()
+        )
+    }
+
+    // 原始值转换为 u64，Signed 按补码扩展，写入时只保留信号长度内的位，浮点数取位模式
+    fn to_u64(&self, value: &str) -> String {
+        match self.raw_type {
+            "f32" => return format!("u64::from({value}.to_bits())"),
+            "f64" => return format!("{value}.to_bits()"),
+            _ => {}
+        }
+        match (self.is_signed(), self.width) {
+            (false, 64) => value.to_string(),
+            (false, _) => format!("u64::from({value})"),
+            (true, _) => format!("{value} as u64"),
+        }
+    }
+
+    /// 物理范围换算成的整数范围，没有给出范围时为 None
+    fn integer_range(&self) -> Option<(i128, i128)> {
+        let s = self.signal;
+        if s.min() >= s.max() {
+            return None;
+        }
+        let (low, high) = raw_bounds(s);
+        Some((
+            (s.min().ceil() as i128).clamp(low, high),
+            (s.max().floor() as i128).clamp(low, high),
+        ))
+    }
+
+    fn members(&self) -> Vec<String> {
+        let name = &self.name;
+        vec![
+            self.getter.clone(),
+            format!("{name}_raw"),
+            format!("set_{name}"),
+            format!("set_{name}_raw"),
+        ]
+    }
+}
+
+impl<'a> RustMessage<'a> {
+    fn new(dbc: &EditableDbc, message: &'a EditableMessage) -> Self {
+        let name = type_name(message.message_name());
+        let multiplexor = message.multiplexor().map(|m| RustSignal::new(&name, m));
+
+        let mut signals = Vec::new();
+        let mut groups: Vec<MuxGroup> = Vec::new();
+        for signal in message.signals() {
+            if Some(signal.name()) == multiplexor.as_ref().map(|m| m.signal.name()) {
+                continue;
+            }
+            let rust_signal = RustSignal::new(&name, signal);
+            match (signal.multiplexer_indicator(), &multiplexor) {
+                (MultiplexIndicator::MultiplexedSignal(value), Some(multiplexor)) => {
+                    let (low, high) = raw_bounds(multiplexor.signal);
+                    // 复用器取不到的值对应的信号永远不会出现
+                    if !(low..=high).contains(&(*value as i128)) {
+                        continue;
+                    }
+                    match groups.iter_mut().find(|g| g.value == *value) {
+                        Some(group) => group.signals.push(rust_signal),
+                        None => groups.push(MuxGroup {
+                            value: *value,
+                            name: String::new(),
+                            signals: vec![rust_signal],
+                        }),
+                    }
+                }
+                _ => signals.push(rust_signal),
+            }
+        }
+        groups.sort_by_key(|g| g.value);
+
+        let mux = match multiplexor {
+            Some(multiplexor) if !groups.is_empty() => {
+                let mux_name = format!("{name}{}", type_name(multiplexor.signal.name()));
+                for group in &mut groups {
+                    group.name = format!("{mux_name}M{}", group.value);
+                }
+                Some(RustMux {
+                    signal: multiplexor,
+                    name: mux_name,
+                    groups,
+                })
+            }
+            // 没有被复用的信号时复用器按普通信号处理
+            Some(multiplexor) => {
+                signals.insert(0, multiplexor);
+                None
+            }
+            None => None,
+        };
+
+        RustMessage {
+            message,
+            name,
+            signals,
+            mux,
+            cycle_time: dbc.message_cycle_time(message.message_id()),
+        }
+    }
+
+    fn all_signals(&self) -> impl Iterator<Item = &RustSignal<'a>> {
+        let grouped = self
+            .mux
+            .iter()
+            .flat_map(|m| m.groups.iter().flat_map(|g| g.signals.iter()));
+        self.signals.iter().chain(grouped)
+    }
+
+    fn check_members(&self) -> Result<(), String> {
+        let mut members: Vec<String> = BUILTIN_MEMBERS.iter().map(|m| m.to_string()).collect();
+        for signal in &self.signals {
+            members.extend(signal.members());
+        }
+        if let Some(mux) = &self.mux {
+            let name = &mux.signal.name;
+            members.push(mux.signal.getter.clone());
+            members.push(format!("{name}_raw"));
+            members.push(format!("set_{name}_raw"));
+            members.extend(
+                mux.groups
+                    .iter()
+                    .map(|g| format!("set_{name}_m{}", g.value)),
+            );
+        }
+        let scope = format!("message {}", self.message.message_name());
+        check_unique(members.iter().map(|m| (m.as_str(), m.as_str())), &scope)?;
+
+        if let Some(mux) = &self.mux {
+            for group in &mux.groups {
+                let members: Vec<String> = std::iter::once("new".to_string())
+                    .chain(group.signals.iter().flat_map(|s| s.members()))
+                    .collect();
+                check_unique(members.iter().map(|m| (m.as_str(), m.as_str())), &scope)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn length(&self) -> u64 {
+        self.message.message_size()
+    }
+}
+
+fn float_literal(value: f64) -> String {
+    format!("{value:?}")
+}
+
+fn int_literal(value: i128) -> String {
+    value.to_string()
+}
+
+// 整数范围检查，与类型边界相同的一侧总是成立，省略以免 clippy 报告无意义的比较
+fn range_condition(value: &str, range: (i128, i128), type_range: (i128, i128)) -> Option<String> {
+    let (low, high) = range;
+    match (low > type_range.0, high < type_range.1) {
+        (true, true) => Some(format!(
+            "!({}..={}).contains(&{value})",
+            int_literal(low),
+            int_literal(high)
+        )),
+        (true, false) => Some(format!("{value} < {}", int_literal(low))),
+        (false, true) => Some(format!("{value} > {}", int_literal(high))),
+        (false, false) => None,
+    }
+}
+
+fn doc_lines(out: &mut String, indent: &str, text: &str) {
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            let _ = writeln!(out, "{indent}///");
+        } else {
+            let _ = writeln!(out, "{indent}/// {line}");
+        }
+    }
+}
+
+fn out_of_range(message: &RustMessage, signal: &RustSignal) -> String {
+    format!(
+        "return Err(CanError::ParameterOutOfRange {{\n                message_id: {}::MESSAGE_ID,\n                signal: {:?},\n            }});",
+        message.name,
+        signal.signal.name()
+    )
+}
+
+fn write_messages_enum(out: &mut String, messages: &[RustMessage]) {
+    out.push_str("/// All messages in the DBC.\n");
+    out.push_str("#[derive(Clone, Copy, Debug, PartialEq)]\npub enum Messages {\n");
+    for message in messages {
+        let _ = writeln!(out, "    {0}({0}),", message.name);
+    }
+    out.push_str("}\n\nimpl Messages {\n");
+    out.push_str(
+        "    /// Decode a payload by CAN ID (without the extended frame flag).\n    \
+         ///\n    \
+         /// The payload may be longer than the message, e.g. padded to a CAN FD length.\n",
+    );
+    let _ = writeln!(
+        out,
+        "    pub fn from_can_message(id: u32, extended: bool, payload: &[u8]) -> {RESULT}<Self, CanError> {{"
+    );
+    // 重复的 ID 只保留第一个，避免生成不可达的分支
+    let mut seen = Vec::new();
+    let arms: Vec<&RustMessage> = messages
+        .iter()
+        .filter(|m| {
+            let key = (
+                m.message.message_id() & CAN_ID_MASK,
+                m.message.frame_format(),
+            );
+            let is_new = !seen.contains(&key);
+            seen.push(key);
+            is_new
+        })
+        .collect();
+    if arms.is_empty() {
+        out.push_str(
+            "        let _ = (extended, payload);\n        Err(CanError::UnknownMessageId(id))\n",
+        );
+    } else {
+        out.push_str("        match (id, extended) {\n");
+        for message in arms {
+            let _ = writeln!(
+                out,
+                "            ({0}::MESSAGE_ID, {0}::IS_EXTENDED) => Ok(Self::{0}({0}::try_from(payload)?)),",
+                message.name
+            );
+        }
+        out.push_str("            _ => Err(CanError::UnknownMessageId(id)),\n        }\n");
+    }
+    out.push_str("    }\n}\n\n");
+}
+
+fn write_message(out: &mut String, message: &RustMessage) {
+    let m = message.message;
+    let name = &message.name;
+    let length = message.length();
+    let id = m.message_id() & CAN_ID_MASK;
+    let extended = m.frame_format() == FrameFormat::Extended;
+
+    // 数据类型
+    for signal in message.all_signals() {
+        if let Kind::Enum(enum_name) = &signal.kind {
+            let title = format!("Values of {}.{}.", m.message_name(), signal.signal.name());
+            write_value_enum(
+                out,
+                &title,
+                enum_name,
+                signal.raw_type,
+                type_bounds(signal.is_signed(), signal.width),
+                signal.signal.value_descriptions(),
+            );
+        }
+    }
+
+    let _ = writeln!(out, "/// {}\n///", m.message_name());
+    let _ = writeln!(
+        out,
+        "/// - ID: 0x{id:X} ({id}){}",
+        if extended { ", extended" } else { "" }
+    );
+    let _ = writeln!(
+        out,
+        "/// - Size: {length} bytes{}",
+        if m.is_fd() { ", CAN FD" } else { "" }
+    );
+    if !m.transmitter().is_empty() {
+        let _ = writeln!(out, "/// - Transmitter: {}", m.transmitter());
+    }
+    if !m.comment().is_empty() {
+        out.push_str("///\n");
+        doc_lines(out, "", m.comment());
+    }
+    let _ = writeln!(
+        out,
+        "#[derive(Clone, Copy, PartialEq, Eq)]\npub struct {name} {{\n    raw: [u8; {length}],\n}}\n"
+    );
+
+    let _ = writeln!(out, "impl {name} {{");
+    let _ = writeln!(out, "    pub const MESSAGE_ID: u32 = 0x{id:X};");
+    let _ = writeln!(out, "    pub const IS_EXTENDED: bool = {extended};");
+    let _ = writeln!(out, "    pub const IS_FD: bool = {};", m.is_fd());
+    let _ = writeln!(out, "    pub const LENGTH: usize = {length};");
+    if let Some(cycle_time) = message.cycle_time {
+        let _ = writeln!(out, "    pub const CYCLE_TIME_MS: u64 = {cycle_time};");
+    }
+    write_range_consts(out, message.all_signals());
+    let _ = writeln!(
+        out,
+        "\n    /// Create a message with all signals set to raw 0.\n    \
+         pub const fn new() -> Self {{\n        Self {{ raw: [0; {length}] }}\n    }}\n"
+    );
+    let _ = writeln!(
+        out,
+        "    /// The payload.\n    pub fn raw(&self) -> &[u8; {length}] {{\n        &self.raw\n    }}\n"
+    );
+    let _ = writeln!(
+        out,
+        "    /// Consume the message and return the payload.\n    \
+         pub fn into_bytes(self) -> [u8; {length}] {{\n        self.raw\n    }}"
+    );
+    for signal in &message.signals {
+        write_accessors(out, message, signal);
+    }
+    if let Some(mux) = &message.mux {
+        write_mux_accessors(out, message, mux);
+    }
+    out.push_str("}\n\n");
+
+    write_common_impls(out, name, &message.signals, message.mux.as_ref());
+
+    let _ = writeln!(
+        out,
+        "impl core::convert::TryFrom<&[u8]> for {name} {{\n    \
+         type Error = CanError;\n\n    \
+         /// Read the message from the start of the payload.\n    \
+         fn try_from(payload: &[u8]) -> {RESULT}<Self, Self::Error> {{"
+    );
+    // 长度为 0 时比较总是成立
+    if length > 0 {
+        out.push_str(
+            "        if payload.len() < Self::LENGTH {\n            \
+             return Err(CanError::InvalidPayloadSize {\n                \
+             expected: Self::LENGTH,\n                \
+             actual: payload.len(),\n            \
+             });\n        \
+             }\n",
+        );
+    }
+    let _ = writeln!(
+        out,
+        "        let mut raw = [0; {length}];\n        \
+         raw.copy_from_slice(&payload[..Self::LENGTH]);\n        \
+         Ok(Self {{ raw }})\n    }}\n}}\n"
+    );
+
+    if let Some(mux) = &message.mux {
+        write_mux_types(out, message, mux);
+    }
+}
+
+fn write_range_consts<'a, 'b: 'a>(
+    out: &mut String,
+    signals: impl Iterator<Item = &'a RustSignal<'b>>,
+) {
+    for signal in signals {
+        let s = signal.signal;
+        let upper = signal.upper();
+        match signal.kind {
+            Kind::Float if s.min() < s.max() => {
+                let _ = writeln!(
+                    out,
+                    "    pub const {upper}_MIN: f64 = {};\n    pub const {upper}_MAX: f64 = {};",
+                    float_literal(s.min()),
+                    float_literal(s.max())
+                );
+            }
+            Kind::Integer => {
+                if let Some((low, high)) = signal.integer_range() {
+                    let ty = signal.raw_type;
+                    let _ = writeln!(
+                        out,
+                        "    pub const {upper}_MIN: {ty} = {};\n    pub const {upper}_MAX: {ty} = {};",
+                        int_literal(low),
+                        int_literal(high)
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn write_signal_doc(out: &mut String, signal: &RustSignal) {
+    let s = signal.signal;
+    let _ = writeln!(out, "\n    /// {}\n    ///", s.name());
+    if !s.comment().is_empty() {
+        doc_lines(out, "    ", s.comment());
+        out.push_str("    ///\n");
+    }
+    if s.min() < s.max() {
+        let unit = if s.unit().is_empty() {
+            String::new()
+        } else {
+            format!(" {}", s.unit())
+        };
+        let _ = writeln!(out, "    /// - Range: {}..={}{unit}", s.min(), s.max());
+    } else if !s.unit().is_empty() {
+        let _ = writeln!(out, "    /// - Unit: {}", s.unit());
+    }
+    let _ = writeln!(
+        out,
+        "    /// - Factor: {}, offset: {}",
+        s.factor(),
+        s.offset()
+    );
+    let _ = writeln!(
+        out,
+        "    /// - Start bit: {}, size: {}, {}, {}",
+        s.start_bit(),
+        s.signal_size(),
+        if *s.byte_order() == ByteOrder::LittleEndian {
+            "Intel"
+        } else {
+            "Motorola"
+        },
+        match signal.raw_type {
+            "f32" => "float32",
+            "f64" => "float64",
+            _ if signal.is_signed() => "signed",
+            _ => "unsigned",
+        }
+    );
+}
+
+fn write_raw_getter(out: &mut String, signal: &RustSignal) {
+    let (name, ty) = (&signal.name, signal.raw_type);
+    let size = signal.signal.signal_size();
+    let read = format!("read_bits(&self.raw, {})", signal.bits_args());
+    let body = match (signal.is_signed(), ty) {
+        (_, "f32") => format!("f32::from_bits({read} as u32)"),
+        (_, "f64") => format!("f64::from_bits({read})"),
+        (false, "u64") => read,
+        (true, _) if size < signal.width as u64 => {
+            // 符号扩展：把符号位移到最高位后算术右移
+            let shift = 64 - size;
+            let extended = format!("(raw << {shift}) as i64 >> {shift}");
+            if ty == "i64" {
+                format!("let raw = {read};\n        {extended}")
+            } else {
+                format!("let raw = {read};\n        ({extended}) as {ty}")
+            }
+        }
+        _ => format!("{read} as {ty}"),
+    };
+    let _ = writeln!(
+        out,
+        "    pub fn {name}_raw(&self) -> {ty} {{\n        {body}\n    }}"
+    );
+}
+
+fn write_raw_setter(out: &mut String, message: &RustMessage, signal: &RustSignal) {
+    let (name, ty) = (&signal.name, signal.raw_type);
+    let _ = writeln!(
+        out,
+        "\n    /// Set the raw value of {}.\n    \
+         pub fn set_{name}_raw(&mut self, value: {ty}) -> {RESULT}<(), CanError> {{",
+        signal.signal.name()
+    );
+    let type_range = type_bounds(signal.is_signed(), signal.width);
+    let condition = if signal.is_float() {
+        None
+    } else {
+        range_condition("value", raw_bounds(signal.signal), type_range)
+    };
+    if let Some(condition) = condition {
+        let _ = writeln!(
+            out,
+            "        if {condition} {{\n            {}\n        }}",
+            out_of_range(message, signal)
+        );
+    }
+    let _ = writeln!(
+        out,
+        "        write_bits(&mut self.raw, {}, {});\n        Ok(())\n    }}",
+        signal.bits_args(),
+        signal.to_u64("value")
+    );
+}
+
+fn write_accessors(out: &mut String, message: &RustMessage, signal: &RustSignal) {
+    let s = signal.signal;
+    let name = &signal.name;
+    let ty = signal.physical_type();
+    write_signal_doc(out, signal);
+
+    // 物理值 getter
+    let getter = match &signal.kind {
+        Kind::Float => {
+            let mut expression = match signal.raw_type {
+                "f32" => format!("f64::from(self.{name}_raw())"),
+                "f64" => format!("self.{name}_raw()"),
+                _ => format!("self.{name}_raw() as f64"),
+            };
+            if s.factor() != 1.0 {
+                expression += &format!(" * {}", float_literal(s.factor()));
+            }
+            if s.offset() < 0.0 {
+                expression += &format!(" - {}", float_literal(-s.offset()));
+            } else if s.offset() != 0.0 {
+                expression += &format!(" + {}", float_literal(s.offset()));
+            }
+            expression
+        }
+        Kind::Integer => format!("self.{name}_raw()"),
+        Kind::Bool => format!("self.{name}_raw() == 1"),
+        Kind::Enum(enum_name) => format!("{enum_name}::from(self.{name}_raw())"),
+    };
+    let _ = writeln!(
+        out,
+        "    pub fn {}(&self) -> {ty} {{\n        {getter}\n    }}",
+        signal.getter
+    );
+
+    // 物理值 setter，先按 DBC 中的 min / max 检查，再按信号位宽检查
+    let _ = writeln!(out, "\n    /// Set {}.", s.name());
+    match &signal.kind {
+        Kind::Float => {
+            let _ = writeln!(
+                out,
+                "    pub fn set_{name}(&mut self, value: f64) -> {RESULT}<(), CanError> {{"
+            );
+            if s.min() < s.max() {
+                let _ = writeln!(
+                    out,
+                    "        if !({}..={}).contains(&value) {{\n            {}\n        }}",
+                    float_literal(s.min()),
+                    float_literal(s.max()),
+                    out_of_range(message, signal)
+                );
+            }
+            let mut expression = "value".to_string();
+            if s.offset() < 0.0 {
+                expression = format!("(value + {})", float_literal(-s.offset()));
+            } else if s.offset() != 0.0 {
+                expression = format!("(value - {})", float_literal(s.offset()));
+            }
+            if s.factor() != 1.0 {
+                expression = format!("({expression} / {})", float_literal(s.factor()));
+            }
+            // 浮点信号不取整，超出 f32 范围的有限值会变成无穷大
+            // 直接使用表达式时去掉最外层的括号，以免 unused_parens 警告
+            let unwrapped = expression
+                .strip_prefix('(')
+                .and_then(|e| e.strip_suffix(')'))
+                .unwrap_or(&expression);
+            match signal.raw_type {
+                "f32" => {
+                    let _ = writeln!(
+                        out,
+                        "        let raw = {unwrapped};\n        \
+                         if raw.is_finite() && (raw as f32).is_infinite() {{\n            {}\n        }}\n        \
+                         self.set_{name}_raw(raw as f32)\n    }}",
+                        out_of_range(message, signal)
+                    );
+                }
+                "f64" => {
+                    let _ = writeln!(out, "        self.set_{name}_raw({unwrapped})\n    }}");
+                }
+                ty => {
+                    let (low, high) = raw_bounds(s);
+                    let _ = writeln!(
+                        out,
+                        "        let raw = {expression}.round();\n        \
+                         if !({}..={}).contains(&raw) {{\n            {}\n        }}\n        \
+                         self.set_{name}_raw(raw as {ty})\n    }}",
+                        float_literal(low as f64),
+                        float_literal(high as f64),
+                        out_of_range(message, signal)
+                    );
+                }
+            }
+        }
+        Kind::Integer => {
+            let _ = writeln!(
+                out,
+                "    pub fn set_{name}(&mut self, value: {ty}) -> {RESULT}<(), CanError> {{"
+            );
+            let type_range = type_bounds(signal.is_signed(), signal.width);
+            if let Some(condition) = signal
+                .integer_range()
+                .and_then(|range| range_condition("value", range, type_range))
+            {
+                let _ = writeln!(
+                    out,
+                    "        if {condition} {{\n            {}\n        }}",
+                    out_of_range(message, signal)
+                );
+            }
+            let _ = writeln!(out, "        self.set_{name}_raw(value)\n    }}");
+        }
+        Kind::Bool => {
+            let _ = writeln!(
+                out,
+                "    pub fn set_{name}(&mut self, value: bool) {{\n        \
+                 write_bits(&mut self.raw, {}, u64::from(value));\n    }}",
+                signal.bits_args()
+            );
+        }
+        Kind::Enum(_) => {
+            let _ = writeln!(
+                out,
+                "    pub fn set_{name}(&mut self, value: {ty}) -> {RESULT}<(), CanError> {{\n        \
+                 self.set_{name}_raw(value.into())\n    }}"
+            );
+        }
+    }
+
+    let _ = writeln!(out, "\n    /// Raw value of {}.", s.name());
+    write_raw_getter(out, signal);
+    write_raw_setter(out, message, signal);
+}
+
+fn write_mux_accessors(out: &mut String, message: &RustMessage, mux: &RustMux) {
+    let signal = &mux.signal;
+    let name = &signal.name;
+    write_signal_doc(out, signal);
+    let _ = writeln!(
+        out,
+        "    ///\n    /// The multiplexor, selects which signals are present.\n    \
+         pub fn {}(&self) -> {RESULT}<{}, CanError> {{\n        match self.{name}_raw() {{",
+        signal.getter, mux.name
+    );
+    for group in &mux.groups {
+        let _ = writeln!(
+            out,
+            "            {} => Ok({}::M{}({} {{ raw: self.raw }})),",
+            group.value, mux.name, group.value, group.name
+        );
+    }
+    let _ = writeln!(
+        out,
+        "            multiplexor => Err(CanError::InvalidMultiplexor {{\n                \
+         message_id: Self::MESSAGE_ID,\n                \
+         multiplexor: {},\n            }}),\n        }}\n    }}",
+        signal.to_u64("multiplexor")
+    );
+
+    let _ = writeln!(out, "\n    /// Raw value of {}.", signal.signal.name());
+    write_raw_getter(out, signal);
+    write_raw_setter(out, message, signal);
+
+    for group in &mux.groups {
+        let _ = writeln!(
+            out,
+            "\n    /// Set {} to {} and copy the signals multiplexed by it.\n    \
+             pub fn set_{name}_m{}(&mut self, value: {}) -> {RESULT}<(), CanError> {{",
+            signal.signal.name(),
+            group.value,
+            group.value,
+            group.name
+        );
+        // 先清除所有被复用的信号，再写入这一组
+        for other in mux.groups.iter().flat_map(|g| g.signals.iter()) {
+            let _ = writeln!(
+                out,
+                "        write_bits(&mut self.raw, {}, 0);",
+                other.bits_args()
+            );
+        }
+        for member in &group.signals {
+            let args = member.bits_args();
+            let _ = writeln!(
+                out,
+                "        write_bits(&mut self.raw, {args}, read_bits(&value.raw, {args}));"
+            );
+        }
+        let _ = writeln!(out, "        self.set_{name}_raw({})\n    }}", group.value);
+    }
+}
+
+fn write_mux_types(out: &mut String, message: &RustMessage, mux: &RustMux) {
+    let m = message.message;
+    let length = message.length();
+    let _ = writeln!(
+        out,
+        "/// Signals of {} selected by {}.\n#[derive(Clone, Copy, Debug, PartialEq)]\npub enum {} {{",
+        m.message_name(),
+        mux.signal.signal.name(),
+        mux.name
+    );
+    for group in &mux.groups {
+        let _ = writeln!(out, "    M{}({}),", group.value, group.name);
+    }
+    out.push_str("}\n\n");
+
+    for group in &mux.groups {
+        let name = &group.name;
+        let _ = writeln!(
+            out,
+            "/// Signals of {} when {} is {}.\n\
+             #[derive(Clone, Copy, PartialEq, Eq)]\npub struct {name} {{\n    raw: [u8; {length}],\n}}\n",
+            m.message_name(),
+            mux.signal.signal.name(),
+            group.value
+        );
+        let _ = writeln!(out, "impl {name} {{");
+        write_range_consts(out, group.signals.iter());
+        if group
+            .signals
+            .iter()
+            .any(|s| matches!(s.kind, Kind::Float | Kind::Integer))
+        {
+            out.push('\n');
+        }
+        let _ = writeln!(
+            out,
+            "    /// Create the signals with all raw values 0.\n    \
+             pub const fn new() -> Self {{\n        Self {{ raw: [0; {length}] }}\n    }}"
+        );
+        for signal in &group.signals {
+            write_accessors(out, message, signal);
+        }
+        out.push_str("}\n\n");
+        write_common_impls(out, name, &group.signals, None);
+    }
+}
+
+fn write_common_impls(out: &mut String, name: &str, signals: &[RustSignal], mux: Option<&RustMux>) {
+    let _ = writeln!(
+        out,
+        "impl core::default::Default for {name} {{\n    fn default() -> Self {{\n        Self::new()\n    }}\n}}\n"
+    );
+    let _ = writeln!(
+        out,
+        "impl core::fmt::Debug for {name} {{\n    \
+         fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{\n        \
+         f.debug_struct({name:?})"
+    );
+    for signal in signals {
+        let _ = writeln!(
+            out,
+            "            .field({:?}, &self.{}())",
+            signal.name, signal.getter
+        );
+    }
+    if let Some(mux) = mux {
+        let _ = writeln!(
+            out,
+            "            .field({:?}, &self.{}())",
+            mux.signal.name, mux.signal.getter
+        );
+    }
+    out.push_str("            .finish()\n    }\n}\n\n");
+}
+
+fn write_value_enum(
+    out: &mut String,
+    title: &str,
+    name: &str,
+    raw_type: &str,
+    type_range: (i128, i128),
+    descriptions: &[(i64, String)],
+) {
+    // 放不下的值和重复的值跳过，变体名重复时加上原始值
+    let mut variants: Vec<(i64, String, &str)> = Vec::new();
+    let mut skipped = Vec::new();
+    for (raw, text) in descriptions {
+        let value = *raw as i128;
+        if value < type_range.0 || value > type_range.1 || variants.iter().any(|(r, _, _)| r == raw)
+        {
+            skipped.push((*raw, text));
+            continue;
+        }
+        let raw_text = if *raw < 0 {
+            format!("M{}", raw.unsigned_abs())
+        } else {
+            raw.to_string()
+        };
+        let mut variant = type_name(text);
+        if variant.is_empty() {
+            variant = format!("Value{raw_text}");
+        }
+        if variants.iter().any(|(_, v, _)| *v == variant) {
+            variant += &raw_text;
+        }
+        variants.push((*raw, variant, text));
+    }
+
+    let _ = writeln!(out, "/// {title}");
+    let _ = writeln!(
+        out,
+        "#[derive(Clone, Copy, Debug, PartialEq, Eq)]\npub enum {name} {{"
+    );
+    for (raw, variant, text) in &variants {
+        let _ = writeln!(out, "    /// {raw}: {}", text.replace(['\r', '\n'], " "));
+        let _ = writeln!(out, "    {variant},");
+    }
+    for (raw, text) in skipped {
+        let _ = writeln!(
+            out,
+            "    // {raw}: {} (duplicate or out of range)",
+            text.replace(['\r', '\n'], " ")
+        );
+    }
+    let _ = writeln!(
+        out,
+        "    /// A value without a description.\n    _Other({raw_type}),\n}}\n"
+    );
+
+    let _ = writeln!(
+        out,
+        "impl core::convert::From<{raw_type}> for {name} {{\n    fn from(raw: {raw_type}) -> Self {{\n        match raw {{"
+    );
+    for (raw, variant, _) in &variants {
+        let _ = writeln!(out, "            {raw} => Self::{variant},");
+    }
+    let _ = writeln!(
+        out,
+        "            _ => Self::_Other(raw),\n        }}\n    }}\n}}\n"
+    );
+
+    let _ = writeln!(
+        out,
+        "impl core::convert::From<{name}> for {raw_type} {{\n    fn from(value: {name}) -> Self {{\n        match value {{"
+    );
+    for (raw, variant, _) in &variants {
+        let _ = writeln!(out, "            {name}::{variant} => {raw},");
+    }
+    let _ = writeln!(
+        out,
+        "            {name}::_Other(raw) => raw,\n        }}\n    }}\n}}\n"
+    );
+}
+
+fn write_error(out: &mut String) {
+    out.push_str(
+        r#"/// Errors of the generated message types.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CanError {
+    /// The payload is shorter than the message.
+    InvalidPayloadSize { expected: usize, actual: usize },
+    /// A value is outside the range of a signal.
+    ParameterOutOfRange {
+        message_id: u32,
+        signal: &'static str,
+    },
+    /// The multiplexor has a value without multiplexed signals.
+    InvalidMultiplexor { message_id: u32, multiplexor: u64 },
+    /// No message has this CAN ID.
+    UnknownMessageId(u32),
+}
+
+impl core::fmt::Display for CanError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidPayloadSize { expected, actual } => {
+                write!(f, "payload has {actual} bytes, expected at least {expected}")
+            }
+            Self::ParameterOutOfRange { message_id, signal } => {
+                write!(f, "value out of range for signal {signal} of message 0x{message_id:X}")
+            }
+            Self::InvalidMultiplexor {
+                message_id,
+                multiplexor,
+            } => write!(
+                f,
+                "no signals for multiplexor value {multiplexor} of message 0x{message_id:X}"
+            ),
+            Self::UnknownMessageId(id) => write!(f, "unknown message ID 0x{id:X}"),
+        }
+    }
+}
+
+impl core::error::Error for CanError {}
+"#,
+    );
+}
+
+// 位编号与 DBC 相同：Intel 的起始位是最低位，Motorola 的起始位是最高位（锯齿编号）
+const BIT_HELPERS: &str = r#"
+/// Read `size` bits in DBC bit numbering, most significant bit first.
+fn read_bits(data: &[u8], start: u32, size: u32, little_endian: bool) -> u64 {
+    let mut position = if little_endian { start + size - 1 } else { start };
+    let mut raw = 0;
+    for i in 0..size {
+        raw = (raw << 1) | u64::from((data[(position / 8) as usize] >> (position % 8)) & 1);
+        if i + 1 < size {
+            position = next_bit(position, little_endian);
+        }
+    }
+    raw
+}
+
+/// Write the low `size` bits of `raw` in DBC bit numbering.
+fn write_bits(data: &mut [u8], start: u32, size: u32, little_endian: bool, raw: u64) {
+    let mut position = if little_endian { start + size - 1 } else { start };
+    for i in (0..size).rev() {
+        let mask = 1 << (position % 8);
+        if (raw >> i) & 1 == 1 {
+            data[(position / 8) as usize] |= mask;
+        } else {
+            data[(position / 8) as usize] &= !mask;
+        }
+        if i > 0 {
+            position = next_bit(position, little_endian);
+        }
+    }
+}
+
+/// The next lower bit; Motorola signals continue at bit 7 of the next byte.
+fn next_bit(position: u32, little_endian: bool) -> u32 {
+    if !little_endian && (position & 7) == 0 {
+        position + 15
+    } else {
+        position - 1
+    }
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use can_dbc::Dbc;
+    use std::process::Command;
+
+    const DBC: &str = r#"VERSION ""
+
+BU_: ECU
+
+BO_ 256 EngineData: 8 ECU
+ SG_ Speed : 0|16@1+ (0.5,0) [0|30000] "rpm" Vector__XXX
+ SG_ Temp : 16|8@1- (1,0) [-40|87] "degC" Vector__XXX
+ SG_ Pressure : 31|12@0+ (0.1,0) [0|409.5] "kPa" Vector__XXX
+ SG_ State : 32|3@1+ (1,0) [0|2] "" Vector__XXX
+ SG_ Torque : 47|13@0- (0.25,-100) [-1000|1000] "Nm" Vector__XXX
+
+BO_ 2566844672 Extended: 8 ECU
+ SG_ Counter : 0|4@1+ (1,0) [0|15] "" Vector__XXX
+ SG_ Flag : 4|1@1+ (1,0) [0|1] "" Vector__XXX
+ SG_ Big : 8|40@1+ (1,0) [0|0] "" Vector__XXX
+ SG_ Wide : 63|8@0- (1,0) [0|0] "" Vector__XXX
+
+BO_ 512 Muxed: 4 ECU
+ SG_ Mux M : 0|4@1+ (1,0) [0|1] "" Vector__XXX
+ SG_ A m0 : 15|16@0- (1,0) [0|0] "" Vector__XXX
+ SG_ B m1 : 8|8@1+ (2,1) [1|511] "" Vector__XXX
+
+BO_ 768 FdFrame: 64 ECU
+ SG_ Tail : 504|8@1+ (1,0) [0|0] "" Vector__XXX
+ SG_ Type : 7|16@0+ (1,0) [0|0] "" Vector__XXX
+
+BO_ 1024 Empty: 0 ECU
+
+BO_ 1280 Floats: 12 ECU
+ SG_ Ratio : 0|32@1- (1,0) [0|0] "" Vector__XXX
+ SG_ Precise : 39|64@0- (0.5,10) [0|100] "" Vector__XXX
+
+CM_ BO_ 256 "Engine status
+second line";
+BA_DEF_ BO_  "VFrameFormat" ENUM  "StandardCAN","ExtendedCAN","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","StandardCAN_FD","ExtendedCAN_FD";
+BA_DEF_ BO_  "GenMsgCycleTime" INT 0 65535;
+BA_DEF_DEF_  "VFrameFormat" "StandardCAN";
+BA_DEF_DEF_  "GenMsgCycleTime" 0;
+BA_ "GenMsgCycleTime" BO_ 256 100;
+BA_ "VFrameFormat" BO_ 768 14;
+BA_ "VFrameFormat" BO_ 1280 14;
+VAL_TABLE_ Gears 1 "First" 2 "Second" -1 "Reverse" ;
+VAL_ 256 State 0 "Off" 1 "Running" 2 "Fault" 3 "fault" ;
+SIG_VALTYPE_ 1280 Ratio : 1;
+SIG_VALTYPE_ 1280 Precise : 2;
+"#;
+
+    // 生成的代码和测试程序中的占位符替换为 Rust 编码得到的负载
+    const MAIN: &str = r#"#[allow(dead_code)]
+mod generated {
+    include!("generated.rs");
+}
+
+use generated::*;
+
+fn main() {
+    let mut engine = EngineData::new();
+    engine.set_speed(1234.5).unwrap();
+    engine.set_temp(-40).unwrap();
+    engine.set_pressure(301.7).unwrap();
+    engine.set_state(EngineDataState::Fault).unwrap();
+    engine.set_torque(-87.25).unwrap();
+    assert_eq!(engine.into_bytes(), ENGINE_DATA);
+    let engine = EngineData::try_from(&ENGINE_DATA[..]).unwrap();
+    assert_eq!(engine.speed(), 1234.5);
+    assert_eq!(engine.temp(), -40);
+    assert!((engine.pressure() - 301.7).abs() < 1e-9);
+    assert_eq!(engine.state(), EngineDataState::Fault);
+    assert_eq!(engine.torque(), -87.25);
+    assert_eq!(engine.torque_raw(), 51);
+    assert_eq!(EngineData::CYCLE_TIME_MS, 100);
+    assert_eq!((EngineData::TEMP_MIN, EngineData::TEMP_MAX), (-40, 87));
+    assert!(format!("{engine:?}").contains("speed: 1234.5"));
+
+    let mut engine = EngineData::new();
+    assert!(engine.set_speed(30000.5).is_err());
+    assert!(engine.set_temp(88).is_err());
+    assert!(engine.set_pressure_raw(4096).is_err());
+    assert!(engine.set_state(EngineDataState::_Other(8)).is_err());
+    assert_eq!(EngineDataState::from(3), EngineDataState::Fault3);
+    assert_eq!(EngineDataState::from(7), EngineDataState::_Other(7));
+    assert_eq!(u8::from(EngineDataState::Running), 1);
+    assert_eq!(Gears::from(-1), Gears::Reverse);
+    assert_eq!(
+        EngineData::try_from(&[0u8; 7][..]),
+        Err(CanError::InvalidPayloadSize { expected: 8, actual: 7 })
+    );
+
+    let mut extended = Extended::new();
+    extended.set_counter(9).unwrap();
+    extended.set_flag(true);
+    extended.set_big(1099511627775).unwrap();
+    extended.set_wide(-2).unwrap();
+    assert_eq!(extended.into_bytes(), EXTENDED);
+    assert!(extended.set_counter(16).is_err());
+    assert!(extended.set_big(1099511627776).is_err());
+    match Messages::from_can_message(0x18FEF100, true, &EXTENDED).unwrap() {
+        Messages::Extended(message) => {
+            assert_eq!(message.counter(), 9);
+            assert!(message.flag());
+            assert_eq!(message.big(), 1099511627775);
+            assert_eq!(message.wide(), -2);
+        }
+        other => panic!("{other:?}"),
+    }
+    assert_eq!(
+        Messages::from_can_message(0x18FEF100, false, &EXTENDED),
+        Err(CanError::UnknownMessageId(0x18FEF100))
+    );
+
+    let mut a = MuxedMuxM0::new();
+    a.set_a(-12345).unwrap();
+    let mut muxed = Muxed::new();
+    muxed.set_mux_m0(a).unwrap();
+    assert_eq!(muxed.into_bytes(), MUXED_0);
+    let mut b = MuxedMuxM1::new();
+    b.set_b(201.0).unwrap();
+    assert!(b.set_b(0.0).is_err());
+    muxed.set_mux_m1(b).unwrap();
+    assert_eq!(muxed.into_bytes(), MUXED_1);
+    match Muxed::try_from(&MUXED_0[..]).unwrap().mux().unwrap() {
+        MuxedMux::M0(signals) => assert_eq!(signals.a(), -12345),
+        other => panic!("{other:?}"),
+    }
+    assert_eq!(
+        Muxed::try_from(&[5u8, 0, 0, 0][..]).unwrap().mux(),
+        Err(CanError::InvalidMultiplexor { message_id: 0x200, multiplexor: 5 })
+    );
+
+    let mut fd = FdFrame::new();
+    fd.set_tail(170).unwrap();
+    fd.set_type(4660).unwrap();
+    assert_eq!(fd.r#type(), 4660);
+    assert_eq!(fd.into_bytes(), FD_FRAME);
+    assert!(FdFrame::IS_FD);
+
+    let mut floats = Floats::new();
+    floats.set_ratio(-0.75).unwrap();
+    floats.set_precise(42.5).unwrap();
+    assert_eq!(floats.into_bytes(), FLOATS);
+    assert_eq!(floats.ratio_raw(), -0.75f32);
+    assert_eq!(floats.precise_raw(), 65.0);
+    assert!(floats.set_ratio(1e300).is_err());
+    assert!(floats.set_precise(100.5).is_err());
+    let floats = Floats::try_from(&FLOATS[..]).unwrap();
+    assert_eq!((floats.ratio(), floats.precise()), (-0.75, 42.5));
+
+    assert_eq!(Empty::try_from(&[][..]).unwrap().into_bytes(), [0u8; 0]);
+}
+"#;
+
+    fn test_dbc() -> EditableDbc {
+        EditableDbc::from_dbc(&Dbc::try_from(DBC).unwrap())
+    }
+
+    // 主机上没有 rustc 时跳过
+    fn rustc(dir: &Path, args: &[&str]) -> bool {
+        let output = match Command::new("rustc")
+            .current_dir(dir)
+            .args(["--edition", "2024", "-D", "warnings"])
+            .args(args)
+            .output()
+        {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("skipping Rust compilation, rustc not available: {e}");
+                return false;
+            }
+        };
+        assert!(
+            output.status.success(),
+            "rustc failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        true
+    }
+
+    fn payload(dbc: &EditableDbc, message_id: u32, values: &[(&str, f64)]) -> String {
+        let data = dbc.get_message(message_id).unwrap().encode(values).unwrap();
+        let bytes: Vec<String> = data.iter().map(|b| format!("0x{b:02X}")).collect();
+        format!("[{}]", bytes.join(", "))
+    }
+
+    #[test]
+    fn test_generate_names() {
+        let code = generate_rust(&test_dbc(), "test.dbc").unwrap();
+        assert!(code.contains("pub struct EngineData {\n    raw: [u8; 8],\n}"));
+        assert!(code.contains("    pub const MESSAGE_ID: u32 = 0x18FEF100;"));
+        assert!(code.contains("    pub fn speed(&self) -> f64 {"));
+        assert!(code.contains("    pub fn temp(&self) -> i8 {"));
+        assert!(code.contains("    pub fn flag(&self) -> bool {"));
+        assert!(code.contains("    pub fn state(&self) -> EngineDataState {"));
+        assert!(code.contains("    pub fn set_mux_m1(&mut self, value: MuxedMuxM1)"));
+        // 关键字使用原始标识符
+        assert!(code.contains("    pub fn r#type(&self) -> u16 {"));
+        assert!(code.contains("/// Engine status\n/// second line\n"));
+        assert!(code.contains("    pub fn ratio_raw(&self) -> f32 {"));
+        assert!(code.contains("    pub fn set_precise_raw(&mut self, value: f64)"));
+
+        let mut dbc = test_dbc();
+        dbc.set_message_name(512, "Engine_Data");
+        let error = generate_rust(&dbc, "test.dbc").unwrap_err();
+        assert!(error.contains("Engine_Data"), "{error}");
+
+        // 复用器的值按整数匹配，不能是浮点信号
+        let text = format!("{DBC}SIG_VALTYPE_ 512 Mux : 1;\n")
+            .replace("Mux M : 0|4@1+", "Mux M : 0|32@1-")
+            .replace("A m0 : 15|16@0-", "A m0 : 39|16@0-")
+            .replace("B m1 : 8|8@1+", "B m1 : 32|8@1+")
+            .replace("BO_ 512 Muxed: 4", "BO_ 512 Muxed: 8");
+        let dbc = EditableDbc::from_dbc(&Dbc::try_from(text.as_str()).unwrap());
+        let error = generate_rust(&dbc, "test.dbc").unwrap_err();
+        assert!(error.contains("Multiplexor Mux"), "{error}");
+    }
+
+    #[test]
+    fn test_round_trip_with_rustc() {
+        let dbc = test_dbc();
+        let dir = temp_dir("codegen-rust-round-trip");
+        fs::write(
+            dir.join("generated.rs"),
+            generate_rust(&dbc, "test.dbc").unwrap(),
+        )
+        .unwrap();
+
+        let main = MAIN
+            .replace(
+                "ENGINE_DATA",
+                &payload(
+                    &dbc,
+                    256,
+                    &[
+                        ("Speed", 1234.5),
+                        ("Temp", -40.0),
+                        ("Pressure", 301.7),
+                        ("State", 2.0),
+                        ("Torque", -87.25),
+                    ],
+                ),
+            )
+            .replace(
+                "EXTENDED",
+                &payload(
+                    &dbc,
+                    0x98FE_F100,
+                    &[
+                        ("Counter", 9.0),
+                        ("Flag", 1.0),
+                        ("Big", 1099511627775.0),
+                        ("Wide", -2.0),
+                    ],
+                ),
+            )
+            .replace(
+                "MUXED_0",
+                &payload(&dbc, 512, &[("Mux", 0.0), ("A", -12345.0)]),
+            )
+            .replace(
+                "MUXED_1",
+                &payload(&dbc, 512, &[("Mux", 1.0), ("B", 201.0)]),
+            )
+            .replace(
+                "FD_FRAME",
+                &payload(&dbc, 768, &[("Tail", 170.0), ("Type", 4660.0)]),
+            )
+            .replace(
+                "FLOATS",
+                &payload(&dbc, 1280, &[("Ratio", -0.75), ("Precise", 42.5)]),
+            );
+        fs::write(dir.join("main.rs"), main).unwrap();
+
+        if !rustc(&dir, &["-o", "round_trip", "main.rs"]) {
+            return;
+        }
+        let output = Command::new(dir.join("round_trip")).output().unwrap();
+        assert!(
+            output.status.success(),
+            "round trip failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    #[test]
+    fn test_sample_files_compile() {
+        let dir = temp_dir("codegen-rust-samples");
+        for sample in [
+            "motbus",
+            "motbus_ext",
+            "motbus_ext_2",
+            "can_data_ev1",
+            "empty",
+        ] {
+            let file = format!("{sample}.rs");
+            generate_rust_file(
+                &Path::new("dbc-sample").join(format!("{sample}.dbc")),
+                &dir.join(&file),
+            )
+            .unwrap();
+            let output = format!("lib{sample}.rmeta");
+            if !rustc(
+                &dir,
+                &[
+                    "--crate-type",
+                    "lib",
+                    "--emit",
+                    "metadata",
+                    "-o",
+                    &output,
+                    &file,
+                ],
+            ) {
+                return;
+            }
+        }
+    }
+}
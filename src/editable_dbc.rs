@@ -1,12 +1,45 @@
 use can_dbc::{
-    ByteOrder, Dbc, Message, MessageId, MultiplexIndicator, Signal, Transmitter, ValueType,
+    AccessNode, AccessType, ByteOrder, Comment, Dbc, EnvType, EnvironmentVariable, Message,
+    MessageId, MultiplexIndicator, Signal, SignalExtendedValueType, Transmitter, ValDescription,
+    ValueDescription, ValueType,
 };
 
+mod arxml;
+mod attributes;
+mod can_fd;
+mod codec;
+mod diff;
+mod file_format;
+mod json;
+mod kcd;
+mod merge;
+mod validation;
+mod writer;
+mod xml;
+
+use writer::unescape;
+
+pub use attributes::{
+    AttributeDefinition, AttributeObjectType, AttributeTarget, AttributeValue, AttributeValueType,
+    AttributeValues, RemovedAttributeValue,
+};
+pub use can_fd::{check_signal_placement, is_valid_message_size, size_to_dlc};
+pub use codec::{DecodedSignal, signal_bits};
+pub(crate) use codec::{may_overlap, swap_bit_order};
+pub use diff::{DbcDiff, DiffKind, FieldChange, MessageDiff, SignalDiff};
+pub use file_format::{
+    ARXML_EXTENSION, FileFormat, NOT_UTF8_WARNING, is_arxml, load_arxml, load_arxml_clusters,
+    load_file, load_file_as, load_file_with_warnings, save_file, save_file_as,
+    save_file_with_warnings,
+};
+pub use merge::{MergeConflict, MergeResolutions, MergeResult, MergeSide};
+pub use validation::{Diagnostic, Severity};
+
 // 这个文件实现了一个可编辑的 Dbc 数据结构，支持基本的编辑操作和历史记录管理
 // 外部可以读取里面的属性，但是不可以编辑
 // 所有的编辑都是通过 EditableDbc 提供的方法来进行的，这些方法会记录操作历史以支持撤销和重做功能
 // EditableDbc 直接通过 Dbc 进行初始化，转换方式等价于 String -> Dbc -> EditableDbc
-// 将来会实现输出 Dbc 文件字符串的功能，即 EditableDbc -> String
+// 输出 Dbc 文件字符串的功能在 writer 子模块中实现，即 EditableDbc -> String
 
 // 整体的操作流程：先使用 can-dbc 库实现 String -> DBC
 // 然后通过 EditableDbc::from_dbc 将 DBC 转换为 EditableDbc
@@ -19,14 +52,16 @@ use can_dbc::{
 // 文件的读写交给上层管理
 
 // 这些都是原子化的操作
-// 在外部使用的时候，如一个窗口的更改
-// 需要外部记录每个复合操作有多少次
-// 然后在需要撤销的时候，调用多次 undo 即可
+// 在外部使用的时候，如一个窗口的更改会包含多个原子操作
+// 这时用 begin_transaction / commit_transaction 包起来
+// 期间的所有操作会合并为一个 Operation::Transaction，调用一次 undo 即可全部撤销
 
 #[allow(dead_code)]
 #[derive(Clone, Debug)]
 pub enum Operation {
+    // 修改 ID 和 Signal 名称后可能出现重复，index 记录目标的位置，撤销时修改的是同一个对象
     SetMessageId {
+        index: usize,
         old_id: u32,
         new_id: u32,
     },
@@ -57,6 +92,7 @@ pub enum Operation {
     },
     SetSignalName {
         message_id: u32,
+        index: usize,
         signal_old_name: String,
         signal_new_name: String,
     },
@@ -132,58 +168,486 @@ pub enum Operation {
         old_comment: String,
         new_comment: String,
     },
+    SetSignalValueDescriptions {
+        message_id: u32,
+        signal_name: String,
+        old_descriptions: Vec<(i64, String)>,
+        new_descriptions: Vec<(i64, String)>,
+    },
+    SetValueTableName {
+        old_name: String,
+        new_name: String,
+    },
+    SetValueTableDescriptions {
+        table_name: String,
+        old_descriptions: Vec<(i64, String)>,
+        new_descriptions: Vec<(i64, String)>,
+    },
+    // index 记录 Message/Signal 在列表中的位置，撤销删除时插回原来的位置
     AddMessage {
         message: EditableMessage,
+        index: usize,
     },
     AddSignal {
         message_id: u32,
         signal: EditableSignal,
+        index: usize,
     },
     DeleteMessage {
         message: EditableMessage,
+        index: usize,
     },
     DeleteSignal {
         message_id: u32,
         signal: EditableSignal,
+        index: usize,
+    },
+    AddValueTable {
+        table: EditableValueTable,
+        index: usize,
+    },
+    // 删除属性定义时，所有对象上的该属性值也一起删除，撤销时插回原来的位置
+    AddAttributeDefinition {
+        definition: AttributeDefinition,
+        index: usize,
+        values: Vec<RemovedAttributeValue>,
+    },
+    DeleteAttributeDefinition {
+        definition: AttributeDefinition,
+        index: usize,
+        values: Vec<RemovedAttributeValue>,
+    },
+    // 修改类型、范围、默认值或名称，改名时对象上的属性值也一起改名
+    SetAttributeDefinition {
+        old_definition: AttributeDefinition,
+        new_definition: AttributeDefinition,
+    },
+    // None 表示没有显式设置，使用默认值
+    SetAttributeValue {
+        target: AttributeTarget,
+        name: String,
+        old_value: Option<AttributeValue>,
+        new_value: Option<AttributeValue>,
+    },
+    DeleteValueTable {
+        table: EditableValueTable,
+        index: usize,
     },
+    // 事务：按顺序执行的一组操作，作为一个整体撤销和重做
+    Transaction {
+        label: String,
+        operations: Vec<Operation>,
+    },
+}
+
+impl Operation {
+    /// 用于界面显示的操作名称，如 "Undo Rename Message"
+    pub fn label(&self) -> String {
+        let label = match self {
+            Operation::SetMessageId { .. } => "Set Message ID",
+            Operation::SetMessageFrameFormat { .. } => "Set Message Frame Format",
+            Operation::SetMessageName { .. } => "Rename Message",
+            Operation::SetMessageSize { .. } => "Set Message Size",
+            Operation::SetMessageTransmitter { .. } => "Set Message Transmitter",
+            Operation::SetMessageComment { .. } => "Set Message Comment",
+            Operation::SetSignalName { .. } => "Rename Signal",
+            Operation::SetSignalMultiplexerIndicator { .. } => "Set Signal Multiplexer",
+            Operation::SetSignalStartBit { .. } => "Set Signal Start Bit",
+            Operation::SetSignalSize { .. } => "Set Signal Size",
+            Operation::SetSignalByteOrder { .. } => "Set Signal Byte Order",
+            Operation::SetSignalValueType { .. } => "Set Signal Value Type",
+            Operation::SetSignalFactor { .. } => "Set Signal Factor",
+            Operation::SetSignalOffset { .. } => "Set Signal Offset",
+            Operation::SetSignalMin { .. } => "Set Signal Min",
+            Operation::SetSignalMax { .. } => "Set Signal Max",
+            Operation::SetSignalUnit { .. } => "Set Signal Unit",
+            Operation::SetSignalReceivers { .. } => "Set Signal Receivers",
+            Operation::SetSignalComment { .. } => "Set Signal Comment",
+            Operation::SetSignalValueDescriptions { .. } => "Set Signal Value Descriptions",
+            Operation::SetValueTableName { .. } => "Rename Value Table",
+            Operation::SetValueTableDescriptions { .. } => "Set Value Table Descriptions",
+            Operation::AddMessage { .. } => "Add Message",
+            Operation::AddSignal { .. } => "Add Signal",
+            Operation::DeleteMessage { .. } => "Delete Message",
+            Operation::DeleteSignal { .. } => "Delete Signal",
+            Operation::AddValueTable { .. } => "Add Value Table",
+            Operation::DeleteValueTable { .. } => "Delete Value Table",
+            Operation::AddAttributeDefinition { .. } => "Add Attribute Definition",
+            Operation::DeleteAttributeDefinition { .. } => "Delete Attribute Definition",
+            Operation::SetAttributeDefinition { .. } => "Modify Attribute Definition",
+            Operation::SetAttributeValue { .. } => "Set Attribute Value",
+            Operation::Transaction { label, .. } => label,
+        };
+        label.to_string()
+    }
+
+    /// 返回逆操作，应用逆操作即可撤销这个操作
+    pub fn inverse(&self) -> Operation {
+        match self.clone() {
+            Operation::SetMessageId {
+                index,
+                old_id,
+                new_id,
+            } => Operation::SetMessageId {
+                index,
+                old_id: new_id,
+                new_id: old_id,
+            },
+            Operation::SetMessageFrameFormat {
+                message_id,
+                old_format,
+                new_format,
+            } => Operation::SetMessageFrameFormat {
+                message_id,
+                old_format: new_format,
+                new_format: old_format,
+            },
+            Operation::SetMessageName {
+                message_id,
+                old_name,
+                new_name,
+            } => Operation::SetMessageName {
+                message_id,
+                old_name: new_name,
+                new_name: old_name,
+            },
+            Operation::SetMessageSize {
+                message_id,
+                old_size,
+                new_size,
+            } => Operation::SetMessageSize {
+                message_id,
+                old_size: new_size,
+                new_size: old_size,
+            },
+            Operation::SetMessageTransmitter {
+                message_id,
+                old_transmitter,
+                new_transmitter,
+            } => Operation::SetMessageTransmitter {
+                message_id,
+                old_transmitter: new_transmitter,
+                new_transmitter: old_transmitter,
+            },
+            Operation::SetMessageComment {
+                message_id,
+                old_comment,
+                new_comment,
+            } => Operation::SetMessageComment {
+                message_id,
+                old_comment: new_comment,
+                new_comment: old_comment,
+            },
+            Operation::SetSignalName {
+                message_id,
+                index,
+                signal_old_name,
+                signal_new_name,
+            } => Operation::SetSignalName {
+                message_id,
+                index,
+                signal_old_name: signal_new_name,
+                signal_new_name: signal_old_name,
+            },
+            Operation::SetSignalMultiplexerIndicator {
+                message_id,
+                signal_name,
+                old_indicator,
+                new_indicator,
+            } => Operation::SetSignalMultiplexerIndicator {
+                message_id,
+                signal_name,
+                old_indicator: new_indicator,
+                new_indicator: old_indicator,
+            },
+            Operation::SetSignalStartBit {
+                message_id,
+                signal_name,
+                old_start_bit,
+                new_start_bit,
+            } => Operation::SetSignalStartBit {
+                message_id,
+                signal_name,
+                old_start_bit: new_start_bit,
+                new_start_bit: old_start_bit,
+            },
+            Operation::SetSignalSize {
+                message_id,
+                signal_name,
+                old_size,
+                new_size,
+            } => Operation::SetSignalSize {
+                message_id,
+                signal_name,
+                old_size: new_size,
+                new_size: old_size,
+            },
+            Operation::SetSignalByteOrder {
+                message_id,
+                signal_name,
+                old_byte_order,
+                new_byte_order,
+            } => Operation::SetSignalByteOrder {
+                message_id,
+                signal_name,
+                old_byte_order: new_byte_order,
+                new_byte_order: old_byte_order,
+            },
+            Operation::SetSignalValueType {
+                message_id,
+                signal_name,
+                old_value_type,
+                new_value_type,
+            } => Operation::SetSignalValueType {
+                message_id,
+                signal_name,
+                old_value_type: new_value_type,
+                new_value_type: old_value_type,
+            },
+            Operation::SetSignalFactor {
+                message_id,
+                signal_name,
+                old_factor,
+                new_factor,
+            } => Operation::SetSignalFactor {
+                message_id,
+                signal_name,
+                old_factor: new_factor,
+                new_factor: old_factor,
+            },
+            Operation::SetSignalOffset {
+                message_id,
+                signal_name,
+                old_offset,
+                new_offset,
+            } => Operation::SetSignalOffset {
+                message_id,
+                signal_name,
+                old_offset: new_offset,
+                new_offset: old_offset,
+            },
+            Operation::SetSignalMin {
+                message_id,
+                signal_name,
+                old_min,
+                new_min,
+            } => Operation::SetSignalMin {
+                message_id,
+                signal_name,
+                old_min: new_min,
+                new_min: old_min,
+            },
+            Operation::SetSignalMax {
+                message_id,
+                signal_name,
+                old_max,
+                new_max,
+            } => Operation::SetSignalMax {
+                message_id,
+                signal_name,
+                old_max: new_max,
+                new_max: old_max,
+            },
+            Operation::SetSignalUnit {
+                message_id,
+                signal_name,
+                old_unit,
+                new_unit,
+            } => Operation::SetSignalUnit {
+                message_id,
+                signal_name,
+                old_unit: new_unit,
+                new_unit: old_unit,
+            },
+            Operation::SetSignalReceivers {
+                message_id,
+                signal_name,
+                old_receivers,
+                new_receivers,
+            } => Operation::SetSignalReceivers {
+                message_id,
+                signal_name,
+                old_receivers: new_receivers,
+                new_receivers: old_receivers,
+            },
+            Operation::SetSignalComment {
+                message_id,
+                signal_name,
+                old_comment,
+                new_comment,
+            } => Operation::SetSignalComment {
+                message_id,
+                signal_name,
+                old_comment: new_comment,
+                new_comment: old_comment,
+            },
+            Operation::SetSignalValueDescriptions {
+                message_id,
+                signal_name,
+                old_descriptions,
+                new_descriptions,
+            } => Operation::SetSignalValueDescriptions {
+                message_id,
+                signal_name,
+                old_descriptions: new_descriptions,
+                new_descriptions: old_descriptions,
+            },
+            Operation::SetValueTableName { old_name, new_name } => Operation::SetValueTableName {
+                old_name: new_name,
+                new_name: old_name,
+            },
+            Operation::SetValueTableDescriptions {
+                table_name,
+                old_descriptions,
+                new_descriptions,
+            } => Operation::SetValueTableDescriptions {
+                table_name,
+                old_descriptions: new_descriptions,
+                new_descriptions: old_descriptions,
+            },
+            Operation::AddMessage { message, index } => Operation::DeleteMessage { message, index },
+            Operation::DeleteMessage { message, index } => Operation::AddMessage { message, index },
+            Operation::AddSignal {
+                message_id,
+                signal,
+                index,
+            } => Operation::DeleteSignal {
+                message_id,
+                signal,
+                index,
+            },
+            Operation::DeleteSignal {
+                message_id,
+                signal,
+                index,
+            } => Operation::AddSignal {
+                message_id,
+                signal,
+                index,
+            },
+            Operation::AddValueTable { table, index } => {
+                Operation::DeleteValueTable { table, index }
+            }
+            Operation::DeleteValueTable { table, index } => {
+                Operation::AddValueTable { table, index }
+            }
+            Operation::AddAttributeDefinition {
+                definition,
+                index,
+                values,
+            } => Operation::DeleteAttributeDefinition {
+                definition,
+                index,
+                values,
+            },
+            Operation::DeleteAttributeDefinition {
+                definition,
+                index,
+                values,
+            } => Operation::AddAttributeDefinition {
+                definition,
+                index,
+                values,
+            },
+            Operation::SetAttributeDefinition {
+                old_definition,
+                new_definition,
+            } => Operation::SetAttributeDefinition {
+                old_definition: new_definition,
+                new_definition: old_definition,
+            },
+            Operation::SetAttributeValue {
+                target,
+                name,
+                old_value,
+                new_value,
+            } => Operation::SetAttributeValue {
+                target,
+                name,
+                old_value: new_value,
+                new_value: old_value,
+            },
+            // 逆序撤销事务中的每一个操作
+            Operation::Transaction { label, operations } => Operation::Transaction {
+                label,
+                operations: operations.iter().rev().map(Operation::inverse).collect(),
+            },
+        }
+    }
 }
 
 #[allow(dead_code)]
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct EditableDbc {
+    version: String,
     nodes: Vec<String>,
+    // CM_ "..." 网络注释和 CM_ BU_ 节点注释，目前只读，保存时原样写回
+    comment: String,
+    node_comments: Vec<(String, String)>,
     messages: Vec<EditableMessage>,
+    // VAL_TABLE_ 定义的全局值表，Signal 自己的值描述 (VAL_) 保存在 EditableSignal 中
+    value_tables: Vec<EditableValueTable>,
+    // EV_ 定义的环境变量，目前只读，保存时原样写回
+    environment_variables: Vec<EditableEnvironmentVariable>,
+    // BA_DEF_ 属性定义，BA_DEF_DEF_ 默认值保存在定义中
+    attribute_definitions: Vec<AttributeDefinition>,
+    // 网络、节点和环境变量的属性值，Message 和 Signal 的属性值保存在各自的结构中
+    network_attributes: AttributeValues,
+    node_attributes: Vec<(String, AttributeValues)>,
+    environment_variable_attributes: Vec<(String, AttributeValues)>,
+    // 读取时无法表示、保存时会丢失的内容，读取和保存时作为警告返回，见 file_format 子模块
+    unsupported_content: Vec<String>,
+    // history[..current_index] 是已经应用的操作，history[current_index..] 是可以重做的操作
     history: Vec<Operation>,
     current_index: usize,
-    head_index: usize,
+    // 上次保存时的历史记录位置，用于判断是否有未保存的修改
+    // 为 None 表示保存时的状态已经无法通过撤销/重做回到
+    saved_index: Option<usize>,
+    // 正在进行的事务，期间的操作先记录在这里，提交时合并为一个 Operation::Transaction
+    transaction: Option<PendingTransaction>,
+    // 每次应用操作后递增，供界面判断缓存的检查结果是否过期
+    revision: u64,
 }
 
-#[derive(Clone, Copy, Debug)]
-pub enum FrameFormat {
-    Standard,
-    Extended,
+#[derive(Clone, Debug)]
+struct PendingTransaction {
+    label: String,
+    operations: Vec<Operation>,
 }
 
-impl Default for FrameFormat {
+impl Default for EditableDbc {
     fn default() -> Self {
-        FrameFormat::Standard
+        Self::new()
     }
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FrameFormat {
+    #[default]
+    Standard,
+    Extended,
+}
+
 #[allow(dead_code)]
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct EditableMessage {
     message_id: u32,
     frame_format: FrameFormat,
     message_name: String,
     message_size: u64,
     transmitter: String,
+    // BO_TX_BU_ 列出的发送节点，目前只读，保存时原样写回
+    extra_transmitters: Vec<String>,
     signals: Vec<EditableSignal>,
     comment: String,
+    attributes: AttributeValues,
+    // CAN FD 标志，由 VFrameFormat 和 CANFD_BRS 属性推导，见 can_fd 子模块
+    fd: bool,
+    bit_rate_switch: bool,
 }
 
 #[allow(dead_code)]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct EditableSignal {
     name: String,
     multiplexer_indicator: MultiplexIndicator,
@@ -191,6 +655,8 @@ pub struct EditableSignal {
     signal_size: u64,
     byte_order: ByteOrder,
     value_type: ValueType,
+    // SIG_VALTYPE_ 声明的 IEEE 浮点类型，None 表示文件中没有这一行
+    extended_value_type: Option<SignalExtendedValueType>,
     factor: f64,
     offset: f64,
     min: f64,
@@ -198,17 +664,54 @@ pub struct EditableSignal {
     unit: String,
     receivers: Vec<String>,
     comment: String,
+    // VAL_ 定义的原始值到描述文本的映射，保持文件中的顺序
+    value_descriptions: Vec<(i64, String)>,
+    attributes: AttributeValues,
+}
+
+/// EV_ 定义的环境变量
+#[derive(Clone, Debug, PartialEq)]
+pub struct EditableEnvironmentVariable {
+    name: String,
+    env_type: EnvType,
+    min: i64,
+    max: i64,
+    unit: String,
+    initial_value: i64,
+    ev_id: i64,
+    access_type: AccessType,
+    access_nodes: Vec<String>,
+    comment: String,
+}
+
+/// VAL_TABLE_ 定义的全局值表
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EditableValueTable {
+    name: String,
+    descriptions: Vec<(i64, String)>,
 }
 
 #[allow(dead_code)]
 impl EditableDbc {
     pub fn new() -> Self {
         Self {
+            version: String::new(),
             nodes: Vec::new(),
+            comment: String::new(),
+            node_comments: Vec::new(),
             messages: Vec::new(),
+            value_tables: Vec::new(),
+            environment_variables: Vec::new(),
+            attribute_definitions: Vec::new(),
+            network_attributes: Vec::new(),
+            node_attributes: Vec::new(),
+            environment_variable_attributes: Vec::new(),
+            unsupported_content: Vec::new(),
             history: Vec::new(),
             current_index: 0,
-            head_index: 0,
+            saved_index: Some(0),
+            transaction: None,
+            revision: 0,
         }
     }
 
@@ -216,42 +719,178 @@ impl EditableDbc {
         self.messages.len()
     }
 
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
     pub fn nodes(&self) -> &Vec<String> {
         &self.nodes
     }
 
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+
+    pub fn node_comment(&self, node: &str) -> Option<&str> {
+        self.node_comments
+            .iter()
+            .find(|(name, _)| name == node)
+            .map(|(_, comment)| comment.as_str())
+    }
+
     pub fn messages(&self) -> &Vec<EditableMessage> {
         &self.messages
     }
 
+    /// 数据版本号，任何修改（包括撤销和重做）都会使其变化
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// 自上次保存以来是否有修改
+    pub fn is_modified(&self) -> bool {
+        self.saved_index != Some(self.current_index)
+    }
+
+    /// 由上层在写入文件成功后调用，记录当前的历史位置
+    pub fn mark_saved(&mut self) {
+        self.saved_index = Some(self.current_index);
+    }
+
+    // 导入时用编辑 API 补充的内容不应该能被撤销，导入完成后清空历史记录
+    fn clear_history(&mut self) {
+        self.history.clear();
+        self.current_index = 0;
+        self.saved_index = Some(0);
+    }
+
     fn push_history(&mut self, op: Operation) {
-        // if current_index is at the end, just push and set current_index and head_index
-        // else set history[current_index] = op
-        if self.current_index == self.history.len() {
-            self.history.push(op);
-            self.current_index += 1;
-            self.head_index += 1;
-        } else {
-            self.history[self.current_index] = op;
+        if let Some(transaction) = &mut self.transaction {
+            transaction.operations.push(op);
+            return;
         }
+
+        // 撤销之后再进行新的编辑，current_index 之后可以重做的操作全部丢弃
+        self.history.truncate(self.current_index);
+        // 如果保存时的位置也被丢弃了，就再也回不到保存时的状态
+        if self.saved_index.is_some_and(|i| i > self.current_index) {
+            self.saved_index = None;
+        }
+        self.history.push(op);
+        self.current_index += 1;
     }
 
     pub fn from_dbc(dbc: &Dbc) -> Self {
         let mut editable_dbc = Self::new();
 
+        editable_dbc.version = unescape(&dbc.version.0);
+
         editable_dbc.nodes = dbc.nodes.iter().map(|x| x.0.clone()).collect();
 
+        for comment in &dbc.comments {
+            match comment {
+                Comment::Plain { comment } => editable_dbc.comment = unescape(comment),
+                Comment::Node { name, comment } => {
+                    editable_dbc
+                        .node_comments
+                        .push((name.clone(), unescape(comment)));
+                }
+                _ => {}
+            }
+        }
+
         editable_dbc.messages = dbc
             .messages
             .iter()
-            .map(|msg| {
-                EditableMessage::from_message(msg, dbc.message_comment(msg.id).unwrap_or(""))
+            .map(|msg| EditableMessage::from_message(msg, dbc))
+            .collect();
+
+        editable_dbc.environment_variables = dbc
+            .environment_variables
+            .iter()
+            .map(|env| EditableEnvironmentVariable::from_environment_variable(env, dbc))
+            .collect();
+
+        editable_dbc.read_attributes(dbc);
+        editable_dbc.sync_can_fd_flags();
+
+        editable_dbc.value_tables = dbc
+            .value_tables
+            .iter()
+            .map(|table| EditableValueTable {
+                name: table.name.clone(),
+                descriptions: value_descriptions_from_dbc(&table.descriptions),
             })
             .collect();
 
+        editable_dbc.unsupported_content = unsupported_dbc_sections(dbc);
+
         editable_dbc
     }
 
+    // 读取属性定义、默认值和属性值
+    // 找不到对应对象的属性值会被丢弃，没有定义的属性值保留原样
+    fn read_attributes(&mut self, dbc: &Dbc) {
+        self.attribute_definitions = dbc
+            .attribute_definitions
+            .iter()
+            .map(AttributeDefinition::from_dbc)
+            .collect();
+        for default in &dbc.attribute_defaults {
+            if let Some(definition) = self
+                .attribute_definitions
+                .iter_mut()
+                .find(|d| d.name() == default.name)
+            {
+                let value = definition.normalize(AttributeValue::from_dbc(&default.value));
+                *definition = definition.clone().with_default(value);
+            }
+        }
+
+        let mut values = Vec::new();
+        for v in &dbc.attribute_values_database {
+            values.push((AttributeTarget::Network, &v.name, &v.value));
+        }
+        for v in &dbc.attribute_values_node {
+            values.push((
+                AttributeTarget::Node(v.node_name.clone()),
+                &v.name,
+                &v.value,
+            ));
+        }
+        for v in &dbc.attribute_values_message {
+            values.push((
+                AttributeTarget::Message(v.message_id.raw()),
+                &v.name,
+                &v.value,
+            ));
+        }
+        for v in &dbc.attribute_values_signal {
+            values.push((
+                AttributeTarget::Signal(v.message_id.raw(), v.signal_name.clone()),
+                &v.name,
+                &v.value,
+            ));
+        }
+        for v in &dbc.attribute_values_env {
+            values.push((
+                AttributeTarget::EnvironmentVariable(v.variable_name.clone()),
+                &v.name,
+                &v.value,
+            ));
+        }
+
+        for (target, name, value) in values {
+            let mut value = AttributeValue::from_dbc(value);
+            if let Some(definition) = self.get_attribute_definition(name) {
+                value = definition.normalize(value);
+            }
+            if let Ok(attributes) = self.attribute_values_mut(&target) {
+                set_attribute_in(attributes, name, Some(value));
+            }
+        }
+    }
+
     pub fn get_message(&self, message_id: u32) -> Option<&EditableMessage> {
         self.messages.iter().find(|m| m.message_id == message_id)
     }
@@ -264,7 +903,7 @@ impl EditableDbc {
 
     fn find_index_signal_index(&self, message_idx: usize, signal_name: &str) -> Option<usize> {
         let msg = &self.messages[message_idx];
-        return msg.signals.iter().position(|s| s.name == signal_name);
+        msg.signals.iter().position(|s| s.name == signal_name)
     }
 
     fn find_message_signal_index(
@@ -272,10 +911,10 @@ impl EditableDbc {
         message_id: u32,
         signal_name: &str,
     ) -> Option<(usize, usize)> {
-        if let Some(msg_idx) = self.find_message_index(message_id) {
-            if let Some(sig_idx) = self.find_index_signal_index(msg_idx, signal_name) {
-                return Some((msg_idx, sig_idx));
-            }
+        if let Some(msg_idx) = self.find_message_index(message_id)
+            && let Some(sig_idx) = self.find_index_signal_index(msg_idx, signal_name)
+        {
+            return Some((msg_idx, sig_idx));
         }
         None
     }
@@ -304,105 +943,591 @@ impl EditableDbc {
         None
     }
 
+    fn get_signal(&self, message_id: u32, signal_name: &str) -> Option<&EditableSignal> {
+        self.get_message(message_id)?
+            .signals
+            .iter()
+            .find(|s| s.name == signal_name)
+    }
+
+    // 与 get_xxx_mut 相同，但找不到时返回错误信息，供 apply_operation 使用
+    fn require_message_mut(&mut self, message_id: u32) -> Result<&mut EditableMessage, String> {
+        self.get_message_mut(message_id)
+            .ok_or_else(|| format!("Message 0x{:X} not found", message_id))
+    }
+
+    fn require_signal_mut(
+        &mut self,
+        message_id: u32,
+        signal_name: &str,
+    ) -> Result<&mut EditableSignal, String> {
+        self.get_signal_mut(message_id, signal_name)
+            .ok_or_else(|| format!("Signal {} not found in 0x{:X}", signal_name, message_id))
+    }
+
+    // 优先使用操作记录的位置，ID 重复时也能找到正确的 Message
+    fn resolve_message_index(&self, index: usize, message_id: u32) -> Result<usize, String> {
+        match self.messages.get(index) {
+            Some(m) if m.message_id == message_id => Ok(index),
+            _ => self
+                .find_message_index(message_id)
+                .ok_or_else(|| format!("Message 0x{:X} not found", message_id)),
+        }
+    }
+
+    fn find_attribute_definition_index(&self, name: &str) -> Option<usize> {
+        self.attribute_definitions
+            .iter()
+            .position(|d| d.name() == name)
+    }
+
+    // 找到对象上保存属性值的列表，节点和环境变量第一次设置时创建
+    fn attribute_values_mut(
+        &mut self,
+        target: &AttributeTarget,
+    ) -> Result<&mut AttributeValues, String> {
+        match target {
+            AttributeTarget::Network => Ok(&mut self.network_attributes),
+            AttributeTarget::Node(node) => {
+                if !self.nodes.contains(node) {
+                    return Err(format!("Node {} not found", node));
+                }
+                Ok(object_attributes_mut(&mut self.node_attributes, node))
+            }
+            AttributeTarget::Message(message_id) => {
+                Ok(&mut self.require_message_mut(*message_id)?.attributes)
+            }
+            AttributeTarget::Signal(message_id, signal_name) => Ok(&mut self
+                .require_signal_mut(*message_id, signal_name)?
+                .attributes),
+            AttributeTarget::EnvironmentVariable(name) => {
+                if !self.environment_variables.iter().any(|e| &e.name == name) {
+                    return Err(format!("Environment variable {} not found", name));
+                }
+                Ok(object_attributes_mut(
+                    &mut self.environment_variable_attributes,
+                    name,
+                ))
+            }
+        }
+    }
+
+    // 删除已经没有属性值的节点和环境变量条目，保证撤销后模型完全一致
+    fn prune_attribute_values(&mut self) {
+        self.node_attributes
+            .retain(|(_, attributes)| !attributes.is_empty());
+        self.environment_variable_attributes
+            .retain(|(_, attributes)| !attributes.is_empty());
+    }
+
+    fn rename_attribute_values(&mut self, old_name: &str, new_name: &str) {
+        let rename = |attributes: &mut AttributeValues| {
+            for (name, _) in attributes.iter_mut() {
+                if name == old_name {
+                    *name = new_name.to_string();
+                }
+            }
+        };
+        rename(&mut self.network_attributes);
+        for (_, attributes) in self
+            .node_attributes
+            .iter_mut()
+            .chain(self.environment_variable_attributes.iter_mut())
+        {
+            rename(attributes);
+        }
+        for message in &mut self.messages {
+            rename(&mut message.attributes);
+            for signal in &mut message.signals {
+                rename(&mut signal.attributes);
+            }
+        }
+    }
+
+    // 收集所有对象上某个属性的值和它们的位置
+    fn collect_attribute_values(&self, name: &str) -> Vec<RemovedAttributeValue> {
+        let mut values = Vec::new();
+        let mut collect =
+            |target: AttributeTarget, attributes: &AttributeValues, object_index: Option<usize>| {
+                if let Some(index) = attributes.iter().position(|(n, _)| n == name) {
+                    values.push(RemovedAttributeValue {
+                        target,
+                        value: attributes[index].1.clone(),
+                        index,
+                        object_index,
+                    });
+                }
+            };
+        collect(AttributeTarget::Network, &self.network_attributes, None);
+        for (i, (node, attributes)) in self.node_attributes.iter().enumerate() {
+            collect(AttributeTarget::Node(node.clone()), attributes, Some(i));
+        }
+        for message in &self.messages {
+            collect(
+                AttributeTarget::Message(message.message_id),
+                &message.attributes,
+                None,
+            );
+            for signal in &message.signals {
+                collect(
+                    AttributeTarget::Signal(message.message_id, signal.name.clone()),
+                    &signal.attributes,
+                    None,
+                );
+            }
+        }
+        for (i, (env, attributes)) in self.environment_variable_attributes.iter().enumerate() {
+            collect(
+                AttributeTarget::EnvironmentVariable(env.clone()),
+                attributes,
+                Some(i),
+            );
+        }
+        values
+    }
+
+    // 把删除的属性值插回原来的位置，节点和环境变量的条目已经被删除时也插回原来的位置
+    fn restore_attribute_value(
+        &mut self,
+        name: &str,
+        removed: &RemovedAttributeValue,
+    ) -> Result<(), String> {
+        let objects = match &removed.target {
+            AttributeTarget::Node(object) => Some((&mut self.node_attributes, object)),
+            AttributeTarget::EnvironmentVariable(object) => {
+                Some((&mut self.environment_variable_attributes, object))
+            }
+            _ => None,
+        };
+        if let (Some((objects, object)), Some(object_index)) = (objects, removed.object_index)
+            && !objects.iter().any(|(n, _)| n == object)
+        {
+            objects.insert(
+                object_index.min(objects.len()),
+                (object.clone(), Vec::new()),
+            );
+        }
+        let attributes = self.attribute_values_mut(&removed.target)?;
+        let index = removed.index.min(attributes.len());
+        attributes.insert(index, (name.to_string(), removed.value.clone()));
+        Ok(())
+    }
+
+    fn require_value_table_mut(&mut self, name: &str) -> Result<&mut EditableValueTable, String> {
+        self.value_tables
+            .iter_mut()
+            .find(|t| t.name == name)
+            .ok_or_else(|| format!("Value table {} not found", name))
+    }
+
+    // 所有的编辑都通过 execute 进行：先应用到数据上，成功后再记录到历史中
+    fn execute(&mut self, op: Operation) {
+        if self.apply_operation(&op).is_ok() {
+            self.push_history(op);
+        }
+    }
+
+    // 将操作应用到数据上，不记录历史
+    // 撤销时应用的是 Operation::inverse 得到的逆操作
+    fn apply_operation(&mut self, op: &Operation) -> Result<(), String> {
+        match op {
+            Operation::SetMessageId {
+                index,
+                old_id,
+                new_id,
+            } => {
+                let index = self.resolve_message_index(*index, *old_id)?;
+                self.messages[index].message_id = *new_id;
+            }
+            Operation::SetMessageFrameFormat {
+                message_id,
+                new_format,
+                ..
+            } => {
+                self.require_message_mut(*message_id)?.frame_format = *new_format;
+            }
+            Operation::SetMessageName {
+                message_id,
+                new_name,
+                ..
+            } => {
+                self.require_message_mut(*message_id)?.message_name = new_name.clone();
+            }
+            Operation::SetMessageSize {
+                message_id,
+                new_size,
+                ..
+            } => {
+                self.require_message_mut(*message_id)?.message_size = *new_size;
+            }
+            Operation::SetMessageTransmitter {
+                message_id,
+                new_transmitter,
+                ..
+            } => {
+                self.require_message_mut(*message_id)?.transmitter = new_transmitter.clone();
+            }
+            Operation::SetMessageComment {
+                message_id,
+                new_comment,
+                ..
+            } => {
+                self.require_message_mut(*message_id)?.comment = new_comment.clone();
+            }
+            Operation::SetSignalName {
+                message_id,
+                index,
+                signal_old_name,
+                signal_new_name,
+            } => {
+                let msg = self.require_message_mut(*message_id)?;
+                let index = resolve_signal_index(msg, *index, signal_old_name)?;
+                msg.signals[index].name = signal_new_name.clone();
+            }
+            Operation::SetSignalMultiplexerIndicator {
+                message_id,
+                signal_name,
+                new_indicator,
+                ..
+            } => {
+                self.require_signal_mut(*message_id, signal_name)?
+                    .multiplexer_indicator = *new_indicator;
+            }
+            Operation::SetSignalStartBit {
+                message_id,
+                signal_name,
+                new_start_bit,
+                ..
+            } => {
+                self.require_signal_mut(*message_id, signal_name)?.start_bit = *new_start_bit;
+            }
+            Operation::SetSignalSize {
+                message_id,
+                signal_name,
+                new_size,
+                ..
+            } => {
+                self.require_signal_mut(*message_id, signal_name)?
+                    .signal_size = *new_size;
+            }
+            Operation::SetSignalByteOrder {
+                message_id,
+                signal_name,
+                new_byte_order,
+                ..
+            } => {
+                self.require_signal_mut(*message_id, signal_name)?
+                    .byte_order = *new_byte_order;
+            }
+            Operation::SetSignalValueType {
+                message_id,
+                signal_name,
+                new_value_type,
+                ..
+            } => {
+                self.require_signal_mut(*message_id, signal_name)?
+                    .value_type = *new_value_type;
+            }
+            Operation::SetSignalFactor {
+                message_id,
+                signal_name,
+                new_factor,
+                ..
+            } => {
+                self.require_signal_mut(*message_id, signal_name)?.factor = *new_factor;
+            }
+            Operation::SetSignalOffset {
+                message_id,
+                signal_name,
+                new_offset,
+                ..
+            } => {
+                self.require_signal_mut(*message_id, signal_name)?.offset = *new_offset;
+            }
+            Operation::SetSignalMin {
+                message_id,
+                signal_name,
+                new_min,
+                ..
+            } => {
+                self.require_signal_mut(*message_id, signal_name)?.min = *new_min;
+            }
+            Operation::SetSignalMax {
+                message_id,
+                signal_name,
+                new_max,
+                ..
+            } => {
+                self.require_signal_mut(*message_id, signal_name)?.max = *new_max;
+            }
+            Operation::SetSignalUnit {
+                message_id,
+                signal_name,
+                new_unit,
+                ..
+            } => {
+                self.require_signal_mut(*message_id, signal_name)?.unit = new_unit.clone();
+            }
+            Operation::SetSignalReceivers {
+                message_id,
+                signal_name,
+                new_receivers,
+                ..
+            } => {
+                self.require_signal_mut(*message_id, signal_name)?.receivers =
+                    new_receivers.clone();
+            }
+            Operation::SetSignalComment {
+                message_id,
+                signal_name,
+                new_comment,
+                ..
+            } => {
+                self.require_signal_mut(*message_id, signal_name)?.comment = new_comment.clone();
+            }
+            Operation::SetSignalValueDescriptions {
+                message_id,
+                signal_name,
+                new_descriptions,
+                ..
+            } => {
+                self.require_signal_mut(*message_id, signal_name)?
+                    .value_descriptions = new_descriptions.clone();
+            }
+            Operation::SetValueTableName { old_name, new_name } => {
+                self.require_value_table_mut(old_name)?.name = new_name.clone();
+            }
+            Operation::SetValueTableDescriptions {
+                table_name,
+                new_descriptions,
+                ..
+            } => {
+                self.require_value_table_mut(table_name)?.descriptions = new_descriptions.clone();
+            }
+            Operation::AddMessage { message, index } => {
+                let index = (*index).min(self.messages.len());
+                self.messages.insert(index, message.clone());
+            }
+            Operation::DeleteMessage { message, index } => {
+                let index = self.resolve_message_index(*index, message.message_id)?;
+                self.messages.remove(index);
+            }
+            Operation::AddSignal {
+                message_id,
+                signal,
+                index,
+            } => {
+                let msg = self.require_message_mut(*message_id)?;
+                let index = (*index).min(msg.signals.len());
+                msg.signals.insert(index, signal.clone());
+            }
+            Operation::DeleteSignal {
+                message_id,
+                signal,
+                index,
+            } => {
+                let msg = self.require_message_mut(*message_id)?;
+                let index = resolve_signal_index(msg, *index, &signal.name)?;
+                msg.signals.remove(index);
+            }
+            Operation::AddValueTable { table, index } => {
+                let index = (*index).min(self.value_tables.len());
+                self.value_tables.insert(index, table.clone());
+            }
+            Operation::DeleteValueTable { table, index } => {
+                let index = match self.value_tables.get(*index) {
+                    Some(t) if t.name == table.name => *index,
+                    _ => self
+                        .value_tables
+                        .iter()
+                        .position(|t| t.name == table.name)
+                        .ok_or_else(|| format!("Value table {} not found", table.name))?,
+                };
+                self.value_tables.remove(index);
+            }
+            Operation::AddAttributeDefinition {
+                definition,
+                index,
+                values,
+            } => {
+                let index = (*index).min(self.attribute_definitions.len());
+                self.attribute_definitions.insert(index, definition.clone());
+                // values 按 collect_attribute_values 的顺序排列，依次插回即可恢复原来的位置
+                for removed in values {
+                    self.restore_attribute_value(definition.name(), removed)?;
+                }
+            }
+            Operation::DeleteAttributeDefinition {
+                definition, values, ..
+            } => {
+                let index = self
+                    .find_attribute_definition_index(definition.name())
+                    .ok_or_else(|| format!("Attribute {} not defined", definition.name()))?;
+                for removed in values {
+                    let attributes = self.attribute_values_mut(&removed.target)?;
+                    set_attribute_in(attributes, definition.name(), None);
+                    self.prune_attribute_values();
+                }
+                self.attribute_definitions.remove(index);
+            }
+            Operation::SetAttributeDefinition {
+                old_definition,
+                new_definition,
+            } => {
+                let index = self
+                    .find_attribute_definition_index(old_definition.name())
+                    .ok_or_else(|| format!("Attribute {} not defined", old_definition.name()))?;
+                if old_definition.name() != new_definition.name() {
+                    self.rename_attribute_values(old_definition.name(), new_definition.name());
+                }
+                self.attribute_definitions[index] = new_definition.clone();
+            }
+            Operation::SetAttributeValue {
+                target,
+                name,
+                new_value,
+                ..
+            } => {
+                let attributes = self.attribute_values_mut(target)?;
+                set_attribute_in(attributes, name, new_value.clone());
+                self.prune_attribute_values();
+            }
+            Operation::Transaction { operations, .. } => {
+                for (applied, op) in operations.iter().enumerate() {
+                    if let Err(e) = self.apply_operation(op) {
+                        // 中途失败时撤销已经应用的部分，保证事务要么全部生效要么全部不生效
+                        for op in operations[..applied].iter().rev() {
+                            let _ = self.apply_operation(&op.inverse());
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+        }
+        // FD 标志由属性推导，属性或新加入的 Message 变化后需要重新计算
+        if matches!(
+            op,
+            Operation::AddMessage { .. }
+                | Operation::AddAttributeDefinition { .. }
+                | Operation::DeleteAttributeDefinition { .. }
+                | Operation::SetAttributeDefinition { .. }
+                | Operation::SetAttributeValue { .. }
+        ) {
+            self.sync_can_fd_flags();
+        }
+        self.revision += 1;
+        Ok(())
+    }
+
+    /// 开始一个事务，之后的所有编辑在 commit_transaction 时合并为一个可撤销的操作
+    pub fn begin_transaction(&mut self, label: &str) -> Result<(), String> {
+        if self.transaction.is_some() {
+            return Err("A transaction is already in progress".into());
+        }
+        self.transaction = Some(PendingTransaction {
+            label: label.to_string(),
+            operations: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// 提交事务，没有任何操作的事务不会产生历史记录
+    pub fn commit_transaction(&mut self) -> Result<(), String> {
+        let Some(transaction) = self.transaction.take() else {
+            return Err("No transaction in progress".into());
+        };
+        if !transaction.operations.is_empty() {
+            self.push_history(Operation::Transaction {
+                label: transaction.label,
+                operations: transaction.operations,
+            });
+        }
+        Ok(())
+    }
+
+    /// 放弃事务，撤销事务中已经应用的所有操作
+    pub fn rollback_transaction(&mut self) -> Result<(), String> {
+        let Some(transaction) = self.transaction.take() else {
+            return Err("No transaction in progress".into());
+        };
+        for op in transaction.operations.iter().rev() {
+            self.apply_operation(&op.inverse())?;
+        }
+        Ok(())
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        self.transaction.is_some()
+    }
+
     pub fn set_message_id(&mut self, old_message_id: u32, new_message_id: u32) {
-        if let Some(msg) = self.get_message_mut(old_message_id) {
-            msg.message_id = new_message_id;
-        } else {
+        let Some(index) = self.find_message_index(old_message_id) else {
             return;
-        }
+        };
 
-        self.history.push(Operation::SetMessageId {
+        self.execute(Operation::SetMessageId {
+            index,
             old_id: old_message_id,
             new_id: new_message_id,
         });
     }
 
     pub fn set_message_frame_format(&mut self, message_id: u32, new_format: FrameFormat) {
-        let old_format = {
-            if let Some(msg) = self.get_message_mut(message_id) {
-                let old_format = msg.frame_format;
-                msg.frame_format = new_format;
-                old_format
-            } else {
-                return;
-            }
+        let Some(msg) = self.get_message(message_id) else {
+            return;
         };
 
-        self.history.push(Operation::SetMessageFrameFormat {
+        self.execute(Operation::SetMessageFrameFormat {
             message_id,
-            old_format,
+            old_format: msg.frame_format,
             new_format,
         });
     }
 
     pub fn set_message_name(&mut self, message_id: u32, new_name: &str) {
-        let old_name = {
-            if let Some(msg) = self.get_message_mut(message_id) {
-                let old_name = msg.message_name.clone();
-                msg.message_name = new_name.to_string();
-                old_name
-            } else {
-                return;
-            }
+        let Some(msg) = self.get_message(message_id) else {
+            return;
         };
 
-        self.history.push(Operation::SetMessageName {
+        self.execute(Operation::SetMessageName {
             message_id,
-            old_name,
+            old_name: msg.message_name.clone(),
             new_name: new_name.to_string(),
         });
     }
 
     pub fn set_message_size(&mut self, message_id: u32, new_size: u64) {
-        let old_size = {
-            if let Some(msg) = self.get_message_mut(message_id) {
-                let old_size = msg.message_size;
-                msg.message_size = new_size;
-                old_size
-            } else {
-                return;
-            }
+        let Some(msg) = self.get_message(message_id) else {
+            return;
         };
 
-        self.history.push(Operation::SetMessageSize {
+        self.execute(Operation::SetMessageSize {
             message_id,
-            old_size,
+            old_size: msg.message_size,
             new_size,
         });
     }
 
     pub fn set_message_transmitter(&mut self, message_id: u32, new_transmitter: &str) {
-        let old_transmitter = {
-            if let Some(msg) = self.get_message_mut(message_id) {
-                let old_transmitter = msg.transmitter.clone();
-                msg.transmitter = new_transmitter.to_string();
-                old_transmitter
-            } else {
-                return;
-            }
+        let Some(msg) = self.get_message(message_id) else {
+            return;
         };
 
-        self.history.push(Operation::SetMessageTransmitter {
+        self.execute(Operation::SetMessageTransmitter {
             message_id,
-            old_transmitter,
+            old_transmitter: msg.transmitter.clone(),
             new_transmitter: new_transmitter.to_string(),
         });
     }
 
     pub fn set_message_comment(&mut self, message_id: u32, new_comment: &str) {
-        let old_comment = {
-            if let Some(msg) = self.get_message_mut(message_id) {
-                let old_comment = msg.comment.clone();
-                msg.comment = new_comment.to_string();
-                old_comment
-            } else {
-                return;
-            }
+        let Some(msg) = self.get_message(message_id) else {
+            return;
         };
 
-        self.history.push(Operation::SetMessageComment {
+        self.execute(Operation::SetMessageComment {
             message_id,
-            old_comment,
+            old_comment: msg.comment.clone(),
             new_comment: new_comment.to_string(),
         });
     }
@@ -413,19 +1538,14 @@ impl EditableDbc {
         signal_old_name: &str,
         signal_new_name: &str,
     ) {
-        let old_name = {
-            if let Some(sig) = self.get_signal_mut(message_id, signal_old_name) {
-                let old_name = sig.name.clone();
-                sig.name = signal_new_name.to_string();
-                old_name
-            } else {
-                return;
-            }
+        let Some(index) = self.find_signal_index(message_id, signal_old_name) else {
+            return;
         };
 
-        self.history.push(Operation::SetSignalName {
+        self.execute(Operation::SetSignalName {
             message_id,
-            signal_old_name: old_name,
+            index,
+            signal_old_name: signal_old_name.to_string(),
             signal_new_name: signal_new_name.to_string(),
         });
     }
@@ -436,59 +1556,41 @@ impl EditableDbc {
         signal_name: &str,
         new_indicator: &MultiplexIndicator,
     ) {
-        let old_indicator = {
-            if let Some(sig) = self.get_signal_mut(message_id, signal_name) {
-                let old_indicator = sig.multiplexer_indicator;
-                sig.multiplexer_indicator = new_indicator.clone();
-                old_indicator
-            } else {
-                return;
-            }
+        let Some(sig) = self.get_signal(message_id, signal_name) else {
+            return;
         };
 
-        self.history.push(Operation::SetSignalMultiplexerIndicator {
-            message_id: message_id,
+        self.execute(Operation::SetSignalMultiplexerIndicator {
+            message_id,
             signal_name: signal_name.to_string(),
-            old_indicator: old_indicator,
-            new_indicator: new_indicator.clone(),
+            old_indicator: sig.multiplexer_indicator,
+            new_indicator: *new_indicator,
         });
     }
 
     pub fn set_signal_start_bit(&mut self, message_id: u32, signal_name: &str, new_start_bit: u64) {
-        let old_start_bit = {
-            if let Some(sig) = self.get_signal_mut(message_id, signal_name) {
-                let old_start_bit = sig.start_bit;
-                sig.start_bit = new_start_bit;
-                old_start_bit
-            } else {
-                return;
-            }
+        let Some(sig) = self.get_signal(message_id, signal_name) else {
+            return;
         };
 
-        self.history.push(Operation::SetSignalStartBit {
-            message_id: message_id,
+        self.execute(Operation::SetSignalStartBit {
+            message_id,
             signal_name: signal_name.to_string(),
-            old_start_bit: old_start_bit,
-            new_start_bit: new_start_bit,
+            old_start_bit: sig.start_bit,
+            new_start_bit,
         });
     }
 
     pub fn set_signal_size(&mut self, message_id: u32, signal_name: &str, new_size: u64) {
-        let old_size = {
-            if let Some(sig) = self.get_signal_mut(message_id, signal_name) {
-                let old_size = sig.signal_size;
-                sig.signal_size = new_size;
-                old_size
-            } else {
-                return;
-            }
+        let Some(sig) = self.get_signal(message_id, signal_name) else {
+            return;
         };
 
-        self.history.push(Operation::SetSignalSize {
-            message_id: message_id,
+        self.execute(Operation::SetSignalSize {
+            message_id,
             signal_name: signal_name.to_string(),
-            old_size: old_size,
-            new_size: new_size,
+            old_size: sig.signal_size,
+            new_size,
         });
     }
 
@@ -498,21 +1600,15 @@ impl EditableDbc {
         signal_name: &str,
         new_byte_order: ByteOrder,
     ) {
-        let old_byte_order = {
-            if let Some(sig) = self.get_signal_mut(message_id, signal_name) {
-                let old_byte_order = sig.byte_order;
-                sig.byte_order = new_byte_order;
-                old_byte_order
-            } else {
-                return;
-            }
+        let Some(sig) = self.get_signal(message_id, signal_name) else {
+            return;
         };
 
-        self.history.push(Operation::SetSignalByteOrder {
-            message_id: message_id,
+        self.execute(Operation::SetSignalByteOrder {
+            message_id,
             signal_name: signal_name.to_string(),
-            old_byte_order: old_byte_order,
-            new_byte_order: new_byte_order,
+            old_byte_order: sig.byte_order,
+            new_byte_order,
         });
     }
 
@@ -522,115 +1618,79 @@ impl EditableDbc {
         signal_name: &str,
         new_value_type: ValueType,
     ) {
-        let old_value_type = {
-            if let Some(sig) = self.get_signal_mut(message_id, signal_name) {
-                let old_value_type = sig.value_type;
-                sig.value_type = new_value_type;
-                old_value_type
-            } else {
-                return;
-            }
+        let Some(sig) = self.get_signal(message_id, signal_name) else {
+            return;
         };
 
-        self.history.push(Operation::SetSignalValueType {
-            message_id: message_id,
+        self.execute(Operation::SetSignalValueType {
+            message_id,
             signal_name: signal_name.to_string(),
-            old_value_type: old_value_type,
-            new_value_type: new_value_type,
+            old_value_type: sig.value_type,
+            new_value_type,
         });
     }
 
     pub fn set_signal_factor(&mut self, message_id: u32, signal_name: &str, new_factor: f64) {
-        let old_factor = {
-            if let Some(sig) = self.get_signal_mut(message_id, signal_name) {
-                let old_factor = sig.factor;
-                sig.factor = new_factor;
-                old_factor
-            } else {
-                return;
-            }
+        let Some(sig) = self.get_signal(message_id, signal_name) else {
+            return;
         };
 
-        self.history.push(Operation::SetSignalFactor {
-            message_id: message_id,
+        self.execute(Operation::SetSignalFactor {
+            message_id,
             signal_name: signal_name.to_string(),
-            old_factor: old_factor,
-            new_factor: new_factor,
+            old_factor: sig.factor,
+            new_factor,
         });
     }
 
     pub fn set_signal_offset(&mut self, message_id: u32, signal_name: &str, new_offset: f64) {
-        let old_offset = {
-            if let Some(sig) = self.get_signal_mut(message_id, signal_name) {
-                let old_offset = sig.offset;
-                sig.offset = new_offset;
-                old_offset
-            } else {
-                return;
-            }
+        let Some(sig) = self.get_signal(message_id, signal_name) else {
+            return;
         };
 
-        self.history.push(Operation::SetSignalOffset {
-            message_id: message_id,
+        self.execute(Operation::SetSignalOffset {
+            message_id,
             signal_name: signal_name.to_string(),
-            old_offset: old_offset,
-            new_offset: new_offset,
+            old_offset: sig.offset,
+            new_offset,
         });
     }
 
     pub fn set_signal_min(&mut self, message_id: u32, signal_name: &str, new_min: f64) {
-        let old_min = {
-            if let Some(sig) = self.get_signal_mut(message_id, signal_name) {
-                let old_min = sig.min;
-                sig.min = new_min;
-                old_min
-            } else {
-                return;
-            }
+        let Some(sig) = self.get_signal(message_id, signal_name) else {
+            return;
         };
 
-        self.history.push(Operation::SetSignalMin {
-            message_id: message_id,
+        self.execute(Operation::SetSignalMin {
+            message_id,
             signal_name: signal_name.to_string(),
-            old_min: old_min,
-            new_min: new_min,
+            old_min: sig.min,
+            new_min,
         });
     }
 
     pub fn set_signal_max(&mut self, message_id: u32, signal_name: &str, new_max: f64) {
-        let old_max = {
-            if let Some(sig) = self.get_signal_mut(message_id, signal_name) {
-                let old_max = sig.max;
-                sig.max = new_max;
-                old_max
-            } else {
-                return;
-            }
+        let Some(sig) = self.get_signal(message_id, signal_name) else {
+            return;
         };
 
-        self.history.push(Operation::SetSignalMax {
-            message_id: message_id,
+        self.execute(Operation::SetSignalMax {
+            message_id,
             signal_name: signal_name.to_string(),
-            old_max: old_max,
-            new_max: new_max,
+            old_max: sig.max,
+            new_max,
         });
     }
 
     pub fn set_signal_unit(&mut self, message_id: u32, signal_name: &str, new_unit: &str) {
-        let old_unit = {
-            if let Some(sig) = self.get_signal_mut(message_id, signal_name) {
-                let old_unit = sig.unit.clone();
-                sig.unit = new_unit.to_string();
-                old_unit
-            } else {
-                return;
-            }
+        let Some(sig) = self.get_signal(message_id, signal_name) else {
+            return;
         };
 
-        self.history.push(Operation::SetSignalUnit {
-            message_id: message_id,
+        self.execute(Operation::SetSignalUnit {
+            message_id,
             signal_name: signal_name.to_string(),
-            old_unit: old_unit,
+            old_unit: sig.unit.clone(),
             new_unit: new_unit.to_string(),
         });
     }
@@ -641,47 +1701,317 @@ impl EditableDbc {
         signal_name: &str,
         new_receivers: Vec<String>,
     ) {
-        let old_receivers = {
-            if let Some(sig) = self.get_signal_mut(message_id, signal_name) {
-                let old_receivers = sig.receivers.clone();
-                sig.receivers = new_receivers.clone();
-                old_receivers
-            } else {
-                return;
-            }
+        let Some(sig) = self.get_signal(message_id, signal_name) else {
+            return;
         };
 
-        self.history.push(Operation::SetSignalReceivers {
-            message_id: message_id,
+        self.execute(Operation::SetSignalReceivers {
+            message_id,
             signal_name: signal_name.to_string(),
-            old_receivers: old_receivers,
-            new_receivers: new_receivers,
+            old_receivers: sig.receivers.clone(),
+            new_receivers,
         });
     }
 
     pub fn set_signal_comment(&mut self, message_id: u32, signal_name: &str, new_comment: &str) {
-        let old_comment = {
-            if let Some(sig) = self.get_signal_mut(message_id, signal_name) {
-                let old_comment = sig.comment.clone();
-                sig.comment = new_comment.to_string();
-                old_comment
-            } else {
-                return;
-            }
+        let Some(sig) = self.get_signal(message_id, signal_name) else {
+            return;
         };
 
-        self.history.push(Operation::SetSignalComment {
-            message_id: message_id,
+        self.execute(Operation::SetSignalComment {
+            message_id,
             signal_name: signal_name.to_string(),
-            old_comment: old_comment,
+            old_comment: sig.comment.clone(),
             new_comment: new_comment.to_string(),
         });
     }
 
+    pub fn set_signal_value_descriptions(
+        &mut self,
+        message_id: u32,
+        signal_name: &str,
+        new_descriptions: Vec<(i64, String)>,
+    ) {
+        let Some(sig) = self.get_signal(message_id, signal_name) else {
+            return;
+        };
+
+        self.execute(Operation::SetSignalValueDescriptions {
+            message_id,
+            signal_name: signal_name.to_string(),
+            old_descriptions: sig.value_descriptions.clone(),
+            new_descriptions,
+        });
+    }
+
+    pub fn value_tables(&self) -> &Vec<EditableValueTable> {
+        &self.value_tables
+    }
+
+    pub fn get_value_table(&self, name: &str) -> Option<&EditableValueTable> {
+        self.value_tables.iter().find(|t| t.name == name)
+    }
+
+    pub fn add_value_table(&mut self, table: &EditableValueTable) {
+        self.execute(Operation::AddValueTable {
+            table: table.clone(),
+            index: self.value_tables.len(),
+        });
+    }
+
+    pub fn delete_value_table(&mut self, name: &str) {
+        let Some(index) = self.value_tables.iter().position(|t| t.name == name) else {
+            return;
+        };
+
+        self.execute(Operation::DeleteValueTable {
+            table: self.value_tables[index].clone(),
+            index,
+        });
+    }
+
+    pub fn set_value_table_name(&mut self, old_name: &str, new_name: &str) {
+        if self.get_value_table(old_name).is_none() {
+            return;
+        }
+
+        self.execute(Operation::SetValueTableName {
+            old_name: old_name.to_string(),
+            new_name: new_name.to_string(),
+        });
+    }
+
+    pub fn set_value_table_descriptions(
+        &mut self,
+        name: &str,
+        new_descriptions: Vec<(i64, String)>,
+    ) {
+        let Some(table) = self.get_value_table(name) else {
+            return;
+        };
+
+        self.execute(Operation::SetValueTableDescriptions {
+            table_name: name.to_string(),
+            old_descriptions: table.descriptions.clone(),
+            new_descriptions,
+        });
+    }
+
+    pub fn environment_variables(&self) -> &Vec<EditableEnvironmentVariable> {
+        &self.environment_variables
+    }
+
+    pub fn attribute_definitions(&self) -> &Vec<AttributeDefinition> {
+        &self.attribute_definitions
+    }
+
+    pub fn get_attribute_definition(&self, name: &str) -> Option<&AttributeDefinition> {
+        self.attribute_definitions.iter().find(|d| d.name() == name)
+    }
+
+    /// 可以附加到该类型对象上的属性定义
+    pub fn attribute_definitions_for(
+        &self,
+        object_type: AttributeObjectType,
+    ) -> impl Iterator<Item = &AttributeDefinition> {
+        self.attribute_definitions
+            .iter()
+            .filter(move |d| d.object_type() == object_type)
+    }
+
+    /// 对象上显式设置的属性值，对象不存在时返回 None
+    pub fn attribute_values(&self, target: &AttributeTarget) -> Option<&AttributeValues> {
+        const EMPTY: &AttributeValues = &Vec::new();
+        match target {
+            AttributeTarget::Network => Some(&self.network_attributes),
+            AttributeTarget::Node(node) => {
+                if !self.nodes.contains(node) {
+                    return None;
+                }
+                Some(object_attributes(&self.node_attributes, node).unwrap_or(EMPTY))
+            }
+            AttributeTarget::Message(message_id) => {
+                Some(&self.get_message(*message_id)?.attributes)
+            }
+            AttributeTarget::Signal(message_id, signal_name) => {
+                Some(&self.get_signal(*message_id, signal_name)?.attributes)
+            }
+            AttributeTarget::EnvironmentVariable(name) => {
+                if !self.environment_variables.iter().any(|e| &e.name == name) {
+                    return None;
+                }
+                Some(
+                    object_attributes(&self.environment_variable_attributes, name).unwrap_or(EMPTY),
+                )
+            }
+        }
+    }
+
+    /// 对象上显式设置的属性值
+    pub fn attribute_value(&self, target: &AttributeTarget, name: &str) -> Option<&AttributeValue> {
+        self.attribute_values(target)?
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value)
+    }
+
+    /// 属性的实际值：没有显式设置时使用定义中的默认值
+    pub fn effective_attribute_value(
+        &self,
+        target: &AttributeTarget,
+        name: &str,
+    ) -> Option<&AttributeValue> {
+        self.attribute_value(target, name)
+            .or_else(|| self.get_attribute_definition(name)?.default())
+    }
+
+    /// Message 的发送周期（`GenMsgCycleTime` 属性，单位为 ms），没有定义或不大于 0 时返回 None
+    pub fn message_cycle_time(&self, message_id: u32) -> Option<u64> {
+        let target = AttributeTarget::Message(message_id);
+        match self.effective_attribute_value(&target, "GenMsgCycleTime")? {
+            AttributeValue::Int(v) if *v > 0 => Some(*v as u64),
+            AttributeValue::Float(v) if *v > 0.0 => Some(v.round() as u64),
+            _ => None,
+        }
+    }
+
+    /// 设置属性值，值必须符合属性定义的类型、范围和枚举列表
+    pub fn set_attribute_value(
+        &mut self,
+        target: &AttributeTarget,
+        name: &str,
+        value: AttributeValue,
+    ) -> Result<(), String> {
+        let definition = self
+            .get_attribute_definition(name)
+            .ok_or_else(|| format!("Attribute {} not defined", name))?;
+        if definition.object_type() != target.object_type() {
+            return Err(format!(
+                "Attribute {} is defined for {} objects, not {}",
+                name,
+                definition.object_type(),
+                target.object_type()
+            ));
+        }
+        let value = definition.normalize(value);
+        definition.validate(&value)?;
+        let old_value = self
+            .attribute_values(target)
+            .ok_or_else(|| format!("{:?} not found", target))?
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.clone());
+        if old_value.as_ref() == Some(&value) {
+            return Ok(());
+        }
+
+        self.execute(Operation::SetAttributeValue {
+            target: target.clone(),
+            name: name.to_string(),
+            old_value,
+            new_value: Some(value),
+        });
+        Ok(())
+    }
+
+    /// 删除显式设置的属性值，恢复为默认值
+    pub fn reset_attribute_value(&mut self, target: &AttributeTarget, name: &str) {
+        let Some(old_value) = self.attribute_value(target, name).cloned() else {
+            return;
+        };
+
+        self.execute(Operation::SetAttributeValue {
+            target: target.clone(),
+            name: name.to_string(),
+            old_value: Some(old_value),
+            new_value: None,
+        });
+    }
+
+    pub fn add_attribute_definition(
+        &mut self,
+        definition: &AttributeDefinition,
+    ) -> Result<(), String> {
+        if self.get_attribute_definition(definition.name()).is_some() {
+            return Err(format!("Attribute {} already defined", definition.name()));
+        }
+        let definition = definition.clone().checked()?;
+
+        self.execute(Operation::AddAttributeDefinition {
+            definition,
+            index: self.attribute_definitions.len(),
+            values: Vec::new(),
+        });
+        Ok(())
+    }
+
+    // 导入其他格式时使用：属性没有定义时先添加定义，再设置属性值
+    fn set_mapped_attribute(
+        &mut self,
+        target: &AttributeTarget,
+        definition: &AttributeDefinition,
+        value: AttributeValue,
+    ) -> Result<(), String> {
+        if self.get_attribute_definition(definition.name()).is_none() {
+            self.add_attribute_definition(definition)?;
+        }
+        self.set_attribute_value(target, definition.name(), value)
+    }
+
+    /// 删除属性定义以及所有对象上的该属性值
+    pub fn delete_attribute_definition(&mut self, name: &str) {
+        let Some(index) = self.find_attribute_definition_index(name) else {
+            return;
+        };
+
+        self.execute(Operation::DeleteAttributeDefinition {
+            definition: self.attribute_definitions[index].clone(),
+            index,
+            values: self.collect_attribute_values(name),
+        });
+    }
+
+    /// 修改属性定义，已有的属性值不会被重新检查
+    pub fn set_attribute_definition(
+        &mut self,
+        name: &str,
+        new_definition: &AttributeDefinition,
+    ) -> Result<(), String> {
+        let old_definition = self
+            .get_attribute_definition(name)
+            .ok_or_else(|| format!("Attribute {} not defined", name))?
+            .clone();
+        if new_definition.name() != name
+            && self
+                .get_attribute_definition(new_definition.name())
+                .is_some()
+        {
+            return Err(format!(
+                "Attribute {} already defined",
+                new_definition.name()
+            ));
+        }
+        if new_definition.object_type() != old_definition.object_type()
+            && !self.collect_attribute_values(name).is_empty()
+        {
+            return Err(format!(
+                "Cannot change the object type of attribute {} while it has values",
+                name
+            ));
+        }
+        let new_definition = new_definition.clone().checked()?;
+
+        self.execute(Operation::SetAttributeDefinition {
+            old_definition,
+            new_definition,
+        });
+        Ok(())
+    }
+
     pub fn add_message(&mut self, message: &EditableMessage) {
-        self.messages.push(message.clone());
-        self.history.push(Operation::AddMessage {
+        self.execute(Operation::AddMessage {
             message: message.clone(),
+            index: self.messages.len(),
         });
     }
 
@@ -691,25 +2021,26 @@ impl EditableDbc {
     }
 
     pub fn delete_message(&mut self, message_id: u32) {
-        let msg = {
-            if let Some(msg_idx) = self.find_message_index(message_id) {
-                self.messages.swap_remove(msg_idx)
-            } else {
-                return;
-            }
+        let Some(index) = self.find_message_index(message_id) else {
+            return;
         };
 
-        self.history.push(Operation::DeleteMessage { message: msg });
+        self.execute(Operation::DeleteMessage {
+            message: self.messages[index].clone(),
+            index,
+        });
     }
 
     pub fn add_signal(&mut self, message_id: u32, signal: &EditableSignal) {
-        if let Some(msg) = self.get_message_mut(message_id) {
-            msg.signals.push(signal.clone());
-            self.history.push(Operation::AddSignal {
-                message_id: message_id,
-                signal: signal.clone(),
-            });
-        }
+        let Some(msg) = self.get_message(message_id) else {
+            return;
+        };
+
+        self.execute(Operation::AddSignal {
+            message_id,
+            signal: signal.clone(),
+            index: msg.signals.len(),
+        });
     }
 
     pub fn new_signal(&mut self, message_id: u32) {
@@ -718,71 +2049,70 @@ impl EditableDbc {
     }
 
     pub fn delete_signal(&mut self, message_id: u32, signal_name: &str) {
-        let sig = {
-            if let Some((msg_idx, sig_idx)) =
-                self.find_message_signal_index(message_id, signal_name)
-            {
-                self.messages[msg_idx].signals.swap_remove(sig_idx)
-            } else {
-                return;
-            }
+        let Some((msg_idx, sig_idx)) = self.find_message_signal_index(message_id, signal_name)
+        else {
+            return;
         };
 
-        self.history.push(Operation::DeleteSignal {
-            message_id: message_id,
-            signal: sig,
+        self.execute(Operation::DeleteSignal {
+            message_id,
+            signal: self.messages[msg_idx].signals[sig_idx].clone(),
+            index: sig_idx,
         });
     }
 
-    // NOT IMPLEMENTED
-    // DO NOT USE
-    pub fn undo(&mut self) -> Result<Operation, String> {
-        if let Some(op) = self.history.pop() {
-            let op_clone = op.clone();
-            match op {
-                Operation::AddSignal { message_id, signal } => {
-                    self.delete_signal(message_id, &signal.name);
-                    Ok(op_clone)
-                }
+    pub fn can_undo(&self) -> bool {
+        self.transaction.is_none() && self.current_index > 0
+    }
 
-                Operation::DeleteSignal { message_id, signal } => {
-                    self.add_signal(message_id, &signal);
-                    Ok(op_clone)
-                }
-                _ => {
-                    println!("Undo not implemented for this operation");
-                    Err("Undo not implemented for this operation".into())
-                }
-            }
-        } else {
-            println!("No operation to undo");
-            Err("No operation to undo".into())
+    pub fn can_redo(&self) -> bool {
+        self.transaction.is_none() && self.current_index < self.history.len()
+    }
+
+    /// 下一次 undo 会撤销的操作名称
+    pub fn undo_label(&self) -> Option<String> {
+        if !self.can_undo() {
+            return None;
         }
+        Some(self.history[self.current_index - 1].label())
     }
 
-    // NOT IMPLEMENTED
-    // DO NOT USE
-    pub fn redo(&mut self) -> Result<Operation, String> {
-        if let Some(op) = self.history.pop() {
-            let op_clone = op.clone();
-            match op {
-                Operation::AddSignal { message_id, signal } => {
-                    self.add_signal(message_id, &signal);
-                    Ok(op_clone)
-                }
+    /// 下一次 redo 会重做的操作名称
+    pub fn redo_label(&self) -> Option<String> {
+        if !self.can_redo() {
+            return None;
+        }
+        Some(self.history[self.current_index].label())
+    }
 
-                Operation::DeleteSignal { message_id, signal } => {
-                    self.delete_signal(message_id, &signal.name);
-                    Ok(op_clone)
-                }
-                _ => {
-                    println!("Redo not implemented for this operation");
-                    Err("Redo not implemented for this operation".into())
-                }
-            }
-        } else {
-            Err("No operation to redo".into())
+    /// 撤销 current_index 前的一个操作，返回被撤销的操作
+    pub fn undo(&mut self) -> Result<Operation, String> {
+        if self.transaction.is_some() {
+            return Err("Cannot undo while a transaction is in progress".into());
+        }
+        if !self.can_undo() {
+            return Err("No operation to undo".into());
+        }
+
+        let op = self.history[self.current_index - 1].clone();
+        self.apply_operation(&op.inverse())?;
+        self.current_index -= 1;
+        Ok(op)
+    }
+
+    /// 重做 current_index 处的操作，返回被重做的操作
+    pub fn redo(&mut self) -> Result<Operation, String> {
+        if self.transaction.is_some() {
+            return Err("Cannot redo while a transaction is in progress".into());
+        }
+        if !self.can_redo() {
+            return Err("No operation to redo".into());
         }
+
+        let op = self.history[self.current_index].clone();
+        self.apply_operation(&op)?;
+        self.current_index += 1;
+        Ok(op)
     }
 }
 
@@ -795,8 +2125,12 @@ impl EditableMessage {
             message_name: String::new(),
             message_size: 0,
             transmitter: "Vector__XXX".to_string(),
+            extra_transmitters: Vec::new(),
             signals: Vec::new(),
             comment: String::new(),
+            attributes: Vec::new(),
+            fd: false,
+            bit_rate_switch: false,
         }
     }
 
@@ -807,16 +2141,42 @@ impl EditableMessage {
             message_name: self.message_name.clone(),
             message_size: self.message_size,
             transmitter: self.transmitter.clone(),
+            extra_transmitters: self.extra_transmitters.clone(),
             signals: Vec::new(),
             comment: self.comment.clone(),
+            attributes: self.attributes.clone(),
+            fd: self.fd,
+            bit_rate_switch: self.bit_rate_switch,
         }
     }
 
-    fn from_message(msg: &Message, comment: &str) -> Self {
+    // 注释不在 Message 结构中，需要从 Dbc 的 CM_ 列表里查找
+    fn from_message(msg: &Message, dbc: &Dbc) -> Self {
         let signals = msg
             .signals
             .iter()
-            .map(|sig| EditableSignal::from_signal(sig))
+            .map(|sig| {
+                let mut signal = EditableSignal::from_signal(
+                    sig,
+                    dbc.signal_comment(msg.id, &sig.name).unwrap_or(""),
+                    dbc.value_descriptions_for_signal(msg.id, &sig.name)
+                        .map(value_descriptions_from_dbc)
+                        .unwrap_or_default(),
+                );
+                signal.extended_value_type = dbc
+                    .signal_extended_value_type_list
+                    .iter()
+                    .find(|v| v.message_id == msg.id && v.signal_name == sig.name)
+                    .map(|v| v.signal_extended_value_type);
+                signal
+            })
+            .collect();
+
+        let extra_transmitters = dbc
+            .message_transmitters
+            .iter()
+            .filter(|t| t.message_id == msg.id)
+            .flat_map(|t| t.transmitter.iter().map(transmitter_name))
             .collect();
 
         let message_id = msg.id.raw();
@@ -826,16 +2186,17 @@ impl EditableMessage {
         };
 
         Self {
-            message_id: message_id,
-            frame_format: frame_format,
+            message_id,
+            frame_format,
             message_name: msg.name.clone(),
             message_size: msg.size,
-            transmitter: match msg.transmitter.clone() {
-                Transmitter::VectorXXX => "Vector__XXX".to_string(),
-                Transmitter::NodeName(name) => name.clone(),
-            },
-            signals: signals,
-            comment: comment.to_string(),
+            transmitter: transmitter_name(&msg.transmitter),
+            extra_transmitters,
+            signals,
+            comment: unescape(dbc.message_comment(msg.id).unwrap_or("")),
+            attributes: Vec::new(),
+            fd: false,
+            bit_rate_switch: false,
         }
     }
 
@@ -858,12 +2219,24 @@ impl EditableMessage {
     pub fn transmitter(&self) -> &str {
         &self.transmitter
     }
+    pub fn extra_transmitters(&self) -> &Vec<String> {
+        &self.extra_transmitters
+    }
     pub fn signals(&self) -> &Vec<EditableSignal> {
         &self.signals
     }
     pub fn comment(&self) -> &str {
         &self.comment
     }
+    pub fn attributes(&self) -> &AttributeValues {
+        &self.attributes
+    }
+}
+
+impl Default for EditableSignal {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[allow(dead_code)]
@@ -876,6 +2249,7 @@ impl EditableSignal {
             signal_size: 0,
             byte_order: ByteOrder::LittleEndian,
             value_type: ValueType::Unsigned,
+            extended_value_type: None,
             factor: 1.0,
             offset: 0.0,
             min: 0.0,
@@ -883,10 +2257,12 @@ impl EditableSignal {
             unit: String::new(),
             receivers: Vec::new(),
             comment: String::new(),
+            value_descriptions: Vec::new(),
+            attributes: Vec::new(),
         }
     }
 
-    fn from_signal(sig: &Signal) -> Self {
+    fn from_signal(sig: &Signal, comment: &str, value_descriptions: Vec<(i64, String)>) -> Self {
         Self {
             name: sig.name.to_string(),
             multiplexer_indicator: sig.multiplexer_indicator,
@@ -894,13 +2270,16 @@ impl EditableSignal {
             signal_size: sig.size,
             byte_order: sig.byte_order,
             value_type: sig.value_type,
+            extended_value_type: None,
             factor: sig.factor,
             offset: sig.offset,
             min: sig.min,
             max: sig.max,
-            unit: sig.unit.clone(),
+            unit: unescape(&sig.unit),
             receivers: sig.receivers.clone(),
-            comment: String::new(),
+            comment: unescape(comment),
+            value_descriptions,
+            attributes: Vec::new(),
         }
     }
     pub fn name(&self) -> &str {
@@ -921,6 +2300,9 @@ impl EditableSignal {
     pub fn value_type(&self) -> &ValueType {
         &self.value_type
     }
+    pub fn extended_value_type(&self) -> Option<SignalExtendedValueType> {
+        self.extended_value_type
+    }
     pub fn factor(&self) -> f64 {
         self.factor
     }
@@ -942,6 +2324,210 @@ impl EditableSignal {
     pub fn comment(&self) -> &str {
         &self.comment
     }
+    pub fn value_descriptions(&self) -> &Vec<(i64, String)> {
+        &self.value_descriptions
+    }
+    pub fn attributes(&self) -> &AttributeValues {
+        &self.attributes
+    }
+
+    /// 查找原始值对应的描述文本
+    pub fn value_description(&self, raw_value: i64) -> Option<&str> {
+        self.value_descriptions
+            .iter()
+            .find(|(value, _)| *value == raw_value)
+            .map(|(_, description)| description.as_str())
+    }
+}
+
+#[allow(dead_code)]
+impl EditableValueTable {
+    pub fn new(name: &str, descriptions: Vec<(i64, String)>) -> Self {
+        Self {
+            name: name.to_string(),
+            descriptions,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn descriptions(&self) -> &Vec<(i64, String)> {
+        &self.descriptions
+    }
+}
+
+#[allow(dead_code)]
+impl EditableEnvironmentVariable {
+    fn from_environment_variable(env: &EnvironmentVariable, dbc: &Dbc) -> Self {
+        let comment = dbc.comments.iter().find_map(|c| match c {
+            Comment::EnvVar { name, comment } if *name == env.name => Some(unescape(comment)),
+            _ => None,
+        });
+
+        Self {
+            name: env.name.clone(),
+            env_type: env.typ,
+            min: env.min,
+            max: env.max,
+            unit: unescape(&env.unit),
+            initial_value: env.initial_value,
+            ev_id: env.ev_id,
+            access_type: env.access_type,
+            access_nodes: env
+                .access_nodes
+                .iter()
+                .map(|node| match node {
+                    AccessNode::VectorXXX => "VECTOR__XXX".to_string(),
+                    AccessNode::Name(name) => name.clone(),
+                })
+                .collect(),
+            comment: comment.unwrap_or_default(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn env_type(&self) -> EnvType {
+        self.env_type
+    }
+    pub fn min(&self) -> i64 {
+        self.min
+    }
+    pub fn max(&self) -> i64 {
+        self.max
+    }
+    pub fn unit(&self) -> &str {
+        &self.unit
+    }
+    pub fn initial_value(&self) -> i64 {
+        self.initial_value
+    }
+    pub fn ev_id(&self) -> i64 {
+        self.ev_id
+    }
+    pub fn access_type(&self) -> AccessType {
+        self.access_type
+    }
+    pub fn access_nodes(&self) -> &Vec<String> {
+        &self.access_nodes
+    }
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+}
+
+// 在属性值列表中设置或删除一个属性值，新的属性追加到末尾
+fn set_attribute_in(attributes: &mut AttributeValues, name: &str, value: Option<AttributeValue>) {
+    let index = attributes.iter().position(|(n, _)| n == name);
+    match (index, value) {
+        (Some(index), Some(value)) => attributes[index].1 = value,
+        (Some(index), None) => {
+            attributes.remove(index);
+        }
+        (None, Some(value)) => attributes.push((name.to_string(), value)),
+        (None, None) => {}
+    }
+}
+
+fn object_attributes<'a>(
+    objects: &'a [(String, AttributeValues)],
+    name: &str,
+) -> Option<&'a AttributeValues> {
+    objects
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, attributes)| attributes)
+}
+
+fn object_attributes_mut<'a>(
+    objects: &'a mut Vec<(String, AttributeValues)>,
+    name: &str,
+) -> &'a mut AttributeValues {
+    let index = match objects.iter().position(|(n, _)| n == name) {
+        Some(index) => index,
+        None => {
+            objects.push((name.to_string(), Vec::new()));
+            objects.len() - 1
+        }
+    };
+    &mut objects[index].1
+}
+
+// 与 EditableDbc::resolve_message_index 相同，Signal 名称重复时也能找到正确的 Signal
+fn resolve_signal_index(
+    message: &EditableMessage,
+    index: usize,
+    signal_name: &str,
+) -> Result<usize, String> {
+    match message.signals.get(index) {
+        Some(s) if s.name == signal_name => Ok(index),
+        _ => message
+            .signals
+            .iter()
+            .position(|s| s.name == signal_name)
+            .ok_or_else(|| {
+                format!(
+                    "Signal {} not found in 0x{:X}",
+                    signal_name, message.message_id
+                )
+            }),
+    }
+}
+
+fn transmitter_name(transmitter: &Transmitter) -> String {
+    match transmitter {
+        Transmitter::VectorXXX => "Vector__XXX".to_string(),
+        Transmitter::NodeName(name) => name.clone(),
+    }
+}
+
+// can-dbc 能解析但 EditableDbc 无法表示的段落，每个段落一条警告
+fn unsupported_dbc_sections(dbc: &Dbc) -> Vec<String> {
+    let env_value_descriptions = dbc
+        .value_descriptions
+        .iter()
+        .filter(|v| matches!(v, ValueDescription::EnvironmentVariable { .. }))
+        .count();
+    let relation_attributes = dbc.relation_attribute_definitions.len()
+        + dbc.relation_attribute_defaults.len()
+        + dbc.relation_attribute_values.len();
+    [
+        (dbc.signal_types.len(), "signal types (SGTYPE_)"),
+        (
+            dbc.signal_type_refs.len(),
+            "signal type references (SIG_TYPE_REF_)",
+        ),
+        (dbc.signal_groups.len(), "signal groups (SIG_GROUP_)"),
+        (
+            dbc.extended_multiplex.len(),
+            "extended multiplexing entries (SG_MUL_VAL_)",
+        ),
+        (
+            dbc.environment_variable_data.len(),
+            "environment variable data entries (ENVVAR_DATA_)",
+        ),
+        (
+            env_value_descriptions,
+            "environment variable value descriptions (VAL_)",
+        ),
+        (
+            relation_attributes,
+            "relation attributes (BA_DEF_REL_ / BA_DEF_DEF_REL_ / BA_REL_)",
+        ),
+    ]
+    .into_iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, section)| format!("{count} {section} are not supported"))
+    .collect()
+}
+
+fn value_descriptions_from_dbc(descriptions: &[ValDescription]) -> Vec<(i64, String)> {
+    descriptions
+        .iter()
+        .map(|d| (d.id, unescape(&d.description)))
+        .collect()
 }
 
 #[cfg(test)]
@@ -1050,4 +2636,463 @@ SIG_VALTYPE_ 2000 Signal_8 : 1;
         assert_eq!(sig_signal_8.unit(), "");
         assert_eq!(sig_signal_8.receivers(), &vec!["Vector__XXX".to_string()]);
     }
+
+    #[test]
+    fn test_modified_tracking() {
+        let dbc = Dbc::try_from(SAMPLE_DBC).unwrap();
+        let mut editable_dbc = EditableDbc::from_dbc(&dbc);
+        assert!(!editable_dbc.is_modified());
+
+        editable_dbc.set_message_name(2000, "Renamed");
+        assert!(editable_dbc.is_modified());
+
+        editable_dbc.mark_saved();
+        assert!(!editable_dbc.is_modified());
+
+        // 对不存在的 Message 的修改不会产生历史记录
+        editable_dbc.set_message_name(1, "Missing");
+        assert!(!editable_dbc.is_modified());
+    }
+
+    // 依次进行每一种编辑操作，返回每一步之后的 Message 列表
+    fn apply_every_operation(editable_dbc: &mut EditableDbc) -> Vec<Vec<EditableMessage>> {
+        let mut snapshots = vec![editable_dbc.messages().clone()];
+        let mut snapshot = |editable_dbc: &EditableDbc| {
+            snapshots.push(editable_dbc.messages().clone());
+        };
+
+        editable_dbc.set_message_id(2000, 2001);
+        snapshot(editable_dbc);
+        editable_dbc.set_message_frame_format(2001, FrameFormat::Extended);
+        snapshot(editable_dbc);
+        editable_dbc.set_message_name(2001, "Renamed");
+        snapshot(editable_dbc);
+        editable_dbc.set_message_size(2001, 8);
+        snapshot(editable_dbc);
+        editable_dbc.set_message_transmitter(2001, "PC");
+        snapshot(editable_dbc);
+        editable_dbc.set_message_comment(2001, "comment");
+        snapshot(editable_dbc);
+        editable_dbc.set_signal_name(2001, "Signal_8", "Speed");
+        snapshot(editable_dbc);
+        editable_dbc.set_signal_multiplexer_indicator(
+            2001,
+            "Speed",
+            &MultiplexIndicator::Multiplexor,
+        );
+        snapshot(editable_dbc);
+        editable_dbc.set_signal_start_bit(2001, "Speed", 32);
+        snapshot(editable_dbc);
+        editable_dbc.set_signal_size(2001, "Speed", 16);
+        snapshot(editable_dbc);
+        editable_dbc.set_signal_byte_order(2001, "Speed", ByteOrder::BigEndian);
+        snapshot(editable_dbc);
+        editable_dbc.set_signal_value_type(2001, "Speed", ValueType::Signed);
+        snapshot(editable_dbc);
+        editable_dbc.set_signal_factor(2001, "Speed", 0.5);
+        snapshot(editable_dbc);
+        editable_dbc.set_signal_offset(2001, "Speed", -10.0);
+        snapshot(editable_dbc);
+        editable_dbc.set_signal_min(2001, "Speed", -10.0);
+        snapshot(editable_dbc);
+        editable_dbc.set_signal_max(2001, "Speed", 100.0);
+        snapshot(editable_dbc);
+        editable_dbc.set_signal_unit(2001, "Speed", "km/h");
+        snapshot(editable_dbc);
+        editable_dbc.set_signal_receivers(2001, "Speed", vec!["PC".to_string()]);
+        snapshot(editable_dbc);
+        editable_dbc.set_signal_comment(2001, "Speed", "vehicle speed");
+        snapshot(editable_dbc);
+        editable_dbc.set_signal_value_descriptions(
+            2001,
+            "Speed",
+            vec![(0, "Stop".to_string()), (1, "Run".to_string())],
+        );
+        snapshot(editable_dbc);
+        editable_dbc.new_signal(2001);
+        snapshot(editable_dbc);
+        editable_dbc.delete_signal(2001, "Signal_6");
+        snapshot(editable_dbc);
+        editable_dbc.new_message();
+        snapshot(editable_dbc);
+        editable_dbc.delete_message(1840);
+        snapshot(editable_dbc);
+
+        snapshots
+    }
+
+    #[test]
+    fn test_undo_redo_every_operation() {
+        let dbc = Dbc::try_from(SAMPLE_DBC).unwrap();
+        let mut editable_dbc = EditableDbc::from_dbc(&dbc);
+        let snapshots = apply_every_operation(&mut editable_dbc);
+        let steps = snapshots.len() - 1;
+        assert_eq!(steps, 24);
+
+        // 每一步撤销都要回到上一步的状态
+        for i in (0..steps).rev() {
+            assert!(editable_dbc.undo().is_ok());
+            assert_eq!(editable_dbc.messages(), &snapshots[i]);
+        }
+        assert!(!editable_dbc.can_undo());
+        assert!(editable_dbc.undo().is_err());
+        assert!(!editable_dbc.is_modified());
+
+        for snapshot in snapshots.iter().skip(1) {
+            assert!(editable_dbc.redo().is_ok());
+            assert_eq!(editable_dbc.messages(), snapshot);
+        }
+        assert!(!editable_dbc.can_redo());
+        assert!(editable_dbc.redo().is_err());
+    }
+
+    #[test]
+    fn test_delete_restores_position() {
+        let dbc = Dbc::try_from(SAMPLE_DBC).unwrap();
+        let mut editable_dbc = EditableDbc::from_dbc(&dbc);
+        let original = editable_dbc.messages().clone();
+
+        editable_dbc.delete_signal(2000, "Signal_7");
+        editable_dbc.delete_message(2000);
+        editable_dbc.undo().unwrap();
+        editable_dbc.undo().unwrap();
+
+        assert_eq!(editable_dbc.messages(), &original);
+    }
+
+    #[test]
+    fn test_undo_edit_that_creates_duplicate() {
+        let dbc = Dbc::try_from(
+            r#"VERSION ""
+
+BS_:
+
+BU_: PC
+
+BO_ 256 M1: 8 PC
+ SG_ S1 : 0|8@1+ (1,0) [0|255] "" PC
+ SG_ S2 : 8|8@1+ (1,0) [0|255] "" PC
+
+BO_ 512 M2: 8 PC
+"#,
+        )
+        .unwrap();
+        let mut editable_dbc = EditableDbc::from_dbc(&dbc);
+        let original = editable_dbc.messages().clone();
+
+        // 修改后两个 Message 的 ID 相同，撤销时要恢复 M2 的 ID 而不是 M1 的
+        editable_dbc.set_message_id(0x200, 0x100);
+        assert_eq!(editable_dbc.messages()[1].message_id(), 0x100);
+        editable_dbc.undo().unwrap();
+        assert_eq!(editable_dbc.messages(), &original);
+        editable_dbc.redo().unwrap();
+        assert_eq!(editable_dbc.messages()[0].message_name(), "M1");
+        assert_eq!(editable_dbc.messages()[1].message_id(), 0x100);
+        editable_dbc.undo().unwrap();
+
+        editable_dbc.set_signal_name(0x100, "S2", "S1");
+        editable_dbc.undo().unwrap();
+        assert_eq!(editable_dbc.messages(), &original);
+    }
+
+    #[test]
+    fn test_new_edit_discards_redo() {
+        let dbc = Dbc::try_from(SAMPLE_DBC).unwrap();
+        let mut editable_dbc = EditableDbc::from_dbc(&dbc);
+
+        editable_dbc.set_message_name(2000, "First");
+        editable_dbc.set_message_name(2000, "Second");
+        editable_dbc.undo().unwrap();
+        assert!(editable_dbc.can_redo());
+
+        editable_dbc.set_message_name(2000, "Third");
+        assert!(!editable_dbc.can_redo());
+
+        editable_dbc.undo().unwrap();
+        assert_eq!(
+            editable_dbc.get_message(2000).unwrap().message_name(),
+            "First"
+        );
+        editable_dbc.undo().unwrap();
+        assert_eq!(
+            editable_dbc.get_message(2000).unwrap().message_name(),
+            "WebData_2000"
+        );
+    }
+
+    #[test]
+    fn test_modified_after_undo() {
+        let dbc = Dbc::try_from(SAMPLE_DBC).unwrap();
+        let mut editable_dbc = EditableDbc::from_dbc(&dbc);
+
+        editable_dbc.set_message_name(2000, "First");
+        editable_dbc.mark_saved();
+        editable_dbc.undo().unwrap();
+        assert!(editable_dbc.is_modified());
+        editable_dbc.redo().unwrap();
+        assert!(!editable_dbc.is_modified());
+
+        // 撤销后进行新的编辑，保存时的状态再也回不去了
+        editable_dbc.undo().unwrap();
+        editable_dbc.set_message_name(2000, "Other");
+        editable_dbc.undo().unwrap();
+        assert!(editable_dbc.is_modified());
+    }
+
+    #[test]
+    fn test_value_descriptions() {
+        let dbc = Dbc::try_from(
+            r#"VERSION ""
+
+BS_:
+
+BU_: PC
+
+VAL_TABLE_ OnOff 1 "On" 0 "Off" ;
+
+BO_ 100 Status: 1 PC
+ SG_ Switch : 0|1@1+ (1,0) [0|1] "" PC
+
+VAL_ 100 Switch 1 "On" 0 "Off" ;
+"#,
+        )
+        .unwrap();
+        let mut editable_dbc = EditableDbc::from_dbc(&dbc);
+
+        let switch = editable_dbc.get_signal(100, "Switch").unwrap();
+        assert_eq!(switch.value_description(1), Some("On"));
+        assert_eq!(switch.value_description(2), None);
+        assert_eq!(editable_dbc.value_tables().len(), 1);
+        assert_eq!(
+            editable_dbc
+                .get_value_table("OnOff")
+                .unwrap()
+                .descriptions(),
+            &vec![(1, "On".to_string()), (0, "Off".to_string())]
+        );
+
+        let original = editable_dbc.clone();
+        editable_dbc.set_value_table_name("OnOff", "Switch");
+        editable_dbc.set_value_table_descriptions("Switch", vec![(0, "Low".to_string())]);
+        editable_dbc.add_value_table(&EditableValueTable::new("Empty", Vec::new()));
+        editable_dbc.delete_value_table("Switch");
+        assert_eq!(
+            editable_dbc.value_tables(),
+            &vec![EditableValueTable::new("Empty", Vec::new())]
+        );
+
+        while editable_dbc.can_undo() {
+            editable_dbc.undo().unwrap();
+        }
+        assert_eq!(editable_dbc.value_tables(), original.value_tables());
+    }
+
+    #[test]
+    fn test_transaction_undo_in_one_step() {
+        let dbc = Dbc::try_from(SAMPLE_DBC).unwrap();
+        let mut editable_dbc = EditableDbc::from_dbc(&dbc);
+        let original = editable_dbc.messages().clone();
+
+        editable_dbc
+            .begin_transaction("Modify Message WebData_2000")
+            .unwrap();
+        editable_dbc.set_message_name(2000, "EngineData");
+        editable_dbc.set_message_size(2000, 8);
+        editable_dbc.set_message_transmitter(2000, "PC");
+        editable_dbc.set_message_comment(2000, "engine");
+        editable_dbc.set_message_id(2000, 0x100);
+        assert!(!editable_dbc.can_undo());
+        editable_dbc.commit_transaction().unwrap();
+        let edited = editable_dbc.messages().clone();
+
+        assert_eq!(
+            editable_dbc.undo_label().as_deref(),
+            Some("Modify Message WebData_2000")
+        );
+        editable_dbc.undo().unwrap();
+        assert_eq!(editable_dbc.messages(), &original);
+        assert!(!editable_dbc.can_undo());
+        assert!(!editable_dbc.is_modified());
+
+        editable_dbc.redo().unwrap();
+        assert_eq!(editable_dbc.messages(), &edited);
+    }
+
+    #[test]
+    fn test_transaction_rollback_and_errors() {
+        let dbc = Dbc::try_from(SAMPLE_DBC).unwrap();
+        let mut editable_dbc = EditableDbc::from_dbc(&dbc);
+        let original = editable_dbc.messages().clone();
+
+        assert!(editable_dbc.commit_transaction().is_err());
+        editable_dbc.begin_transaction("Edit").unwrap();
+        assert!(editable_dbc.begin_transaction("Nested").is_err());
+        editable_dbc.delete_signal(2000, "Signal_7");
+        editable_dbc.set_message_name(2000, "Renamed");
+        assert!(editable_dbc.undo().is_err());
+        editable_dbc.rollback_transaction().unwrap();
+
+        assert_eq!(editable_dbc.messages(), &original);
+        assert!(!editable_dbc.can_undo());
+
+        // 空事务不产生历史记录
+        editable_dbc.begin_transaction("Empty").unwrap();
+        editable_dbc.commit_transaction().unwrap();
+        assert!(!editable_dbc.is_modified());
+        assert_eq!(editable_dbc.undo_label(), None);
+    }
+
+    #[test]
+    fn test_undo_delete_attribute_definition_restores_order() {
+        let dbc = Dbc::try_from(
+            r#"VERSION ""
+
+BS_:
+
+BU_: A B
+
+BO_ 100 Status: 8 A
+ SG_ Speed : 0|16@1+ (1,0) [0|65535] "" B
+
+BA_DEF_ BO_  "First" INT 0 100;
+BA_DEF_ BO_  "Second" INT 0 100;
+BA_DEF_ BU_  "NodeFirst" INT 0 100;
+BA_DEF_ BU_  "NodeSecond" INT 0 100;
+BA_ "First" BO_ 100 1;
+BA_ "Second" BO_ 100 2;
+BA_ "NodeFirst" BU_ A 1;
+BA_ "NodeSecond" BU_ A 2;
+BA_ "NodeFirst" BU_ B 3;
+"#,
+        )
+        .unwrap();
+        let mut editable_dbc = EditableDbc::from_dbc(&dbc);
+        let original = editable_dbc.clone();
+
+        // 删除后 B 上没有属性值了，条目也被删除，撤销时要插回 A 的后面
+        editable_dbc.delete_attribute_definition("First");
+        editable_dbc.delete_attribute_definition("NodeFirst");
+        assert_eq!(editable_dbc.node_attributes.len(), 1);
+        editable_dbc.undo().unwrap();
+        editable_dbc.undo().unwrap();
+
+        assert_eq!(editable_dbc.messages(), original.messages());
+        assert_eq!(editable_dbc.node_attributes, original.node_attributes);
+        assert_eq!(editable_dbc.to_string(), original.to_string());
+    }
+
+    #[test]
+    fn test_attributes() {
+        let dbc = Dbc::try_from(SAMPLE_DBC).unwrap();
+        let mut editable_dbc = EditableDbc::from_dbc(&dbc);
+        let original = editable_dbc.clone();
+        assert_eq!(editable_dbc.message_cycle_time(2000), None);
+
+        let cycle_time = AttributeDefinition::new(
+            "GenMsgCycleTime",
+            AttributeObjectType::Message,
+            AttributeValueType::Int { min: 0, max: 1000 },
+        )
+        .with_default(AttributeValue::Int(100));
+        editable_dbc.add_attribute_definition(&cycle_time).unwrap();
+        assert!(editable_dbc.add_attribute_definition(&cycle_time).is_err());
+        let node_type = AttributeDefinition::new(
+            "NodeType",
+            AttributeObjectType::Node,
+            AttributeValueType::Enum(vec!["Gateway".into(), "Sensor".into()]),
+        );
+        editable_dbc.add_attribute_definition(&node_type).unwrap();
+
+        let message = AttributeTarget::Message(2000);
+        let node = AttributeTarget::Node("PC".to_string());
+        assert_eq!(
+            editable_dbc.effective_attribute_value(&message, "GenMsgCycleTime"),
+            Some(&AttributeValue::Int(100))
+        );
+
+        // 类型、范围、枚举列表和对象类型都要检查
+        assert!(
+            editable_dbc
+                .set_attribute_value(&message, "GenMsgCycleTime", AttributeValue::Int(5000))
+                .is_err()
+        );
+        assert!(
+            editable_dbc
+                .set_attribute_value(
+                    &message,
+                    "GenMsgCycleTime",
+                    AttributeValue::String("x".into())
+                )
+                .is_err()
+        );
+        assert!(
+            editable_dbc
+                .set_attribute_value(&node, "NodeType", AttributeValue::String("Actuator".into()))
+                .is_err()
+        );
+        assert!(
+            editable_dbc
+                .set_attribute_value(&node, "GenMsgCycleTime", AttributeValue::Int(10))
+                .is_err()
+        );
+        assert!(
+            editable_dbc
+                .set_attribute_value(
+                    &AttributeTarget::Node("Missing".into()),
+                    "NodeType",
+                    AttributeValue::Int(0)
+                )
+                .is_err()
+        );
+
+        editable_dbc
+            .set_attribute_value(&message, "GenMsgCycleTime", AttributeValue::Int(20))
+            .unwrap();
+        editable_dbc
+            .set_attribute_value(&node, "NodeType", AttributeValue::Int(1))
+            .unwrap();
+        assert_eq!(
+            editable_dbc.attribute_value(&message, "GenMsgCycleTime"),
+            Some(&AttributeValue::Int(20))
+        );
+        assert_eq!(editable_dbc.message_cycle_time(2000), Some(20));
+
+        // 改名时属性值跟随，删除定义时属性值一起删除
+        let renamed = AttributeDefinition::new(
+            "CycleTime",
+            AttributeObjectType::Message,
+            AttributeValueType::Int { min: 0, max: 1000 },
+        );
+        editable_dbc
+            .set_attribute_definition("GenMsgCycleTime", &renamed)
+            .unwrap();
+        assert_eq!(
+            editable_dbc.attribute_value(&message, "CycleTime"),
+            Some(&AttributeValue::Int(20))
+        );
+        editable_dbc.delete_attribute_definition("NodeType");
+        assert_eq!(editable_dbc.attribute_value(&node, "NodeType"), None);
+
+        editable_dbc.undo().unwrap();
+        assert_eq!(
+            editable_dbc.attribute_value(&node, "NodeType"),
+            Some(&AttributeValue::Int(1))
+        );
+
+        editable_dbc.reset_attribute_value(&message, "CycleTime");
+        assert_eq!(
+            editable_dbc.effective_attribute_value(&message, "CycleTime"),
+            None
+        );
+
+        while editable_dbc.can_undo() {
+            editable_dbc.undo().unwrap();
+        }
+        assert_eq!(editable_dbc.messages(), original.messages());
+        assert_eq!(
+            editable_dbc.attribute_definitions(),
+            original.attribute_definitions()
+        );
+        assert_eq!(editable_dbc.node_attributes, original.node_attributes);
+    }
 }
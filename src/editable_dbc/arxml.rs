@@ -0,0 +1,1235 @@
+use std::collections::HashMap;
+
+use can_dbc::{ByteOrder, MultiplexIndicator, ValueType};
+
+use super::codec::swap_bit_order;
+use super::writer::EXTENDED_ID_FLAG;
+use super::xml::{self, Element};
+use super::{
+    AttributeDefinition, AttributeTarget, AttributeValue, EditableDbc, EditableMessage,
+    EditableSignal, FrameFormat,
+};
+
+// AUTOSAR 系统描述 (ARXML, AUTOSAR 4.x) 的导入，只导入一个 CAN 集群
+// 元素之间通过 *-REF 中的完整路径（各级 SHORT-NAME 用 / 连接）引用，先建立路径索引再转换
+//
+// 对应关系：
+// - CAN-CLUSTER 的名称和 BAUDRATE 对应网络属性 DBName 和 Baudrate
+// - CAN-FRAME-TRIGGERING 对应 Message：IDENTIFIER、CAN-ADDRESSING-MODE 和 CAN-FD 帧格式来自
+//   triggering，名称、长度和注释来自引用的 CAN-FRAME
+// - FRAME-PORT-REFS 引用 ECU-INSTANCE 中的 FRAME-PORT，方向为 OUT 的 ECU 是发送节点，
+//   方向为 IN 的 ECU 是所有信号的接收节点
+// - CAN-FRAME 中的 I-SIGNAL-I-PDU / NM-PDU 的 I-SIGNAL-TO-I-PDU-MAPPING 对应 Signal，
+//   PDU 的 CYCLIC-TIMING 对应 GenMsgCycleTime，I-SIGNAL 的 INIT-VALUE 对应 GenSigStartValue
+// - MULTIPLEXED-I-PDU 的选择字段对应复用器，DYNAMIC-PART-ALTERNATIVE 中的信号对应复用信号
+// - COMPU-METHOD 的线性 COMPU-SCALE 对应 factor / offset，带 VT 的 COMPU-SCALE 对应值描述
+// - START-POSITION 是信号最低位的位置，Motorola 信号需要转换成 DBC 中最高位的位置
+// 无法表示的内容记录在转换日志中
+
+/// 其他总线的集群，导入时跳过
+const OTHER_CLUSTERS: [&str; 3] = ["LIN-CLUSTER", "FLEXRAY-CLUSTER", "ETHERNET-CLUSTER"];
+
+fn short_name(element: &Element) -> &str {
+    element.child_text("SHORT-NAME").unwrap_or("")
+}
+
+// DESC 中的第一段文字，通常每种语言一段
+fn description(element: &Element) -> Option<String> {
+    let text = element.child("DESC")?.descendant("L-2")?.text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn number<T: std::str::FromStr>(element: &Element, name: &str) -> Option<T> {
+    element.child_text(name)?.trim().parse().ok()
+}
+
+// LOWER-LIMIT / UPPER-LIMIT 可以写成 INF，只取有限的值
+fn limit(element: &Element, name: &str) -> Option<f64> {
+    number::<f64>(element, name).filter(|v| v.is_finite())
+}
+
+/// 由最低位的位置得到 DBC 的起始位，Motorola 信号超出负载开头时返回 None
+fn dbc_start_bit(position: u64, length: u64, byte_order: ByteOrder) -> Option<u64> {
+    match byte_order {
+        ByteOrder::LittleEndian => Some(position),
+        ByteOrder::BigEndian => {
+            // 按字节从高位到低位连续编号时，Motorola 信号的最高位在最低位前面 length - 1 位
+            let lsb = swap_bit_order(position);
+            let msb = lsb.checked_sub(length.max(1) - 1)?;
+            Some(swap_bit_order(msb))
+        }
+    }
+}
+
+fn byte_order(text: Option<&str>) -> ByteOrder {
+    match text {
+        Some("MOST-SIGNIFICANT-BYTE-FIRST") => ByteOrder::BigEndian,
+        _ => ByteOrder::LittleEndian,
+    }
+}
+
+fn parse_arxml(text: &str) -> Result<Element, String> {
+    let root = xml::parse(text)?;
+    if root.name != "AUTOSAR" {
+        return Err(format!(
+            "Not an ARXML file: the root element is <{}>, expected <AUTOSAR>",
+            root.name
+        ));
+    }
+    Ok(root)
+}
+
+impl EditableDbc {
+    /// ARXML 中所有 CAN 集群的名称，按文件中的顺序
+    pub fn arxml_clusters(text: &str) -> Result<Vec<String>, String> {
+        let root = parse_arxml(text)?;
+        Ok(root
+            .descendants("CAN-CLUSTER")
+            .into_iter()
+            .map(|cluster| short_name(cluster).to_string())
+            .collect())
+    }
+
+    /// 从 AUTOSAR 系统描述中导入一个 CAN 集群，返回结果和转换日志
+    ///
+    /// cluster 为 None 时文件中只能有一个 CAN 集群，有多个时先用 `arxml_clusters` 列出再选择
+    pub fn from_arxml(text: &str, cluster: Option<&str>) -> Result<(Self, Vec<String>), String> {
+        let root = parse_arxml(text)?;
+        let clusters = root.descendants("CAN-CLUSTER");
+        let cluster = match cluster {
+            Some(name) => clusters
+                .iter()
+                .find(|c| short_name(c) == name)
+                .ok_or_else(|| format!("CAN cluster {name} not found"))?,
+            None => match clusters.as_slice() {
+                [] => return Err("The file contains no CAN cluster".to_string()),
+                [only] => only,
+                _ => {
+                    let names: Vec<&str> = clusters.iter().map(|c| short_name(c)).collect();
+                    return Err(format!(
+                        "The file contains {} CAN clusters, choose one of: {}",
+                        names.len(),
+                        names.join(", ")
+                    ));
+                }
+            },
+        };
+
+        let mut reader = ArxmlReader::default();
+        reader.index(&root, "", None);
+        for kind in OTHER_CLUSTERS {
+            for other in root.descendants(kind) {
+                reader.log.push(format!(
+                    "{kind} {}: only CAN clusters are imported",
+                    short_name(other)
+                ));
+            }
+        }
+        Ok(reader.read_cluster(cluster))
+    }
+}
+
+/// 一个 Frame 转换的结果，属性值在所有 Message 添加后再设置
+struct ImportedFrame {
+    message: EditableMessage,
+    fd: bool,
+    cycle_time: Option<i64>,
+    start_values: Vec<(String, i64)>,
+}
+
+/// 转换过程中的状态：路径索引、端口所属的 ECU 和转换日志
+#[derive(Default)]
+struct ArxmlReader<'a> {
+    elements: HashMap<String, &'a Element>,
+    // FRAME-PORT 的路径到 (ECU 名称, 是否为发送方向)
+    frame_ports: HashMap<String, (&'a str, bool)>,
+    // 所有 ECU-INSTANCE，按文件中的顺序
+    ecus: Vec<&'a str>,
+    // 在导入的 Frame 中发送或接收的 ECU
+    used_ecus: Vec<&'a str>,
+    log: Vec<String>,
+}
+
+impl<'a> ArxmlReader<'a> {
+    // 记录所有带 SHORT-NAME 的元素的路径，同时记录 ECU 的 FRAME-PORT
+    fn index(&mut self, element: &'a Element, parent: &str, ecu: Option<&'a str>) {
+        let mut ecu = ecu;
+        let path = match element.child_text("SHORT-NAME") {
+            Some(name) => {
+                let path = format!("{parent}/{name}");
+                self.elements.insert(path.clone(), element);
+                if element.name == "ECU-INSTANCE" {
+                    self.ecus.push(name);
+                    ecu = Some(name);
+                }
+                path
+            }
+            None => parent.to_string(),
+        };
+        if element.name == "FRAME-PORT"
+            && let Some(ecu) = ecu
+        {
+            let sends = element.child_text("COMMUNICATION-DIRECTION") == Some("OUT");
+            self.frame_ports.insert(path.clone(), (ecu, sends));
+        }
+        for child in &element.children {
+            self.index(child, &path, ecu);
+        }
+    }
+
+    // 子元素 name（*-REF）引用的元素，引用不存在时记录日志
+    fn resolve(&mut self, element: &Element, name: &str, context: &str) -> Option<&'a Element> {
+        let reference = element.child(name)?;
+        self.lookup(reference, context)
+    }
+
+    // 在多个元素的后代中依次查找引用，返回第一个找到的引用指向的元素
+    fn resolve_in(
+        &mut self,
+        sources: &[&'a Element],
+        name: &str,
+        context: &str,
+    ) -> Option<&'a Element> {
+        let reference = sources.iter().find_map(|source| source.descendant(name))?;
+        self.lookup(reference, context)
+    }
+
+    fn lookup(&mut self, reference: &Element, context: &str) -> Option<&'a Element> {
+        let path = reference.text.trim();
+        let target = self.elements.get(path).copied();
+        if target.is_none() {
+            self.log.push(format!(
+                "{context}: {} {path} does not exist",
+                reference.name
+            ));
+        }
+        target
+    }
+
+    fn use_ecu(&mut self, ecu: &'a str) {
+        if !self.used_ecus.contains(&ecu) {
+            self.used_ecus.push(ecu);
+        }
+    }
+
+    fn read_cluster(mut self, cluster: &'a Element) -> (EditableDbc, Vec<String>) {
+        let cluster_name = short_name(cluster);
+        let mut mapped = vec![(
+            AttributeTarget::Network,
+            AttributeDefinition::bus_name(),
+            AttributeValue::String(cluster_name.to_string()),
+        )];
+        if let Some(baudrate) = cluster
+            .descendant("BAUDRATE")
+            .and_then(|b| b.text.trim().parse().ok())
+        {
+            mapped.push((
+                AttributeTarget::Network,
+                AttributeDefinition::baudrate(),
+                AttributeValue::Int(baudrate),
+            ));
+        }
+        if let Some(fd_baudrate) = cluster.descendant("CAN-FD-BAUDRATE") {
+            self.log.push(format!(
+                "CAN cluster {cluster_name}: CAN FD data baudrate {} is not imported",
+                fd_baudrate.text
+            ));
+        }
+
+        let mut frames: Vec<ImportedFrame> = Vec::new();
+        for triggering in cluster.descendants("CAN-FRAME-TRIGGERING") {
+            if let Some(frame) = self.read_frame(triggering, &frames) {
+                frames.push(frame);
+            }
+        }
+
+        let mut dbc = EditableDbc::new();
+        // 节点只包含与导入的 Frame 有关的 ECU
+        dbc.nodes = self
+            .ecus
+            .iter()
+            .filter(|ecu| self.used_ecus.contains(ecu))
+            .map(|ecu| ecu.to_string())
+            .collect();
+        let mut fd_messages = Vec::new();
+        for frame in frames {
+            let id = frame.message.message_id;
+            if frame.fd {
+                fd_messages.push(id);
+            }
+            if let Some(cycle_time) = frame.cycle_time {
+                mapped.push((
+                    AttributeTarget::Message(id),
+                    AttributeDefinition::cycle_time(),
+                    AttributeValue::Int(cycle_time),
+                ));
+            }
+            for (signal, value) in frame.start_values {
+                mapped.push((
+                    AttributeTarget::Signal(id, signal),
+                    AttributeDefinition::start_value(),
+                    AttributeValue::Int(value),
+                ));
+            }
+            dbc.messages.push(frame.message);
+        }
+
+        // ARXML 中没有单独的 BRS 设置，CAN FD 帧都按使用位速率切换导入
+        for id in fd_messages {
+            if let Err(e) = dbc.set_message_can_fd(id, true, true) {
+                self.log.push(e);
+            }
+        }
+        for (target, definition, value) in mapped {
+            if let Err(e) = dbc.set_mapped_attribute(&target, &definition, value) {
+                self.log.push(e);
+            }
+        }
+        dbc.clear_history();
+        (dbc, self.log)
+    }
+
+    // 与已经导入的 Frame 的 ID 相同时跳过
+    fn read_frame(
+        &mut self,
+        triggering: &'a Element,
+        imported: &[ImportedFrame],
+    ) -> Option<ImportedFrame> {
+        let context = format!("Frame triggering {}", short_name(triggering));
+        let frame_format = match triggering.child_text("CAN-ADDRESSING-MODE") {
+            Some("EXTENDED") => FrameFormat::Extended,
+            _ => FrameFormat::Standard,
+        };
+        let max_id = match frame_format {
+            FrameFormat::Standard => 0x7FF,
+            FrameFormat::Extended => 0x1FFF_FFFF,
+        };
+        let Some(id) = number::<u32>(triggering, "IDENTIFIER").filter(|id| *id <= max_id) else {
+            self.log.push(format!(
+                "{context}: invalid identifier {:?}, skipped",
+                triggering.child_text("IDENTIFIER").unwrap_or("")
+            ));
+            return None;
+        };
+        let fd = ["CAN-FRAME-TX-BEHAVIOR", "CAN-FRAME-RX-BEHAVIOR"]
+            .iter()
+            .any(|name| triggering.child_text(name) == Some("CAN-FD"));
+        let frame = self.resolve(triggering, "FRAME-REF", &context)?;
+
+        let name = short_name(frame);
+        let context = format!("Frame {name}");
+        let message_id = match frame_format {
+            FrameFormat::Standard => id,
+            FrameFormat::Extended => id | EXTENDED_ID_FLAG,
+        };
+        if let Some(other) = imported.iter().find(|f| f.message.message_id == message_id) {
+            self.log.push(format!(
+                "{context}: id 0x{id:X} is already used by {}, skipped",
+                other.message.message_name
+            ));
+            return None;
+        }
+        let mut message = EditableMessage {
+            message_id,
+            frame_format,
+            message_name: name.to_string(),
+            message_size: number(frame, "FRAME-LENGTH").unwrap_or(0),
+            comment: description(frame).unwrap_or_default(),
+            ..EditableMessage::new()
+        };
+
+        let mut senders = Vec::new();
+        let mut receivers = Vec::new();
+        let port_refs = triggering
+            .child("FRAME-PORT-REFS")
+            .map(|refs| refs.children.as_slice())
+            .unwrap_or_default();
+        for port_ref in port_refs {
+            match self.frame_ports.get(port_ref.text.trim()).copied() {
+                Some((ecu, true)) => senders.push(ecu),
+                Some((ecu, false)) => receivers.push(ecu),
+                None => self.log.push(format!(
+                    "{context}: frame port {} does not belong to an ECU",
+                    port_ref.text.trim()
+                )),
+            }
+        }
+        if senders.len() > 1 {
+            self.log.push(format!(
+                "{context}: sent by {}, only {} is imported as the transmitter",
+                senders.join(", "),
+                senders[0]
+            ));
+        }
+        if let Some(sender) = senders.first() {
+            message.transmitter = sender.to_string();
+            self.use_ecu(sender);
+        }
+        for receiver in &receivers {
+            self.use_ecu(receiver);
+        }
+
+        let mut cycle_time = None;
+        let mut start_values = Vec::new();
+        let mappings = frame
+            .child("PDU-TO-FRAME-MAPPINGS")
+            .map(|m| m.children.as_slice())
+            .unwrap_or_default();
+        for mapping in mappings {
+            let Some(pdu) = self.resolve(mapping, "PDU-REF", &context) else {
+                continue;
+            };
+            // 多个 PDU 时信号按 PDU 的位置平移，平移整字节时两种字节序的起始位都只需要相加
+            let offset = number::<u64>(mapping, "START-POSITION").unwrap_or(0);
+            if !offset.is_multiple_of(8) {
+                self.log.push(format!(
+                    "{context}: PDU {} starts at bit {offset}, which is not a byte boundary, skipped",
+                    short_name(pdu)
+                ));
+                continue;
+            }
+            cycle_time = cycle_time.or_else(|| pdu_cycle_time(pdu));
+            for (mut signal, start_value) in self.read_pdu(pdu, &context) {
+                signal.start_bit += offset;
+                signal.receivers = receivers.iter().map(|r| r.to_string()).collect();
+                if message.signals.iter().any(|s| s.name == signal.name) {
+                    self.log.push(format!(
+                        "{context}: duplicate signal {}, skipped",
+                        signal.name
+                    ));
+                    continue;
+                }
+                if let Some(value) = start_value {
+                    start_values.push((signal.name.clone(), value));
+                }
+                message.signals.push(signal);
+            }
+        }
+
+        Some(ImportedFrame {
+            message,
+            fd,
+            cycle_time,
+            start_values,
+        })
+    }
+
+    fn read_pdu(&mut self, pdu: &'a Element, context: &str) -> Vec<(EditableSignal, Option<i64>)> {
+        match pdu.name.as_str() {
+            "I-SIGNAL-I-PDU" | "NM-PDU" => {
+                self.read_signal_mappings(pdu, MultiplexIndicator::Plain, context)
+            }
+            "MULTIPLEXED-I-PDU" => self.read_multiplexed_pdu(pdu, context),
+            kind => {
+                self.log.push(format!(
+                    "{context}: {kind} {} is not supported, its content is not imported",
+                    short_name(pdu)
+                ));
+                Vec::new()
+            }
+        }
+    }
+
+    // 选择字段作为复用器，静态部分的信号是普通信号，动态部分的信号按 SELECTOR-FIELD-CODE 复用
+    fn read_multiplexed_pdu(
+        &mut self,
+        pdu: &'a Element,
+        context: &str,
+    ) -> Vec<(EditableSignal, Option<i64>)> {
+        let pdu_name = short_name(pdu);
+        let mut signals = Vec::new();
+        let mut has_selector = false;
+        let length = number::<u64>(pdu, "SELECTOR-FIELD-LENGTH").unwrap_or(0);
+        let position = number::<u64>(pdu, "SELECTOR-FIELD-START-POSITION").unwrap_or(0);
+        let order = byte_order(pdu.child_text("SELECTOR-FIELD-BYTE-ORDER"));
+        match dbc_start_bit(position, length, order).filter(|_| length > 0) {
+            Some(start_bit) => {
+                has_selector = true;
+                signals.push((
+                    EditableSignal {
+                        name: format!("{pdu_name}_Selector"),
+                        multiplexer_indicator: MultiplexIndicator::Multiplexor,
+                        start_bit,
+                        signal_size: length,
+                        byte_order: order,
+                        ..EditableSignal::new()
+                    },
+                    None,
+                ))
+            }
+            None => self.log.push(format!(
+                "{context}: invalid selector field in {pdu_name}, multiplexing is not imported"
+            )),
+        }
+
+        for part in pdu.descendants("STATIC-PART") {
+            if let Some(part_pdu) = self.resolve(part, "I-PDU-REF", context) {
+                let part_signals =
+                    self.read_signal_mappings(part_pdu, MultiplexIndicator::Plain, context);
+                signals.extend(part_signals);
+            }
+        }
+        for alternative in pdu.descendants("DYNAMIC-PART-ALTERNATIVE") {
+            let Some(code) = number::<u64>(alternative, "SELECTOR-FIELD-CODE") else {
+                self.log.push(format!(
+                    "{context}: dynamic part of {pdu_name} without SELECTOR-FIELD-CODE, skipped"
+                ));
+                continue;
+            };
+            if let Some(part_pdu) = self.resolve(alternative, "I-PDU-REF", context) {
+                let indicator = if has_selector {
+                    MultiplexIndicator::MultiplexedSignal(code)
+                } else {
+                    MultiplexIndicator::Plain
+                };
+                let part_signals = self.read_signal_mappings(part_pdu, indicator, context);
+                signals.extend(part_signals);
+            }
+        }
+        signals
+    }
+
+    fn read_signal_mappings(
+        &mut self,
+        pdu: &'a Element,
+        indicator: MultiplexIndicator,
+        context: &str,
+    ) -> Vec<(EditableSignal, Option<i64>)> {
+        let mut signals = Vec::new();
+        let mappings = pdu
+            .child("I-SIGNAL-TO-PDU-MAPPINGS")
+            .map(|m| m.children.as_slice())
+            .unwrap_or_default();
+        for mapping in mappings {
+            if mapping.child("I-SIGNAL-GROUP-REF").is_some() {
+                self.log.push(format!(
+                    "{context}: signal group {} is not imported",
+                    short_name(mapping)
+                ));
+                continue;
+            }
+            let Some(isignal) = self.resolve(mapping, "I-SIGNAL-REF", context) else {
+                continue;
+            };
+            if let Some(signal) = self.read_signal(isignal, mapping, indicator, context) {
+                signals.push(signal);
+            }
+        }
+        signals
+    }
+
+    fn read_signal(
+        &mut self,
+        isignal: &'a Element,
+        mapping: &'a Element,
+        multiplexer_indicator: MultiplexIndicator,
+        context: &str,
+    ) -> Option<(EditableSignal, Option<i64>)> {
+        let name = short_name(isignal);
+        let context = format!("{context}, signal {name}");
+        let order = byte_order(mapping.child_text("PACKING-BYTE-ORDER"));
+        let length = number::<u64>(isignal, "LENGTH").filter(|l| (1..=64).contains(l));
+        let position = number::<u64>(mapping, "START-POSITION");
+        let Some((length, start_bit)) = length
+            .zip(position)
+            .and_then(|(length, position)| Some((length, dbc_start_bit(position, length, order)?)))
+        else {
+            self.log.push(format!(
+                "{context}: invalid LENGTH or START-POSITION, skipped"
+            ));
+            return None;
+        };
+        if mapping.child("UPDATE-INDICATION-BIT-POSITION").is_some() {
+            self.log
+                .push(format!("{context}: the update bit is not imported"));
+        }
+
+        let mut signal = EditableSignal {
+            name: name.to_string(),
+            multiplexer_indicator,
+            start_bit,
+            signal_size: length,
+            byte_order: order,
+            ..EditableSignal::new()
+        };
+        // 网络表示 (NETWORK-REPRESENTATION-PROPS) 优先，没有时使用系统信号的物理表示
+        let system_signal = self.resolve(isignal, "SYSTEM-SIGNAL-REF", &context);
+        let representation = isignal.child("NETWORK-REPRESENTATION-PROPS");
+        let sources: Vec<&'a Element> = representation
+            .into_iter()
+            .chain(system_signal.and_then(|s| s.child("PHYSICAL-PROPS")))
+            .collect();
+        signal.comment = system_signal
+            .and_then(description)
+            .or_else(|| description(isignal))
+            .unwrap_or_default();
+
+        if let Some(base_type) = self.resolve_in(&sources, "BASE-TYPE-REF", &context) {
+            match base_type.descendant("BASE-TYPE-ENCODING").map(|e| e.text.as_str()) {
+                Some("2C") => signal.value_type = ValueType::Signed,
+                None | Some("NONE" | "BOOLEAN" | "VOID") => {}
+                Some(encoding) => self.log.push(format!(
+                    "{context}: encoding {encoding} is not supported, imported as an unsigned integer"
+                )),
+            }
+        }
+        let compu_method = self.resolve_in(&sources, "COMPU-METHOD-REF", &context);
+        let raw_limits =
+            compu_method.and_then(|c| self.read_compu_method(c, &mut signal, &context));
+        // 单位优先使用计算方法中的
+        let unit_sources: Vec<&'a Element> = compu_method
+            .into_iter()
+            .chain(sources.iter().copied())
+            .collect();
+        if let Some(unit) = self.resolve_in(&unit_sources, "UNIT-REF", &context) {
+            signal.unit = unit
+                .child_text("DISPLAY-NAME")
+                .unwrap_or(short_name(unit))
+                .to_string();
+        }
+
+        // 物理值的范围优先，其次是原始值的范围
+        let to_physical = |raw: f64| raw * signal.factor + signal.offset;
+        let constraint = self
+            .resolve_in(&sources, "DATA-CONSTR-REF", &context)
+            .and_then(|c| c.descendant("DATA-CONSTR-RULE"));
+        let physical = constraint
+            .and_then(|rule| rule.child("PHYS-CONSTRS"))
+            .and_then(|c| limit(c, "LOWER-LIMIT").zip(limit(c, "UPPER-LIMIT")));
+        let internal = constraint
+            .and_then(|rule| rule.child("INTERNAL-CONSTRS"))
+            .and_then(|c| limit(c, "LOWER-LIMIT").zip(limit(c, "UPPER-LIMIT")))
+            .or(raw_limits)
+            .map(|(lower, upper)| {
+                let (a, b) = (to_physical(lower), to_physical(upper));
+                (a.min(b), a.max(b))
+            });
+        if let Some((min, max)) = physical.or(internal) {
+            signal.min = min;
+            signal.max = max;
+        }
+
+        let start_value = isignal
+            .child("INIT-VALUE")
+            .and_then(|v| v.descendant("VALUE"))
+            .and_then(|v| v.text.trim().parse::<f64>().ok());
+        let start_value = match start_value {
+            Some(value) if value.fract() == 0.0 && value != 0.0 => Some(value as i64),
+            Some(value) if value != 0.0 => {
+                self.log
+                    .push(format!("{context}: initial value {value} is not imported"));
+                None
+            }
+            _ => None,
+        };
+        Some((signal, start_value))
+    }
+
+    // 读取 factor / offset 和值描述，返回线性 COMPU-SCALE 的原始值范围
+    fn read_compu_method(
+        &mut self,
+        compu_method: &'a Element,
+        signal: &mut EditableSignal,
+        context: &str,
+    ) -> Option<(f64, f64)> {
+        let name = short_name(compu_method);
+        let category = compu_method.child_text("CATEGORY").unwrap_or("");
+        if !matches!(
+            category,
+            "" | "IDENTICAL"
+                | "LINEAR"
+                | "SCALE_LINEAR"
+                | "TEXTTABLE"
+                | "SCALE_LINEAR_AND_TEXTTABLE"
+        ) {
+            self.log.push(format!(
+                "{context}: compu method {name} of category {category} is only partly imported"
+            ));
+        }
+
+        let mut raw_limits = None;
+        let mut linear = false;
+        let scales = compu_method
+            .child("COMPU-INTERNAL-TO-PHYS")
+            .map(|c| c.descendants("COMPU-SCALE"))
+            .unwrap_or_default();
+        for scale in scales {
+            let lower = limit(scale, "LOWER-LIMIT");
+            let upper = limit(scale, "UPPER-LIMIT");
+            if let Some(vt) = scale.descendant("VT") {
+                match lower.zip(upper) {
+                    Some((lower, upper)) if lower == upper && lower.fract() == 0.0 => signal
+                        .value_descriptions
+                        .push((lower as i64, vt.text.clone())),
+                    _ => self.log.push(format!(
+                        "{context}: value range {}..{} \"{}\" of {name} is not supported",
+                        lower.map(|v| v.to_string()).unwrap_or_default(),
+                        upper.map(|v| v.to_string()).unwrap_or_default(),
+                        vt.text
+                    )),
+                }
+            } else if let Some(coefficients) = scale.child("COMPU-RATIONAL-COEFFS") {
+                let values = |name: &str| -> Vec<f64> {
+                    coefficients
+                        .child(name)
+                        .map(|c| {
+                            c.children_named("V")
+                                .filter_map(|v| v.text.trim().parse().ok())
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                };
+                let (numerator, denominator) =
+                    (values("COMPU-NUMERATOR"), values("COMPU-DENOMINATOR"));
+                let denominator = match denominator.as_slice() {
+                    [] => Some(1.0),
+                    [d] if *d != 0.0 => Some(*d),
+                    _ => None,
+                };
+                match (numerator.as_slice(), denominator) {
+                    ([offset, factor], Some(d)) if !linear => {
+                        signal.factor = factor / d;
+                        signal.offset = offset / d;
+                        raw_limits = lower.zip(upper);
+                        linear = true;
+                    }
+                    ([_, _], Some(_)) => self.log.push(format!(
+                        "{context}: compu method {name} has several linear scales, only the first is imported"
+                    )),
+                    _ => self.log.push(format!(
+                        "{context}: rational function of {name} is not supported"
+                    )),
+                }
+            } else if scale.child("COMPU-CONST").is_some() {
+                self.log.push(format!(
+                    "{context}: constant scale of {name} is not supported"
+                ));
+            }
+        }
+        raw_limits
+    }
+}
+
+// 发送周期，单位为 ms，来自 PDU 的 CYCLIC-TIMING（秒）
+fn pdu_cycle_time(pdu: &Element) -> Option<i64> {
+    let seconds: f64 = pdu
+        .descendant("CYCLIC-TIMING")?
+        .descendant("VALUE")?
+        .text
+        .trim()
+        .parse()
+        .ok()?;
+    let milliseconds = (seconds * 1000.0).round() as i64;
+    (milliseconds > 0).then_some(milliseconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editable_dbc::FileFormat;
+
+    const SAMPLE_ARXML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<AUTOSAR xmlns="http://autosar.org/schema/r4.0">
+  <AR-PACKAGES>
+    <AR-PACKAGE>
+      <SHORT-NAME>Net</SHORT-NAME>
+      <ELEMENTS>
+        <CAN-CLUSTER>
+          <SHORT-NAME>Powertrain</SHORT-NAME>
+          <CAN-CLUSTER-VARIANTS>
+            <CAN-CLUSTER-CONDITIONAL>
+              <BAUDRATE>500000</BAUDRATE>
+              <PHYSICAL-CHANNELS>
+                <CAN-PHYSICAL-CHANNEL>
+                  <SHORT-NAME>Channel</SHORT-NAME>
+                  <FRAME-TRIGGERINGS>
+                    <CAN-FRAME-TRIGGERING>
+                      <SHORT-NAME>EngineDataTriggering</SHORT-NAME>
+                      <FRAME-PORT-REFS>
+                        <FRAME-PORT-REF DEST="FRAME-PORT">/Ecus/Engine/Connector/EngineOut</FRAME-PORT-REF>
+                        <FRAME-PORT-REF DEST="FRAME-PORT">/Ecus/Dash/Connector/EngineIn</FRAME-PORT-REF>
+                      </FRAME-PORT-REFS>
+                      <FRAME-REF DEST="CAN-FRAME">/Frames/EngineData</FRAME-REF>
+                      <CAN-ADDRESSING-MODE>STANDARD</CAN-ADDRESSING-MODE>
+                      <IDENTIFIER>256</IDENTIFIER>
+                    </CAN-FRAME-TRIGGERING>
+                    <CAN-FRAME-TRIGGERING>
+                      <SHORT-NAME>GearTriggering</SHORT-NAME>
+                      <FRAME-REF DEST="CAN-FRAME">/Frames/Gear</FRAME-REF>
+                      <CAN-ADDRESSING-MODE>EXTENDED</CAN-ADDRESSING-MODE>
+                      <CAN-FRAME-TX-BEHAVIOR>CAN-FD</CAN-FRAME-TX-BEHAVIOR>
+                      <IDENTIFIER>419430401</IDENTIFIER>
+                    </CAN-FRAME-TRIGGERING>
+                    <CAN-FRAME-TRIGGERING>
+                      <SHORT-NAME>DuplicateTriggering</SHORT-NAME>
+                      <FRAME-REF DEST="CAN-FRAME">/Frames/Gear</FRAME-REF>
+                      <IDENTIFIER>256</IDENTIFIER>
+                    </CAN-FRAME-TRIGGERING>
+                  </FRAME-TRIGGERINGS>
+                </CAN-PHYSICAL-CHANNEL>
+              </PHYSICAL-CHANNELS>
+            </CAN-CLUSTER-CONDITIONAL>
+          </CAN-CLUSTER-VARIANTS>
+        </CAN-CLUSTER>
+        <CAN-CLUSTER>
+          <SHORT-NAME>Body</SHORT-NAME>
+        </CAN-CLUSTER>
+        <LIN-CLUSTER>
+          <SHORT-NAME>Seats</SHORT-NAME>
+        </LIN-CLUSTER>
+      </ELEMENTS>
+    </AR-PACKAGE>
+    <AR-PACKAGE>
+      <SHORT-NAME>Ecus</SHORT-NAME>
+      <ELEMENTS>
+        <ECU-INSTANCE>
+          <SHORT-NAME>Dash</SHORT-NAME>
+          <CONNECTORS>
+            <CAN-COMMUNICATION-CONNECTOR>
+              <SHORT-NAME>Connector</SHORT-NAME>
+              <ECU-COMM-PORT-INSTANCES>
+                <FRAME-PORT>
+                  <SHORT-NAME>EngineIn</SHORT-NAME>
+                  <COMMUNICATION-DIRECTION>IN</COMMUNICATION-DIRECTION>
+                </FRAME-PORT>
+              </ECU-COMM-PORT-INSTANCES>
+            </CAN-COMMUNICATION-CONNECTOR>
+          </CONNECTORS>
+        </ECU-INSTANCE>
+        <ECU-INSTANCE>
+          <SHORT-NAME>Unused</SHORT-NAME>
+        </ECU-INSTANCE>
+        <ECU-INSTANCE>
+          <SHORT-NAME>Engine</SHORT-NAME>
+          <CONNECTORS>
+            <CAN-COMMUNICATION-CONNECTOR>
+              <SHORT-NAME>Connector</SHORT-NAME>
+              <ECU-COMM-PORT-INSTANCES>
+                <FRAME-PORT>
+                  <SHORT-NAME>EngineOut</SHORT-NAME>
+                  <COMMUNICATION-DIRECTION>OUT</COMMUNICATION-DIRECTION>
+                </FRAME-PORT>
+              </ECU-COMM-PORT-INSTANCES>
+            </CAN-COMMUNICATION-CONNECTOR>
+          </CONNECTORS>
+        </ECU-INSTANCE>
+      </ELEMENTS>
+    </AR-PACKAGE>
+    <AR-PACKAGE>
+      <SHORT-NAME>Frames</SHORT-NAME>
+      <ELEMENTS>
+        <CAN-FRAME>
+          <SHORT-NAME>EngineData</SHORT-NAME>
+          <DESC><L-2 L="EN">Engine status</L-2></DESC>
+          <FRAME-LENGTH>8</FRAME-LENGTH>
+          <PDU-TO-FRAME-MAPPINGS>
+            <PDU-TO-FRAME-MAPPING>
+              <SHORT-NAME>EngineDataMapping</SHORT-NAME>
+              <PACKING-BYTE-ORDER>MOST-SIGNIFICANT-BYTE-LAST</PACKING-BYTE-ORDER>
+              <PDU-REF DEST="MULTIPLEXED-I-PDU">/Pdus/EngineMux</PDU-REF>
+              <START-POSITION>0</START-POSITION>
+            </PDU-TO-FRAME-MAPPING>
+          </PDU-TO-FRAME-MAPPINGS>
+        </CAN-FRAME>
+        <CAN-FRAME>
+          <SHORT-NAME>Gear</SHORT-NAME>
+          <FRAME-LENGTH>12</FRAME-LENGTH>
+          <PDU-TO-FRAME-MAPPINGS>
+            <PDU-TO-FRAME-MAPPING>
+              <SHORT-NAME>GearMapping</SHORT-NAME>
+              <PDU-REF DEST="I-SIGNAL-I-PDU">/Pdus/GearPdu</PDU-REF>
+              <START-POSITION>0</START-POSITION>
+            </PDU-TO-FRAME-MAPPING>
+          </PDU-TO-FRAME-MAPPINGS>
+        </CAN-FRAME>
+      </ELEMENTS>
+    </AR-PACKAGE>
+    <AR-PACKAGE>
+      <SHORT-NAME>Pdus</SHORT-NAME>
+      <ELEMENTS>
+        <MULTIPLEXED-I-PDU>
+          <SHORT-NAME>EngineMux</SHORT-NAME>
+          <LENGTH>8</LENGTH>
+          <DYNAMIC-PARTS>
+            <DYNAMIC-PART>
+              <DYNAMIC-PART-ALTERNATIVES>
+                <DYNAMIC-PART-ALTERNATIVE>
+                  <I-PDU-REF DEST="I-SIGNAL-I-PDU">/Pdus/Mode0</I-PDU-REF>
+                  <SELECTOR-FIELD-CODE>0</SELECTOR-FIELD-CODE>
+                </DYNAMIC-PART-ALTERNATIVE>
+                <DYNAMIC-PART-ALTERNATIVE>
+                  <I-PDU-REF DEST="I-SIGNAL-I-PDU">/Pdus/Mode1</I-PDU-REF>
+                  <SELECTOR-FIELD-CODE>1</SELECTOR-FIELD-CODE>
+                </DYNAMIC-PART-ALTERNATIVE>
+              </DYNAMIC-PART-ALTERNATIVES>
+            </DYNAMIC-PART>
+          </DYNAMIC-PARTS>
+          <I-PDU-TIMING-SPECIFICATIONS>
+            <I-PDU-TIMING>
+              <TRANSMISSION-MODE-DECLARATION>
+                <TRANSMISSION-MODE-TRUE-TIMING>
+                  <CYCLIC-TIMING>
+                    <TIME-PERIOD><VALUE>0.1</VALUE></TIME-PERIOD>
+                  </CYCLIC-TIMING>
+                </TRANSMISSION-MODE-TRUE-TIMING>
+              </TRANSMISSION-MODE-DECLARATION>
+            </I-PDU-TIMING>
+          </I-PDU-TIMING-SPECIFICATIONS>
+          <SELECTOR-FIELD-BYTE-ORDER>MOST-SIGNIFICANT-BYTE-LAST</SELECTOR-FIELD-BYTE-ORDER>
+          <SELECTOR-FIELD-LENGTH>4</SELECTOR-FIELD-LENGTH>
+          <SELECTOR-FIELD-START-POSITION>0</SELECTOR-FIELD-START-POSITION>
+          <STATIC-PARTS>
+            <STATIC-PART>
+              <I-PDU-REF DEST="I-SIGNAL-I-PDU">/Pdus/Static</I-PDU-REF>
+            </STATIC-PART>
+          </STATIC-PARTS>
+        </MULTIPLEXED-I-PDU>
+        <I-SIGNAL-I-PDU>
+          <SHORT-NAME>Static</SHORT-NAME>
+          <I-SIGNAL-TO-PDU-MAPPINGS>
+            <I-SIGNAL-TO-I-PDU-MAPPING>
+              <SHORT-NAME>StatusMapping</SHORT-NAME>
+              <I-SIGNAL-REF DEST="I-SIGNAL">/Signals/Status</I-SIGNAL-REF>
+              <PACKING-BYTE-ORDER>MOST-SIGNIFICANT-BYTE-LAST</PACKING-BYTE-ORDER>
+              <START-POSITION>4</START-POSITION>
+              <UPDATE-INDICATION-BIT-POSITION>63</UPDATE-INDICATION-BIT-POSITION>
+            </I-SIGNAL-TO-I-PDU-MAPPING>
+            <I-SIGNAL-TO-I-PDU-MAPPING>
+              <SHORT-NAME>Protection</SHORT-NAME>
+              <I-SIGNAL-GROUP-REF DEST="I-SIGNAL-GROUP">/Signals/Group</I-SIGNAL-GROUP-REF>
+            </I-SIGNAL-TO-I-PDU-MAPPING>
+          </I-SIGNAL-TO-PDU-MAPPINGS>
+        </I-SIGNAL-I-PDU>
+        <I-SIGNAL-I-PDU>
+          <SHORT-NAME>Mode0</SHORT-NAME>
+          <I-SIGNAL-TO-PDU-MAPPINGS>
+            <I-SIGNAL-TO-I-PDU-MAPPING>
+              <SHORT-NAME>RpmMapping</SHORT-NAME>
+              <I-SIGNAL-REF DEST="I-SIGNAL">/Signals/Rpm</I-SIGNAL-REF>
+              <PACKING-BYTE-ORDER>MOST-SIGNIFICANT-BYTE-LAST</PACKING-BYTE-ORDER>
+              <START-POSITION>8</START-POSITION>
+            </I-SIGNAL-TO-I-PDU-MAPPING>
+          </I-SIGNAL-TO-PDU-MAPPINGS>
+        </I-SIGNAL-I-PDU>
+        <I-SIGNAL-I-PDU>
+          <SHORT-NAME>Mode1</SHORT-NAME>
+          <I-SIGNAL-TO-PDU-MAPPINGS>
+            <I-SIGNAL-TO-I-PDU-MAPPING>
+              <SHORT-NAME>TempMapping</SHORT-NAME>
+              <I-SIGNAL-REF DEST="I-SIGNAL">/Signals/Temp</I-SIGNAL-REF>
+              <PACKING-BYTE-ORDER>MOST-SIGNIFICANT-BYTE-LAST</PACKING-BYTE-ORDER>
+              <START-POSITION>8</START-POSITION>
+            </I-SIGNAL-TO-I-PDU-MAPPING>
+            <I-SIGNAL-TO-I-PDU-MAPPING>
+              <SHORT-NAME>MissingMapping</SHORT-NAME>
+              <I-SIGNAL-REF DEST="I-SIGNAL">/Signals/Missing</I-SIGNAL-REF>
+              <START-POSITION>16</START-POSITION>
+            </I-SIGNAL-TO-I-PDU-MAPPING>
+          </I-SIGNAL-TO-PDU-MAPPINGS>
+        </I-SIGNAL-I-PDU>
+        <I-SIGNAL-I-PDU>
+          <SHORT-NAME>GearPdu</SHORT-NAME>
+          <I-SIGNAL-TO-PDU-MAPPINGS>
+            <I-SIGNAL-TO-I-PDU-MAPPING>
+              <SHORT-NAME>GearMapping</SHORT-NAME>
+              <I-SIGNAL-REF DEST="I-SIGNAL">/Signals/GearPosition</I-SIGNAL-REF>
+              <PACKING-BYTE-ORDER>MOST-SIGNIFICANT-BYTE-FIRST</PACKING-BYTE-ORDER>
+              <START-POSITION>12</START-POSITION>
+            </I-SIGNAL-TO-I-PDU-MAPPING>
+            <I-SIGNAL-TO-I-PDU-MAPPING>
+              <SHORT-NAME>RatioMapping</SHORT-NAME>
+              <I-SIGNAL-REF DEST="I-SIGNAL">/Signals/Ratio</I-SIGNAL-REF>
+              <PACKING-BYTE-ORDER>MOST-SIGNIFICANT-BYTE-LAST</PACKING-BYTE-ORDER>
+              <START-POSITION>32</START-POSITION>
+            </I-SIGNAL-TO-I-PDU-MAPPING>
+          </I-SIGNAL-TO-PDU-MAPPINGS>
+        </I-SIGNAL-I-PDU>
+      </ELEMENTS>
+    </AR-PACKAGE>
+    <AR-PACKAGE>
+      <SHORT-NAME>Signals</SHORT-NAME>
+      <ELEMENTS>
+        <I-SIGNAL>
+          <SHORT-NAME>Status</SHORT-NAME>
+          <LENGTH>4</LENGTH>
+          <SYSTEM-SIGNAL-REF DEST="SYSTEM-SIGNAL">/Signals/StatusSystem</SYSTEM-SIGNAL-REF>
+        </I-SIGNAL>
+        <SYSTEM-SIGNAL>
+          <SHORT-NAME>StatusSystem</SHORT-NAME>
+          <DESC><L-2 L="EN">Engine state</L-2></DESC>
+          <PHYSICAL-PROPS>
+            <SW-DATA-DEF-PROPS-VARIANTS>
+              <SW-DATA-DEF-PROPS-CONDITIONAL>
+                <COMPU-METHOD-REF DEST="COMPU-METHOD">/Compu/StatusTable</COMPU-METHOD-REF>
+              </SW-DATA-DEF-PROPS-CONDITIONAL>
+            </SW-DATA-DEF-PROPS-VARIANTS>
+          </PHYSICAL-PROPS>
+        </SYSTEM-SIGNAL>
+        <I-SIGNAL>
+          <SHORT-NAME>Rpm</SHORT-NAME>
+          <INIT-VALUE>
+            <NUMERICAL-VALUE-SPECIFICATION><VALUE>100</VALUE></NUMERICAL-VALUE-SPECIFICATION>
+          </INIT-VALUE>
+          <LENGTH>16</LENGTH>
+          <NETWORK-REPRESENTATION-PROPS>
+            <SW-DATA-DEF-PROPS-VARIANTS>
+              <SW-DATA-DEF-PROPS-CONDITIONAL>
+                <BASE-TYPE-REF DEST="SW-BASE-TYPE">/Types/uint16</BASE-TYPE-REF>
+                <COMPU-METHOD-REF DEST="COMPU-METHOD">/Compu/RpmScale</COMPU-METHOD-REF>
+              </SW-DATA-DEF-PROPS-CONDITIONAL>
+            </SW-DATA-DEF-PROPS-VARIANTS>
+          </NETWORK-REPRESENTATION-PROPS>
+        </I-SIGNAL>
+        <I-SIGNAL>
+          <SHORT-NAME>Temp</SHORT-NAME>
+          <LENGTH>8</LENGTH>
+          <NETWORK-REPRESENTATION-PROPS>
+            <SW-DATA-DEF-PROPS-VARIANTS>
+              <SW-DATA-DEF-PROPS-CONDITIONAL>
+                <BASE-TYPE-REF DEST="SW-BASE-TYPE">/Types/sint8</BASE-TYPE-REF>
+                <COMPU-METHOD-REF DEST="COMPU-METHOD">/Compu/TempScale</COMPU-METHOD-REF>
+                <DATA-CONSTR-REF DEST="DATA-CONSTR">/Compu/TempRange</DATA-CONSTR-REF>
+              </SW-DATA-DEF-PROPS-CONDITIONAL>
+            </SW-DATA-DEF-PROPS-VARIANTS>
+          </NETWORK-REPRESENTATION-PROPS>
+        </I-SIGNAL>
+        <I-SIGNAL>
+          <SHORT-NAME>GearPosition</SHORT-NAME>
+          <LENGTH>12</LENGTH>
+        </I-SIGNAL>
+        <I-SIGNAL>
+          <SHORT-NAME>Ratio</SHORT-NAME>
+          <LENGTH>32</LENGTH>
+          <NETWORK-REPRESENTATION-PROPS>
+            <SW-DATA-DEF-PROPS-VARIANTS>
+              <SW-DATA-DEF-PROPS-CONDITIONAL>
+                <BASE-TYPE-REF DEST="SW-BASE-TYPE">/Types/float32</BASE-TYPE-REF>
+              </SW-DATA-DEF-PROPS-CONDITIONAL>
+            </SW-DATA-DEF-PROPS-VARIANTS>
+          </NETWORK-REPRESENTATION-PROPS>
+        </I-SIGNAL>
+      </ELEMENTS>
+    </AR-PACKAGE>
+    <AR-PACKAGE>
+      <SHORT-NAME>Compu</SHORT-NAME>
+      <ELEMENTS>
+        <COMPU-METHOD>
+          <SHORT-NAME>StatusTable</SHORT-NAME>
+          <CATEGORY>TEXTTABLE</CATEGORY>
+          <COMPU-INTERNAL-TO-PHYS>
+            <COMPU-SCALES>
+              <COMPU-SCALE>
+                <LOWER-LIMIT>0</LOWER-LIMIT>
+                <UPPER-LIMIT>0</UPPER-LIMIT>
+                <COMPU-CONST><VT>Off</VT></COMPU-CONST>
+              </COMPU-SCALE>
+              <COMPU-SCALE>
+                <LOWER-LIMIT>1</LOWER-LIMIT>
+                <UPPER-LIMIT>1</UPPER-LIMIT>
+                <COMPU-CONST><VT>Running</VT></COMPU-CONST>
+              </COMPU-SCALE>
+              <COMPU-SCALE>
+                <LOWER-LIMIT>2</LOWER-LIMIT>
+                <UPPER-LIMIT>15</UPPER-LIMIT>
+                <COMPU-CONST><VT>Reserved</VT></COMPU-CONST>
+              </COMPU-SCALE>
+            </COMPU-SCALES>
+          </COMPU-INTERNAL-TO-PHYS>
+        </COMPU-METHOD>
+        <COMPU-METHOD>
+          <SHORT-NAME>RpmScale</SHORT-NAME>
+          <CATEGORY>LINEAR</CATEGORY>
+          <UNIT-REF DEST="UNIT">/Units/rpm</UNIT-REF>
+          <COMPU-INTERNAL-TO-PHYS>
+            <COMPU-SCALES>
+              <COMPU-SCALE>
+                <LOWER-LIMIT>0</LOWER-LIMIT>
+                <UPPER-LIMIT>65535</UPPER-LIMIT>
+                <COMPU-RATIONAL-COEFFS>
+                  <COMPU-NUMERATOR><V>0</V><V>1</V></COMPU-NUMERATOR>
+                  <COMPU-DENOMINATOR><V>4</V></COMPU-DENOMINATOR>
+                </COMPU-RATIONAL-COEFFS>
+              </COMPU-SCALE>
+            </COMPU-SCALES>
+          </COMPU-INTERNAL-TO-PHYS>
+        </COMPU-METHOD>
+        <COMPU-METHOD>
+          <SHORT-NAME>TempScale</SHORT-NAME>
+          <CATEGORY>LINEAR</CATEGORY>
+          <COMPU-INTERNAL-TO-PHYS>
+            <COMPU-SCALES>
+              <COMPU-SCALE>
+                <COMPU-RATIONAL-COEFFS>
+                  <COMPU-NUMERATOR><V>-40</V><V>0.5</V></COMPU-NUMERATOR>
+                  <COMPU-DENOMINATOR><V>1</V></COMPU-DENOMINATOR>
+                </COMPU-RATIONAL-COEFFS>
+              </COMPU-SCALE>
+            </COMPU-SCALES>
+          </COMPU-INTERNAL-TO-PHYS>
+        </COMPU-METHOD>
+        <DATA-CONSTR>
+          <SHORT-NAME>TempRange</SHORT-NAME>
+          <DATA-CONSTR-RULES>
+            <DATA-CONSTR-RULE>
+              <PHYS-CONSTRS>
+                <LOWER-LIMIT>-40</LOWER-LIMIT>
+                <UPPER-LIMIT>85</UPPER-LIMIT>
+              </PHYS-CONSTRS>
+            </DATA-CONSTR-RULE>
+          </DATA-CONSTR-RULES>
+        </DATA-CONSTR>
+      </ELEMENTS>
+    </AR-PACKAGE>
+    <AR-PACKAGE>
+      <SHORT-NAME>Types</SHORT-NAME>
+      <ELEMENTS>
+        <SW-BASE-TYPE>
+          <SHORT-NAME>uint16</SHORT-NAME>
+          <BASE-TYPE-ENCODING>NONE</BASE-TYPE-ENCODING>
+        </SW-BASE-TYPE>
+        <SW-BASE-TYPE>
+          <SHORT-NAME>sint8</SHORT-NAME>
+          <BASE-TYPE-ENCODING>2C</BASE-TYPE-ENCODING>
+        </SW-BASE-TYPE>
+        <SW-BASE-TYPE>
+          <SHORT-NAME>float32</SHORT-NAME>
+          <BASE-TYPE-ENCODING>IEEE754</BASE-TYPE-ENCODING>
+        </SW-BASE-TYPE>
+      </ELEMENTS>
+    </AR-PACKAGE>
+    <AR-PACKAGE>
+      <SHORT-NAME>Units</SHORT-NAME>
+      <ELEMENTS>
+        <UNIT>
+          <SHORT-NAME>rpm</SHORT-NAME>
+          <DISPLAY-NAME>1/min</DISPLAY-NAME>
+        </UNIT>
+      </ELEMENTS>
+    </AR-PACKAGE>
+  </AR-PACKAGES>
+</AUTOSAR>
+"#;
+
+    #[test]
+    fn test_clusters() {
+        assert_eq!(
+            EditableDbc::arxml_clusters(SAMPLE_ARXML).unwrap(),
+            ["Powertrain", "Body"]
+        );
+        let error = EditableDbc::from_arxml(SAMPLE_ARXML, None).unwrap_err();
+        assert!(error.contains("choose one of: Powertrain, Body"), "{error}");
+        let error = EditableDbc::from_arxml(SAMPLE_ARXML, Some("Chassis")).unwrap_err();
+        assert!(error.contains("Chassis not found"), "{error}");
+        assert!(EditableDbc::arxml_clusters("<NetworkDefinition/>").is_err());
+        assert!(EditableDbc::from_arxml("<AUTOSAR/>", None).is_err());
+
+        let (body, _) = EditableDbc::from_arxml(SAMPLE_ARXML, Some("Body")).unwrap();
+        assert_eq!(body.message_count(), 0);
+        assert!(body.nodes().is_empty());
+    }
+
+    #[test]
+    fn test_import() {
+        let (dbc, _) = EditableDbc::from_arxml(SAMPLE_ARXML, Some("Powertrain")).unwrap();
+        assert!(!dbc.can_undo());
+        assert!(!dbc.is_modified());
+        assert_eq!(dbc.nodes(), &vec!["Dash".to_string(), "Engine".to_string()]);
+        assert_eq!(
+            dbc.attribute_value(&AttributeTarget::Network, "DBName"),
+            Some(&AttributeValue::String("Powertrain".to_string()))
+        );
+        assert_eq!(
+            dbc.attribute_value(&AttributeTarget::Network, "Baudrate"),
+            Some(&AttributeValue::Int(500_000))
+        );
+
+        let engine = dbc.get_message(256).unwrap();
+        assert_eq!(engine.message_name(), "EngineData");
+        assert_eq!(engine.message_size(), 8);
+        assert_eq!(engine.transmitter(), "Engine");
+        assert_eq!(engine.comment(), "Engine status");
+        assert_eq!(dbc.message_cycle_time(256), Some(100));
+        let names: Vec<&str> = engine.signals().iter().map(|s| s.name()).collect();
+        assert_eq!(names, ["EngineMux_Selector", "Status", "Rpm", "Temp"]);
+
+        let signals = engine.signals();
+        assert_eq!(
+            *signals[0].multiplexer_indicator(),
+            MultiplexIndicator::Multiplexor
+        );
+        assert_eq!(signals[0].signal_size(), 4);
+        assert_eq!(
+            *signals[1].multiplexer_indicator(),
+            MultiplexIndicator::Plain
+        );
+        assert_eq!(signals[1].comment(), "Engine state");
+        assert_eq!(
+            signals[1].value_descriptions(),
+            &vec![(0, "Off".to_string()), (1, "Running".to_string())]
+        );
+        assert_eq!(signals[1].receivers(), &vec!["Dash".to_string()]);
+
+        let rpm = &signals[2];
+        assert_eq!(
+            *rpm.multiplexer_indicator(),
+            MultiplexIndicator::MultiplexedSignal(0)
+        );
+        assert_eq!((rpm.start_bit(), rpm.signal_size()), (8, 16));
+        assert_eq!((rpm.factor(), rpm.offset()), (0.25, 0.0));
+        assert_eq!((rpm.min(), rpm.max()), (0.0, 16383.75));
+        assert_eq!(rpm.unit(), "1/min");
+        assert_eq!(*rpm.value_type(), ValueType::Unsigned);
+        assert_eq!(
+            dbc.attribute_value(
+                &AttributeTarget::Signal(256, "Rpm".to_string()),
+                "GenSigStartValue"
+            ),
+            Some(&AttributeValue::Int(100))
+        );
+
+        let temp = &signals[3];
+        assert_eq!(
+            *temp.multiplexer_indicator(),
+            MultiplexIndicator::MultiplexedSignal(1)
+        );
+        assert_eq!(*temp.value_type(), ValueType::Signed);
+        assert_eq!((temp.factor(), temp.offset()), (0.5, -40.0));
+        assert_eq!((temp.min(), temp.max()), (-40.0, 85.0));
+
+        // START-POSITION 是最低位：12 位的 Motorola 信号最低位在第 1 字节的第 4 位，最高位在第 0 字节的第 7 位
+        let gear = dbc.get_message(419430401 | EXTENDED_ID_FLAG).unwrap();
+        assert_eq!(gear.frame_format(), FrameFormat::Extended);
+        assert!(gear.is_fd() && gear.bit_rate_switch());
+        assert_eq!(gear.message_size(), 12);
+        assert_eq!(gear.transmitter(), "Vector__XXX");
+        let position = &gear.signals()[0];
+        assert_eq!(*position.byte_order(), ByteOrder::BigEndian);
+        assert_eq!((position.start_bit(), position.signal_size()), (7, 12));
+        assert_eq!(dbc.message_count(), 2);
+
+        // 转换结果可以写成 DBC，重新读取后写出的内容相同
+        let text = FileFormat::Dbc.write(&dbc);
+        let restored = FileFormat::Dbc.parse(&text).unwrap();
+        assert_eq!(FileFormat::Dbc.write(&restored), text);
+    }
+
+    #[test]
+    fn test_conversion_log() {
+        let (_, log) = EditableDbc::from_arxml(SAMPLE_ARXML, Some("Powertrain")).unwrap();
+        for expected in [
+            "LIN-CLUSTER Seats: only CAN clusters are imported",
+            "Frame EngineData, signal Status: the update bit is not imported",
+            "Frame EngineData: signal group Protection is not imported",
+            "value range 2..15 \"Reserved\" of StatusTable is not supported",
+            "Frame EngineData: I-SIGNAL-REF /Signals/Missing does not exist",
+            "Frame Gear, signal Ratio: encoding IEEE754 is not supported",
+            "Frame Gear: id 0x100 is already used by EngineData, skipped",
+        ] {
+            assert!(
+                log.iter().any(|l| l.contains(expected)),
+                "{expected}: {log:?}"
+            );
+        }
+        assert_eq!(log.len(), 7, "{log:?}");
+    }
+}
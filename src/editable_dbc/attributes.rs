@@ -0,0 +1,376 @@
+use std::fmt::{self, Display, Formatter};
+
+use can_dbc::{AttributeDefinition as DbcAttributeDefinition, NumericValue};
+use can_dbc::{AttributeValue as DbcAttributeValue, AttributeValueType as DbcAttributeValueType};
+
+use super::writer::unescape;
+
+// 属性 (BA_DEF_ / BA_DEF_DEF_ / BA_) 相关的数据结构
+// 属性定义和默认值保存在 EditableDbc 中
+// 属性值保存在各自的对象上：Message 和 Signal 的属性值在 EditableMessage / EditableSignal 中
+// 网络、节点和环境变量没有单独的结构，属性值保存在 EditableDbc 中
+
+/// 属性可以附加的对象类型，对应 BA_DEF_ 后面的 BU_ / BO_ / SG_ / EV_，没有前缀时为 Network
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AttributeObjectType {
+    #[default]
+    Network,
+    Node,
+    Message,
+    Signal,
+    EnvironmentVariable,
+}
+
+/// 属性值的类型和取值范围
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttributeValueType {
+    Int { min: i64, max: i64 },
+    Hex { min: i64, max: i64 },
+    Float { min: f64, max: f64 },
+    String,
+    Enum(Vec<String>),
+}
+
+/// 属性值
+///
+/// ENUM 类型的值在 BA_ 中通常写成序号，在 BA_DEF_DEF_ 中通常写成字符串，两种写法都保留原样
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttributeValue {
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+/// 属性定义，包括 BA_DEF_DEF_ 给出的默认值
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributeDefinition {
+    name: String,
+    object_type: AttributeObjectType,
+    value_type: AttributeValueType,
+    default: Option<AttributeValue>,
+}
+
+/// 一个对象上显式设置的属性值，按属性名保存，保持文件中的顺序
+pub type AttributeValues = Vec<(String, AttributeValue)>;
+
+/// 删除属性定义时移除的一个属性值，记录位置以便撤销时插回原处
+#[derive(Clone, Debug, PartialEq)]
+pub struct RemovedAttributeValue {
+    pub(super) target: AttributeTarget,
+    pub(super) value: AttributeValue,
+    // 在对象的属性值列表中的位置
+    pub(super) index: usize,
+    // 节点和环境变量的属性值条目在 EditableDbc 的列表中的位置，其他对象为 None
+    pub(super) object_index: Option<usize>,
+}
+
+/// 属性值所属的具体对象
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum AttributeTarget {
+    #[default]
+    Network,
+    Node(String),
+    Message(u32),
+    Signal(u32, String),
+    EnvironmentVariable(String),
+}
+
+impl AttributeObjectType {
+    /// DBC 文件中的对象关键字，Network 没有关键字
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            AttributeObjectType::Network => "",
+            AttributeObjectType::Node => "BU_",
+            AttributeObjectType::Message => "BO_",
+            AttributeObjectType::Signal => "SG_",
+            AttributeObjectType::EnvironmentVariable => "EV_",
+        }
+    }
+}
+
+impl Display for AttributeObjectType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AttributeObjectType::Network => "Network",
+            AttributeObjectType::Node => "Node",
+            AttributeObjectType::Message => "Message",
+            AttributeObjectType::Signal => "Signal",
+            AttributeObjectType::EnvironmentVariable => "Environment Variable",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl AttributeValueType {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            AttributeValueType::Int { .. } => "INT",
+            AttributeValueType::Hex { .. } => "HEX",
+            AttributeValueType::Float { .. } => "FLOAT",
+            AttributeValueType::String => "STRING",
+            AttributeValueType::Enum(_) => "ENUM",
+        }
+    }
+
+    fn from_dbc(value_type: &DbcAttributeValueType) -> Self {
+        match value_type {
+            DbcAttributeValueType::Int(min, max) => AttributeValueType::Int {
+                min: numeric_to_i64(min),
+                max: numeric_to_i64(max),
+            },
+            DbcAttributeValueType::Hex(min, max) => AttributeValueType::Hex {
+                min: numeric_to_i64(min),
+                max: numeric_to_i64(max),
+            },
+            DbcAttributeValueType::Float(min, max) => AttributeValueType::Float {
+                min: numeric_to_f64(min),
+                max: numeric_to_f64(max),
+            },
+            DbcAttributeValueType::String => AttributeValueType::String,
+            DbcAttributeValueType::Enum(values) => {
+                AttributeValueType::Enum(values.iter().map(|v| unescape(v)).collect())
+            }
+        }
+    }
+}
+
+impl AttributeValue {
+    pub(super) fn from_dbc(value: &DbcAttributeValue) -> Self {
+        match value {
+            DbcAttributeValue::Uint(v) => {
+                AttributeValue::Int(i64::try_from(*v).unwrap_or(i64::MAX))
+            }
+            DbcAttributeValue::Int(v) => AttributeValue::Int(*v),
+            DbcAttributeValue::Double(v) => AttributeValue::Float(*v),
+            DbcAttributeValue::String(v) => AttributeValue::String(unescape(v)),
+        }
+    }
+}
+
+impl Display for AttributeValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeValue::Int(v) => write!(f, "{v}"),
+            AttributeValue::Float(v) => write!(f, "{v}"),
+            AttributeValue::String(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+impl AttributeTarget {
+    pub fn object_type(&self) -> AttributeObjectType {
+        match self {
+            AttributeTarget::Network => AttributeObjectType::Network,
+            AttributeTarget::Node(_) => AttributeObjectType::Node,
+            AttributeTarget::Message(_) => AttributeObjectType::Message,
+            AttributeTarget::Signal(..) => AttributeObjectType::Signal,
+            AttributeTarget::EnvironmentVariable(_) => AttributeObjectType::EnvironmentVariable,
+        }
+    }
+}
+
+impl AttributeDefinition {
+    pub fn new(
+        name: &str,
+        object_type: AttributeObjectType,
+        value_type: AttributeValueType,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            object_type,
+            value_type,
+            default: None,
+        }
+    }
+
+    pub fn with_default(mut self, default: AttributeValue) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    pub(super) fn from_dbc(definition: &DbcAttributeDefinition) -> Self {
+        let (object_type, name, value_type) = match definition {
+            DbcAttributeDefinition::Message(name, t) => (AttributeObjectType::Message, name, t),
+            DbcAttributeDefinition::Node(name, t) => (AttributeObjectType::Node, name, t),
+            DbcAttributeDefinition::Signal(name, t) => (AttributeObjectType::Signal, name, t),
+            DbcAttributeDefinition::EnvironmentVariable(name, t) => {
+                (AttributeObjectType::EnvironmentVariable, name, t)
+            }
+            DbcAttributeDefinition::Plain(name, t) => (AttributeObjectType::Network, name, t),
+        };
+        Self::new(name, object_type, AttributeValueType::from_dbc(value_type))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn object_type(&self) -> AttributeObjectType {
+        self.object_type
+    }
+    pub fn value_type(&self) -> &AttributeValueType {
+        &self.value_type
+    }
+    pub fn default(&self) -> Option<&AttributeValue> {
+        self.default.as_ref()
+    }
+
+    /// FLOAT 类型的整数值统一保存为 Float，保证写出再读入后得到相同的值
+    pub fn normalize(&self, value: AttributeValue) -> AttributeValue {
+        match (&self.value_type, value) {
+            (AttributeValueType::Float { .. }, AttributeValue::Int(v)) => {
+                AttributeValue::Float(v as f64)
+            }
+            (_, value) => value,
+        }
+    }
+
+    // 对默认值进行 normalize，并检查默认值是否合法
+    pub(super) fn checked(mut self) -> Result<Self, String> {
+        if let Some(default) = self.default.take() {
+            let default = self.normalize(default);
+            self.validate(&default)?;
+            self.default = Some(default);
+        }
+        Ok(self)
+    }
+
+    /// 检查属性值是否符合定义的类型、范围和枚举列表
+    pub fn validate(&self, value: &AttributeValue) -> Result<(), String> {
+        match (&self.value_type, value) {
+            (
+                AttributeValueType::Int { min, max } | AttributeValueType::Hex { min, max },
+                AttributeValue::Int(v),
+            ) => {
+                // 范围都为 0 表示不限制
+                if (*min != 0 || *max != 0) && (v < min || v > max) {
+                    return Err(format!(
+                        "Value {} of attribute {} is out of range [{}, {}]",
+                        v, self.name, min, max
+                    ));
+                }
+            }
+            (AttributeValueType::Float { min, max }, AttributeValue::Int(_))
+            | (AttributeValueType::Float { min, max }, AttributeValue::Float(_)) => {
+                let v = match value {
+                    AttributeValue::Int(v) => *v as f64,
+                    AttributeValue::Float(v) => *v,
+                    AttributeValue::String(_) => unreachable!(),
+                };
+                if (*min != 0.0 || *max != 0.0) && (v < *min || v > *max) {
+                    return Err(format!(
+                        "Value {} of attribute {} is out of range [{}, {}]",
+                        v, self.name, min, max
+                    ));
+                }
+            }
+            (AttributeValueType::String, AttributeValue::String(_)) => {}
+            (AttributeValueType::Enum(values), AttributeValue::Int(index)) => {
+                if *index < 0 || *index as usize >= values.len() {
+                    return Err(format!(
+                        "Index {} of attribute {} is not in the enum list",
+                        index, self.name
+                    ));
+                }
+            }
+            (AttributeValueType::Enum(values), AttributeValue::String(v)) => {
+                if !values.contains(v) {
+                    return Err(format!(
+                        "Value \"{}\" of attribute {} is not in the enum list",
+                        v, self.name
+                    ));
+                }
+            }
+            (value_type, _) => {
+                return Err(format!(
+                    "Value {} does not match the {} type of attribute {}",
+                    value,
+                    value_type.type_name(),
+                    self.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 将 ENUM 类型的值统一转换为枚举文本，其他类型原样输出
+    pub fn display_value(&self, value: &AttributeValue) -> String {
+        match (&self.value_type, value) {
+            (AttributeValueType::Enum(values), AttributeValue::Int(index)) => {
+                usize::try_from(*index)
+                    .ok()
+                    .and_then(|i| values.get(i))
+                    .cloned()
+                    .unwrap_or_else(|| index.to_string())
+            }
+            (AttributeValueType::Hex { .. }, AttributeValue::Int(v)) => format!("0x{v:X}"),
+            _ => value.to_string(),
+        }
+    }
+}
+
+// Vector 工具的标准属性，导入 KCD / ARXML 时用来保存 DBC 语法之外的内容
+pub(super) const CYCLE_TIME_ATTRIBUTE: &str = "GenMsgCycleTime";
+pub(super) const BUS_NAME_ATTRIBUTE: &str = "DBName";
+pub(super) const BAUDRATE_ATTRIBUTE: &str = "Baudrate";
+pub(super) const START_VALUE_ATTRIBUTE: &str = "GenSigStartValue";
+
+impl AttributeDefinition {
+    /// Message 的发送周期，单位为 ms
+    pub(super) fn cycle_time() -> Self {
+        AttributeDefinition::new(
+            CYCLE_TIME_ATTRIBUTE,
+            AttributeObjectType::Message,
+            AttributeValueType::Int { min: 0, max: 65535 },
+        )
+        .with_default(AttributeValue::Int(0))
+    }
+
+    /// 总线名
+    pub(super) fn bus_name() -> Self {
+        AttributeDefinition::new(
+            BUS_NAME_ATTRIBUTE,
+            AttributeObjectType::Network,
+            AttributeValueType::String,
+        )
+        .with_default(AttributeValue::String(String::new()))
+    }
+
+    /// 波特率，默认值与 KCD 的 baudrate 默认值相同
+    pub(super) fn baudrate() -> Self {
+        AttributeDefinition::new(
+            BAUDRATE_ATTRIBUTE,
+            AttributeObjectType::Network,
+            AttributeValueType::Int {
+                min: 0,
+                max: 1_000_000,
+            },
+        )
+        .with_default(AttributeValue::Int(500_000))
+    }
+
+    /// Signal 的初始值，是原始值而不是物理值
+    pub(super) fn start_value() -> Self {
+        AttributeDefinition::new(
+            START_VALUE_ATTRIBUTE,
+            AttributeObjectType::Signal,
+            AttributeValueType::Int { min: 0, max: 0 },
+        )
+        .with_default(AttributeValue::Int(0))
+    }
+}
+
+fn numeric_to_i64(value: &NumericValue) -> i64 {
+    match value {
+        NumericValue::Uint(v) => i64::try_from(*v).unwrap_or(i64::MAX),
+        NumericValue::Int(v) => *v,
+        NumericValue::Double(v) => *v as i64,
+    }
+}
+
+fn numeric_to_f64(value: &NumericValue) -> f64 {
+    match value {
+        NumericValue::Uint(v) => *v as f64,
+        NumericValue::Int(v) => *v as f64,
+        NumericValue::Double(v) => *v,
+    }
+}
@@ -0,0 +1,406 @@
+use can_dbc::ByteOrder;
+
+use super::codec::swap_bit_order;
+use super::{
+    AttributeDefinition, AttributeObjectType, AttributeTarget, AttributeValue, AttributeValueType,
+    EditableDbc, EditableMessage, EditableSignal, FrameFormat,
+};
+
+// CAN FD 相关的功能：负载长度、VFrameFormat / CANFD_BRS 属性映射、信号位置检查
+// DBC 文件本身没有 FD 的语法，Vector 工具用 Message 属性 VFrameFormat 和 CANFD_BRS 表示
+// EditableMessage 中的 FD / BRS 标志由这两个属性推导，修改标志就是修改属性值，因此可以直接撤销
+
+/// Vector 工具使用的帧格式属性名
+pub(super) const FRAME_FORMAT_ATTRIBUTE: &str = "VFrameFormat";
+/// Vector 工具使用的位速率切换属性名
+pub(super) const BIT_RATE_SWITCH_ATTRIBUTE: &str = "CANFD_BRS";
+
+/// 经典 CAN 的最大负载长度
+pub const CLASSIC_CAN_MAX_SIZE: u64 = 8;
+/// CAN FD 在 8 字节以上允许的负载长度，对应 DLC 9 到 15
+const CAN_FD_SIZES: [u64; 7] = [12, 16, 20, 24, 32, 48, 64];
+
+/// 检查负载长度是否合法：经典 CAN 为 0-8，CAN FD 另外允许 12、16、20、24、32、48、64
+pub fn is_valid_message_size(size: u64, fd: bool) -> bool {
+    size <= CLASSIC_CAN_MAX_SIZE || (fd && CAN_FD_SIZES.contains(&size))
+}
+
+/// 负载长度对应的 DLC 编码，CAN FD 的长度不在允许的列表中时向上取到下一个允许的长度
+pub fn size_to_dlc(size: u64) -> u8 {
+    if size <= CLASSIC_CAN_MAX_SIZE {
+        return size as u8;
+    }
+    let index = CAN_FD_SIZES
+        .iter()
+        .position(|&s| s >= size)
+        .unwrap_or(CAN_FD_SIZES.len() - 1);
+    9 + index as u8
+}
+
+/// 检查信号是否完整地位于长度为 message_size 字节的负载中
+///
+/// Intel 格式的起始位是最低位，Motorola 格式的起始位是最高位（DBC 的锯齿编号）
+pub fn check_signal_placement(signal: &EditableSignal, message_size: u64) -> Result<(), String> {
+    if signal.signal_size == 0 || signal.signal_size > 64 {
+        return Err(format!(
+            "Signal {} has an invalid length of {} bits",
+            signal.name, signal.signal_size
+        ));
+    }
+    let payload_bits = message_size * 8;
+    let last_bit = match signal.byte_order {
+        ByteOrder::LittleEndian => signal.start_bit + signal.signal_size - 1,
+        ByteOrder::BigEndian => {
+            // 转换成按字节从高位到低位连续编号的位置，Motorola 信号在这种编号下是连续的
+            swap_bit_order(signal.start_bit) + signal.signal_size - 1
+        }
+    };
+    if signal.start_bit >= payload_bits || last_bit >= payload_bits {
+        return Err(format!(
+            "Signal {} (start bit {}, length {}) does not fit in {} bytes",
+            signal.name, signal.start_bit, signal.signal_size, message_size
+        ));
+    }
+    Ok(())
+}
+
+/// Vector 定义的 VFrameFormat 属性，序号 2-13 为保留值
+fn frame_format_definition() -> AttributeDefinition {
+    let mut values = vec!["StandardCAN".to_string(), "ExtendedCAN".to_string()];
+    values.extend((2..14).map(|_| "reserved".to_string()));
+    values.extend(["StandardCAN_FD".to_string(), "ExtendedCAN_FD".to_string()]);
+    AttributeDefinition::new(
+        FRAME_FORMAT_ATTRIBUTE,
+        AttributeObjectType::Message,
+        AttributeValueType::Enum(values),
+    )
+    .with_default(AttributeValue::String("StandardCAN".to_string()))
+}
+
+/// Vector 定义的 CANFD_BRS 属性，默认开启位速率切换
+fn bit_rate_switch_definition() -> AttributeDefinition {
+    AttributeDefinition::new(
+        BIT_RATE_SWITCH_ATTRIBUTE,
+        AttributeObjectType::Message,
+        AttributeValueType::Enum(vec!["0".to_string(), "1".to_string()]),
+    )
+    .with_default(AttributeValue::String("1".to_string()))
+}
+
+impl EditableMessage {
+    pub fn is_fd(&self) -> bool {
+        self.fd
+    }
+
+    /// 是否使用位速率切换，只对 CAN FD 报文有意义
+    pub fn bit_rate_switch(&self) -> bool {
+        self.bit_rate_switch
+    }
+
+    /// 检查所有信号是否都位于负载中，返回第一个错误
+    pub fn check_signals_placement(&self, message_size: u64) -> Result<(), String> {
+        self.signals
+            .iter()
+            .try_for_each(|signal| check_signal_placement(signal, message_size))
+    }
+}
+
+impl EditableDbc {
+    /// 设置 Message 的 CAN FD 和 BRS 标志
+    ///
+    /// 修改的是 VFrameFormat 和 CANFD_BRS 属性值，属性未定义时自动添加 Vector 的标准定义
+    /// 关闭 FD 时 Message 长度不能超过 8 字节
+    pub fn set_message_can_fd(
+        &mut self,
+        message_id: u32,
+        fd: bool,
+        bit_rate_switch: bool,
+    ) -> Result<(), String> {
+        let message = self
+            .get_message(message_id)
+            .ok_or_else(|| format!("Message {} not found", message_id))?;
+        if !fd && message.message_size > CLASSIC_CAN_MAX_SIZE {
+            return Err(format!(
+                "Message {} has {} bytes, classic CAN allows at most {} bytes",
+                message.message_name, message.message_size, CLASSIC_CAN_MAX_SIZE
+            ));
+        }
+        if message.fd == fd && (!fd || message.bit_rate_switch == bit_rate_switch) {
+            return Ok(());
+        }
+
+        // 已经在外部事务中时直接合并进去，否则单独作为一个事务
+        let own_transaction = !self.in_transaction();
+        if own_transaction {
+            self.begin_transaction(&format!("Set CAN FD {}", message.message_name))?;
+        }
+        let result = self.write_can_fd_attributes(message_id, fd, bit_rate_switch);
+        if own_transaction {
+            match &result {
+                Ok(()) => self.commit_transaction()?,
+                Err(_) => self.rollback_transaction()?,
+            }
+        }
+        result
+    }
+
+    fn write_can_fd_attributes(
+        &mut self,
+        message_id: u32,
+        fd: bool,
+        bit_rate_switch: bool,
+    ) -> Result<(), String> {
+        if self
+            .get_attribute_definition(FRAME_FORMAT_ATTRIBUTE)
+            .is_none()
+        {
+            self.add_attribute_definition(&frame_format_definition())?;
+        }
+        if fd
+            && self
+                .get_attribute_definition(BIT_RATE_SWITCH_ATTRIBUTE)
+                .is_none()
+        {
+            self.add_attribute_definition(&bit_rate_switch_definition())?;
+        }
+
+        let target = AttributeTarget::Message(message_id);
+        let extended = self
+            .get_message(message_id)
+            .is_some_and(|m| m.frame_format == FrameFormat::Extended);
+        let frame_format = match (extended, fd) {
+            (false, false) => "StandardCAN",
+            (true, false) => "ExtendedCAN",
+            (false, true) => "StandardCAN_FD",
+            (true, true) => "ExtendedCAN_FD",
+        };
+        let value = self.enum_attribute_value(FRAME_FORMAT_ATTRIBUTE, frame_format)?;
+        self.set_attribute_value(&target, FRAME_FORMAT_ATTRIBUTE, value)?;
+
+        if fd {
+            let brs = if bit_rate_switch { "1" } else { "0" };
+            let value = self.enum_attribute_value(BIT_RATE_SWITCH_ATTRIBUTE, brs)?;
+            self.set_attribute_value(&target, BIT_RATE_SWITCH_ATTRIBUTE, value)?;
+        } else {
+            // 经典 CAN 没有 BRS，删除显式设置的值
+            self.reset_attribute_value(&target, BIT_RATE_SWITCH_ATTRIBUTE);
+        }
+        Ok(())
+    }
+
+    // ENUM 属性在 BA_ 中按惯例写成序号
+    fn enum_attribute_value(&self, name: &str, text: &str) -> Result<AttributeValue, String> {
+        let definition = self
+            .get_attribute_definition(name)
+            .ok_or_else(|| format!("Attribute {} not defined", name))?;
+        match definition.value_type() {
+            AttributeValueType::Enum(values) => values
+                .iter()
+                .position(|v| v == text)
+                .map(|i| AttributeValue::Int(i as i64))
+                .ok_or_else(|| format!("Attribute {} has no value \"{}\"", name, text)),
+            _ => Err(format!("Attribute {} is not an ENUM", name)),
+        }
+    }
+
+    // 属性值或属性定义变化之后，重新根据属性推导所有 Message 的 FD / BRS 标志
+    pub(super) fn sync_can_fd_flags(&mut self) {
+        let flags: Vec<(bool, bool)> = self
+            .messages
+            .iter()
+            .map(|message| {
+                let target = AttributeTarget::Message(message.message_id);
+                let fd = self
+                    .attribute_text(&target, FRAME_FORMAT_ATTRIBUTE)
+                    .is_some_and(|text| text.ends_with("_FD"));
+                let bit_rate_switch = fd
+                    && self
+                        .attribute_text(&target, BIT_RATE_SWITCH_ATTRIBUTE)
+                        .is_some_and(|text| text == "1");
+                (fd, bit_rate_switch)
+            })
+            .collect();
+        for (message, (fd, bit_rate_switch)) in self.messages.iter_mut().zip(flags) {
+            message.fd = fd;
+            message.bit_rate_switch = bit_rate_switch;
+        }
+    }
+
+    // 属性的实际值转换成文本，ENUM 的序号转换成枚举文本
+    fn attribute_text(&self, target: &AttributeTarget, name: &str) -> Option<String> {
+        let value = self.effective_attribute_value(target, name)?;
+        Some(match self.get_attribute_definition(name) {
+            Some(definition) => definition.display_value(value),
+            None => value.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use can_dbc::Dbc;
+
+    const FD_DBC: &str = r#"VERSION ""
+
+BU_: ECU
+
+BO_ 256 Classic: 8 ECU
+ SG_ Speed : 0|16@1+ (1,0) [0|0] "" Vector__XXX
+
+BO_ 512 FdFrame: 64 ECU
+ SG_ Tail : 504|8@1+ (1,0) [0|0] "" Vector__XXX
+ SG_ Counter : 7|8@0+ (1,0) [0|0] "" Vector__XXX
+
+BO_ 2147484160 FdExtended: 12 ECU
+ SG_ Value : 88|8@1+ (1,0) [0|0] "" Vector__XXX
+
+BA_DEF_ BO_  "VFrameFormat" ENUM  "StandardCAN","ExtendedCAN","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","StandardCAN_FD","ExtendedCAN_FD";
+BA_DEF_ BO_  "CANFD_BRS" ENUM  "0","1";
+BA_DEF_DEF_  "VFrameFormat" "StandardCAN";
+BA_DEF_DEF_  "CANFD_BRS" "1";
+BA_ "VFrameFormat" BO_ 512 14;
+BA_ "VFrameFormat" BO_ 2147484160 15;
+BA_ "CANFD_BRS" BO_ 2147484160 0;
+"#;
+
+    fn signal(start_bit: u64, signal_size: u64, byte_order: ByteOrder) -> EditableSignal {
+        EditableSignal {
+            name: "S".to_string(),
+            start_bit,
+            signal_size,
+            byte_order,
+            ..EditableSignal::new()
+        }
+    }
+
+    #[test]
+    fn test_message_sizes() {
+        assert_eq!(size_to_dlc(8), 8);
+        assert_eq!(size_to_dlc(12), 9);
+        assert_eq!(size_to_dlc(20), 11);
+        assert_eq!(size_to_dlc(64), 15);
+        assert!(is_valid_message_size(8, false));
+        assert!(!is_valid_message_size(12, false));
+        assert!(!is_valid_message_size(64, false));
+        for size in CAN_FD_SIZES {
+            assert!(is_valid_message_size(size, true));
+        }
+        assert!(!is_valid_message_size(10, true));
+        assert!(!is_valid_message_size(65, true));
+    }
+
+    #[test]
+    fn test_signal_placement() {
+        // Intel：起始位是最低位
+        assert!(check_signal_placement(&signal(56, 8, ByteOrder::LittleEndian), 8).is_ok());
+        assert!(check_signal_placement(&signal(57, 8, ByteOrder::LittleEndian), 8).is_err());
+        assert!(check_signal_placement(&signal(504, 8, ByteOrder::LittleEndian), 64).is_ok());
+        assert!(check_signal_placement(&signal(505, 8, ByteOrder::LittleEndian), 64).is_err());
+
+        // Motorola：起始位是最高位，向后面的字节延伸
+        assert!(check_signal_placement(&signal(7, 64, ByteOrder::BigEndian), 8).is_ok());
+        assert!(check_signal_placement(&signal(6, 64, ByteOrder::BigEndian), 8).is_err());
+        assert!(check_signal_placement(&signal(63, 8, ByteOrder::BigEndian), 8).is_ok());
+        assert!(check_signal_placement(&signal(59, 8, ByteOrder::BigEndian), 8).is_err());
+        assert!(check_signal_placement(&signal(59, 8, ByteOrder::BigEndian), 12).is_ok());
+        assert!(check_signal_placement(&signal(511, 8, ByteOrder::BigEndian), 64).is_ok());
+
+        assert!(check_signal_placement(&signal(0, 0, ByteOrder::LittleEndian), 8).is_err());
+        assert!(check_signal_placement(&signal(64, 1, ByteOrder::LittleEndian), 8).is_err());
+    }
+
+    #[test]
+    fn test_read_fd_flags() {
+        let dbc = EditableDbc::from_dbc(&Dbc::try_from(FD_DBC).unwrap());
+
+        let classic = dbc.get_message(256).unwrap();
+        assert!(!classic.is_fd());
+        assert!(!classic.bit_rate_switch());
+
+        let fd = dbc.get_message(512).unwrap();
+        assert_eq!(fd.message_size(), 64);
+        assert!(fd.is_fd());
+        // 没有显式设置 CANFD_BRS 时使用默认值 "1"
+        assert!(fd.bit_rate_switch());
+        assert!(fd.check_signals_placement(64).is_ok());
+        assert!(fd.check_signals_placement(8).is_err());
+
+        let extended = dbc.get_message(0x8000_0200).unwrap();
+        assert!(extended.is_fd());
+        assert!(!extended.bit_rate_switch());
+
+        // 写出再读入后标志不变
+        let reparsed = EditableDbc::from_dbc(&Dbc::try_from(dbc.to_string().as_str()).unwrap());
+        for (a, b) in dbc.messages().iter().zip(reparsed.messages()) {
+            assert_eq!(a.is_fd(), b.is_fd());
+            assert_eq!(a.bit_rate_switch(), b.bit_rate_switch());
+        }
+    }
+
+    #[test]
+    fn test_set_message_can_fd() {
+        let mut dbc = EditableDbc::from_dbc(&Dbc::try_from(FD_DBC).unwrap());
+
+        // 长度超过 8 字节的 Message 不能关闭 FD
+        assert!(dbc.set_message_can_fd(512, false, false).is_err());
+        assert!(!dbc.can_undo());
+
+        dbc.set_message_can_fd(256, true, false).unwrap();
+        let message = dbc.get_message(256).unwrap();
+        assert!(message.is_fd());
+        assert!(!message.bit_rate_switch());
+        assert_eq!(
+            dbc.attribute_value(&AttributeTarget::Message(256), FRAME_FORMAT_ATTRIBUTE),
+            Some(&AttributeValue::Int(14))
+        );
+
+        // 一次撤销恢复 FD 和 BRS 两个属性
+        assert_eq!(dbc.undo_label().as_deref(), Some("Set CAN FD Classic"));
+        dbc.undo().unwrap();
+        assert!(!dbc.get_message(256).unwrap().is_fd());
+        assert!(!dbc.is_modified());
+
+        // 在属性面板中修改属性值同样会更新标志
+        dbc.set_attribute_value(
+            &AttributeTarget::Message(256),
+            FRAME_FORMAT_ATTRIBUTE,
+            AttributeValue::Int(14),
+        )
+        .unwrap();
+        assert!(dbc.get_message(256).unwrap().is_fd());
+        assert!(dbc.get_message(256).unwrap().bit_rate_switch());
+    }
+
+    #[test]
+    fn test_set_message_can_fd_adds_definitions() {
+        let mut dbc = EditableDbc::new();
+        let mut message = EditableMessage::new();
+        message.message_id = 0x10;
+        message.message_name = "M".to_string();
+        message.message_size = 8;
+        dbc.add_message(&message);
+
+        dbc.set_message_can_fd(0x10, true, true).unwrap();
+        assert!(
+            dbc.get_attribute_definition(FRAME_FORMAT_ATTRIBUTE)
+                .is_some()
+        );
+        assert!(
+            dbc.get_attribute_definition(BIT_RATE_SWITCH_ATTRIBUTE)
+                .is_some()
+        );
+        dbc.set_message_size(0x10, 64);
+
+        let reparsed = EditableDbc::from_dbc(&Dbc::try_from(dbc.to_string().as_str()).unwrap());
+        let message = reparsed.get_message(0x10).unwrap();
+        assert!(message.is_fd());
+        assert!(message.bit_rate_switch());
+        assert_eq!(message.message_size(), 64);
+
+        dbc.undo().unwrap();
+        dbc.undo().unwrap();
+        assert!(dbc.attribute_definitions().is_empty());
+        assert!(!dbc.get_message(0x10).unwrap().is_fd());
+    }
+}
@@ -0,0 +1,538 @@
+use can_dbc::{ByteOrder, MultiplexIndicator, SignalExtendedValueType, ValueType};
+
+use super::can_fd::check_signal_placement;
+use super::{EditableMessage, EditableSignal};
+
+// 报文负载的编码和解码：原始字节 <-> 信号物理值
+// 直接使用 EditableMessage / EditableSignal 的定义，编辑后的结果可以立即用于测试台
+//
+// 位编号遵循 DBC 的约定：
+// - Intel (@1)：起始位是最低位，位号 = 字节号 * 8 + 字节内位号，向高位连续
+// - Motorola (@0)：起始位是最高位，同一字节内向低位走，走到字节的第 0 位后跳到下一个字节的第 7 位
+//   也就是常说的"锯齿"编号
+//
+// 物理值 = 原始值 * factor + offset，Signed 信号的原始值按二进制补码解释
+// SIG_VALTYPE_ 声明为 IEEE 浮点的信号，原始值是 f32 / f64 的位模式，长度必须是 32 / 64 位
+
+/// 解码得到的一个信号值
+#[allow(dead_code)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedSignal {
+    pub name: String,
+    /// 原始值，Signed 信号已经做了符号扩展，浮点信号是不做符号扩展的位模式
+    pub raw: i64,
+    /// 物理值
+    pub value: f64,
+}
+
+// 按照信号的位顺序依次访问每一位，从最高位到最低位
+// 返回 (字节号, 字节内位号)
+fn bit_positions(signal: &EditableSignal) -> impl Iterator<Item = (usize, u32)> {
+    layout_bits(signal.start_bit, signal.signal_size, signal.byte_order)
+        .map(|bit| ((bit / 8) as usize, (bit % 8) as u32))
+}
+
+fn layout_bits(start_bit: u64, size: u64, byte_order: ByteOrder) -> impl Iterator<Item = u64> {
+    let mut position = match byte_order {
+        // Intel 的最高位是 start_bit + size - 1，从这里往低位走
+        ByteOrder::LittleEndian => start_bit + size.max(1) - 1,
+        ByteOrder::BigEndian => start_bit,
+    };
+    (0..size).map(move |i| {
+        let current = position;
+        if i + 1 < size {
+            position = match byte_order {
+                ByteOrder::LittleEndian => position - 1,
+                ByteOrder::BigEndian if position % 8 == 0 => position + 15,
+                ByteOrder::BigEndian => position - 1,
+            };
+        }
+        current
+    })
+}
+
+/// 按照给定的布局，信号占用的所有位号（字节号 * 8 + 字节内位号），从最高位到最低位
+///
+/// 不检查是否超出负载，供布局编辑器预览拖动结果
+pub fn signal_bits(start_bit: u64, size: u64, byte_order: ByteOrder) -> Vec<u64> {
+    layout_bits(start_bit, size, byte_order).collect()
+}
+
+/// 字节内的位号反过来：DBC 的位号和按字节从高位到低位连续编号的位置之间的转换，转换两次得到原来的值
+///
+/// Motorola 信号在后一种编号下是连续的
+pub(crate) fn swap_bit_order(bit: u64) -> u64 {
+    bit / 8 * 8 + (7 - bit % 8)
+}
+
+/// 两个信号是否可能同时出现在一帧中：复用值不同的被复用信号不会同时出现，可以共用同一段位
+pub(crate) fn may_overlap(a: &EditableSignal, b: &EditableSignal) -> bool {
+    !matches!(
+        (a.multiplexer_indicator, b.multiplexer_indicator),
+        (MultiplexIndicator::MultiplexedSignal(x), MultiplexIndicator::MultiplexedSignal(y)) if x != y
+    )
+}
+
+fn mask(size: u64) -> u64 {
+    if size >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << size) - 1
+    }
+}
+
+#[allow(dead_code)]
+impl EditableSignal {
+    /// 信号占用的所有位号，从最高位到最低位
+    pub fn bits(&self) -> Vec<u64> {
+        signal_bits(self.start_bit, self.signal_size, self.byte_order)
+    }
+
+    /// 从负载中取出原始位，不做符号扩展
+    pub fn extract_bits(&self, data: &[u8]) -> Result<u64, String> {
+        check_signal_placement(self, data.len() as u64)?;
+        Ok(bit_positions(self).fold(0u64, |raw, (byte, bit)| {
+            (raw << 1) | ((data[byte] >> bit) & 1) as u64
+        }))
+    }
+
+    /// 将原始位写入负载，超出信号长度的高位被忽略
+    pub fn insert_bits(&self, data: &mut [u8], raw: u64) -> Result<(), String> {
+        check_signal_placement(self, data.len() as u64)?;
+        let raw = raw & mask(self.signal_size);
+        for (i, (byte, bit)) in bit_positions(self).enumerate() {
+            // 第 i 个访问到的位对应原始值从最高位数起的第 i 位
+            let value = (raw >> (self.signal_size - 1 - i as u64)) & 1;
+            if value == 1 {
+                data[byte] |= 1 << bit;
+            } else {
+                data[byte] &= !(1 << bit);
+            }
+        }
+        Ok(())
+    }
+
+    /// IEEE 浮点信号的位数，不是浮点信号时为 None，长度与类型不符时报错
+    pub(crate) fn float_size(&self) -> Result<Option<u64>, String> {
+        let size = match self.extended_value_type {
+            Some(SignalExtendedValueType::IEEEfloat32Bit) => 32,
+            Some(SignalExtendedValueType::IEEEdouble64bit) => 64,
+            Some(SignalExtendedValueType::SignedOrUnsignedInteger) | None => return Ok(None),
+        };
+        if self.signal_size != size {
+            return Err(format!(
+                "Signal {} is a {}-bit float but {} bits long",
+                self.name, size, self.signal_size
+            ));
+        }
+        Ok(Some(size))
+    }
+
+    /// 原始值，Signed 信号按补码做符号扩展，浮点信号返回位模式
+    pub fn decode_raw(&self, data: &[u8]) -> Result<i64, String> {
+        let bits = self.extract_bits(data)?;
+        if self.float_size()?.is_some() {
+            return Ok(bits as i64);
+        }
+        Ok(match self.value_type {
+            ValueType::Signed if self.signal_size < 64 => {
+                let shift = 64 - self.signal_size;
+                ((bits << shift) as i64) >> shift
+            }
+            _ => bits as i64,
+        })
+    }
+
+    /// 物理值 = 原始值 * factor + offset
+    pub fn decode(&self, data: &[u8]) -> Result<f64, String> {
+        let raw = self.decode_raw(data)?;
+        let raw = match (self.float_size()?, self.value_type) {
+            (Some(32), _) => f32::from_bits(raw as u32) as f64,
+            (Some(_), _) => f64::from_bits(raw as u64),
+            // 64 位 Unsigned 的原始值可能超出 i64
+            (None, ValueType::Unsigned) => raw as u64 as f64,
+            (None, ValueType::Signed) => raw as f64,
+        };
+        Ok(raw * self.factor + self.offset)
+    }
+
+    /// 物理值转换为原始值，结果按信号长度和符号检查范围，浮点信号返回位模式
+    pub fn physical_to_raw(&self, value: f64) -> Result<i64, String> {
+        if self.factor == 0.0 {
+            return Err(format!("Signal {} has a factor of 0", self.name));
+        }
+        let scaled = (value - self.offset) / self.factor;
+        match self.float_size()? {
+            Some(32) => {
+                let raw = scaled as f32;
+                // 超出 f32 范围的有限值会变成无穷大
+                if raw.is_infinite() && scaled.is_finite() {
+                    return Err(format!(
+                        "Value {} is out of the range of 32-bit float signal {}",
+                        value, self.name
+                    ));
+                }
+                return Ok(raw.to_bits() as i64);
+            }
+            Some(_) => return Ok(scaled.to_bits() as i64),
+            None => {}
+        }
+        let raw = scaled.round();
+        let (min, max) = self.raw_range();
+        if !raw.is_finite() || raw < min as f64 || raw > max as f64 {
+            return Err(format!(
+                "Value {} is out of the raw range [{}, {}] of signal {}",
+                value, min, max, self.name
+            ));
+        }
+        Ok(raw as i64)
+    }
+
+    /// 原始值的取值范围，64 位 Unsigned 信号的上限受 i64 限制
+    pub fn raw_range(&self) -> (i64, i64) {
+        match self.value_type {
+            ValueType::Signed => {
+                let half = 1i128 << (self.signal_size.clamp(1, 64) - 1);
+                (-half as i64, (half - 1) as i64)
+            }
+            ValueType::Unsigned => (0, mask(self.signal_size).min(i64::MAX as u64) as i64),
+        }
+    }
+
+    /// 将物理值编码后写入负载
+    pub fn encode(&self, data: &mut [u8], value: f64) -> Result<(), String> {
+        let raw = self.physical_to_raw(value)?;
+        self.insert_bits(data, raw as u64)
+    }
+}
+
+#[allow(dead_code)]
+impl EditableMessage {
+    /// 复用器信号，一个 Message 最多只有一个
+    pub fn multiplexor(&self) -> Option<&EditableSignal> {
+        self.signals.iter().find(|s| {
+            matches!(
+                s.multiplexer_indicator,
+                MultiplexIndicator::Multiplexor
+                    | MultiplexIndicator::MultiplexorAndMultiplexedSignal(_)
+            )
+        })
+    }
+
+    /// 解码负载中的所有信号
+    ///
+    /// 被复用的信号只有在复用器的值与其复用值相同时才会出现在结果中
+    /// 负载比信号定义短时，放不下的信号被跳过
+    pub fn decode(&self, data: &[u8]) -> Vec<DecodedSignal> {
+        let multiplexor_value = self.multiplexor().and_then(|m| m.decode_raw(data).ok());
+        self.signals
+            .iter()
+            .filter(|signal| is_active(signal, multiplexor_value))
+            .filter_map(|signal| {
+                Some(DecodedSignal {
+                    name: signal.name.clone(),
+                    raw: signal.decode_raw(data).ok()?,
+                    value: signal.decode(data).ok()?,
+                })
+            })
+            .collect()
+    }
+
+    /// 根据物理值构造长度为 message_size 的负载，没有给出的信号原始值为 0
+    ///
+    /// 给出被复用的信号时，复用器的值必须与之匹配（复用器没有给出时按 0 处理）
+    pub fn encode(&self, values: &[(&str, f64)]) -> Result<Vec<u8>, String> {
+        let mut data = vec![0u8; self.message_size as usize];
+
+        let multiplexor_value = match self.multiplexor() {
+            Some(multiplexor) => {
+                let value = values
+                    .iter()
+                    .find(|(name, _)| *name == multiplexor.name)
+                    .map_or(Ok(0), |(_, v)| multiplexor.physical_to_raw(*v))?;
+                Some(value)
+            }
+            None => None,
+        };
+
+        for (name, value) in values {
+            let signal = self
+                .signals
+                .iter()
+                .find(|s| s.name == *name)
+                .ok_or_else(|| format!("Signal {} not found in {}", name, self.message_name))?;
+            if !is_active(signal, multiplexor_value) {
+                return Err(format!(
+                    "Signal {} is not active for multiplexor value {}",
+                    name,
+                    multiplexor_value.unwrap_or_default()
+                ));
+            }
+            signal.encode(&mut data, *value)?;
+        }
+        Ok(data)
+    }
+}
+
+// 被复用的信号只在复用器取对应值时有效
+// 扩展复用（SG_MUL_VAL_）不在支持范围内，同时是复用器和被复用信号的信号总是有效
+fn is_active(signal: &EditableSignal, multiplexor_value: Option<i64>) -> bool {
+    match signal.multiplexer_indicator {
+        MultiplexIndicator::MultiplexedSignal(value) => multiplexor_value == Some(value as i64),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editable_dbc::EditableDbc;
+    use can_dbc::Dbc;
+
+    const CODEC_DBC: &str = r#"VERSION ""
+
+BU_: ECU
+
+BO_ 100 Mixed: 8 ECU
+ SG_ IntelU16 : 0|16@1+ (1,0) [0|0] "" Vector__XXX
+ SG_ IntelS12 : 16|12@1- (0.5,-10) [0|0] "" Vector__XXX
+ SG_ MotU16 : 39|16@0+ (1,0) [0|0] "" Vector__XXX
+ SG_ MotS10 : 53|10@0- (1,0) [0|0] "" Vector__XXX
+
+BO_ 200 Muxed: 8 ECU
+ SG_ Mux M : 0|8@1+ (1,0) [0|0] "" Vector__XXX
+ SG_ A m0 : 8|16@1+ (0.1,0) [0|0] "" Vector__XXX
+ SG_ B m1 : 8|8@1- (1,0) [0|0] "" Vector__XXX
+ SG_ Plain : 56|8@1+ (1,0) [0|0] "" Vector__XXX
+"#;
+
+    fn signal(start_bit: u64, signal_size: u64, byte_order: ByteOrder) -> EditableSignal {
+        EditableSignal {
+            name: "S".to_string(),
+            start_bit,
+            signal_size,
+            byte_order,
+            ..EditableSignal::new()
+        }
+    }
+
+    // 参考实现：8 字节负载按大端转换为 u64 后，Motorola 信号是其中连续的一段
+    fn reference_motorola(data: &[u8; 8], start_bit: u64, size: u64) -> u64 {
+        let word = u64::from_be_bytes(*data);
+        let msb = start_bit / 8 * 8 + (7 - start_bit % 8);
+        (word >> (64 - msb - size)) & mask(size)
+    }
+
+    // 参考实现：8 字节负载按小端转换为 u64 后，Intel 信号是其中连续的一段
+    fn reference_intel(data: &[u8; 8], start_bit: u64, size: u64) -> u64 {
+        (u64::from_le_bytes(*data) >> start_bit) & mask(size)
+    }
+
+    // 简单的伪随机数据，避免依赖外部 crate
+    fn pattern(seed: u64) -> [u8; 8] {
+        let mut x = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ 0xD1B5_4A32_D192_ED03;
+        x ^= x >> 29;
+        x.to_le_bytes()
+    }
+
+    #[test]
+    fn test_motorola_exhaustive() {
+        // 所有能放进 8 字节的 (起始位, 长度) 组合都与参考实现一致
+        let mut checked = 0;
+        for start_bit in 0..64 {
+            for size in 1..=64 {
+                let sig = signal(start_bit, size, ByteOrder::BigEndian);
+                if check_signal_placement(&sig, 8).is_err() {
+                    continue;
+                }
+                for seed in 0..4 {
+                    let data = pattern(start_bit * 64 + size + seed);
+                    let expected = reference_motorola(&data, start_bit, size);
+                    assert_eq!(
+                        sig.extract_bits(&data).unwrap(),
+                        expected,
+                        "extract start_bit={start_bit} size={size}"
+                    );
+
+                    // 写入后只改变信号所在的位
+                    let mut written = data;
+                    let raw = !expected & mask(size);
+                    sig.insert_bits(&mut written, raw).unwrap();
+                    assert_eq!(sig.extract_bits(&written).unwrap(), raw);
+                    let changed = u64::from_be_bytes(written) ^ u64::from_be_bytes(data);
+                    let msb = start_bit / 8 * 8 + (7 - start_bit % 8);
+                    let field = mask(size) << (64 - msb - size);
+                    assert_eq!(changed & !field, 0, "start_bit={start_bit} size={size}");
+                }
+                checked += 1;
+            }
+        }
+        // 8 字节中每个长度 n 有 65 - n 个合法位置
+        assert_eq!(checked, (1..=64).map(|n| 65 - n).sum::<u64>());
+    }
+
+    #[test]
+    fn test_intel_exhaustive() {
+        for start_bit in 0..64 {
+            for size in 1..=(64 - start_bit) {
+                let sig = signal(start_bit, size, ByteOrder::LittleEndian);
+                let data = pattern(start_bit * 64 + size);
+                let expected = reference_intel(&data, start_bit, size);
+                assert_eq!(sig.extract_bits(&data).unwrap(), expected);
+
+                let mut written = [0u8; 8];
+                sig.insert_bits(&mut written, expected).unwrap();
+                assert_eq!(u64::from_le_bytes(written), expected << start_bit);
+            }
+        }
+    }
+
+    #[test]
+    fn test_motorola_known_layouts() {
+        // 7|16@0+：第 0 字节是高字节
+        let sig = signal(7, 16, ByteOrder::BigEndian);
+        assert_eq!(
+            sig.extract_bits(&[0x12, 0x34, 0, 0, 0, 0, 0, 0]).unwrap(),
+            0x1234
+        );
+
+        // 3|8@0+：高 4 位在第 0 字节的低 4 位，低 4 位在第 1 字节的高 4 位
+        let sig = signal(3, 8, ByteOrder::BigEndian);
+        assert_eq!(
+            sig.extract_bits(&[0x0A, 0xB0, 0, 0, 0, 0, 0, 0]).unwrap(),
+            0xAB
+        );
+
+        // 占用的位号：从最高位开始沿锯齿编号走
+        assert_eq!(sig.bits(), [3, 2, 1, 0, 15, 14, 13, 12]);
+        assert_eq!(swap_bit_order(3), 4);
+        assert_eq!(swap_bit_order(15), 8);
+        assert!((0..512).all(|bit| swap_bit_order(swap_bit_order(bit)) == bit));
+        assert_eq!(
+            signal(14, 4, ByteOrder::LittleEndian).bits(),
+            [17, 16, 15, 14]
+        );
+
+        // 单个位
+        let sig = signal(12, 1, ByteOrder::BigEndian);
+        assert_eq!(sig.extract_bits(&[0, 0x10, 0, 0, 0, 0, 0, 0]).unwrap(), 1);
+
+        // CAN FD 负载中的 Motorola 信号
+        let sig = signal(503, 16, ByteOrder::BigEndian);
+        let mut data = [0u8; 64];
+        data[62] = 0xBE;
+        data[63] = 0xEF;
+        assert_eq!(sig.extract_bits(&data).unwrap(), 0xBEEF);
+
+        // 超出负载
+        assert!(
+            signal(7, 16, ByteOrder::BigEndian)
+                .extract_bits(&[0x12])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_sign_factor_offset() {
+        let dbc = EditableDbc::from_dbc(&Dbc::try_from(CODEC_DBC).unwrap());
+        let message = dbc.get_message(100).unwrap();
+
+        let data = message
+            .encode(&[
+                ("IntelU16", 4660.0),
+                ("IntelS12", -20.0),
+                ("MotU16", 0xBEEF as f64),
+                ("MotS10", -3.0),
+            ])
+            .unwrap();
+        assert_eq!(&data[0..2], &[0x34, 0x12]);
+
+        let decoded = message.decode(&data);
+        let value = |name: &str| decoded.iter().find(|d| d.name == name).unwrap().clone();
+        assert_eq!(value("IntelU16").value, 4660.0);
+        // (-20 - (-10)) / 0.5 = -20
+        assert_eq!(value("IntelS12").raw, -20);
+        assert_eq!(value("IntelS12").value, -20.0);
+        assert_eq!(value("MotU16").raw, 0xBEEF);
+        assert_eq!(value("MotS10").raw, -3);
+
+        // 超出原始值范围
+        assert!(message.encode(&[("IntelU16", 65536.0)]).is_err());
+        assert!(message.encode(&[("MotS10", 512.0)]).is_err());
+        assert!(message.encode(&[("MotS10", -512.0)]).is_ok());
+        assert!(message.encode(&[("Missing", 0.0)]).is_err());
+    }
+
+    #[test]
+    fn test_multiplexed_signals() {
+        let dbc = EditableDbc::from_dbc(&Dbc::try_from(CODEC_DBC).unwrap());
+        let message = dbc.get_message(200).unwrap();
+        assert_eq!(message.multiplexor().unwrap().name(), "Mux");
+
+        let data = message
+            .encode(&[("Mux", 1.0), ("B", -5.0), ("Plain", 7.0)])
+            .unwrap();
+        let decoded = message.decode(&data);
+        let names: Vec<&str> = decoded.iter().map(|d| d.name.as_str()).collect();
+        // 被复用的信号 A 不出现
+        assert_eq!(names, ["Mux", "B", "Plain"]);
+        assert_eq!(decoded[1].value, -5.0);
+
+        let data = message.encode(&[("A", 12.3)]).unwrap();
+        let decoded = message.decode(&data);
+        assert_eq!(decoded[0].raw, 0);
+        assert_eq!(decoded[1].name, "A");
+        assert!((decoded[1].value - 12.3).abs() < 1e-9);
+
+        // 复用器的值与信号不匹配
+        assert!(message.encode(&[("Mux", 0.0), ("B", 1.0)]).is_err());
+
+        // 复用值不同的 A 和 B 不会同时出现，与其他信号都可能同时出现
+        let signal = |name: &str| message.signals().iter().find(|s| s.name() == name).unwrap();
+        assert!(!may_overlap(signal("A"), signal("B")));
+        assert!(may_overlap(signal("A"), signal("A")));
+        assert!(may_overlap(signal("Mux"), signal("B")));
+        assert!(may_overlap(signal("A"), signal("Plain")));
+    }
+
+    #[test]
+    fn test_float_signals() {
+        let dbc = EditableDbc::from_dbc(
+            &Dbc::try_from(
+                r#"VERSION ""
+
+BU_: ECU
+
+BO_ 300 Floats: 16 ECU
+ SG_ Single : 0|32@1- (1,0) [0|0] "" Vector__XXX
+ SG_ Double : 32|64@1- (2,1) [0|0] "" Vector__XXX
+ SG_ Short : 96|16@1- (1,0) [0|0] "" Vector__XXX
+
+SIG_VALTYPE_ 300 Single : 1;
+SIG_VALTYPE_ 300 Double : 2;
+SIG_VALTYPE_ 300 Short : 1;
+"#,
+            )
+            .unwrap(),
+        );
+        let message = dbc.get_message(300).unwrap();
+        let data = message
+            .encode(&[("Single", -1.5), ("Double", 7.5)])
+            .unwrap();
+        assert_eq!(&data[0..4], &(-1.5f32).to_le_bytes());
+        // (7.5 - 1) / 2 = 3.25
+        assert_eq!(&data[4..12], &3.25f64.to_le_bytes());
+
+        let decoded = message.decode(&data);
+        let value = |name: &str| decoded.iter().find(|d| d.name == name).cloned();
+        assert_eq!(value("Single").unwrap().value, -1.5);
+        assert_eq!(value("Single").unwrap().raw, (-1.5f32).to_bits() as i64);
+        assert_eq!(value("Double").unwrap().value, 7.5);
+        // 长度与浮点类型不符的信号无法编解码
+        assert!(value("Short").is_none());
+        assert!(message.encode(&[("Short", 1.0)]).is_err());
+
+        assert!(message.encode(&[("Single", 1e40)]).is_err());
+        assert!(message.encode(&[("Single", f64::INFINITY)]).is_ok());
+    }
+}
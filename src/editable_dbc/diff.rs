@@ -0,0 +1,581 @@
+use std::fmt::{self, Display, Formatter};
+
+use can_dbc::{ByteOrder, MultiplexIndicator, SignalExtendedValueType, ValueType};
+
+use super::{AttributeValues, EditableDbc, EditableMessage, EditableSignal, FrameFormat};
+
+// 两个 EditableDbc 之间的结构化比较
+// Message 按 ID 匹配，ID 没有对应时再按名称匹配（即修改了 ID 的 Message）
+// Signal 在匹配的 Message 内按名称匹配，因此调整顺序不会产生差异
+
+/// 对象在两边的存在情况
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// 一个字段的新旧值，值已经格式化为字符串
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignalDiff {
+    pub kind: DiffKind,
+    pub name: String,
+    pub changes: Vec<FieldChange>,
+}
+
+/// 一个 Message 的差异，新增的 Message 只有 new_id，删除的只有 old_id
+#[derive(Clone, Debug, PartialEq)]
+pub struct MessageDiff {
+    pub kind: DiffKind,
+    pub old_id: Option<u32>,
+    pub new_id: Option<u32>,
+    pub name: String,
+    pub changes: Vec<FieldChange>,
+    pub signals: Vec<SignalDiff>,
+}
+
+/// 两个 DBC 之间的全部差异，没有差异时所有列表为空
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DbcDiff {
+    /// 网络注释和节点注释的修改
+    pub changes: Vec<FieldChange>,
+    pub added_nodes: Vec<String>,
+    pub removed_nodes: Vec<String>,
+    pub messages: Vec<MessageDiff>,
+}
+
+impl DbcDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+            && self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.messages.is_empty()
+    }
+
+    /// 旧文件中指定 ID 的 Message 的差异
+    pub fn for_old_message(&self, message_id: u32) -> Option<&MessageDiff> {
+        self.messages.iter().find(|m| m.old_id == Some(message_id))
+    }
+
+    /// 新文件中指定 ID 的 Message 的差异
+    pub fn for_new_message(&self, message_id: u32) -> Option<&MessageDiff> {
+        self.messages.iter().find(|m| m.new_id == Some(message_id))
+    }
+}
+
+// 值不同时记录一条修改
+fn compare<T: PartialEq + Display>(changes: &mut Vec<FieldChange>, field: &str, old: T, new: T) {
+    if old != new {
+        changes.push(FieldChange {
+            field: field.to_string(),
+            old: old.to_string(),
+            new: new.to_string(),
+        });
+    }
+}
+
+fn compare_attributes(
+    changes: &mut Vec<FieldChange>,
+    old: &AttributeValues,
+    new: &AttributeValues,
+) {
+    let lookup = |values: &AttributeValues, name: &str| {
+        values
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.to_string())
+            .unwrap_or_else(|| "(default)".to_string())
+    };
+    let mut names: Vec<&str> = old.iter().map(|(n, _)| n.as_str()).collect();
+    for (name, _) in new {
+        if !names.contains(&name.as_str()) {
+            names.push(name);
+        }
+    }
+    for name in names {
+        compare(
+            changes,
+            &format!("attribute {name}"),
+            lookup(old, name),
+            lookup(new, name),
+        );
+    }
+}
+
+// 节点注释按节点名匹配，没有注释视为空注释
+fn compare_node_comments(
+    changes: &mut Vec<FieldChange>,
+    old: &[(String, String)],
+    new: &[(String, String)],
+) {
+    let lookup = |comments: &[(String, String)], name: &str| {
+        comments
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, c)| c.clone())
+            .unwrap_or_default()
+    };
+    let mut names: Vec<&str> = old.iter().map(|(n, _)| n.as_str()).collect();
+    for (name, _) in new {
+        if !names.contains(&name.as_str()) {
+            names.push(name);
+        }
+    }
+    for name in names {
+        compare(
+            changes,
+            &format!("node {name} comment"),
+            lookup(old, name),
+            lookup(new, name),
+        );
+    }
+}
+
+fn format_frame_format(format: FrameFormat) -> &'static str {
+    match format {
+        FrameFormat::Standard => "standard",
+        FrameFormat::Extended => "extended",
+    }
+}
+
+fn format_multiplexing(indicator: &MultiplexIndicator) -> String {
+    match indicator {
+        MultiplexIndicator::Plain => "plain".to_string(),
+        MultiplexIndicator::Multiplexor => "multiplexor".to_string(),
+        MultiplexIndicator::MultiplexedSignal(value) => format!("m{value}"),
+        MultiplexIndicator::MultiplexorAndMultiplexedSignal(value) => format!("m{value}M"),
+    }
+}
+
+fn format_byte_order(order: &ByteOrder) -> &'static str {
+    match order {
+        ByteOrder::LittleEndian => "Intel",
+        ByteOrder::BigEndian => "Motorola",
+    }
+}
+
+fn format_value_type(value_type: &ValueType) -> &'static str {
+    match value_type {
+        ValueType::Signed => "signed",
+        ValueType::Unsigned => "unsigned",
+    }
+}
+
+// 没有 SIG_VALTYPE_ 与整数相同
+fn format_extended_value_type(value_type: Option<SignalExtendedValueType>) -> &'static str {
+    match value_type {
+        None | Some(SignalExtendedValueType::SignedOrUnsignedInteger) => "integer",
+        Some(SignalExtendedValueType::IEEEfloat32Bit) => "float32",
+        Some(SignalExtendedValueType::IEEEdouble64bit) => "float64",
+    }
+}
+
+fn format_value_descriptions(descriptions: &[(i64, String)]) -> String {
+    descriptions
+        .iter()
+        .map(|(value, text)| format!("{value}=\"{text}\""))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn diff_signal(old: &EditableSignal, new: &EditableSignal) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    compare(
+        &mut changes,
+        "multiplexing",
+        format_multiplexing(&old.multiplexer_indicator),
+        format_multiplexing(&new.multiplexer_indicator),
+    );
+    compare(&mut changes, "start bit", old.start_bit, new.start_bit);
+    compare(&mut changes, "size", old.signal_size, new.signal_size);
+    compare(
+        &mut changes,
+        "byte order",
+        format_byte_order(&old.byte_order),
+        format_byte_order(&new.byte_order),
+    );
+    compare(
+        &mut changes,
+        "value type",
+        format_value_type(&old.value_type),
+        format_value_type(&new.value_type),
+    );
+    compare(
+        &mut changes,
+        "extended value type",
+        format_extended_value_type(old.extended_value_type),
+        format_extended_value_type(new.extended_value_type),
+    );
+    compare(&mut changes, "factor", old.factor, new.factor);
+    compare(&mut changes, "offset", old.offset, new.offset);
+    compare(&mut changes, "min", old.min, new.min);
+    compare(&mut changes, "max", old.max, new.max);
+    compare(&mut changes, "unit", &old.unit, &new.unit);
+    compare(
+        &mut changes,
+        "receivers",
+        old.receivers.join(","),
+        new.receivers.join(","),
+    );
+    compare(&mut changes, "comment", &old.comment, &new.comment);
+    compare(
+        &mut changes,
+        "value descriptions",
+        format_value_descriptions(&old.value_descriptions),
+        format_value_descriptions(&new.value_descriptions),
+    );
+    compare_attributes(&mut changes, &old.attributes, &new.attributes);
+    changes
+}
+
+fn diff_message(old: &EditableMessage, new: &EditableMessage) -> Option<MessageDiff> {
+    let mut changes = Vec::new();
+    compare(
+        &mut changes,
+        "id",
+        format!("0x{:X}", old.message_id),
+        format!("0x{:X}", new.message_id),
+    );
+    compare(&mut changes, "name", &old.message_name, &new.message_name);
+    compare(
+        &mut changes,
+        "frame format",
+        format_frame_format(old.frame_format),
+        format_frame_format(new.frame_format),
+    );
+    compare(&mut changes, "size", old.message_size, new.message_size);
+    compare(&mut changes, "CAN FD", old.fd, new.fd);
+    compare(
+        &mut changes,
+        "bit rate switch",
+        old.bit_rate_switch,
+        new.bit_rate_switch,
+    );
+    compare(
+        &mut changes,
+        "transmitter",
+        &old.transmitter,
+        &new.transmitter,
+    );
+    compare(
+        &mut changes,
+        "extra transmitters",
+        old.extra_transmitters.join(","),
+        new.extra_transmitters.join(","),
+    );
+    compare(&mut changes, "comment", &old.comment, &new.comment);
+    compare_attributes(&mut changes, &old.attributes, &new.attributes);
+
+    let mut signals = Vec::new();
+    for signal in &old.signals {
+        match new.signals.iter().find(|s| s.name == signal.name) {
+            Some(other) => {
+                let changes = diff_signal(signal, other);
+                if !changes.is_empty() {
+                    signals.push(SignalDiff {
+                        kind: DiffKind::Changed,
+                        name: signal.name.clone(),
+                        changes,
+                    });
+                }
+            }
+            None => signals.push(SignalDiff {
+                kind: DiffKind::Removed,
+                name: signal.name.clone(),
+                changes: Vec::new(),
+            }),
+        }
+    }
+    for signal in &new.signals {
+        if !old.signals.iter().any(|s| s.name == signal.name) {
+            signals.push(SignalDiff {
+                kind: DiffKind::Added,
+                name: signal.name.clone(),
+                changes: Vec::new(),
+            });
+        }
+    }
+
+    if changes.is_empty() && signals.is_empty() {
+        return None;
+    }
+    Some(MessageDiff {
+        kind: DiffKind::Changed,
+        old_id: Some(old.message_id),
+        new_id: Some(new.message_id),
+        name: new.message_name.clone(),
+        changes,
+        signals,
+    })
+}
+
+impl EditableDbc {
+    /// 比较 self（旧）和 other（新），结果按旧文件中 Message 的顺序，新增的 Message 在最后
+    pub fn diff(&self, other: &EditableDbc) -> DbcDiff {
+        let mut changes = Vec::new();
+        compare(&mut changes, "comment", &self.comment, &other.comment);
+        compare_node_comments(&mut changes, &self.node_comments, &other.node_comments);
+        let mut diff = DbcDiff {
+            changes,
+            added_nodes: other
+                .nodes
+                .iter()
+                .filter(|n| !self.nodes.contains(n))
+                .cloned()
+                .collect(),
+            removed_nodes: self
+                .nodes
+                .iter()
+                .filter(|n| !other.nodes.contains(n))
+                .cloned()
+                .collect(),
+            messages: Vec::new(),
+        };
+
+        // 先按 ID 匹配，剩下的再按名称匹配
+        let mut matched_new = vec![false; other.messages.len()];
+        let mut pairs: Vec<Option<usize>> = self
+            .messages
+            .iter()
+            .map(|m| {
+                let index = other
+                    .messages
+                    .iter()
+                    .enumerate()
+                    .position(|(i, o)| !matched_new[i] && o.message_id == m.message_id)?;
+                matched_new[index] = true;
+                Some(index)
+            })
+            .collect();
+        for (message, pair) in self.messages.iter().zip(pairs.iter_mut()) {
+            if pair.is_some() {
+                continue;
+            }
+            *pair = other
+                .messages
+                .iter()
+                .enumerate()
+                .position(|(i, o)| !matched_new[i] && o.message_name == message.message_name);
+            if let Some(index) = *pair {
+                matched_new[index] = true;
+            }
+        }
+
+        for (message, pair) in self.messages.iter().zip(pairs) {
+            match pair {
+                Some(index) => diff
+                    .messages
+                    .extend(diff_message(message, &other.messages[index])),
+                None => diff.messages.push(MessageDiff {
+                    kind: DiffKind::Removed,
+                    old_id: Some(message.message_id),
+                    new_id: None,
+                    name: message.message_name.clone(),
+                    changes: Vec::new(),
+                    signals: Vec::new(),
+                }),
+            }
+        }
+        for (message, matched) in other.messages.iter().zip(matched_new) {
+            if !matched {
+                diff.messages.push(MessageDiff {
+                    kind: DiffKind::Added,
+                    old_id: None,
+                    new_id: Some(message.message_id),
+                    name: message.message_name.clone(),
+                    changes: Vec::new(),
+                    signals: Vec::new(),
+                });
+            }
+        }
+        diff
+    }
+}
+
+impl DiffKind {
+    /// 文本输出中的前缀
+    pub fn symbol(&self) -> char {
+        match self {
+            DiffKind::Added => '+',
+            DiffKind::Removed => '-',
+            DiffKind::Changed => '~',
+        }
+    }
+}
+
+impl Display for FieldChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {:?} -> {:?}", self.field, self.old, self.new)
+    }
+}
+
+impl Display for DbcDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "~ {change}")?;
+        }
+        for node in &self.removed_nodes {
+            writeln!(f, "- node {node}")?;
+        }
+        for node in &self.added_nodes {
+            writeln!(f, "+ node {node}")?;
+        }
+        for message in &self.messages {
+            let id = message.new_id.or(message.old_id).unwrap_or_default();
+            writeln!(
+                f,
+                "{} message 0x{:03X} {}",
+                message.kind.symbol(),
+                id,
+                message.name
+            )?;
+            for change in &message.changes {
+                writeln!(f, "    {change}")?;
+            }
+            for signal in &message.signals {
+                writeln!(f, "    {} signal {}", signal.kind.symbol(), signal.name)?;
+                for change in &signal.changes {
+                    writeln!(f, "        {change}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use can_dbc::Dbc;
+
+    const OLD_DBC: &str = r#"VERSION ""
+
+BU_: ECU Gateway
+
+BO_ 100 Engine: 8 ECU
+ SG_ Speed : 0|16@1+ (0.1,0) [0|6000] "rpm" Gateway
+ SG_ Temp : 16|8@1- (1,-40) [-40|85] "degC" Gateway
+
+BO_ 200 Status: 2 ECU
+ SG_ Flag : 0|1@1+ (1,0) [0|1] "" Gateway
+
+BO_ 300 Old: 1 ECU
+"#;
+
+    // Message 和 Signal 的顺序都调整过
+    const NEW_DBC: &str = r#"VERSION ""
+
+BU_: ECU Dashboard
+
+BO_ 201 Status: 2 ECU
+ SG_ Flag : 0|1@1+ (1,0) [0|1] "" Gateway
+
+BO_ 100 EngineData: 8 Gateway
+ SG_ Temp : 16|8@1- (1,-40) [-40|85] "degC" Gateway
+ SG_ Speed : 0|16@1+ (0.25,0) [0|6000] "rpm" Gateway
+ SG_ Load : 24|8@1+ (1,0) [0|100] "%" Gateway
+
+BO_ 400 New: 1 ECU
+"#;
+
+    fn load(text: &str) -> EditableDbc {
+        EditableDbc::from_dbc(&Dbc::try_from(text).unwrap())
+    }
+
+    #[test]
+    fn test_identical() {
+        let old = load(OLD_DBC);
+        assert!(old.diff(&old.clone()).is_empty());
+        assert_eq!(old.diff(&old).to_string(), "");
+    }
+
+    #[test]
+    fn test_diff() {
+        let diff = load(OLD_DBC).diff(&load(NEW_DBC));
+        assert_eq!(diff.added_nodes, vec!["Dashboard"]);
+        assert_eq!(diff.removed_nodes, vec!["Gateway"]);
+
+        // 同 ID 改名，以及按名称匹配上的 ID 修改
+        let engine = diff.for_old_message(100).unwrap();
+        assert_eq!(engine.kind, DiffKind::Changed);
+        let fields: Vec<&str> = engine.changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["name", "transmitter"]);
+        assert_eq!(
+            engine.signals,
+            vec![
+                SignalDiff {
+                    kind: DiffKind::Changed,
+                    name: "Speed".to_string(),
+                    changes: vec![FieldChange {
+                        field: "factor".to_string(),
+                        old: "0.1".to_string(),
+                        new: "0.25".to_string(),
+                    }],
+                },
+                SignalDiff {
+                    kind: DiffKind::Added,
+                    name: "Load".to_string(),
+                    changes: Vec::new(),
+                },
+            ]
+        );
+
+        let status = diff.for_new_message(201).unwrap();
+        assert_eq!(status.old_id, Some(200));
+        assert_eq!(status.changes[0].field, "id");
+        assert!(status.signals.is_empty());
+
+        assert_eq!(diff.for_old_message(300).unwrap().kind, DiffKind::Removed);
+        assert_eq!(diff.for_new_message(400).unwrap().kind, DiffKind::Added);
+        assert_eq!(diff.messages.len(), 4);
+    }
+
+    #[test]
+    fn test_display() {
+        let old = load(OLD_DBC);
+        let mut new = old.clone();
+        new.set_message_size(200, 4);
+        assert_eq!(
+            old.diff(&new).to_string(),
+            "~ message 0x0C8 Status\n    size: \"2\" -> \"4\"\n"
+        );
+    }
+
+    #[test]
+    fn test_comments_transmitters_and_value_types() {
+        let old = load(OLD_DBC);
+        let mut new = old.clone();
+        new.comment = "Powertrain".to_string();
+        new.node_comments
+            .push(("ECU".to_string(), "Engine control unit".to_string()));
+        new.messages[0].extra_transmitters = vec!["ECU".to_string(), "Gateway".to_string()];
+        new.messages[0].signals[0].extended_value_type =
+            Some(SignalExtendedValueType::IEEEfloat32Bit);
+        // 整数类型的 SIG_VALTYPE_ 与没有 SIG_VALTYPE_ 相同
+        new.messages[0].signals[1].extended_value_type =
+            Some(SignalExtendedValueType::SignedOrUnsignedInteger);
+
+        let diff = old.diff(&new);
+        assert!(!diff.is_empty());
+        assert!(diff.added_nodes.is_empty() && diff.removed_nodes.is_empty());
+        assert_eq!(
+            diff.to_string(),
+            concat!(
+                "~ comment: \"\" -> \"Powertrain\"\n",
+                "~ node ECU comment: \"\" -> \"Engine control unit\"\n",
+                "~ message 0x064 Engine\n",
+                "    extra transmitters: \"\" -> \"ECU,Gateway\"\n",
+                "    ~ signal Speed\n",
+                "        extended value type: \"integer\" -> \"float32\"\n",
+            )
+        );
+    }
+}
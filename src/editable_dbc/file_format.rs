@@ -0,0 +1,289 @@
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+use can_dbc::Dbc;
+
+use super::EditableDbc;
+use super::writer::dbc_differences;
+
+// 文件读写：根据扩展名选择格式，GUI 的打开/保存和命令行共用
+
+/// ARXML 的扩展名，不带点
+pub const ARXML_EXTENSION: &str = "arxml";
+
+/// 文件不是 UTF-8 编码时 `load_file_with_warnings` 返回的警告，写回原文件会破坏无法解码的字节
+pub const NOT_UTF8_WARNING: &str =
+    "The file is not valid UTF-8, undecodable bytes were replaced with U+FFFD";
+
+/// 支持读写的文件格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileFormat {
+    Dbc,
+    /// 见 `EditableDbc::to_json`
+    Json,
+    /// Kayak 的 XML 格式，见 `EditableDbc::to_kcd`
+    Kcd,
+}
+
+impl FileFormat {
+    /// 所有格式，用于文件对话框的过滤器
+    pub const ALL: [FileFormat; 3] = [FileFormat::Dbc, FileFormat::Json, FileFormat::Kcd];
+
+    /// 文件扩展名，不带点
+    pub fn extension(&self) -> &'static str {
+        match self {
+            FileFormat::Dbc => "dbc",
+            FileFormat::Json => "json",
+            FileFormat::Kcd => "kcd",
+        }
+    }
+
+    /// 根据扩展名判断格式，不区分大小写
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        Self::ALL
+            .into_iter()
+            .find(|format| extension.as_deref() == Some(format.extension()))
+            .ok_or_else(|| format!("Unsupported file format: {}", path.display()))
+    }
+
+    /// 从字符串解析
+    pub fn parse(&self, contents: &str) -> Result<EditableDbc, String> {
+        self.parse_with_warnings(contents).map(|(dbc, _)| dbc)
+    }
+
+    /// 从字符串解析，同时返回转换中丢弃的内容
+    pub fn parse_with_warnings(
+        &self,
+        contents: &str,
+    ) -> Result<(EditableDbc, Vec<String>), String> {
+        match self {
+            FileFormat::Dbc => {
+                let dbc = Dbc::try_from(contents).map_err(|e| format!("{e:?}"))?;
+                let mut editable = EditableDbc::from_dbc(&dbc);
+                // can-dbc 解析后直接丢弃 BS_ 的参数，只能从原文判断
+                if has_bit_timing_parameters(contents) {
+                    editable
+                        .unsupported_content
+                        .push("Bit timing parameters (BS_) are not supported".to_string());
+                }
+                let warnings = editable.unsupported_content.clone();
+                Ok((editable, warnings))
+            }
+            FileFormat::Json => Ok((EditableDbc::from_json(contents)?, Vec::new())),
+            FileFormat::Kcd => EditableDbc::from_kcd(contents),
+        }
+    }
+
+    /// 序列化为字符串
+    pub fn write(&self, dbc: &EditableDbc) -> String {
+        self.write_with_warnings(dbc).0
+    }
+
+    /// 序列化为字符串，同时返回格式无法表示而丢弃的内容，包括读取时就无法表示的内容
+    pub fn write_with_warnings(&self, dbc: &EditableDbc) -> (String, Vec<String>) {
+        let mut warnings = dbc.unsupported_content.clone();
+        let contents = match self {
+            FileFormat::Dbc => {
+                // can-dbc 的语法只接受一个访问节点，其余的写出时丢弃
+                for env in &dbc.environment_variables {
+                    if env.access_nodes.len() > 1 {
+                        warnings.push(format!(
+                            "Environment variable {}: only the first access node is written",
+                            env.name
+                        ));
+                    }
+                }
+                dbc.to_string()
+            }
+            FileFormat::Json => dbc.to_json(),
+            FileFormat::Kcd => {
+                let (contents, kcd_warnings) = dbc.to_kcd();
+                warnings.extend(kcd_warnings);
+                contents
+            }
+        };
+        (contents, warnings)
+    }
+
+    /// 检查写出的内容重新解析后是否与原内容相同，不同时返回说明
+    ///
+    /// DBC 按 can-dbc 解析出的完整内容逐段比较，不经过 `EditableDbc`，其他格式比较解析结果
+    pub fn check_round_trip(&self, original: &str, written: &str) -> Result<(), String> {
+        let differences = match self {
+            FileFormat::Dbc => {
+                let parse = |contents| Dbc::try_from(contents).map_err(|e| format!("{e:?}"));
+                dbc_differences(&parse(original)?, &parse(written)?)
+            }
+            FileFormat::Json | FileFormat::Kcd => {
+                if self.parse(original)?.to_json() == self.parse(written)?.to_json() {
+                    Vec::new()
+                } else {
+                    vec!["contents"]
+                }
+            }
+        };
+        if differences.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("{} changed", differences.join(", ")))
+        }
+    }
+}
+
+impl Display for FileFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FileFormat::Dbc => write!(f, "DBC"),
+            FileFormat::Json => write!(f, "JSON"),
+            FileFormat::Kcd => write!(f, "KCD"),
+        }
+    }
+}
+
+/// 读取文件，格式由扩展名决定
+///
+/// DBC 文件常见 Windows-1252 等非 UTF-8 编码，无法解码的字节被替换而不是报错，
+/// `load_file_with_warnings` 此时返回 `NOT_UTF8_WARNING`
+pub fn load_file(path: &Path) -> Result<EditableDbc, String> {
+    load_file_as(path, FileFormat::from_path(path)?)
+}
+
+/// 读取文件，同时返回转换中丢弃的内容，格式由扩展名决定
+pub fn load_file_with_warnings(path: &Path) -> Result<(EditableDbc, Vec<String>), String> {
+    read_file(path, FileFormat::from_path(path)?)
+}
+
+/// 按指定格式读取文件，用于 git 合并驱动传入的没有扩展名的临时文件
+pub fn load_file_as(path: &Path, format: FileFormat) -> Result<EditableDbc, String> {
+    read_file(path, format).map(|(dbc, _)| dbc)
+}
+
+fn read_file(path: &Path, format: FileFormat) -> Result<(EditableDbc, Vec<String>), String> {
+    let (text, is_utf8) = read_text(path)?;
+    let (dbc, mut warnings) = format
+        .parse_with_warnings(&text)
+        .map_err(|e| format!("Failed to parse {}: {}: {}", format, path.display(), e))?;
+    if !is_utf8 {
+        warnings.insert(0, NOT_UTF8_WARNING.to_string());
+    }
+    Ok((dbc, warnings))
+}
+
+// BS_ 后面是否带有波特率和 BTR 参数
+fn has_bit_timing_parameters(contents: &str) -> bool {
+    contents
+        .lines()
+        .filter_map(|line| line.trim_start().strip_prefix("BS_"))
+        .filter_map(|rest| rest.trim_start().strip_prefix(':'))
+        .map(str::trim)
+        .any(|rest| !rest.is_empty() && !rest.starts_with("//"))
+}
+
+// 返回解码后的内容和文件是否为合法的 UTF-8
+fn read_text(path: &Path) -> Result<(String, bool), String> {
+    let bytes =
+        fs::read(path).map_err(|e| format!("Failed to open file: {}: {}", path.display(), e))?;
+    Ok(match String::from_utf8(bytes) {
+        Ok(text) => (text, true),
+        Err(e) => (String::from_utf8_lossy(e.as_bytes()).into_owned(), false),
+    })
+}
+
+/// 是否为 ARXML 文件，ARXML 只能导入，不属于 `FileFormat`
+pub fn is_arxml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case(ARXML_EXTENSION))
+}
+
+/// ARXML 文件中所有 CAN 集群的名称
+pub fn load_arxml_clusters(path: &Path) -> Result<Vec<String>, String> {
+    EditableDbc::arxml_clusters(&read_text(path)?.0)
+        .map_err(|e| format!("Failed to parse ARXML: {}: {}", path.display(), e))
+}
+
+/// 导入 ARXML 文件中的一个 CAN 集群，返回结果和转换日志，见 `EditableDbc::from_arxml`
+pub fn load_arxml(
+    path: &Path,
+    cluster: Option<&str>,
+) -> Result<(EditableDbc, Vec<String>), String> {
+    EditableDbc::from_arxml(&read_text(path)?.0, cluster)
+        .map_err(|e| format!("Failed to import ARXML: {}: {}", path.display(), e))
+}
+
+/// 写入文件，格式由扩展名决定
+pub fn save_file(dbc: &EditableDbc, path: &Path) -> Result<(), String> {
+    save_file_as(dbc, path, FileFormat::from_path(path)?)
+}
+
+/// 写入文件，返回格式无法表示而丢弃的内容，格式由扩展名决定
+pub fn save_file_with_warnings(dbc: &EditableDbc, path: &Path) -> Result<Vec<String>, String> {
+    write_file(dbc, path, FileFormat::from_path(path)?)
+}
+
+/// 按指定格式写入文件
+pub fn save_file_as(dbc: &EditableDbc, path: &Path, format: FileFormat) -> Result<(), String> {
+    write_file(dbc, path, format).map(|_| ())
+}
+
+fn write_file(dbc: &EditableDbc, path: &Path, format: FileFormat) -> Result<Vec<String>, String> {
+    let (contents, warnings) = format.write_with_warnings(dbc);
+    fs::write(path, contents)
+        .map_err(|e| format!("Failed to save file: {}: {}", path.display(), e))?;
+    Ok(warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            FileFormat::from_path(Path::new("a/b.dbc")),
+            Ok(FileFormat::Dbc)
+        );
+        assert_eq!(
+            FileFormat::from_path(Path::new("B.DBC")),
+            Ok(FileFormat::Dbc)
+        );
+        assert_eq!(
+            FileFormat::from_path(Path::new("b.Json")),
+            Ok(FileFormat::Json)
+        );
+        assert_eq!(
+            FileFormat::from_path(Path::new("b.kcd")),
+            Ok(FileFormat::Kcd)
+        );
+        assert!(FileFormat::from_path(Path::new("b.txt")).is_err());
+        assert!(FileFormat::from_path(Path::new("dbc")).is_err());
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert!(FileFormat::Dbc.parse("not a dbc").is_err());
+        assert!(FileFormat::Json.parse("not json").is_err());
+        assert!(FileFormat::Kcd.parse("<NotKcd/>").is_err());
+    }
+
+    #[test]
+    fn test_not_utf8_warning() {
+        let path = temp_dir("file-format").join("latin1.dbc");
+        // Windows-1252 编码的 "°C"
+        fs::write(&path, b"VERSION \"\xB0C\"\n\nBU_: ECU\n").unwrap();
+        let (dbc, warnings) = load_file_with_warnings(&path).unwrap();
+        assert_eq!(dbc.version(), "\u{FFFD}C");
+        assert_eq!(warnings, [NOT_UTF8_WARNING]);
+
+        fs::write(&path, "VERSION \"°C\"\n\nBU_: ECU\n").unwrap();
+        let (dbc, warnings) = load_file_with_warnings(&path).unwrap();
+        assert_eq!(dbc.version(), "°C");
+        assert!(warnings.is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,1108 @@
+use can_dbc::{
+    AccessType, ByteOrder, EnvType, MultiplexIndicator, SignalExtendedValueType, ValueType,
+};
+use serde_json::{Number, Value};
+
+use super::writer::EXTENDED_ID_FLAG;
+use super::{
+    AttributeDefinition, AttributeObjectType, AttributeValue, AttributeValueType, AttributeValues,
+    EditableDbc, EditableEnvironmentVariable, EditableMessage, EditableSignal, EditableValueTable,
+    FrameFormat,
+};
+
+// JSON 格式的导出和导入，供网页和 Python 等不解析 DBC 的工具读取网络定义
+// 语法和转义由 serde_json 处理，这里只负责 serde_json::Value 和模型之间的映射
+// 格式见 EditableDbc::to_json 的文档，修改格式时需要增加 SCHEMA_VERSION
+
+/// 顶层 format 字段的值，用于识别文件
+const FORMAT_NAME: &str = "roxy-dbc";
+/// 格式版本，只在不兼容的修改时增加，新增可选字段不改变版本
+const SCHEMA_VERSION: u64 = 1;
+
+const BYTE_ORDERS: [(ByteOrder, &str); 2] = [
+    (ByteOrder::LittleEndian, "little_endian"),
+    (ByteOrder::BigEndian, "big_endian"),
+];
+const OBJECT_TYPES: [(AttributeObjectType, &str); 5] = [
+    (AttributeObjectType::Network, "network"),
+    (AttributeObjectType::Node, "node"),
+    (AttributeObjectType::Message, "message"),
+    (AttributeObjectType::Signal, "signal"),
+    (
+        AttributeObjectType::EnvironmentVariable,
+        "environment_variable",
+    ),
+];
+const ENV_TYPES: [(EnvType, &str); 3] = [
+    (EnvType::Integer, "integer"),
+    (EnvType::Float, "float"),
+    (EnvType::String, "string"),
+];
+const EXTENDED_VALUE_TYPES: [(SignalExtendedValueType, &str); 3] = [
+    (SignalExtendedValueType::SignedOrUnsignedInteger, "integer"),
+    (SignalExtendedValueType::IEEEfloat32Bit, "float32"),
+    (SignalExtendedValueType::IEEEdouble64bit, "float64"),
+];
+const ACCESS_TYPES: [(AccessType, &str); 4] = [
+    (AccessType::DummyNodeVector0, "unrestricted"),
+    (AccessType::DummyNodeVector1, "read"),
+    (AccessType::DummyNodeVector2, "write"),
+    (AccessType::DummyNodeVector3, "read_write"),
+];
+
+impl EditableDbc {
+    /// 导出为 JSON，包含 DBC 中的全部内容，`from_json` 读回后与原来的内容相同
+    ///
+    /// 格式（版本 1）：
+    ///
+    /// ```text
+    /// {
+    ///   "format": "roxy-dbc",
+    ///   "schema_version": 1,
+    ///   "version": "",                  // VERSION
+    ///   "nodes": ["ECU"],               // BU_
+    ///   "comment": "",                  // CM_ 网络注释
+    ///   "node_comments": [{"node": "ECU", "comment": ""}],  // CM_ BU_
+    ///   "messages": [{
+    ///     "id": 256,                    // CAN ID，不带 DBC 中扩展帧的最高位标志
+    ///     "extended": false,            // 29 位扩展帧
+    ///     "name": "Engine",
+    ///     "size": 8,                    // 负载字节数
+    ///     "transmitter": "ECU",         // 没有发送节点时为 "Vector__XXX"
+    ///     "extra_transmitters": [],     // BO_TX_BU_
+    ///     "fd": false,                  // CAN FD，由 VFrameFormat 属性决定
+    ///     "bit_rate_switch": false,     // CAN FD 位速率切换，由 CANFD_BRS 属性决定
+    ///     "comment": "",
+    ///     "attributes": [{"name": "GenMsgCycleTime", "value": 100}],
+    ///     "signals": [{
+    ///       "name": "Speed",
+    ///       "multiplexor": false,       // 是否为复用器（M）
+    ///       "multiplexer_value": null,  // 所属的复用器值（m0），不属于复用组时为 null
+    ///       "start_bit": 0,             // DBC 中的起始位，Motorola 格式为最高位
+    ///       "size": 16,
+    ///       "byte_order": "little_endian",  // Intel 为 little_endian，Motorola 为 big_endian
+    ///       "signed": false,
+    ///       "extended_value_type": null,  // SIG_VALTYPE_：integer / float32 / float64，没有时为 null
+    ///       "factor": 0.1,
+    ///       "offset": 0.0,
+    ///       "min": 0.0,
+    ///       "max": 6553.5,
+    ///       "unit": "km/h",
+    ///       "receivers": ["Dash"],
+    ///       "comment": "",
+    ///       "value_descriptions": [{"value": 0, "description": "Off"}],  // VAL_
+    ///       "attributes": []
+    ///     }]
+    ///   }],
+    ///   "value_tables": [{"name": "OnOff", "descriptions": [{"value": 1, "description": "On"}]}],
+    ///   "environment_variables": [{
+    ///     "name": "EnvVar", "type": "integer",          // integer / float / string
+    ///     "min": 0, "max": 10, "unit": "", "initial_value": 0, "id": 1,
+    ///     "access_type": "read",                        // unrestricted / read / write / read_write
+    ///     "access_nodes": ["ECU"], "comment": ""
+    ///   }],
+    ///   "attribute_definitions": [{
+    ///     "name": "GenMsgCycleTime",
+    ///     "object_type": "message",   // network / node / message / signal / environment_variable
+    ///     "value_type": {"type": "int", "min": 0, "max": 10000},
+    ///                                 // int / hex / float 带 min 和 max，string 没有其他字段，
+    ///                                 // enum 带 "values": ["A", "B"]
+    ///     "default": 100              // 没有默认值时为 null
+    ///   }],
+    ///   "network_attributes": [{"name": "BusType", "value": "CAN"}],
+    ///   "node_attributes": [{"node": "ECU", "attributes": [...]}],
+    ///   "environment_variable_attributes": [{"environment_variable": "EnvVar", "attributes": [...]}]
+    /// }
+    /// ```
+    ///
+    /// 属性值按 JSON 类型区分：整数为 INT / HEX / ENUM 序号，带小数点或指数的数为 FLOAT，字符串为 STRING。
+    /// 数值不是有限值时写成字符串 "NaN"、"Infinity"、"-Infinity"。
+    pub fn to_json(&self) -> String {
+        let root = object(vec![
+            ("format", string(FORMAT_NAME)),
+            ("schema_version", integer(SCHEMA_VERSION)),
+            ("version", string(&self.version)),
+            ("nodes", strings(&self.nodes)),
+            ("comment", string(&self.comment)),
+            (
+                "node_comments",
+                Value::Array(
+                    self.node_comments
+                        .iter()
+                        .map(|(node, comment)| {
+                            object(vec![("node", string(node)), ("comment", string(comment))])
+                        })
+                        .collect(),
+                ),
+            ),
+            (
+                "messages",
+                Value::Array(self.messages.iter().map(message_to_json).collect()),
+            ),
+            (
+                "value_tables",
+                Value::Array(
+                    self.value_tables
+                        .iter()
+                        .map(|table| {
+                            object(vec![
+                                ("name", string(&table.name)),
+                                ("descriptions", descriptions_to_json(&table.descriptions)),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+            (
+                "environment_variables",
+                Value::Array(
+                    self.environment_variables
+                        .iter()
+                        .map(environment_variable_to_json)
+                        .collect(),
+                ),
+            ),
+            (
+                "attribute_definitions",
+                Value::Array(
+                    self.attribute_definitions
+                        .iter()
+                        .map(definition_to_json)
+                        .collect(),
+                ),
+            ),
+            (
+                "network_attributes",
+                attributes_to_json(&self.network_attributes),
+            ),
+            (
+                "node_attributes",
+                object_attributes_to_json(&self.node_attributes, "node"),
+            ),
+            (
+                "environment_variable_attributes",
+                object_attributes_to_json(
+                    &self.environment_variable_attributes,
+                    "environment_variable",
+                ),
+            ),
+        ]);
+        // Value 的键都是字符串，序列化不会失败
+        let mut out = serde_json::to_string_pretty(&root).unwrap();
+        out.push('\n');
+        out
+    }
+
+    /// 从 `to_json` 的格式读取，没有撤销历史
+    ///
+    /// 除了 Message 的 id 和 name 以及 Signal 的 name、start_bit 和 size，其他字段都可以省略，
+    /// 省略或为 null 时使用新建对象的默认值。未知的字段被忽略。
+    /// fd 和 bit_rate_switch 与属性不一致时按这两个字段修改 VFrameFormat 和 CANFD_BRS 属性。
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let json: Value = serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {e}"))?;
+        let root = Node::root(&json);
+        root.expect_object()?;
+        if let Some(format) = root.field("format") {
+            let name = format.as_str()?;
+            if name != FORMAT_NAME {
+                return Err(format.error(&format!("expected \"{FORMAT_NAME}\", found \"{name}\"")));
+            }
+        }
+        if let Some(version) = root.field("schema_version") {
+            let value = version.as_u64()?;
+            if value > SCHEMA_VERSION {
+                return Err(version.error(&format!(
+                    "schema version {value} is newer than the supported version {SCHEMA_VERSION}"
+                )));
+            }
+        }
+
+        let mut dbc = EditableDbc::new();
+        dbc.version = root.string_or("version", "")?;
+        dbc.nodes = root.strings("nodes")?;
+        dbc.comment = root.string_or("comment", "")?;
+        for node in root.list("node_comments")? {
+            node.expect_object()?;
+            dbc.node_comments.push((
+                node.required("node")?.as_str()?.to_string(),
+                node.string_or("comment", "")?,
+            ));
+        }
+
+        let mut fd_flags = Vec::new();
+        for node in root.list("messages")? {
+            let message = message_from_json(&node)?;
+            if dbc
+                .messages
+                .iter()
+                .any(|m| m.message_id == message.message_id)
+            {
+                return Err(node.error(&format!(
+                    "duplicate message id 0x{:X}",
+                    message.message_id & !EXTENDED_ID_FLAG
+                )));
+            }
+            let fd = node.field("fd").map(|n| n.as_bool()).transpose()?;
+            let bit_rate_switch = node
+                .field("bit_rate_switch")
+                .map(|n| n.as_bool())
+                .transpose()?;
+            fd_flags.push((message.message_id, fd, bit_rate_switch, node.path.clone()));
+            dbc.messages.push(message);
+        }
+
+        for node in root.list("value_tables")? {
+            dbc.value_tables.push(EditableValueTable {
+                name: node.required("name")?.as_str()?.to_string(),
+                descriptions: descriptions_from_json(&node, "descriptions")?,
+            });
+        }
+        for node in root.list("environment_variables")? {
+            dbc.environment_variables
+                .push(environment_variable_from_json(&node)?);
+        }
+        for node in root.list("attribute_definitions")? {
+            dbc.attribute_definitions.push(definition_from_json(&node)?);
+        }
+        dbc.network_attributes = attributes_from_json(&root, "network_attributes")?;
+        dbc.node_attributes = object_attributes_from_json(&root, "node_attributes", "node")?;
+        dbc.environment_variable_attributes = object_attributes_from_json(
+            &root,
+            "environment_variable_attributes",
+            "environment_variable",
+        )?;
+        dbc.normalize_attribute_values();
+        dbc.sync_can_fd_flags();
+
+        // 显式给出的 FD 标志优先于属性，修改属性产生的历史记录随后清除
+        for (message_id, fd, bit_rate_switch, path) in fd_flags {
+            let message = dbc.get_message(message_id).unwrap();
+            let fd = fd.unwrap_or(message.fd);
+            // 新开启 FD 而没有给出 BRS 时与 CANFD_BRS 的默认值一致，开启位速率切换
+            let bit_rate_switch = bit_rate_switch.unwrap_or(message.bit_rate_switch || !message.fd);
+            if fd != message.fd || (fd && bit_rate_switch != message.bit_rate_switch) {
+                dbc.set_message_can_fd(message_id, fd, bit_rate_switch)
+                    .map_err(|e| format!("{path}: {e}"))?;
+            }
+        }
+        dbc.clear_history();
+        Ok(dbc)
+    }
+
+    // 与读取 DBC 时相同，按属性定义把 FLOAT 属性的整数值转换为 Float
+    fn normalize_attribute_values(&mut self) {
+        let definitions = self.attribute_definitions.clone();
+        let normalize = |attributes: &mut AttributeValues| {
+            for (name, value) in attributes.iter_mut() {
+                if let Some(definition) = definitions.iter().find(|d| d.name() == name) {
+                    *value = definition.normalize(value.clone());
+                }
+            }
+        };
+        normalize(&mut self.network_attributes);
+        for (_, attributes) in &mut self.node_attributes {
+            normalize(attributes);
+        }
+        for (_, attributes) in &mut self.environment_variable_attributes {
+            normalize(attributes);
+        }
+        for message in &mut self.messages {
+            normalize(&mut message.attributes);
+            for signal in &mut message.signals {
+                normalize(&mut signal.attributes);
+            }
+        }
+    }
+}
+
+fn message_to_json(message: &EditableMessage) -> Value {
+    object(vec![
+        ("id", integer(message.message_id & !EXTENDED_ID_FLAG)),
+        (
+            "extended",
+            Value::Bool(message.frame_format == FrameFormat::Extended),
+        ),
+        ("name", string(&message.message_name)),
+        ("size", integer(message.message_size)),
+        ("transmitter", string(&message.transmitter)),
+        ("extra_transmitters", strings(&message.extra_transmitters)),
+        ("fd", Value::Bool(message.fd)),
+        ("bit_rate_switch", Value::Bool(message.bit_rate_switch)),
+        ("comment", string(&message.comment)),
+        ("attributes", attributes_to_json(&message.attributes)),
+        (
+            "signals",
+            Value::Array(message.signals.iter().map(signal_to_json).collect()),
+        ),
+    ])
+}
+
+// fd 和 bit_rate_switch 由属性推导，在 from_json 中处理
+fn message_from_json(node: &Node) -> Result<EditableMessage, String> {
+    node.expect_object()?;
+    let id_node = node.required("id")?;
+    let id = u32::try_from(id_node.as_u64()?)
+        .ok()
+        .filter(|id| id & EXTENDED_ID_FLAG == 0)
+        .ok_or_else(|| id_node.error("CAN ID must be less than 0x80000000"))?;
+    let extended = node.bool_or("extended", false)?;
+    let default = EditableMessage::new();
+    let mut message = EditableMessage {
+        message_id: if extended { id | EXTENDED_ID_FLAG } else { id },
+        frame_format: if extended {
+            FrameFormat::Extended
+        } else {
+            FrameFormat::Standard
+        },
+        message_name: node.required("name")?.as_str()?.to_string(),
+        message_size: node.u64_or("size", default.message_size)?,
+        transmitter: node.string_or("transmitter", &default.transmitter)?,
+        extra_transmitters: node.strings("extra_transmitters")?,
+        comment: node.string_or("comment", "")?,
+        attributes: attributes_from_json(node, "attributes")?,
+        ..default
+    };
+    for signal_node in node.list("signals")? {
+        let signal = signal_from_json(&signal_node)?;
+        if message.signals.iter().any(|s| s.name == signal.name) {
+            return Err(signal_node.error(&format!("duplicate signal name {}", signal.name)));
+        }
+        message.signals.push(signal);
+    }
+    Ok(message)
+}
+
+fn signal_to_json(signal: &EditableSignal) -> Value {
+    let (multiplexor, multiplexer_value) = match signal.multiplexer_indicator {
+        MultiplexIndicator::Plain => (false, None),
+        MultiplexIndicator::Multiplexor => (true, None),
+        MultiplexIndicator::MultiplexedSignal(value) => (false, Some(value)),
+        MultiplexIndicator::MultiplexorAndMultiplexedSignal(value) => (true, Some(value)),
+    };
+    object(vec![
+        ("name", string(&signal.name)),
+        ("multiplexor", Value::Bool(multiplexor)),
+        (
+            "multiplexer_value",
+            multiplexer_value.map_or(Value::Null, integer),
+        ),
+        ("start_bit", integer(signal.start_bit)),
+        ("size", integer(signal.signal_size)),
+        (
+            "byte_order",
+            string(name_of(&BYTE_ORDERS, &signal.byte_order)),
+        ),
+        (
+            "signed",
+            Value::Bool(signal.value_type == ValueType::Signed),
+        ),
+        (
+            "extended_value_type",
+            signal
+                .extended_value_type
+                .map_or(Value::Null, |t| string(name_of(&EXTENDED_VALUE_TYPES, &t))),
+        ),
+        ("factor", float(signal.factor)),
+        ("offset", float(signal.offset)),
+        ("min", float(signal.min)),
+        ("max", float(signal.max)),
+        ("unit", string(&signal.unit)),
+        ("receivers", strings(&signal.receivers)),
+        ("comment", string(&signal.comment)),
+        (
+            "value_descriptions",
+            descriptions_to_json(&signal.value_descriptions),
+        ),
+        ("attributes", attributes_to_json(&signal.attributes)),
+    ])
+}
+
+fn signal_from_json(node: &Node) -> Result<EditableSignal, String> {
+    node.expect_object()?;
+    let multiplexor = node.bool_or("multiplexor", false)?;
+    let multiplexer_value = node
+        .field("multiplexer_value")
+        .map(|n| n.as_u64())
+        .transpose()?;
+    let default = EditableSignal::new();
+    Ok(EditableSignal {
+        name: node.required("name")?.as_str()?.to_string(),
+        multiplexer_indicator: match (multiplexor, multiplexer_value) {
+            (false, None) => MultiplexIndicator::Plain,
+            (true, None) => MultiplexIndicator::Multiplexor,
+            (false, Some(value)) => MultiplexIndicator::MultiplexedSignal(value),
+            (true, Some(value)) => MultiplexIndicator::MultiplexorAndMultiplexedSignal(value),
+        },
+        start_bit: node.required("start_bit")?.as_u64()?,
+        signal_size: node.required("size")?.as_u64()?,
+        byte_order: match node.field("byte_order") {
+            Some(n) => value_of(&n, &BYTE_ORDERS)?,
+            None => default.byte_order,
+        },
+        value_type: if node.bool_or("signed", false)? {
+            ValueType::Signed
+        } else {
+            ValueType::Unsigned
+        },
+        extended_value_type: node
+            .field("extended_value_type")
+            .map(|n| value_of(&n, &EXTENDED_VALUE_TYPES))
+            .transpose()?,
+        factor: node.f64_or("factor", default.factor)?,
+        offset: node.f64_or("offset", default.offset)?,
+        min: node.f64_or("min", default.min)?,
+        max: node.f64_or("max", default.max)?,
+        unit: node.string_or("unit", "")?,
+        receivers: node.strings("receivers")?,
+        comment: node.string_or("comment", "")?,
+        value_descriptions: descriptions_from_json(node, "value_descriptions")?,
+        attributes: attributes_from_json(node, "attributes")?,
+    })
+}
+
+fn environment_variable_to_json(variable: &EditableEnvironmentVariable) -> Value {
+    object(vec![
+        ("name", string(&variable.name)),
+        ("type", string(name_of(&ENV_TYPES, &variable.env_type))),
+        ("min", integer(variable.min)),
+        ("max", integer(variable.max)),
+        ("unit", string(&variable.unit)),
+        ("initial_value", integer(variable.initial_value)),
+        ("id", integer(variable.ev_id)),
+        (
+            "access_type",
+            string(name_of(&ACCESS_TYPES, &variable.access_type)),
+        ),
+        ("access_nodes", strings(&variable.access_nodes)),
+        ("comment", string(&variable.comment)),
+    ])
+}
+
+fn environment_variable_from_json(node: &Node) -> Result<EditableEnvironmentVariable, String> {
+    node.expect_object()?;
+    Ok(EditableEnvironmentVariable {
+        name: node.required("name")?.as_str()?.to_string(),
+        env_type: match node.field("type") {
+            Some(n) => value_of(&n, &ENV_TYPES)?,
+            None => EnvType::Integer,
+        },
+        min: node.i64_or("min", 0)?,
+        max: node.i64_or("max", 0)?,
+        unit: node.string_or("unit", "")?,
+        initial_value: node.i64_or("initial_value", 0)?,
+        ev_id: node.i64_or("id", 0)?,
+        access_type: match node.field("access_type") {
+            Some(n) => value_of(&n, &ACCESS_TYPES)?,
+            None => AccessType::DummyNodeVector0,
+        },
+        access_nodes: node.strings("access_nodes")?,
+        comment: node.string_or("comment", "")?,
+    })
+}
+
+fn definition_to_json(definition: &AttributeDefinition) -> Value {
+    let value_type = match definition.value_type() {
+        AttributeValueType::Int { min, max } => object(vec![
+            ("type", string("int")),
+            ("min", integer(*min)),
+            ("max", integer(*max)),
+        ]),
+        AttributeValueType::Hex { min, max } => object(vec![
+            ("type", string("hex")),
+            ("min", integer(*min)),
+            ("max", integer(*max)),
+        ]),
+        AttributeValueType::Float { min, max } => object(vec![
+            ("type", string("float")),
+            ("min", float(*min)),
+            ("max", float(*max)),
+        ]),
+        AttributeValueType::String => object(vec![("type", string("string"))]),
+        AttributeValueType::Enum(values) => {
+            object(vec![("type", string("enum")), ("values", strings(values))])
+        }
+    };
+    object(vec![
+        ("name", string(definition.name())),
+        (
+            "object_type",
+            string(name_of(&OBJECT_TYPES, &definition.object_type())),
+        ),
+        ("value_type", value_type),
+        (
+            "default",
+            definition
+                .default()
+                .map_or(Value::Null, attribute_value_to_json),
+        ),
+    ])
+}
+
+fn definition_from_json(node: &Node) -> Result<AttributeDefinition, String> {
+    node.expect_object()?;
+    let object_type = match node.field("object_type") {
+        Some(n) => value_of(&n, &OBJECT_TYPES)?,
+        None => AttributeObjectType::Network,
+    };
+    let value_type_node = node.required("value_type")?;
+    value_type_node.expect_object()?;
+    let type_node = value_type_node.required("type")?;
+    let value_type = match type_node.as_str()? {
+        "int" => AttributeValueType::Int {
+            min: value_type_node.i64_or("min", 0)?,
+            max: value_type_node.i64_or("max", 0)?,
+        },
+        "hex" => AttributeValueType::Hex {
+            min: value_type_node.i64_or("min", 0)?,
+            max: value_type_node.i64_or("max", 0)?,
+        },
+        "float" => AttributeValueType::Float {
+            min: value_type_node.f64_or("min", 0.0)?,
+            max: value_type_node.f64_or("max", 0.0)?,
+        },
+        "string" => AttributeValueType::String,
+        "enum" => AttributeValueType::Enum(value_type_node.strings("values")?),
+        other => {
+            return Err(type_node.error(&format!(
+                "unknown value \"{other}\", expected one of int, hex, float, string, enum"
+            )));
+        }
+    };
+    let mut definition =
+        AttributeDefinition::new(node.required("name")?.as_str()?, object_type, value_type);
+    if let Some(default) = node.field("default") {
+        definition = definition.with_default(attribute_value_from_json(&default)?);
+    }
+    definition.checked().map_err(|e| node.error(&e))
+}
+
+fn attribute_value_to_json(value: &AttributeValue) -> Value {
+    match value {
+        AttributeValue::Int(v) => integer(*v),
+        AttributeValue::Float(v) => float(*v),
+        AttributeValue::String(v) => string(v),
+    }
+}
+
+fn attribute_value_from_json(node: &Node) -> Result<AttributeValue, String> {
+    match node.json {
+        Value::Number(number) if number.is_f64() => Ok(AttributeValue::Float(node.as_f64()?)),
+        Value::Number(_) => Ok(AttributeValue::Int(node.as_i64()?)),
+        Value::String(text) => Ok(AttributeValue::String(text.clone())),
+        _ => Err(node.error("expected a number or a string")),
+    }
+}
+
+fn attributes_to_json(attributes: &AttributeValues) -> Value {
+    Value::Array(
+        attributes
+            .iter()
+            .map(|(name, value)| {
+                object(vec![
+                    ("name", string(name)),
+                    ("value", attribute_value_to_json(value)),
+                ])
+            })
+            .collect(),
+    )
+}
+
+fn attributes_from_json(node: &Node, key: &str) -> Result<AttributeValues, String> {
+    node.list(key)?
+        .iter()
+        .map(|item| {
+            item.expect_object()?;
+            Ok((
+                item.required("name")?.as_str()?.to_string(),
+                attribute_value_from_json(&item.required("value")?)?,
+            ))
+        })
+        .collect()
+}
+
+// 节点和环境变量的属性值，owner 为对象名的字段名
+fn object_attributes_to_json(objects: &[(String, AttributeValues)], owner: &str) -> Value {
+    Value::Array(
+        objects
+            .iter()
+            .map(|(name, attributes)| {
+                object(vec![
+                    (owner, string(name)),
+                    ("attributes", attributes_to_json(attributes)),
+                ])
+            })
+            .collect(),
+    )
+}
+
+fn object_attributes_from_json(
+    node: &Node,
+    key: &str,
+    owner: &str,
+) -> Result<Vec<(String, AttributeValues)>, String> {
+    node.list(key)?
+        .iter()
+        .map(|item| {
+            item.expect_object()?;
+            Ok((
+                item.required(owner)?.as_str()?.to_string(),
+                attributes_from_json(item, "attributes")?,
+            ))
+        })
+        .collect()
+}
+
+fn descriptions_to_json(descriptions: &[(i64, String)]) -> Value {
+    Value::Array(
+        descriptions
+            .iter()
+            .map(|(value, description)| {
+                object(vec![
+                    ("value", integer(*value)),
+                    ("description", string(description)),
+                ])
+            })
+            .collect(),
+    )
+}
+
+fn descriptions_from_json(node: &Node, key: &str) -> Result<Vec<(i64, String)>, String> {
+    node.list(key)?
+        .iter()
+        .map(|item| {
+            item.expect_object()?;
+            Ok((
+                item.required("value")?.as_i64()?,
+                item.required("description")?.as_str()?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+fn name_of<T: PartialEq>(table: &[(T, &'static str)], value: &T) -> &'static str {
+    table
+        .iter()
+        .find(|(v, _)| v == value)
+        .map(|(_, name)| *name)
+        .unwrap()
+}
+
+fn value_of<T: Copy>(node: &Node, table: &[(T, &'static str)]) -> Result<T, String> {
+    let text = node.as_str()?;
+    table
+        .iter()
+        .find(|(_, name)| *name == text)
+        .map(|(value, _)| *value)
+        .ok_or_else(|| {
+            let names: Vec<&str> = table.iter().map(|(_, name)| *name).collect();
+            node.error(&format!(
+                "unknown value \"{text}\", expected one of {}",
+                names.join(", ")
+            ))
+        })
+}
+
+fn object(fields: Vec<(&str, Value)>) -> Value {
+    Value::Object(
+        fields
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+    )
+}
+
+fn string(text: &str) -> Value {
+    Value::String(text.to_string())
+}
+
+fn strings(items: &[String]) -> Value {
+    Value::Array(items.iter().map(|item| string(item)).collect())
+}
+
+fn integer(value: impl Into<Number>) -> Value {
+    Value::Number(value.into())
+}
+
+// serde_json 输出能精确读回的最短表示，并且总是带小数点或指数
+fn float(value: f64) -> Value {
+    match Number::from_f64(value) {
+        Some(number) => Value::Number(number),
+        None if value.is_nan() => string("NaN"),
+        None if value > 0.0 => string("Infinity"),
+        None => string("-Infinity"),
+    }
+}
+
+/// 读取时的 JSON 值和它在文档中的路径，错误信息以路径开头，例如 messages[3].signals[1].factor
+struct Node<'a> {
+    json: &'a Value,
+    path: String,
+}
+
+impl<'a> Node<'a> {
+    fn root(json: &'a Value) -> Self {
+        Self {
+            json,
+            path: String::new(),
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        if self.path.is_empty() {
+            format!("Invalid document: {message}")
+        } else {
+            format!("{}: {message}", self.path)
+        }
+    }
+
+    fn child_path(&self, key: &str) -> String {
+        if self.path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{key}", self.path)
+        }
+    }
+
+    fn expect_object(&self) -> Result<(), String> {
+        if self.json.is_object() {
+            Ok(())
+        } else {
+            Err(self.error("expected an object"))
+        }
+    }
+
+    /// 对象中的字段，不存在或为 null 时返回 None
+    fn field(&self, key: &str) -> Option<Node<'a>> {
+        self.json
+            .get(key)
+            .filter(|json| !json.is_null())
+            .map(|json| Node {
+                json,
+                path: self.child_path(key),
+            })
+    }
+
+    fn required(&self, key: &str) -> Result<Node<'a>, String> {
+        self.field(key)
+            .ok_or_else(|| format!("{}: missing field", self.child_path(key)))
+    }
+
+    /// 数组字段的元素，不存在时为空
+    fn list(&self, key: &str) -> Result<Vec<Node<'a>>, String> {
+        let Some(node) = self.field(key) else {
+            return Ok(Vec::new());
+        };
+        let Some(items) = node.json.as_array() else {
+            return Err(node.error("expected an array"));
+        };
+        Ok(items
+            .iter()
+            .enumerate()
+            .map(|(i, json)| Node {
+                json,
+                path: format!("{}[{i}]", node.path),
+            })
+            .collect())
+    }
+
+    fn strings(&self, key: &str) -> Result<Vec<String>, String> {
+        self.list(key)?
+            .iter()
+            .map(|item| item.as_str().map(str::to_string))
+            .collect()
+    }
+
+    fn string_or(&self, key: &str, default: &str) -> Result<String, String> {
+        Ok(self
+            .field(key)
+            .map(|n| n.as_str())
+            .transpose()?
+            .unwrap_or(default)
+            .to_string())
+    }
+
+    fn bool_or(&self, key: &str, default: bool) -> Result<bool, String> {
+        Ok(self
+            .field(key)
+            .map(|n| n.as_bool())
+            .transpose()?
+            .unwrap_or(default))
+    }
+
+    fn u64_or(&self, key: &str, default: u64) -> Result<u64, String> {
+        Ok(self
+            .field(key)
+            .map(|n| n.as_u64())
+            .transpose()?
+            .unwrap_or(default))
+    }
+
+    fn i64_or(&self, key: &str, default: i64) -> Result<i64, String> {
+        Ok(self
+            .field(key)
+            .map(|n| n.as_i64())
+            .transpose()?
+            .unwrap_or(default))
+    }
+
+    fn f64_or(&self, key: &str, default: f64) -> Result<f64, String> {
+        Ok(self
+            .field(key)
+            .map(|n| n.as_f64())
+            .transpose()?
+            .unwrap_or(default))
+    }
+
+    fn as_str(&self) -> Result<&'a str, String> {
+        self.json
+            .as_str()
+            .ok_or_else(|| self.error("expected a string"))
+    }
+
+    fn as_bool(&self) -> Result<bool, String> {
+        self.json
+            .as_bool()
+            .ok_or_else(|| self.error("expected true or false"))
+    }
+
+    fn as_u64(&self) -> Result<u64, String> {
+        match self.json {
+            Value::Number(number) => number.as_u64().ok_or_else(|| {
+                self.error(&format!("expected an unsigned integer, found {number}"))
+            }),
+            _ => Err(self.error("expected an unsigned integer")),
+        }
+    }
+
+    fn as_i64(&self) -> Result<i64, String> {
+        match self.json {
+            Value::Number(number) => number
+                .as_i64()
+                .ok_or_else(|| self.error(&format!("expected an integer, found {number}"))),
+            _ => Err(self.error("expected an integer")),
+        }
+    }
+
+    fn as_f64(&self) -> Result<f64, String> {
+        match self.json {
+            Value::Number(number) => number
+                .as_f64()
+                .ok_or_else(|| self.error(&format!("expected a number, found {number}"))),
+            Value::String(text) if text == "NaN" => Ok(f64::NAN),
+            Value::String(text) if text == "Infinity" => Ok(f64::INFINITY),
+            Value::String(text) if text == "-Infinity" => Ok(f64::NEG_INFINITY),
+            _ => Err(self.error("expected a number")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::editable_dbc::{AttributeTarget, load_file};
+
+    const RICH_DBC: &str = r#"VERSION "1.2"
+
+BU_: Engine Dash Gateway
+
+VAL_TABLE_ OnOff 1 "On" 0 "Off" ;
+
+BO_ 256 EngineData: 8 Engine
+ SG_ Mode M : 0|2@1+ (1,0) [0|3] "" Dash
+ SG_ Rpm m0 : 8|16@1+ (0.25,0) [0|16383.75] "rpm" Dash,Gateway
+ SG_ Temp m1 : 8|8@1- (0.5,-40) [-104|23.5] "degC" Dash
+ SG_ Sub m2M : 16|4@1+ (1,0) [0|15] "" Dash
+ SG_ Tiny : 63|8@0- (1e-07,0.001) [-1e+20|1e+20] "" Vector__XXX
+
+BO_ 2566914049 Extended: 12 Gateway
+ SG_ Counter : 7|4@0+ (1,0) [0|15] "" Engine
+
+BO_ 512 Plain: 0 Vector__XXX
+
+EV_ EnvSwitch: 0 [0|1] "" 0 3 DUMMY_NODE_VECTOR1 Engine;
+
+CM_ "Network comment";
+CM_ BU_ Engine "Engine ECU";
+CM_ BO_ 256 "Engine \"data\"
+second line";
+CM_ SG_ 256 Rpm "Engine speed";
+
+BA_DEF_ BO_  "GenMsgCycleTime" INT 0 10000;
+BA_DEF_ SG_  "GenSigStartValue" FLOAT 0 100;
+BA_DEF_  "BusType" STRING ;
+BA_DEF_ BU_  "NodeLayer" HEX 0 255;
+BA_DEF_ EV_  "EvKind" ENUM  "A","B";
+BA_DEF_ BO_  "VFrameFormat" ENUM  "StandardCAN","ExtendedCAN","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","reserved","StandardCAN_FD","ExtendedCAN_FD";
+BA_DEF_ BO_  "CANFD_BRS" ENUM  "0","1";
+BA_DEF_DEF_  "GenMsgCycleTime" 100;
+BA_DEF_DEF_  "GenSigStartValue" 2;
+BA_DEF_DEF_  "BusType" "CAN";
+BA_DEF_DEF_  "VFrameFormat" "StandardCAN";
+BA_DEF_DEF_  "CANFD_BRS" "1";
+BA_ "BusType" "CAN FD";
+BA_ "NodeLayer" BU_ Engine 16;
+BA_ "EvKind" EV_ EnvSwitch 1;
+BA_ "GenMsgCycleTime" BO_ 256 20;
+BA_ "VFrameFormat" BO_ 2566914049 15;
+BA_ "CANFD_BRS" BO_ 2566914049 0;
+BA_ "GenSigStartValue" SG_ 256 Rpm 12.5;
+
+VAL_ 256 Mode 0 "Rpm" 1 "Temp" 2 "Sub" ;
+"#;
+
+    fn assert_round_trip(dbc: &EditableDbc) {
+        let json = dbc.to_json();
+        let restored = EditableDbc::from_json(&json).unwrap_or_else(|e| panic!("{e}\n{json}"));
+        assert_eq!(restored.messages, dbc.messages);
+        assert_eq!(restored.to_string(), dbc.to_string());
+        assert!(dbc.diff(&restored).is_empty());
+        assert!(!restored.can_undo());
+        assert!(!restored.is_modified());
+        assert_eq!(restored.to_json(), json);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dbc = EditableDbc::from_dbc(&can_dbc::Dbc::try_from(RICH_DBC).unwrap());
+        assert_round_trip(&dbc);
+
+        let json = dbc.to_json();
+        assert!(json.contains("\"id\": 256,"));
+        assert!(json.contains("\"id\": 419430401,"));
+        assert!(json.contains("\"extended\": true,"));
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value["messages"][0]["signals"][1]["receivers"],
+            serde_json::json!(["Dash", "Gateway"])
+        );
+        assert!(json.contains("\"factor\": 1e-7,"));
+        // 读取 DBC 时去掉转义，JSON 中按 JSON 的规则转义，换行写成 \n
+        assert!(json.contains(r#""comment": "Engine \"data\"\nsecond line","#));
+
+        let extended = dbc.get_message(0x9900_0001).unwrap();
+        assert!(extended.is_fd());
+        assert!(!extended.bit_rate_switch());
+
+        for sample in [
+            "motbus",
+            "motbus_ext",
+            "motbus_ext_2",
+            "can_data_ev1",
+            "empty",
+        ] {
+            let path = Path::new("dbc-sample").join(format!("{sample}.dbc"));
+            assert_round_trip(&load_file(&path).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_minimal_document() {
+        let json = r#"{
+            "nodes": ["ECU"],
+            "messages": [{
+                "id": 291,
+                "name": "Status",
+                "size": 64,
+                "fd": true,
+                "signals": [
+                    {"name": "Mode", "start_bit": 0, "size": 2, "multiplexor": true},
+                    {"name": "Value", "start_bit": 7, "size": 8, "byte_order": "big_endian",
+                     "signed": true, "factor": 0.5, "multiplexer_value": 1, "max": "Infinity",
+                     "attributes": [{"name": "GenSigStartValue", "value": 3}]}
+                ]
+            }],
+            "attribute_definitions": [{
+                "name": "GenSigStartValue", "object_type": "signal",
+                "value_type": {"type": "float", "min": 0, "max": 10}
+            }],
+            "extra": {"ignored": [1, 2, 3]}
+        }"#;
+        let dbc = EditableDbc::from_json(json).unwrap();
+        let message = dbc.get_message(0x123).unwrap();
+        assert_eq!(message.transmitter(), "Vector__XXX");
+        assert!(message.is_fd());
+        // 没有定义 CANFD_BRS 时自动添加 Vector 的标准定义，默认开启
+        assert!(message.bit_rate_switch());
+        assert!(
+            dbc.attribute_value(&AttributeTarget::Message(0x123), "VFrameFormat")
+                .is_some()
+        );
+        assert!(!dbc.can_undo());
+
+        let value = &message.signals()[1];
+        assert_eq!(
+            *value.multiplexer_indicator(),
+            MultiplexIndicator::MultiplexedSignal(1)
+        );
+        assert_eq!(*value.byte_order(), ByteOrder::BigEndian);
+        assert_eq!(*value.value_type(), ValueType::Signed);
+        assert_eq!(value.factor(), 0.5);
+        assert_eq!(value.max(), f64::INFINITY);
+        // FLOAT 属性的整数值按定义转换为 Float
+        assert_eq!(value.attributes()[0].1, AttributeValue::Float(3.0));
+        assert_eq!(
+            *message.signals()[0].multiplexer_indicator(),
+            MultiplexIndicator::Multiplexor
+        );
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let cases = [
+            ("", "line 1 column 0"),
+            ("{\"messages\": [}", "line 1 column 15"),
+            ("{\n  \"version\": \"a\nb\"\n}", "control character"),
+            ("[1, 2]", "expected an object"),
+            ("{\"format\": \"other\"}", "format"),
+            ("{\"schema_version\": 2}", "schema_version"),
+            (
+                "{\"messages\": [{\"name\": \"A\"}]}",
+                "messages[0].id: missing field",
+            ),
+            (
+                "{\"messages\": [{\"id\": 2147483648, \"name\": \"A\"}]}",
+                "messages[0].id: CAN ID must be less than",
+            ),
+            (
+                "{\"messages\": [{\"id\": 1, \"name\": \"A\", \"signals\": [{\"name\": \"S\", \"start_bit\": 0, \"size\": 8, \"factor\": \"x\"}]}]}",
+                "messages[0].signals[0].factor: expected a number",
+            ),
+            (
+                "{\"messages\": [{\"id\": 1, \"name\": \"A\", \"signals\": [{\"name\": \"S\", \"start_bit\": 0, \"size\": 8, \"byte_order\": \"intel\"}]}]}",
+                "messages[0].signals[0].byte_order: unknown value",
+            ),
+            (
+                "{\"messages\": [{\"id\": 1, \"name\": \"A\"}, {\"id\": 1, \"name\": \"B\"}]}",
+                "duplicate message id 0x1",
+            ),
+            (
+                "{\"messages\": [{\"id\": 1, \"name\": \"A\", \"fd\": 1}]}",
+                "messages[0].fd: expected true or false",
+            ),
+            (
+                "{\"attribute_definitions\": [{\"name\": \"X\", \"value_type\": {\"type\": \"int\", \"min\": 0, \"max\": 1}, \"default\": 5}]}",
+                "attribute_definitions[0]: Value 5 of attribute X is out of range",
+            ),
+        ];
+        for (json, expected) in cases {
+            let error = EditableDbc::from_json(json).unwrap_err();
+            assert!(error.contains(expected), "{json}: {error}");
+        }
+    }
+
+    #[test]
+    fn test_strings() {
+        let text = "a\"b\\c/\n\t\u{1}é😀";
+        let mut dbc = EditableDbc::new();
+        dbc.comment = text.to_string();
+        let json = dbc.to_json();
+        assert!(json.contains(r#""comment": "a\"b\\c/\n\t\u0001é😀","#));
+        assert_eq!(EditableDbc::from_json(&json).unwrap().comment, text);
+
+        // \u 转义和代理对
+        let dbc = EditableDbc::from_json(r#"{"comment": "\u00e9\ud83d\ude00\/"}"#).unwrap();
+        assert_eq!(dbc.comment, "é😀/");
+    }
+}
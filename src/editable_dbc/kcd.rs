@@ -0,0 +1,933 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use can_dbc::{ByteOrder, MultiplexIndicator, SignalExtendedValueType, ValueType};
+
+use super::attributes::{BAUDRATE_ATTRIBUTE, BUS_NAME_ATTRIBUTE, CYCLE_TIME_ATTRIBUTE};
+use super::can_fd::{BIT_RATE_SWITCH_ATTRIBUTE, FRAME_FORMAT_ATTRIBUTE};
+use super::codec::swap_bit_order;
+use super::writer::{EXTENDED_ID_FLAG, VECTOR_XXX};
+use super::xml::{self, Element};
+use super::{
+    AttributeDefinition, AttributeTarget, AttributeValue, EditableDbc, EditableMessage,
+    EditableSignal, FrameFormat,
+};
+
+// KCD（Kayak 的 XML 格式）的读写，格式定义见 https://github.com/julietkilo/kcd
+// KCD 能描述的内容比 DBC 少，转换中丢弃或无法表示的内容作为警告返回，由调用者显示
+//
+// 对应关系：
+// - Document 的 version 对应 VERSION，Node 对应 BU_，NodeRef 通过 Node 的 id 引用节点
+// - 只转换第一个 Bus，Bus 的 name 和 baudrate 对应网络属性 DBName 和 Baudrate
+// - Message 的 interval 对应 GenMsgCycleTime 属性，Notes 对应注释，Producer 对应发送节点
+// - Multiplex 对应复用器 (M)，MuxGroup 中的 Signal 对应复用信号 (m<count>)
+// - Value 的 type 对应符号和 SIG_VALTYPE_ 的浮点类型（single / double），slope (factor)、intercept (offset)、
+//   min、max 和单位直接对应，LabelSet 中的 Label 对应值描述
+// - Motorola 信号的 offset 是最高位在按字节从高位到低位编号时的位置，与 DBC 的起始位在字节内是反的
+
+const KCD_NAMESPACE: &str = "http://kayak.2codeornot2code.org/1.0";
+/// 没有 DBName 属性时导出的总线名
+const DEFAULT_BUS_NAME: &str = "CAN";
+
+/// 放下信号需要的负载字节数
+fn required_size(signal: &EditableSignal) -> u64 {
+    let size = signal.signal_size.max(1);
+    let last_bit = match signal.byte_order {
+        ByteOrder::LittleEndian => signal.start_bit + size - 1,
+        ByteOrder::BigEndian => swap_bit_order(signal.start_bit) + size - 1,
+    };
+    last_bit / 8 + 1
+}
+
+impl EditableDbc {
+    /// 读取 KCD，返回结果和转换中丢弃的内容（不支持的元素和属性）
+    pub fn from_kcd(text: &str) -> Result<(Self, Vec<String>), String> {
+        let root = xml::parse(text)?;
+        if root.name != "NetworkDefinition" {
+            return Err(format!(
+                "Not a KCD file: the root element is <{}>, expected <NetworkDefinition>",
+                root.name
+            ));
+        }
+
+        let mut reader = KcdReader::default();
+        let mut dbc = EditableDbc::new();
+        // NodeRef 引用的节点必须先读取
+        let mut buses = Vec::new();
+        for element in &root.children {
+            match element.name.as_str() {
+                "Document" => dbc.version = reader.read_document(element),
+                "Node" => dbc.nodes.push(reader.read_node(element)?),
+                "Bus" => buses.push(element),
+                _ => reader.unsupported(element, "NetworkDefinition"),
+            }
+        }
+
+        let Some((bus, other_buses)) = buses.split_first() else {
+            dbc.clear_history();
+            return Ok((dbc, reader.warnings));
+        };
+        for other in other_buses {
+            reader.warnings.push(format!(
+                "Bus {}: only the first bus is imported, {} messages skipped",
+                other.attribute("name").unwrap_or(""),
+                other.children_named("Message").count()
+            ));
+        }
+        let bus_context = format!("Bus {}", bus.attribute("name").unwrap_or(""));
+        let mut cycle_times = Vec::new();
+        for element in &bus.children {
+            if element.name != "Message" {
+                reader.unsupported(element, &bus_context);
+                continue;
+            }
+            let (message, interval) = reader.read_message(element)?;
+            if dbc
+                .messages
+                .iter()
+                .any(|m| m.message_id == message.message_id)
+            {
+                return Err(format!(
+                    "Message {}: duplicate id 0x{:X}",
+                    message.message_name,
+                    message.message_id & !EXTENDED_ID_FLAG
+                ));
+            }
+            if let Some(interval) = interval {
+                cycle_times.push((message.message_id, interval));
+            }
+            dbc.messages.push(message);
+        }
+
+        // 映射到属性的内容用编辑 API 设置，属性未定义时添加 Vector 的标准定义
+        let mut mapped = Vec::new();
+        if let Some(name) = bus.attribute("name") {
+            mapped.push((
+                AttributeTarget::Network,
+                AttributeDefinition::bus_name(),
+                AttributeValue::String(name.to_string()),
+            ));
+        }
+        if let Some(baudrate) = number::<i64>(bus, "baudrate", &bus_context)? {
+            mapped.push((
+                AttributeTarget::Network,
+                AttributeDefinition::baudrate(),
+                AttributeValue::Int(baudrate),
+            ));
+        }
+        for (message_id, interval) in cycle_times {
+            mapped.push((
+                AttributeTarget::Message(message_id),
+                AttributeDefinition::cycle_time(),
+                AttributeValue::Int(interval),
+            ));
+        }
+        for (target, definition, value) in mapped {
+            if let Err(e) = dbc.set_mapped_attribute(&target, &definition, value) {
+                reader.warnings.push(e);
+            }
+        }
+        dbc.clear_history();
+        Ok((dbc, reader.warnings))
+    }
+
+    /// 导出为 KCD，返回内容和 KCD 无法表示而丢弃的内容
+    pub fn to_kcd(&self) -> (String, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut root = Element::new("NetworkDefinition").with("xmlns", KCD_NAMESPACE);
+        let mut document = Element::new("Document");
+        if !self.version.is_empty() {
+            document = document.with("version", &self.version);
+        }
+        root.push(document);
+        for (i, node) in self.nodes.iter().enumerate() {
+            root.push(Element::new("Node").with("id", i + 1).with("name", node));
+        }
+
+        let bus_name =
+            match self.effective_attribute_value(&AttributeTarget::Network, BUS_NAME_ATTRIBUTE) {
+                Some(AttributeValue::String(name)) if !name.is_empty() => name.clone(),
+                _ => DEFAULT_BUS_NAME.to_string(),
+            };
+        let mut bus = Element::new("Bus").with("name", bus_name);
+        if let Some(AttributeValue::Int(baudrate)) =
+            self.effective_attribute_value(&AttributeTarget::Network, BAUDRATE_ATTRIBUTE)
+        {
+            bus = bus.with("baudrate", baudrate);
+        }
+        for message in &self.messages {
+            bus.push(self.message_to_kcd(message, &mut warnings));
+        }
+        root.push(bus);
+
+        if !self.value_tables.is_empty() {
+            warnings.push(format!(
+                "{} value tables (VAL_TABLE_) are not supported",
+                self.value_tables.len()
+            ));
+        }
+        if !self.environment_variables.is_empty() {
+            warnings.push(format!(
+                "{} environment variables (EV_) are not supported",
+                self.environment_variables.len()
+            ));
+        }
+        for name in self.unsupported_kcd_attributes() {
+            warnings.push(format!("Attribute {name} is not supported"));
+        }
+        (xml::write(&root), warnings)
+    }
+
+    fn message_to_kcd(&self, message: &EditableMessage, warnings: &mut Vec<String>) -> Element {
+        let context = format!("Message {}", message.message_name);
+        let mut element = Element::new("Message")
+            .with(
+                "id",
+                format!("0x{:03X}", message.message_id & !EXTENDED_ID_FLAG),
+            )
+            .with("name", &message.message_name)
+            .with("length", message.message_size);
+        if let Some(interval) = self.message_cycle_time(message.message_id) {
+            element = element.with("interval", interval);
+        }
+        if message.frame_format == FrameFormat::Extended {
+            element = element.with("format", "extended");
+        }
+        if message.fd {
+            warnings.push(format!(
+                "{context}: CAN FD is not supported, exported as a classic CAN message"
+            ));
+        }
+
+        if !message.comment.is_empty() {
+            element.push(Element::new("Notes").with_text(&message.comment));
+        }
+        if message.transmitter != VECTOR_XXX {
+            match self.node_ref(&message.transmitter) {
+                Some(node_ref) => {
+                    let mut producer = Element::new("Producer");
+                    producer.push(node_ref);
+                    element.push(producer);
+                }
+                None => warnings.push(format!(
+                    "{context}: transmitter {} is not a node",
+                    message.transmitter
+                )),
+            }
+        }
+
+        // KCD 每个 Message 只有一个复用器，没有扩展复用
+        let multiplexor = message
+            .signals
+            .iter()
+            .position(|s| s.multiplexer_indicator == MultiplexIndicator::Multiplexor);
+        let mut groups: BTreeMap<u64, Vec<Element>> = BTreeMap::new();
+        let mut plain = Vec::new();
+        for (i, signal) in message.signals.iter().enumerate() {
+            if Some(i) == multiplexor {
+                continue;
+            }
+            let value = match signal.multiplexer_indicator {
+                MultiplexIndicator::Plain => None,
+                MultiplexIndicator::Multiplexor => {
+                    warnings.push(format!(
+                        "{context}: only one multiplexor is supported, {} exported as a plain signal",
+                        signal.name
+                    ));
+                    None
+                }
+                MultiplexIndicator::MultiplexorAndMultiplexedSignal(value) => {
+                    warnings.push(format!(
+                        "{context}: extended multiplexing is not supported, {} exported as a multiplexed signal",
+                        signal.name
+                    ));
+                    Some(value)
+                }
+                MultiplexIndicator::MultiplexedSignal(value) => Some(value),
+            };
+            let kcd_signal = self.signal_to_kcd(signal, "Signal", &context, warnings);
+            match value {
+                Some(value) if multiplexor.is_some() => {
+                    groups.entry(value).or_default().push(kcd_signal)
+                }
+                Some(_) => {
+                    warnings.push(format!(
+                        "{context}: {} has no multiplexor, exported as a plain signal",
+                        signal.name
+                    ));
+                    plain.push(kcd_signal);
+                }
+                None => plain.push(kcd_signal),
+            }
+        }
+        if let Some(index) = multiplexor {
+            let mut multiplex =
+                self.signal_to_kcd(&message.signals[index], "Multiplex", &context, warnings);
+            for (count, signals) in groups {
+                let mut group = Element::new("MuxGroup").with("count", count);
+                group.children = signals;
+                multiplex.push(group);
+            }
+            element.push(multiplex);
+        }
+        element.children.extend(plain);
+        element
+    }
+
+    fn signal_to_kcd(
+        &self,
+        signal: &EditableSignal,
+        tag: &str,
+        context: &str,
+        warnings: &mut Vec<String>,
+    ) -> Element {
+        let offset = match signal.byte_order {
+            ByteOrder::LittleEndian => signal.start_bit,
+            ByteOrder::BigEndian => swap_bit_order(signal.start_bit),
+        };
+        let mut element = Element::new(tag)
+            .with("name", &signal.name)
+            .with("offset", offset)
+            .with("length", signal.signal_size);
+        if signal.byte_order == ByteOrder::BigEndian {
+            element = element.with("endianess", "big");
+        }
+
+        if !signal.comment.is_empty() {
+            element.push(Element::new("Notes").with_text(&signal.comment));
+        }
+        let mut consumer = Element::new("Consumer");
+        for receiver in signal.receivers.iter().filter(|r| *r != VECTOR_XXX) {
+            match self.node_ref(receiver) {
+                Some(node_ref) => consumer.push(node_ref),
+                None => warnings.push(format!(
+                    "{context}: receiver {receiver} of {} is not a node",
+                    signal.name
+                )),
+            }
+        }
+        if !consumer.children.is_empty() {
+            element.push(consumer);
+        }
+
+        let mut value = Element::new("Value");
+        match (signal.extended_value_type, signal.value_type) {
+            (Some(SignalExtendedValueType::IEEEfloat32Bit), _) => {
+                value = value.with("type", "single");
+            }
+            (Some(SignalExtendedValueType::IEEEdouble64bit), _) => {
+                value = value.with("type", "double");
+            }
+            (_, ValueType::Signed) => value = value.with("type", "signed"),
+            (_, ValueType::Unsigned) => {}
+        }
+        if signal.factor != 1.0 {
+            value = value.with("slope", signal.factor);
+        }
+        if signal.offset != 0.0 {
+            value = value.with("intercept", signal.offset);
+        }
+        if !signal.unit.is_empty() {
+            value = value.with("unit", &signal.unit);
+        }
+        if signal.min != 0.0 || signal.max != 0.0 {
+            value = value.with("min", signal.min).with("max", signal.max);
+        }
+        if !value.attributes.is_empty() {
+            element.push(value);
+        }
+
+        if !signal.value_descriptions.is_empty() {
+            let mut labels = Element::new("LabelSet");
+            for (raw, description) in &signal.value_descriptions {
+                labels.push(
+                    Element::new("Label")
+                        .with("name", description)
+                        .with("value", raw),
+                );
+            }
+            element.push(labels);
+        }
+        element
+    }
+
+    fn node_ref(&self, name: &str) -> Option<Element> {
+        let index = self.nodes.iter().position(|n| n == name)?;
+        Some(Element::new("NodeRef").with("id", index + 1))
+    }
+
+    // 有显式值但 KCD 无法表示的属性名，映射到 KCD 的属性和 CAN FD 属性除外
+    fn unsupported_kcd_attributes(&self) -> Vec<&str> {
+        let mut all = self.network_attributes.iter().collect::<Vec<_>>();
+        for (_, attributes) in self
+            .node_attributes
+            .iter()
+            .chain(&self.environment_variable_attributes)
+        {
+            all.extend(attributes);
+        }
+        for message in &self.messages {
+            all.extend(&message.attributes);
+            for signal in &message.signals {
+                all.extend(&signal.attributes);
+            }
+        }
+
+        let mut names: Vec<&str> = Vec::new();
+        for (name, _) in all {
+            if ![
+                CYCLE_TIME_ATTRIBUTE,
+                BUS_NAME_ATTRIBUTE,
+                BAUDRATE_ATTRIBUTE,
+                FRAME_FORMAT_ATTRIBUTE,
+                BIT_RATE_SWITCH_ATTRIBUTE,
+            ]
+            .contains(&name.as_str())
+                && !names.contains(&name.as_str())
+            {
+                names.push(name);
+            }
+        }
+        names
+    }
+}
+
+/// 读取过程中的状态：节点 id 到名称的映射和警告
+#[derive(Default)]
+struct KcdReader {
+    nodes: Vec<(String, String)>,
+    warnings: Vec<String>,
+}
+
+impl KcdReader {
+    fn unsupported(&mut self, element: &Element, context: &str) {
+        self.warnings
+            .push(format!("{context}: <{}> is not supported", element.name));
+    }
+
+    // 返回 version，其他描述信息没有对应的内容
+    fn read_document(&mut self, element: &Element) -> String {
+        let mut dropped: Vec<&str> = element
+            .attributes
+            .iter()
+            .filter(|(name, value)| name != "version" && !value.is_empty())
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if !element.text.is_empty() {
+            dropped.push("description");
+        }
+        if !dropped.is_empty() {
+            self.warnings
+                .push(format!("Document: {} not imported", dropped.join(", ")));
+        }
+        element.attribute("version").unwrap_or("").to_string()
+    }
+
+    fn read_node(&mut self, element: &Element) -> Result<String, String> {
+        let id = required(element, "id", "Node")?;
+        let name = required(element, "name", &format!("Node {id}"))?;
+        for child in &element.children {
+            self.unsupported(child, &format!("Node {name}"));
+        }
+        self.nodes.push((id.to_string(), name.to_string()));
+        Ok(name.to_string())
+    }
+
+    // Producer / Consumer 中 NodeRef 引用的节点名
+    fn node_refs(&mut self, element: &Element, context: &str) -> Vec<String> {
+        let mut names = Vec::new();
+        for child in &element.children {
+            if child.name != "NodeRef" {
+                self.unsupported(child, context);
+                continue;
+            }
+            let id = child.attribute("id").unwrap_or("");
+            match self.nodes.iter().find(|(node_id, _)| node_id == id) {
+                Some((_, name)) => names.push(name.clone()),
+                None => self
+                    .warnings
+                    .push(format!("{context}: unknown node id \"{id}\"")),
+            }
+        }
+        names
+    }
+
+    // 返回 Message 和 interval
+    fn read_message(
+        &mut self,
+        element: &Element,
+    ) -> Result<(EditableMessage, Option<i64>), String> {
+        let name = required(element, "name", "Message")?;
+        let context = format!("Message {name}");
+        let id_text = required(element, "id", &context)?;
+        let id = parse_id(id_text)
+            .filter(|id| id & EXTENDED_ID_FLAG == 0)
+            .ok_or_else(|| format!("{context}: invalid id \"{id_text}\""))?;
+        let frame_format = match element.attribute("format") {
+            None | Some("standard") => FrameFormat::Standard,
+            Some("extended") => FrameFormat::Extended,
+            Some(other) => return Err(format!("{context}: invalid format \"{other}\"")),
+        };
+        for flag in ["triggered", "remote"] {
+            if boolean(element, flag, &context)? {
+                self.warnings.push(format!(
+                    "{context}: {flag} messages are not supported, imported as a normal message"
+                ));
+            }
+        }
+        if element.attribute("count").is_some() {
+            self.warnings
+                .push(format!("{context}: count is not supported"));
+        }
+        let interval = number::<i64>(element, "interval", &context)?;
+
+        let mut message = EditableMessage {
+            message_id: match frame_format {
+                FrameFormat::Standard => id,
+                FrameFormat::Extended => id | EXTENDED_ID_FLAG,
+            },
+            frame_format,
+            message_name: name.to_string(),
+            ..EditableMessage::new()
+        };
+        let mut has_multiplexor = false;
+        for child in &element.children {
+            match child.name.as_str() {
+                "Notes" => message.comment = child.text.clone(),
+                "Producer" => {
+                    let producers = self.node_refs(child, &context);
+                    if producers.len() > 1 {
+                        self.warnings.push(format!(
+                            "{context}: only the first of {} producers is imported",
+                            producers.len()
+                        ));
+                    }
+                    if let Some(producer) = producers.into_iter().next() {
+                        message.transmitter = producer;
+                    }
+                }
+                "Signal" => {
+                    let signal = self.read_signal(child, MultiplexIndicator::Plain, name)?;
+                    message.signals.push(signal);
+                }
+                "Multiplex" if has_multiplexor => {
+                    let signal = self.read_signal(child, MultiplexIndicator::Plain, name)?;
+                    self.warnings.push(format!(
+                        "{context}: only one Multiplex is supported, {} imported as a plain signal without its groups",
+                        signal.name
+                    ));
+                    message.signals.push(signal);
+                }
+                "Multiplex" => {
+                    has_multiplexor = true;
+                    let signal = self.read_signal(child, MultiplexIndicator::Multiplexor, name)?;
+                    let group_context = format!("Multiplex {name}.{}", signal.name);
+                    message.signals.push(signal);
+                    for group in child.children_named("MuxGroup") {
+                        let count = number::<u64>(group, "count", &group_context)?
+                            .ok_or_else(|| format!("{group_context}: MuxGroup without count"))?;
+                        for grouped in &group.children {
+                            if grouped.name != "Signal" {
+                                self.unsupported(
+                                    grouped,
+                                    &format!("{group_context} group {count}"),
+                                );
+                                continue;
+                            }
+                            let indicator = MultiplexIndicator::MultiplexedSignal(count);
+                            message
+                                .signals
+                                .push(self.read_signal(grouped, indicator, name)?);
+                        }
+                    }
+                }
+                _ => self.unsupported(child, &context),
+            }
+        }
+        for (i, signal) in message.signals.iter().enumerate() {
+            if message.signals[..i].iter().any(|s| s.name == signal.name) {
+                return Err(format!("{context}: duplicate signal {}", signal.name));
+            }
+        }
+
+        message.message_size = match element.attribute("length") {
+            None | Some("auto") => message.signals.iter().map(required_size).max().unwrap_or(0),
+            Some(_) => number::<u64>(element, "length", &context)?.unwrap_or(0),
+        };
+        Ok((message, interval))
+    }
+
+    // Signal 和 Multiplex 元素，Multiplex 的 MuxGroup 由调用者处理
+    fn read_signal(
+        &mut self,
+        element: &Element,
+        multiplexer_indicator: MultiplexIndicator,
+        message_name: &str,
+    ) -> Result<EditableSignal, String> {
+        let name = required(element, "name", &format!("Message {message_name}"))?;
+        let context = format!("Signal {message_name}.{name}");
+        let offset = number::<u64>(element, "offset", &context)?
+            .ok_or_else(|| format!("{context}: missing attribute offset"))?;
+        let byte_order = match element.attribute("endianess") {
+            None | Some("little") => ByteOrder::LittleEndian,
+            Some("big") => ByteOrder::BigEndian,
+            Some(other) => return Err(format!("{context}: invalid endianess \"{other}\"")),
+        };
+        let mut signal = EditableSignal {
+            name: name.to_string(),
+            multiplexer_indicator,
+            start_bit: match byte_order {
+                ByteOrder::LittleEndian => offset,
+                ByteOrder::BigEndian => swap_bit_order(offset),
+            },
+            signal_size: number(element, "length", &context)?.unwrap_or(1),
+            byte_order,
+            ..EditableSignal::new()
+        };
+        for child in &element.children {
+            match child.name.as_str() {
+                "Notes" => signal.comment = child.text.clone(),
+                "Consumer" => signal.receivers = self.node_refs(child, &context),
+                "Value" => self.read_value(child, &mut signal, &context)?,
+                "LabelSet" => self.read_labels(child, &mut signal, &context)?,
+                "MuxGroup" if element.name == "Multiplex" => {}
+                _ => self.unsupported(child, &context),
+            }
+        }
+        Ok(signal)
+    }
+
+    fn read_value(
+        &mut self,
+        element: &Element,
+        signal: &mut EditableSignal,
+        context: &str,
+    ) -> Result<(), String> {
+        match element.attribute("type") {
+            None | Some("unsigned") => {}
+            Some("signed") => signal.value_type = ValueType::Signed,
+            // 浮点数总是有符号的，与 CANdb++ 一致
+            Some("single") => {
+                signal.value_type = ValueType::Signed;
+                signal.extended_value_type = Some(SignalExtendedValueType::IEEEfloat32Bit);
+            }
+            Some("double") => {
+                signal.value_type = ValueType::Signed;
+                signal.extended_value_type = Some(SignalExtendedValueType::IEEEdouble64bit);
+            }
+            Some(other) => return Err(format!("{context}: invalid value type \"{other}\"")),
+        }
+        signal.factor = number(element, "slope", context)?.unwrap_or(1.0);
+        signal.offset = number(element, "intercept", context)?.unwrap_or(0.0);
+        signal.min = number(element, "min", context)?.unwrap_or(0.0);
+        signal.max = number(element, "max", context)?.unwrap_or(0.0);
+        signal.unit = element.attribute("unit").unwrap_or("").to_string();
+        Ok(())
+    }
+
+    fn read_labels(
+        &mut self,
+        element: &Element,
+        signal: &mut EditableSignal,
+        context: &str,
+    ) -> Result<(), String> {
+        for child in &element.children {
+            match child.name.as_str() {
+                "Label" => {
+                    let value = number::<i64>(child, "value", context)?
+                        .ok_or_else(|| format!("{context}: Label without value"))?;
+                    let description = child.attribute("name").unwrap_or("");
+                    signal
+                        .value_descriptions
+                        .push((value, description.to_string()));
+                }
+                "LabelGroup" => self.warnings.push(format!(
+                    "{context}: value range {}..{} \"{}\" (LabelGroup) is not supported",
+                    child.attribute("from").unwrap_or(""),
+                    child.attribute("to").unwrap_or(""),
+                    child.attribute("name").unwrap_or("")
+                )),
+                _ => self.unsupported(child, context),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn required<'a>(element: &'a Element, name: &str, context: &str) -> Result<&'a str, String> {
+    element
+        .attribute(name)
+        .ok_or_else(|| format!("{context}: <{}> without {name}", element.name))
+}
+
+fn number<T: FromStr>(element: &Element, name: &str, context: &str) -> Result<Option<T>, String> {
+    element
+        .attribute(name)
+        .map(|text| {
+            text.trim()
+                .parse()
+                .map_err(|_| format!("{context}: invalid {name} \"{text}\""))
+        })
+        .transpose()
+}
+
+fn boolean(element: &Element, name: &str, context: &str) -> Result<bool, String> {
+    match element.attribute(name) {
+        None | Some("false" | "0") => Ok(false),
+        Some("true" | "1") => Ok(true),
+        Some(other) => Err(format!("{context}: invalid {name} \"{other}\"")),
+    }
+}
+
+// KCD 的 id 通常写成 0x 开头的十六进制
+fn parse_id(text: &str) -> Option<u32> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::editable_dbc::{FileFormat, load_file};
+
+    const SAMPLE_KCD: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<NetworkDefinition xmlns="http://kayak.2codeornot2code.org/1.0"
+    xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <Document name="Sample" version="1.1" author="someone">Sample network</Document>
+  <Node id="1" name="Engine"/>
+  <Node id="2" name="Dash"/>
+  <Bus name="Powertrain" baudrate="250000">
+    <Message id="0x123" name="EngineData" length="8" interval="100">
+      <Notes>Engine status</Notes>
+      <Producer>
+        <NodeRef id="1"/>
+      </Producer>
+      <Multiplex name="Mode" offset="0" length="2">
+        <MuxGroup count="0">
+          <Signal name="Rpm" offset="8" length="16">
+            <Consumer><NodeRef id="2"/></Consumer>
+            <Value slope="0.25" unit="rpm" min="0" max="16383.75"/>
+          </Signal>
+        </MuxGroup>
+        <MuxGroup count="1">
+          <Signal name="Temp" offset="8" length="8">
+            <Value type="signed" slope="0.5" intercept="-40"/>
+          </Signal>
+        </MuxGroup>
+      </Multiplex>
+      <Signal name="Gear" offset="7" length="4" endianess="big">
+        <Notes>Current gear</Notes>
+        <LabelSet>
+          <Label name="Neutral" value="0"/>
+          <Label name="First" value="1"/>
+          <LabelGroup name="Other" from="2" to="15"/>
+        </LabelSet>
+      </Signal>
+      <Signal name="Flag" offset="63"/>
+    </Message>
+    <Message id="0x18FEF100" name="Auto" format="extended" triggered="true">
+      <Signal name="Ratio" offset="0" length="32">
+        <Value type="single"/>
+      </Signal>
+      <Signal name="Tail" offset="23" length="16" endianess="big"/>
+    </Message>
+  </Bus>
+  <Bus name="Body">
+    <Message id="0x1" name="Ignored"/>
+  </Bus>
+</NetworkDefinition>
+"#;
+
+    fn has_warning(warnings: &[String], text: &str) -> bool {
+        warnings.iter().any(|w| w.contains(text))
+    }
+
+    #[test]
+    fn test_read_kcd() {
+        let (dbc, warnings) = EditableDbc::from_kcd(SAMPLE_KCD).unwrap();
+        assert_eq!(dbc.version(), "1.1");
+        assert_eq!(dbc.nodes(), &vec!["Engine".to_string(), "Dash".to_string()]);
+        assert!(!dbc.can_undo());
+
+        let message = dbc.get_message(0x123).unwrap();
+        assert_eq!(message.message_name(), "EngineData");
+        assert_eq!(message.message_size(), 8);
+        assert_eq!(message.transmitter(), "Engine");
+        assert_eq!(message.comment(), "Engine status");
+        assert_eq!(dbc.message_cycle_time(0x123), Some(100));
+
+        let names: Vec<&str> = message.signals().iter().map(|s| s.name()).collect();
+        assert_eq!(names, ["Mode", "Rpm", "Temp", "Gear", "Flag"]);
+        let signals = message.signals();
+        assert_eq!(
+            *signals[0].multiplexer_indicator(),
+            MultiplexIndicator::Multiplexor
+        );
+        assert_eq!(
+            *signals[2].multiplexer_indicator(),
+            MultiplexIndicator::MultiplexedSignal(1)
+        );
+        assert_eq!(signals[1].factor(), 0.25);
+        assert_eq!(signals[1].max(), 16383.75);
+        assert_eq!(signals[1].unit(), "rpm");
+        assert_eq!(signals[1].receivers(), &vec!["Dash".to_string()]);
+        assert_eq!(*signals[2].value_type(), ValueType::Signed);
+        assert_eq!(signals[2].offset(), -40.0);
+        // Motorola：KCD offset 7 是第一个字节的最低位，对应 DBC 起始位 0
+        assert_eq!(signals[3].start_bit(), 0);
+        assert_eq!(*signals[3].byte_order(), ByteOrder::BigEndian);
+        assert_eq!(signals[3].comment(), "Current gear");
+        assert_eq!(signals[3].value_descriptions().len(), 2);
+        assert_eq!(signals[4].signal_size(), 1);
+
+        // length 为 auto 时按信号计算
+        let extended = dbc.get_message(0x98FE_F100).unwrap();
+        assert_eq!(extended.frame_format(), FrameFormat::Extended);
+        assert_eq!(extended.message_size(), 5);
+        assert_eq!(extended.transmitter(), VECTOR_XXX);
+        assert_eq!(
+            extended.signals()[0].extended_value_type(),
+            Some(SignalExtendedValueType::IEEEfloat32Bit)
+        );
+
+        assert_eq!(dbc.message_count(), 2);
+        assert_eq!(
+            dbc.attribute_value(&AttributeTarget::Network, BUS_NAME_ATTRIBUTE),
+            Some(&AttributeValue::String("Powertrain".to_string()))
+        );
+        assert_eq!(
+            dbc.attribute_value(&AttributeTarget::Network, BAUDRATE_ATTRIBUTE),
+            Some(&AttributeValue::Int(250_000))
+        );
+
+        for expected in [
+            "Document: name, author, description not imported",
+            "LabelGroup",
+            "Message Auto: triggered",
+            "Bus Body: only the first bus is imported, 1 messages skipped",
+        ] {
+            assert!(has_warning(&warnings, expected), "{expected}: {warnings:?}");
+        }
+        assert_eq!(warnings.len(), 4, "{warnings:?}");
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let cases = [
+            ("<Other/>", "Not a KCD file"),
+            (
+                "<NetworkDefinition><Bus><Message name=\"A\"/></Bus></NetworkDefinition>",
+                "Message A: <Message> without id",
+            ),
+            (
+                "<NetworkDefinition><Bus><Message id=\"0xZZ\" name=\"A\"/></Bus></NetworkDefinition>",
+                "invalid id",
+            ),
+            (
+                "<NetworkDefinition><Bus><Message id=\"1\" name=\"A\"><Signal name=\"S\"/></Message></Bus></NetworkDefinition>",
+                "Signal A.S: missing attribute offset",
+            ),
+            (
+                "<NetworkDefinition><Bus><Message id=\"1\" name=\"A\"/><Message id=\"1\" name=\"B\"/></Bus></NetworkDefinition>",
+                "duplicate id",
+            ),
+        ];
+        for (kcd, expected) in cases {
+            let error = EditableDbc::from_kcd(kcd).unwrap_err();
+            assert!(error.contains(expected), "{kcd}: {error}");
+        }
+    }
+
+    #[test]
+    fn test_write_kcd() {
+        let (dbc, _) = EditableDbc::from_kcd(SAMPLE_KCD).unwrap();
+        let (kcd, warnings) = dbc.to_kcd();
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert!(kcd.contains("<Bus name=\"Powertrain\" baudrate=\"250000\">"));
+        assert!(
+            kcd.contains(
+                "<Message id=\"0x123\" name=\"EngineData\" length=\"8\" interval=\"100\">"
+            )
+        );
+        assert!(kcd.contains("<Signal name=\"Gear\" offset=\"7\" length=\"4\" endianess=\"big\">"));
+        assert!(kcd.contains("<Value type=\"signed\" slope=\"0.5\" intercept=\"-40\"/>"));
+
+        // 写出再读回得到相同的内容
+        let (restored, warnings) = EditableDbc::from_kcd(&kcd).unwrap();
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(restored.messages, dbc.messages);
+        assert_eq!(restored.to_kcd().0, kcd);
+    }
+
+    #[test]
+    fn test_dbc_round_trip() {
+        for sample in ["motbus", "motbus_ext", "motbus_ext_2", "can_data_ev1"] {
+            let path = Path::new("dbc-sample").join(format!("{sample}.dbc"));
+            let dbc = load_file(&path).unwrap();
+            let (kcd, _) = dbc.to_kcd();
+            let (restored, warnings) = EditableDbc::from_kcd(&kcd).unwrap();
+            assert!(warnings.is_empty(), "{sample}: {warnings:?}");
+            // 没有接收节点时 DBC 写作 Vector__XXX，重新读取 DBC 后再比较
+            let restored = FileFormat::Dbc
+                .parse(&FileFormat::Dbc.write(&restored))
+                .unwrap();
+            // KCD 不能表示的属性之外，Message 和 Signal 的内容都保留了
+            let diff = dbc.diff(&restored);
+            for message in &diff.messages {
+                assert!(
+                    message
+                        .signals
+                        .iter()
+                        .all(|s| s.changes.iter().all(|c| c.field.starts_with("attribute"))),
+                    "{sample}: {message:?}"
+                );
+                assert!(
+                    message
+                        .changes
+                        .iter()
+                        .all(|c| c.field.starts_with("attribute")),
+                    "{sample}: {message:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_warnings() {
+        let text = r#"VERSION ""
+
+BU_: ECU
+
+VAL_TABLE_ OnOff 1 "On" 0 "Off" ;
+
+BO_ 256 Mux: 8 Gateway
+ SG_ A M : 0|4@1+ (1,0) [0|0] "" ECU
+ SG_ B M : 4|4@1+ (1,0) [0|0] "" Other
+ SG_ C m1M : 8|4@1+ (1,0) [0|0] "" ECU
+
+BO_ 512 NoMux: 8 ECU
+ SG_ D m1 : 0|4@1+ (1,0) [0|0] "" ECU
+
+BA_DEF_ SG_  "GenSigStartValue" INT 0 100;
+BA_ "GenSigStartValue" SG_ 256 A 1;
+"#;
+        let dbc = EditableDbc::from_dbc(&can_dbc::Dbc::try_from(text).unwrap());
+        let (_, warnings) = dbc.to_kcd();
+        for expected in [
+            "Message Mux: transmitter Gateway is not a node",
+            "only one multiplexor is supported, B",
+            "receiver Other of B is not a node",
+            "extended multiplexing is not supported, C",
+            "Message NoMux: D has no multiplexor",
+            "1 value tables",
+            "Attribute GenSigStartValue is not supported",
+        ] {
+            assert!(has_warning(&warnings, expected), "{expected}: {warnings:?}");
+        }
+    }
+}
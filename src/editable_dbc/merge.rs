@@ -0,0 +1,627 @@
+use std::collections::HashMap;
+
+use super::{
+    AttributeDefinition, AttributeValues, EditableDbc, EditableEnvironmentVariable,
+    EditableMessage, EditableSignal, EditableValueTable,
+};
+
+// 三方合并：base 是共同祖先，ours / theirs 是两个分支的修改
+// 合并粒度为网络和节点注释、Message 的各个字段、Signal 和属性值：
+// - 只有一方修改的对象直接采用修改后的值
+// - 两方修改成相同的值不算冲突
+// - 两方修改成不同的值是冲突，按 resolutions 中的选择决定，没有选择时暂时采用 ours
+// 冲突用 location 字符串标识，界面和命令行用它来记录用户的选择，重新合并即可应用选择
+
+/// 冲突时采用的一方
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeSide {
+    Ours,
+    Theirs,
+}
+
+/// 一个冲突，三方的值都已格式化为字符串，None 表示该方没有这个对象
+#[derive(Clone, Debug, PartialEq)]
+pub struct MergeConflict {
+    pub location: String,
+    pub base: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+    pub resolution: Option<MergeSide>,
+}
+
+/// 冲突的解决方式，键为 MergeConflict::location
+pub type MergeResolutions = HashMap<String, MergeSide>;
+
+/// 合并结果，未解决的冲突在 merged 中采用 ours 的值
+#[derive(Clone, Debug)]
+pub struct MergeResult {
+    pub merged: EditableDbc,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl MergeResult {
+    /// 还没有选择解决方式的冲突数量
+    pub fn unresolved_count(&self) -> usize {
+        self.conflicts
+            .iter()
+            .filter(|c| c.resolution.is_none())
+            .count()
+    }
+}
+
+struct Merger<'a> {
+    resolutions: &'a MergeResolutions,
+    conflicts: Vec<MergeConflict>,
+}
+
+impl Merger<'_> {
+    // 记录冲突，返回按选择采用的值
+    fn conflict<T: Clone>(
+        &mut self,
+        location: String,
+        base: Option<&T>,
+        ours: Option<&T>,
+        theirs: Option<&T>,
+        describe: impl Fn(&T) -> String,
+    ) -> Option<T> {
+        let resolution = self.resolutions.get(&location).copied();
+        self.conflicts.push(MergeConflict {
+            location,
+            base: base.map(&describe),
+            ours: ours.map(&describe),
+            theirs: theirs.map(&describe),
+            resolution,
+        });
+        match resolution {
+            Some(MergeSide::Theirs) => theirs.cloned(),
+            _ => ours.cloned(),
+        }
+    }
+
+    // 单个值的三方合并
+    fn value<T: Clone + PartialEq + ToString>(
+        &mut self,
+        location: String,
+        base: &T,
+        ours: &T,
+        theirs: &T,
+    ) -> T {
+        self.value_with(location, base, ours, theirs, T::to_string)
+    }
+
+    // 同 value，用于没有实现 ToString 的值
+    fn value_with<T: Clone + PartialEq>(
+        &mut self,
+        location: String,
+        base: &T,
+        ours: &T,
+        theirs: &T,
+        describe: impl Fn(&T) -> String,
+    ) -> T {
+        if ours == theirs || base == theirs {
+            return ours.clone();
+        }
+        if base == ours {
+            return theirs.clone();
+        }
+        self.conflict(location, Some(base), Some(ours), Some(theirs), describe)
+            .expect("both sides have a value")
+    }
+
+    // 按键匹配的列表的三方合并，结果按 ours 的顺序，theirs 新增的对象在最后
+    // 两方都修改了同一个对象时调用 merge_item，由它进一步合并或记录冲突
+    fn keyed<'a, T, K>(
+        &mut self,
+        base: &'a [T],
+        ours: &'a [T],
+        theirs: &'a [T],
+        key: impl Fn(&T) -> K,
+        mut merge_item: impl FnMut(&mut Self, &K, Option<&T>, Option<&T>, Option<&T>) -> Option<T>,
+    ) -> Vec<T>
+    where
+        T: Clone + PartialEq,
+        K: PartialEq,
+    {
+        // 键可能重复（例如重复的 Message ID），用 (键, 第几次出现) 区分
+        let keys = |items: &[T]| -> Vec<(K, usize)> {
+            let mut keys: Vec<(K, usize)> = Vec::new();
+            for item in items {
+                let k = key(item);
+                let n = keys.iter().filter(|(other, _)| *other == k).count();
+                keys.push((k, n));
+            }
+            keys
+        };
+        let (base_keys, our_keys, their_keys) = (keys(base), keys(ours), keys(theirs));
+        let find = |items: &'a [T], keys: &[(K, usize)], k: &(K, usize)| -> Option<&'a T> {
+            keys.iter().position(|other| other == k).map(|i| &items[i])
+        };
+
+        let mut all_keys: Vec<&(K, usize)> = our_keys.iter().collect();
+        all_keys.extend(their_keys.iter().filter(|k| !our_keys.contains(k)));
+
+        let mut merged = Vec::new();
+        for k in all_keys {
+            let b = find(base, &base_keys, k);
+            let o = find(ours, &our_keys, k);
+            let t = find(theirs, &their_keys, k);
+            let item = if o == t || b == t {
+                o.cloned()
+            } else if b == o {
+                t.cloned()
+            } else {
+                merge_item(self, &k.0, b, o, t)
+            };
+            merged.extend(item);
+        }
+        merged
+    }
+
+    fn attributes(
+        &mut self,
+        location: &str,
+        base: &AttributeValues,
+        ours: &AttributeValues,
+        theirs: &AttributeValues,
+    ) -> AttributeValues {
+        self.keyed(
+            base,
+            ours,
+            theirs,
+            |(name, _)| name.clone(),
+            |merger, name, b, o, t| {
+                merger.conflict(
+                    format!("{location}attribute {name}"),
+                    b,
+                    o,
+                    t,
+                    |(_, value)| value.to_string(),
+                )
+            },
+        )
+    }
+
+    // 节点和环境变量的属性按对象名匹配
+    fn object_attributes(
+        &mut self,
+        kind: &str,
+        base: &[(String, AttributeValues)],
+        ours: &[(String, AttributeValues)],
+        theirs: &[(String, AttributeValues)],
+    ) -> Vec<(String, AttributeValues)> {
+        self.keyed(
+            base,
+            ours,
+            theirs,
+            |(name, _)| name.clone(),
+            |merger, name, b, o, t| match (o, t) {
+                (Some((_, o)), Some((_, t))) => {
+                    let empty = AttributeValues::new();
+                    let b = b.map(|(_, b)| b).unwrap_or(&empty);
+                    let location = format!("{kind} {name} ");
+                    Some((name.clone(), merger.attributes(&location, b, o, t)))
+                }
+                _ => merger.conflict(
+                    format!("{kind} {name} attributes"),
+                    b,
+                    o,
+                    t,
+                    |(_, values)| describe_attributes(values),
+                ),
+            },
+        )
+    }
+
+    fn message(
+        &mut self,
+        base: &EditableMessage,
+        ours: &EditableMessage,
+        theirs: &EditableMessage,
+    ) -> EditableMessage {
+        let location = format!("message 0x{:X} ", ours.message_id);
+        let mut merged = ours.clone();
+        merged.message_name = self.value(
+            format!("{location}name"),
+            &base.message_name,
+            &ours.message_name,
+            &theirs.message_name,
+        );
+        merged.frame_format = if ours.frame_format == theirs.frame_format
+            || base.frame_format == theirs.frame_format
+        {
+            ours.frame_format
+        } else if base.frame_format == ours.frame_format {
+            theirs.frame_format
+        } else {
+            unreachable!("frame format has only two values")
+        };
+        merged.message_size = self.value(
+            format!("{location}size"),
+            &base.message_size,
+            &ours.message_size,
+            &theirs.message_size,
+        );
+        merged.transmitter = self.value(
+            format!("{location}transmitter"),
+            &base.transmitter,
+            &ours.transmitter,
+            &theirs.transmitter,
+        );
+        merged.extra_transmitters = self.value_with(
+            format!("{location}transmitters"),
+            &base.extra_transmitters,
+            &ours.extra_transmitters,
+            &theirs.extra_transmitters,
+            |nodes| nodes.join(","),
+        );
+        merged.comment = self.value(
+            format!("{location}comment"),
+            &base.comment,
+            &ours.comment,
+            &theirs.comment,
+        );
+        merged.attributes = self.attributes(
+            &location,
+            &base.attributes,
+            &ours.attributes,
+            &theirs.attributes,
+        );
+        merged.signals = self.keyed(
+            &base.signals,
+            &ours.signals,
+            &theirs.signals,
+            |s| s.name.clone(),
+            |merger, name, b, o, t| {
+                merger.conflict(
+                    format!("{location}signal {name}"),
+                    b,
+                    o,
+                    t,
+                    EditableSignal::to_string,
+                )
+            },
+        );
+        merged
+    }
+}
+
+fn describe_message(message: &EditableMessage) -> String {
+    format!(
+        "BO_ {} {}: {} {} ({} signals)",
+        message.message_id,
+        message.message_name,
+        message.message_size,
+        message.transmitter,
+        message.signals.len()
+    )
+}
+
+fn describe_attributes(values: &AttributeValues) -> String {
+    values
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn describe_definition(definition: &AttributeDefinition) -> String {
+    let default = definition
+        .default()
+        .map(|d| definition.display_value(d))
+        .unwrap_or_default();
+    format!(
+        "{} {} default {}",
+        definition.object_type(),
+        definition.value_type().type_name(),
+        default
+    )
+}
+
+fn describe_value_table(table: &EditableValueTable) -> String {
+    table
+        .descriptions
+        .iter()
+        .map(|(value, text)| format!("{value}=\"{text}\""))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn describe_environment_variable(env: &EditableEnvironmentVariable) -> String {
+    format!("{} [{}|{}] \"{}\"", env.name, env.min, env.max, env.unit)
+}
+
+impl EditableDbc {
+    /// 三方合并，返回合并结果和所有冲突（包括已经按 resolutions 解决的）
+    pub fn merge(
+        base: &EditableDbc,
+        ours: &EditableDbc,
+        theirs: &EditableDbc,
+        resolutions: &MergeResolutions,
+    ) -> MergeResult {
+        let mut merger = Merger {
+            resolutions,
+            conflicts: Vec::new(),
+        };
+        let mut merged = EditableDbc::new();
+
+        merged.version = merger.value(
+            "version".to_string(),
+            &base.version,
+            &ours.version,
+            &theirs.version,
+        );
+        // 节点名就是键，两方不可能把同一个节点改成不同的值
+        merged.nodes = merger.keyed(
+            &base.nodes,
+            &ours.nodes,
+            &theirs.nodes,
+            |n| n.clone(),
+            |_, _, _, o, _| o.cloned(),
+        );
+        merged.comment = merger.value(
+            "comment".to_string(),
+            &base.comment,
+            &ours.comment,
+            &theirs.comment,
+        );
+        merged.node_comments = merger.keyed(
+            &base.node_comments,
+            &ours.node_comments,
+            &theirs.node_comments,
+            |(name, _)| name.clone(),
+            |merger, name, b, o, t| {
+                merger.conflict(format!("node {name} comment"), b, o, t, |(_, comment)| {
+                    comment.clone()
+                })
+            },
+        );
+        merged.value_tables = merger.keyed(
+            &base.value_tables,
+            &ours.value_tables,
+            &theirs.value_tables,
+            |t| t.name.clone(),
+            |merger, name, b, o, t| {
+                merger.conflict(format!("value table {name}"), b, o, t, describe_value_table)
+            },
+        );
+        merged.messages = merger.keyed(
+            &base.messages,
+            &ours.messages,
+            &theirs.messages,
+            |m| m.message_id,
+            |merger, id, b, o, t| match (b, o, t) {
+                (Some(b), Some(o), Some(t)) => Some(merger.message(b, o, t)),
+                // 一方删除另一方修改，或者两方新增了不同的 Message
+                _ => merger.conflict(format!("message 0x{id:X}"), b, o, t, describe_message),
+            },
+        );
+        merged.environment_variables = merger.keyed(
+            &base.environment_variables,
+            &ours.environment_variables,
+            &theirs.environment_variables,
+            |e| e.name.clone(),
+            |merger, name, b, o, t| {
+                merger.conflict(
+                    format!("environment variable {name}"),
+                    b,
+                    o,
+                    t,
+                    describe_environment_variable,
+                )
+            },
+        );
+        merged.attribute_definitions = merger.keyed(
+            &base.attribute_definitions,
+            &ours.attribute_definitions,
+            &theirs.attribute_definitions,
+            |d| d.name().to_string(),
+            |merger, name, b, o, t| {
+                merger.conflict(
+                    format!("attribute definition {name}"),
+                    b,
+                    o,
+                    t,
+                    describe_definition,
+                )
+            },
+        );
+        merged.network_attributes = merger.attributes(
+            "network ",
+            &base.network_attributes,
+            &ours.network_attributes,
+            &theirs.network_attributes,
+        );
+        merged.node_attributes = merger.object_attributes(
+            "node",
+            &base.node_attributes,
+            &ours.node_attributes,
+            &theirs.node_attributes,
+        );
+        merged.environment_variable_attributes = merger.object_attributes(
+            "environment variable",
+            &base.environment_variable_attributes,
+            &ours.environment_variable_attributes,
+            &theirs.environment_variable_attributes,
+        );
+        merged.sync_can_fd_flags();
+
+        MergeResult {
+            merged,
+            conflicts: merger.conflicts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editable_dbc::{AttributeTarget, AttributeValue};
+    use can_dbc::Dbc;
+
+    const BASE: &str = r#"VERSION ""
+
+BU_: ECU Gateway
+
+BO_ 100 Engine: 8 ECU
+ SG_ Speed : 0|16@1+ (0.1,0) [0|6000] "rpm" Gateway
+ SG_ Temp : 16|8@1- (1,-40) [-40|85] "degC" Gateway
+
+BO_ 200 Status: 2 ECU
+ SG_ Flag : 0|1@1+ (1,0) [0|1] "" Gateway
+
+BA_DEF_ BO_ "GenMsgCycleTime" INT 0 10000;
+BA_DEF_DEF_ "GenMsgCycleTime" 100;
+BA_ "GenMsgCycleTime" BO_ 100 10;
+"#;
+
+    fn load(text: &str) -> EditableDbc {
+        EditableDbc::from_dbc(&Dbc::try_from(text).unwrap())
+    }
+
+    fn merge(base: &EditableDbc, ours: &EditableDbc, theirs: &EditableDbc) -> MergeResult {
+        EditableDbc::merge(base, ours, theirs, &MergeResolutions::new())
+    }
+
+    #[test]
+    fn test_independent_changes_merge() {
+        let base = load(BASE);
+        let mut ours = base.clone();
+        ours.set_message_name(100, "EngineData");
+        ours.set_signal_unit(100, "Speed", "1/min");
+        let mut theirs = base.clone();
+        theirs.set_message_size(200, 4);
+        theirs.set_signal_factor(100, "Temp", 0.5);
+        theirs.delete_signal(200, "Flag");
+
+        let result = merge(&base, &ours, &theirs);
+        assert!(result.conflicts.is_empty(), "{:?}", result.conflicts);
+        let merged = result.merged;
+        let engine = merged.get_message(100).unwrap();
+        assert_eq!(engine.message_name(), "EngineData");
+        assert_eq!(engine.signals()[0].unit(), "1/min");
+        assert_eq!(engine.signals()[1].factor(), 0.5);
+        let status = merged.get_message(200).unwrap();
+        assert_eq!(status.message_size(), 4);
+        assert!(status.signals().is_empty());
+        assert!(!merged.can_undo());
+    }
+
+    #[test]
+    fn test_same_change_is_not_a_conflict() {
+        let base = load(BASE);
+        let mut ours = base.clone();
+        ours.set_message_size(100, 6);
+        let theirs = ours.clone();
+        let result = merge(&base, &ours, &theirs);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(result.merged.get_message(100).unwrap().message_size(), 6);
+    }
+
+    #[test]
+    fn test_conflicts_and_resolutions() {
+        let base = load(BASE);
+        let mut ours = base.clone();
+        ours.set_signal_start_bit(100, "Speed", 8);
+        ours.set_message_transmitter(200, "Gateway");
+        let mut theirs = base.clone();
+        theirs.set_signal_size(100, "Speed", 12);
+        theirs.delete_message(200);
+
+        let result = merge(&base, &ours, &theirs);
+        let locations: Vec<&str> = result
+            .conflicts
+            .iter()
+            .map(|c| c.location.as_str())
+            .collect();
+        assert_eq!(locations, vec!["message 0x64 signal Speed", "message 0xC8"]);
+        assert_eq!(result.unresolved_count(), 2);
+        assert_eq!(result.conflicts[1].theirs, None);
+        // 没有选择时采用 ours
+        assert_eq!(
+            result.merged.get_message(100).unwrap().signals()[0].start_bit(),
+            8
+        );
+        assert!(result.merged.get_message(200).is_some());
+
+        let mut resolutions = MergeResolutions::new();
+        resolutions.insert("message 0x64 signal Speed".to_string(), MergeSide::Theirs);
+        resolutions.insert("message 0xC8".to_string(), MergeSide::Theirs);
+        let result = EditableDbc::merge(&base, &ours, &theirs, &resolutions);
+        assert_eq!(result.unresolved_count(), 0);
+        let speed = &result.merged.get_message(100).unwrap().signals()[0];
+        assert_eq!((speed.start_bit(), speed.signal_size()), (0, 12));
+        assert!(result.merged.get_message(200).is_none());
+    }
+
+    #[test]
+    fn test_attribute_conflict() {
+        let base = load(BASE);
+        let target = AttributeTarget::Message(100);
+        let mut ours = base.clone();
+        ours.set_attribute_value(&target, "GenMsgCycleTime", AttributeValue::Int(20))
+            .unwrap();
+        let mut theirs = base.clone();
+        theirs
+            .set_attribute_value(&target, "GenMsgCycleTime", AttributeValue::Int(50))
+            .unwrap();
+
+        let result = merge(&base, &ours, &theirs);
+        assert_eq!(result.conflicts.len(), 1);
+        let conflict = &result.conflicts[0];
+        assert_eq!(conflict.location, "message 0x64 attribute GenMsgCycleTime");
+        assert_eq!(conflict.base.as_deref(), Some("10"));
+        assert_eq!(conflict.ours.as_deref(), Some("20"));
+        assert_eq!(conflict.theirs.as_deref(), Some("50"));
+    }
+
+    #[test]
+    fn test_comments_and_transmitters() {
+        let mut base = load(BASE);
+        base.node_comments
+            .push(("ECU".to_string(), "Engine".to_string()));
+        let mut ours = base.clone();
+        ours.comment = "Powertrain".to_string();
+        ours.set_message_comment(100, "Engine data");
+        let mut theirs = base.clone();
+        theirs
+            .node_comments
+            .push(("Gateway".to_string(), "Gateway".to_string()));
+        theirs.messages[0].extra_transmitters = vec!["ECU".to_string(), "Gateway".to_string()];
+
+        let result = merge(&base, &ours, &theirs);
+        assert!(result.conflicts.is_empty(), "{:?}", result.conflicts);
+        let merged = result.merged;
+        assert_eq!(merged.comment(), "Powertrain");
+        assert_eq!(merged.node_comment("ECU"), Some("Engine"));
+        assert_eq!(merged.node_comment("Gateway"), Some("Gateway"));
+        let engine = merged.get_message(100).unwrap();
+        assert_eq!(engine.comment(), "Engine data");
+        assert_eq!(engine.extra_transmitters(), &vec!["ECU", "Gateway"]);
+
+        // 两方修改了同一个节点注释
+        ours.node_comments[0].1 = "Engine ECU".to_string();
+        theirs.node_comments[0].1 = "Motor ECU".to_string();
+        let result = merge(&base, &ours, &theirs);
+        assert_eq!(result.conflicts.len(), 1);
+        let conflict = &result.conflicts[0];
+        assert_eq!(conflict.location, "node ECU comment");
+        assert_eq!(conflict.theirs.as_deref(), Some("Motor ECU"));
+        assert_eq!(result.merged.node_comment("ECU"), Some("Engine ECU"));
+    }
+
+    #[test]
+    fn test_added_messages_and_nodes() {
+        let base = load(BASE);
+        let mut ours = base.clone();
+        ours.nodes.push("Dashboard".to_string());
+        let mut theirs = base.clone();
+        let mut message = EditableMessage::new();
+        message.message_id = 300;
+        message.message_name = "New".to_string();
+        theirs.add_message(&message);
+
+        let merged = merge(&base, &ours, &theirs).merged;
+        assert_eq!(merged.nodes(), &vec!["ECU", "Gateway", "Dashboard"]);
+        assert_eq!(merged.messages().last().unwrap().message_id(), 300);
+    }
+}
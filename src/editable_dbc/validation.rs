@@ -0,0 +1,481 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+use can_dbc::{MultiplexIndicator, SignalExtendedValueType};
+
+use super::can_fd::check_signal_placement;
+use super::codec::may_overlap;
+use super::writer::VECTOR_XXX;
+use super::{EditableDbc, EditableMessage, EditableSignal, is_valid_message_size};
+
+// DBC 检查：编辑操作本身不阻止不合法的数据（例如重复的 Message ID）
+// 这里对整个 EditableDbc 做一次检查，返回带有固定规则编号的诊断信息
+// 规则编号一旦发布就不再改变，方便在 CI 中按编号过滤
+
+/// 诊断的严重程度
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// 检查规则，每个规则有固定的编号
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+    DuplicateMessageId,
+    DuplicateMessageName,
+    DuplicateSignalName,
+    InvalidMessageSize,
+    SignalOutOfRange,
+    OverlappingSignals,
+    MinGreaterThanMax,
+    RangeNotRepresentable,
+    InvalidIdentifier,
+    UnknownTransmitter,
+    UnknownReceiver,
+    MissingMultiplexor,
+}
+
+/// 一条诊断信息，定位到 Message 或 Message 中的 Signal
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub text: String,
+    pub message_id: Option<u32>,
+    pub signal_name: Option<String>,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+impl Rule {
+    /// 规则编号，E 开头为错误，W 开头为警告
+    pub fn code(&self) -> &'static str {
+        match self {
+            Rule::DuplicateMessageId => "E001",
+            Rule::DuplicateMessageName => "E002",
+            Rule::DuplicateSignalName => "E003",
+            Rule::InvalidMessageSize => "E004",
+            Rule::SignalOutOfRange => "E005",
+            Rule::OverlappingSignals => "E006",
+            Rule::MinGreaterThanMax => "E007",
+            Rule::InvalidIdentifier => "E008",
+            Rule::MissingMultiplexor => "E009",
+            Rule::RangeNotRepresentable => "W001",
+            Rule::UnknownTransmitter => "W002",
+            Rule::UnknownReceiver => "W003",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            Rule::RangeNotRepresentable | Rule::UnknownTransmitter | Rule::UnknownReceiver => {
+                Severity::Warning
+            }
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl Diagnostic {
+    fn new(rule: Rule, text: String) -> Self {
+        Self {
+            rule,
+            text,
+            message_id: None,
+            signal_name: None,
+        }
+    }
+
+    fn at_message(mut self, message: &EditableMessage) -> Self {
+        self.message_id = Some(message.message_id);
+        self
+    }
+
+    fn at_signal(mut self, message: &EditableMessage, signal: &EditableSignal) -> Self {
+        self.message_id = Some(message.message_id);
+        self.signal_name = Some(signal.name.clone());
+        self
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.rule.severity()
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {}",
+            self.severity().as_str(),
+            self.rule.code(),
+            self.text
+        )
+    }
+}
+
+/// DBC 中的名称必须是 C 标识符
+pub fn is_c_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl EditableDbc {
+    /// 检查整个 DBC，先按 Message 的顺序，同一个 Message 内按规则的顺序
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        for node in &self.nodes {
+            if !is_c_identifier(node) {
+                diagnostics.push(Diagnostic::new(
+                    Rule::InvalidIdentifier,
+                    format!("Node name \"{}\" is not a valid C identifier", node),
+                ));
+            }
+        }
+
+        let mut ids: HashMap<u32, usize> = HashMap::new();
+        let mut names: HashMap<&str, usize> = HashMap::new();
+        for message in &self.messages {
+            *ids.entry(message.message_id).or_default() += 1;
+            *names.entry(&message.message_name).or_default() += 1;
+        }
+
+        for message in &self.messages {
+            if ids[&message.message_id] > 1 {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Rule::DuplicateMessageId,
+                        format!(
+                            "Message ID 0x{:X} is used by more than one message",
+                            message.message_id
+                        ),
+                    )
+                    .at_message(message),
+                );
+            }
+            if names[message.message_name.as_str()] > 1 {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Rule::DuplicateMessageName,
+                        format!(
+                            "Message name {} is used by more than one message",
+                            message.message_name
+                        ),
+                    )
+                    .at_message(message),
+                );
+            }
+            self.validate_message(message, &mut diagnostics);
+        }
+
+        diagnostics
+    }
+
+    fn validate_message(&self, message: &EditableMessage, diagnostics: &mut Vec<Diagnostic>) {
+        if !is_c_identifier(&message.message_name) {
+            diagnostics.push(
+                Diagnostic::new(
+                    Rule::InvalidIdentifier,
+                    format!(
+                        "Message name \"{}\" is not a valid C identifier",
+                        message.message_name
+                    ),
+                )
+                .at_message(message),
+            );
+        }
+        if !is_valid_message_size(message.message_size, message.fd) {
+            diagnostics.push(
+                Diagnostic::new(
+                    Rule::InvalidMessageSize,
+                    format!(
+                        "Message {} has an invalid {} size of {} bytes",
+                        message.message_name,
+                        if message.fd { "CAN FD" } else { "CAN" },
+                        message.message_size
+                    ),
+                )
+                .at_message(message),
+            );
+        }
+        if message.transmitter != VECTOR_XXX && !self.nodes.contains(&message.transmitter) {
+            diagnostics.push(
+                Diagnostic::new(
+                    Rule::UnknownTransmitter,
+                    format!(
+                        "Transmitter {} of message {} is not a defined node",
+                        message.transmitter, message.message_name
+                    ),
+                )
+                .at_message(message),
+            );
+        }
+
+        let has_multiplexor = message.multiplexor().is_some();
+        for (i, signal) in message.signals.iter().enumerate() {
+            if message.signals[..i].iter().any(|s| s.name == signal.name) {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Rule::DuplicateSignalName,
+                        format!(
+                            "Signal {} appears more than once in message {}",
+                            signal.name, message.message_name
+                        ),
+                    )
+                    .at_signal(message, signal),
+                );
+            }
+            self.validate_signal(message, signal, has_multiplexor, diagnostics);
+        }
+
+        self.validate_overlaps(message, diagnostics);
+    }
+
+    fn validate_signal(
+        &self,
+        message: &EditableMessage,
+        signal: &EditableSignal,
+        has_multiplexor: bool,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let mut push = |rule: Rule, text: String| {
+            diagnostics.push(Diagnostic::new(rule, text).at_signal(message, signal));
+        };
+
+        if !is_c_identifier(&signal.name) {
+            push(
+                Rule::InvalidIdentifier,
+                format!(
+                    "Signal name \"{}\" is not a valid C identifier",
+                    signal.name
+                ),
+            );
+        }
+        if let Err(e) = check_signal_placement(signal, message.message_size) {
+            push(Rule::SignalOutOfRange, e);
+        }
+        if signal.min > signal.max {
+            push(
+                Rule::MinGreaterThanMax,
+                format!(
+                    "Signal {} has min {} greater than max {}",
+                    signal.name, signal.min, signal.max
+                ),
+            );
+        } else if let Some((low, high)) = representable_range(signal)
+            // [0|0] 表示没有指定范围
+            && !(signal.min == 0.0 && signal.max == 0.0)
+            && (signal.min < low - tolerance(low) || signal.max > high + tolerance(high))
+        {
+            push(
+                Rule::RangeNotRepresentable,
+                format!(
+                    "Signal {} range [{}, {}] exceeds what {} {} bits can represent [{}, {}]",
+                    signal.name,
+                    signal.min,
+                    signal.max,
+                    signal.signal_size,
+                    match signal.value_type {
+                        can_dbc::ValueType::Signed => "signed",
+                        can_dbc::ValueType::Unsigned => "unsigned",
+                    },
+                    low,
+                    high
+                ),
+            );
+        }
+        if matches!(
+            signal.multiplexer_indicator,
+            MultiplexIndicator::MultiplexedSignal(_)
+        ) && !has_multiplexor
+        {
+            push(
+                Rule::MissingMultiplexor,
+                format!(
+                    "Signal {} is multiplexed but message {} has no multiplexor",
+                    signal.name, message.message_name
+                ),
+            );
+        }
+        for receiver in &signal.receivers {
+            if receiver != VECTOR_XXX && !self.nodes.contains(receiver) {
+                push(
+                    Rule::UnknownReceiver,
+                    format!(
+                        "Receiver {} of signal {} is not a defined node",
+                        receiver, signal.name
+                    ),
+                );
+            }
+        }
+    }
+
+    // 每对重叠的信号只报告一次，报告在后出现的信号上
+    fn validate_overlaps(&self, message: &EditableMessage, diagnostics: &mut Vec<Diagnostic>) {
+        let bits: Vec<Vec<u64>> = message.signals.iter().map(|s| s.bits()).collect();
+        for (i, signal) in message.signals.iter().enumerate() {
+            for (j, other) in message.signals[..i].iter().enumerate() {
+                if !may_overlap(signal, other) {
+                    continue;
+                }
+                if let Some(bit) = bits[i].iter().find(|b| bits[j].contains(b)) {
+                    diagnostics.push(
+                        Diagnostic::new(
+                            Rule::OverlappingSignals,
+                            format!(
+                                "Signals {} and {} overlap at bit {}",
+                                other.name, signal.name, bit
+                            ),
+                        )
+                        .at_signal(message, signal),
+                    );
+                }
+            }
+        }
+    }
+}
+
+// 原始值范围对应的物理值范围，factor 为负数时上下限交换
+// 浮点信号的范围由 IEEE 格式决定，不检查
+fn representable_range(signal: &EditableSignal) -> Option<(f64, f64)> {
+    if signal.signal_size == 0
+        || signal.signal_size > 64
+        || matches!(
+            signal.extended_value_type,
+            Some(
+                SignalExtendedValueType::IEEEfloat32Bit | SignalExtendedValueType::IEEEdouble64bit
+            )
+        )
+    {
+        return None;
+    }
+    let (raw_min, raw_max) = signal.raw_range();
+    let a = raw_min as f64 * signal.factor + signal.offset;
+    let b = raw_max as f64 * signal.factor + signal.offset;
+    Some((a.min(b), a.max(b)))
+}
+
+// 浮点计算的误差容限
+fn tolerance(value: f64) -> f64 {
+    value.abs().max(1.0) * 1e-9
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use can_dbc::Dbc;
+
+    const BROKEN_DBC: &str = r#"VERSION ""
+
+BU_: ECU Gateway
+
+BO_ 100 Engine: 8 ECU
+ SG_ Speed : 0|16@1+ (0.1,0) [0|6000] "rpm" Gateway
+ SG_ Torque : 8|16@1+ (1,0) [0|100] "Nm" Dashboard
+ SG_ Temp : 56|16@1- (1,0) [10|-10] "" Vector__XXX
+
+BO_ 100 Engine: 8 Unknown
+ SG_ Flag m1 : 0|1@1+ (1,0) [0|1] "" Vector__XXX
+
+BO_ 200 Status: 4 ECU
+ SG_ Mux M : 0|4@1+ (1,0) [0|0] "" Vector__XXX
+ SG_ A m0 : 8|8@1+ (1,0) [0|300] "" Vector__XXX
+ SG_ B m1 : 8|8@1- (1,0) [-128|127] "" Vector__XXX
+"#;
+
+    fn codes(diagnostics: &[Diagnostic]) -> Vec<&'static str> {
+        diagnostics.iter().map(|d| d.rule.code()).collect()
+    }
+
+    #[test]
+    fn test_clean_dbc_has_no_diagnostics() {
+        let sample = include_str!("../../dbc-sample/motbus.dbc");
+        let dbc = EditableDbc::from_dbc(&Dbc::try_from(sample).unwrap());
+        let errors: Vec<_> = dbc
+            .validate()
+            .into_iter()
+            .filter(|d| d.severity() == Severity::Error)
+            .collect();
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_rules() {
+        let dbc = EditableDbc::from_dbc(&Dbc::try_from(BROKEN_DBC).unwrap());
+        let diagnostics = dbc.validate();
+        let found = codes(&diagnostics);
+
+        // 重复的 ID 和名称在两个 Message 上都报告
+        assert_eq!(found.iter().filter(|c| **c == "E001").count(), 2);
+        assert_eq!(found.iter().filter(|c| **c == "E002").count(), 2);
+        // Temp 超出 8 字节，Speed 和 Torque 重叠
+        assert!(found.contains(&"E005"));
+        let overlap = diagnostics
+            .iter()
+            .find(|d| d.rule == Rule::OverlappingSignals)
+            .unwrap();
+        assert_eq!(overlap.signal_name.as_deref(), Some("Torque"));
+        assert_eq!(overlap.message_id, Some(100));
+        // Temp 的 min > max
+        assert!(found.contains(&"E007"));
+        // Flag 所在的 Message 没有复用器
+        assert!(found.contains(&"E009"));
+        // A 是 8 位无符号，最大只能到 255
+        let range = diagnostics
+            .iter()
+            .find(|d| d.rule == Rule::RangeNotRepresentable)
+            .unwrap();
+        assert_eq!(range.signal_name.as_deref(), Some("A"));
+        assert_eq!(range.severity(), Severity::Warning);
+        // 未定义的发送节点和接收节点
+        assert!(found.contains(&"W002"));
+        assert!(found.contains(&"W003"));
+        // 复用值不同的 A 和 B 共用位，不算重叠
+        assert!(
+            !diagnostics
+                .iter()
+                .any(|d| d.rule == Rule::OverlappingSignals && d.message_id == Some(200))
+        );
+    }
+
+    #[test]
+    fn test_edits_are_checked() {
+        let mut dbc = EditableDbc::from_dbc(&Dbc::try_from(BROKEN_DBC).unwrap());
+        dbc.set_message_name(200, "2Status");
+        dbc.set_message_size(200, 10);
+        let found = codes(&dbc.validate());
+        assert!(found.contains(&"E008"));
+        assert!(found.contains(&"E004"));
+
+        let diagnostic = dbc
+            .validate()
+            .into_iter()
+            .find(|d| d.rule == Rule::InvalidIdentifier)
+            .unwrap();
+        assert_eq!(
+            diagnostic.to_string(),
+            "error E008: Message name \"2Status\" is not a valid C identifier"
+        );
+    }
+
+    #[test]
+    fn test_c_identifier() {
+        assert!(is_c_identifier("Engine_Speed2"));
+        assert!(is_c_identifier("_x"));
+        assert!(!is_c_identifier(""));
+        assert!(!is_c_identifier("2x"));
+        assert!(!is_c_identifier("a-b"));
+        assert!(!is_c_identifier("温度"));
+    }
+}
@@ -0,0 +1,934 @@
+use std::fmt::{self, Display, Formatter};
+
+use can_dbc::{
+    AccessType, ByteOrder, Dbc, EnvType, MultiplexIndicator, SignalExtendedValueType, ValueType,
+};
+
+use super::{
+    AttributeDefinition, AttributeValue, AttributeValueType, AttributeValues, EditableDbc,
+    EditableEnvironmentVariable, EditableMessage, EditableSignal, EditableValueTable, FrameFormat,
+};
+
+// EditableDbc -> String
+// 输出的字符串必须能被 can_dbc::Dbc::try_from 重新解析，并且解析后得到等价的 EditableDbc
+// 各个段落的顺序与 Vector CANdb++ 保存的文件保持一致
+
+// NS_ 段只是一个固定的关键字列表，can-dbc 解析后也不保存，直接输出标准内容
+const NEW_SYMBOLS: &[&str] = &[
+    "NS_DESC_",
+    "CM_",
+    "BA_DEF_",
+    "BA_",
+    "VAL_",
+    "CAT_DEF_",
+    "CAT_",
+    "FILTER",
+    "BA_DEF_DEF_",
+    "EV_DATA_",
+    "ENVVAR_DATA_",
+    "SGTYPE_",
+    "SGTYPE_VAL_",
+    "BA_DEF_SGTYPE_",
+    "BA_SGTYPE_",
+    "SIG_TYPE_REF_",
+    "VAL_TABLE_",
+    "SIG_GROUP_",
+    "SIG_VALTYPE_",
+    "SIGTYPE_VALTYPE_",
+    "BO_TX_BU_",
+    "BA_DEF_REL_",
+    "BA_REL_",
+    "BA_DEF_DEF_REL_",
+    "BU_SG_REL_",
+    "BU_EV_REL_",
+    "BU_BO_REL_",
+    "SG_MUL_VAL_",
+];
+
+// 没有指定节点时使用的占位节点名
+pub(super) const VECTOR_XXX: &str = "Vector__XXX";
+
+pub(super) const EXTENDED_ID_FLAG: u32 = 1 << 31;
+
+impl Display for EditableDbc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "VERSION \"{}\"", escape(&self.version))?;
+        writeln!(f)?;
+        writeln!(f)?;
+
+        writeln!(f, "NS_ :")?;
+        for symbol in NEW_SYMBOLS {
+            writeln!(f, "\t{symbol}")?;
+        }
+        writeln!(f)?;
+
+        writeln!(f, "BS_:")?;
+        writeln!(f)?;
+
+        write!(f, "BU_:")?;
+        for node in &self.nodes {
+            write!(f, " {node}")?;
+        }
+        writeln!(f)?;
+
+        for table in &self.value_tables {
+            write_value_table(f, table)?;
+        }
+        writeln!(f)?;
+        writeln!(f)?;
+
+        for message in &self.messages {
+            write_message(f, message)?;
+            writeln!(f)?;
+        }
+        writeln!(f)?;
+
+        for message in &self.messages {
+            write_extra_transmitters(f, message)?;
+        }
+
+        for env in &self.environment_variables {
+            write_environment_variable(f, env)?;
+        }
+        if !self.environment_variables.is_empty() {
+            writeln!(f)?;
+        }
+
+        self.write_comments(f)?;
+
+        self.write_attributes(f)?;
+
+        for message in &self.messages {
+            write_signal_value_descriptions(f, message)?;
+        }
+
+        for message in &self.messages {
+            write_signal_value_types(f, message)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl EditableDbc {
+    // CM_ 段落，按网络、节点、Message / Signal、环境变量的顺序
+    fn write_comments(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if !self.comment.is_empty() {
+            writeln!(f, "CM_ \"{}\";", escape(&self.comment))?;
+        }
+        for (node, comment) in &self.node_comments {
+            writeln!(f, "CM_ BU_ {} \"{}\";", node, escape(comment))?;
+        }
+        for message in &self.messages {
+            write_comments(f, message)?;
+        }
+        for env in &self.environment_variables {
+            if !env.comment.is_empty() {
+                writeln!(f, "CM_ EV_ {} \"{}\";", env.name, escape(&env.comment))?;
+            }
+        }
+        Ok(())
+    }
+
+    // BA_DEF_、BA_DEF_DEF_、BA_ 三个段落
+    fn write_attributes(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for definition in &self.attribute_definitions {
+            write_attribute_definition(f, definition)?;
+        }
+        for definition in &self.attribute_definitions {
+            if let Some(default) = definition.default() {
+                writeln!(
+                    f,
+                    "BA_DEF_DEF_ \"{}\" {};",
+                    definition.name(),
+                    format_attribute_value(default)
+                )?;
+            }
+        }
+
+        write_attribute_values(f, "", &self.network_attributes)?;
+        for (node, attributes) in &self.node_attributes {
+            write_attribute_values(f, &format!("BU_ {node} "), attributes)?;
+        }
+        for message in &self.messages {
+            let message_id = raw_message_id(message);
+            write_attribute_values(f, &format!("BO_ {message_id} "), &message.attributes)?;
+            for signal in &message.signals {
+                write_attribute_values(
+                    f,
+                    &format!("SG_ {message_id} {} ", signal.name),
+                    &signal.attributes,
+                )?;
+            }
+        }
+        for (env, attributes) in &self.environment_variable_attributes {
+            write_attribute_values(f, &format!("EV_ {env} "), attributes)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_message(f: &mut Formatter<'_>, message: &EditableMessage) -> fmt::Result {
+    writeln!(
+        f,
+        "BO_ {} {}: {} {}",
+        raw_message_id(message),
+        message.message_name,
+        message.message_size,
+        node_or_vector_xxx(&message.transmitter)
+    )?;
+    for signal in &message.signals {
+        write_signal(f, signal)?;
+    }
+    Ok(())
+}
+
+fn write_extra_transmitters(f: &mut Formatter<'_>, message: &EditableMessage) -> fmt::Result {
+    if !message.extra_transmitters.is_empty() {
+        writeln!(
+            f,
+            "BO_TX_BU_ {} : {};",
+            raw_message_id(message),
+            message.extra_transmitters.join(",")
+        )?;
+    }
+    Ok(())
+}
+
+fn write_signal(f: &mut Formatter<'_>, signal: &EditableSignal) -> fmt::Result {
+    writeln!(f, " {}", signal)
+}
+
+// SG_ 行，不带缩进和换行，合并冲突等地方也用它来显示 Signal 的定义
+impl Display for EditableSignal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let byte_order = match self.byte_order {
+            ByteOrder::LittleEndian => '1',
+            ByteOrder::BigEndian => '0',
+        };
+        let value_type = match self.value_type {
+            ValueType::Unsigned => '+',
+            ValueType::Signed => '-',
+        };
+        // 接收节点至少要有一个，否则无法被重新解析
+        let receivers = if self.receivers.is_empty() {
+            VECTOR_XXX.to_string()
+        } else {
+            self.receivers.join(",")
+        };
+
+        write!(
+            f,
+            "SG_ {}{} : {}|{}@{}{} ({},{}) [{}|{}] \"{}\" {}",
+            self.name,
+            multiplexer_indicator(&self.multiplexer_indicator),
+            self.start_bit,
+            self.signal_size,
+            byte_order,
+            value_type,
+            self.factor,
+            self.offset,
+            self.min,
+            self.max,
+            escape(&self.unit),
+            receivers
+        )
+    }
+}
+
+fn write_comments(f: &mut Formatter<'_>, message: &EditableMessage) -> fmt::Result {
+    let message_id = raw_message_id(message);
+    if !message.comment.is_empty() {
+        writeln!(
+            f,
+            "CM_ BO_ {} \"{}\";",
+            message_id,
+            escape(&message.comment)
+        )?;
+    }
+    for signal in &message.signals {
+        if !signal.comment.is_empty() {
+            writeln!(
+                f,
+                "CM_ SG_ {} {} \"{}\";",
+                message_id,
+                signal.name,
+                escape(&signal.comment)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn write_environment_variable(
+    f: &mut Formatter<'_>,
+    env: &EditableEnvironmentVariable,
+) -> fmt::Result {
+    let env_type = match env.env_type {
+        EnvType::Integer => 0,
+        EnvType::Float => 1,
+        EnvType::String => 2,
+    };
+    let access_type = match env.access_type {
+        AccessType::DummyNodeVector0 => 0,
+        AccessType::DummyNodeVector1 => 1,
+        AccessType::DummyNodeVector2 => 2,
+        AccessType::DummyNodeVector3 => 3,
+    };
+    // can-dbc 的语法只接受一个访问节点
+    let access_node = env
+        .access_nodes
+        .first()
+        .map(String::as_str)
+        .unwrap_or(VECTOR_XXX);
+    writeln!(
+        f,
+        "EV_ {}: {} [{}|{}] \"{}\" {} {} DUMMY_NODE_VECTOR{} {};",
+        env.name,
+        env_type,
+        env.min,
+        env.max,
+        escape(&env.unit),
+        env.initial_value,
+        env.ev_id,
+        access_type,
+        access_node
+    )
+}
+
+fn write_attribute_definition(
+    f: &mut Formatter<'_>,
+    definition: &AttributeDefinition,
+) -> fmt::Result {
+    let object_type = definition.object_type().keyword();
+    write!(f, "BA_DEF_ {object_type} ")?;
+    if !object_type.is_empty() {
+        write!(f, " ")?;
+    }
+    write!(f, "\"{}\" ", definition.name())?;
+    match definition.value_type() {
+        AttributeValueType::Int { min, max } => write!(f, "INT {min} {max}")?,
+        AttributeValueType::Hex { min, max } => write!(f, "HEX {min} {max}")?,
+        AttributeValueType::Float { min, max } => write!(f, "FLOAT {min} {max}")?,
+        AttributeValueType::String => write!(f, "STRING ")?,
+        AttributeValueType::Enum(values) => {
+            let values: Vec<String> = values
+                .iter()
+                .map(|v| format!("\"{}\"", escape(v)))
+                .collect();
+            write!(f, "ENUM  {}", values.join(","))?;
+        }
+    }
+    writeln!(f, ";")
+}
+
+// prefix 是对象部分，如 "BO_ 100 "，网络属性为空
+fn write_attribute_values(
+    f: &mut Formatter<'_>,
+    prefix: &str,
+    attributes: &AttributeValues,
+) -> fmt::Result {
+    for (name, value) in attributes {
+        writeln!(
+            f,
+            "BA_ \"{}\" {}{};",
+            name,
+            prefix,
+            format_attribute_value(value)
+        )?;
+    }
+    Ok(())
+}
+
+fn format_attribute_value(value: &AttributeValue) -> String {
+    match value {
+        AttributeValue::Int(v) => v.to_string(),
+        AttributeValue::Float(v) => v.to_string(),
+        AttributeValue::String(v) => format!("\"{}\"", escape(v)),
+    }
+}
+
+fn write_value_table(f: &mut Formatter<'_>, table: &EditableValueTable) -> fmt::Result {
+    write!(f, "VAL_TABLE_ {}", table.name)?;
+    write_value_descriptions(f, &table.descriptions)?;
+    writeln!(f, ";")
+}
+
+fn write_signal_value_descriptions(
+    f: &mut Formatter<'_>,
+    message: &EditableMessage,
+) -> fmt::Result {
+    let message_id = raw_message_id(message);
+    for signal in &message.signals {
+        if !signal.value_descriptions.is_empty() {
+            write!(f, "VAL_ {} {}", message_id, signal.name)?;
+            write_value_descriptions(f, &signal.value_descriptions)?;
+            writeln!(f, ";")?;
+        }
+    }
+    Ok(())
+}
+
+fn write_signal_value_types(f: &mut Formatter<'_>, message: &EditableMessage) -> fmt::Result {
+    let message_id = raw_message_id(message);
+    for signal in &message.signals {
+        if let Some(value_type) = signal.extended_value_type {
+            let value_type = match value_type {
+                SignalExtendedValueType::SignedOrUnsignedInteger => 0,
+                SignalExtendedValueType::IEEEfloat32Bit => 1,
+                SignalExtendedValueType::IEEEdouble64bit => 2,
+            };
+            writeln!(
+                f,
+                "SIG_VALTYPE_ {} {} : {};",
+                message_id, signal.name, value_type
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn write_value_descriptions(f: &mut Formatter<'_>, descriptions: &[(i64, String)]) -> fmt::Result {
+    for (value, description) in descriptions {
+        write!(f, " {} \"{}\"", value, escape(description))?;
+    }
+    write!(f, " ")
+}
+
+// DBC 文件中扩展帧的 ID 需要带上最高位标记
+fn raw_message_id(message: &EditableMessage) -> u32 {
+    match message.frame_format {
+        FrameFormat::Standard => message.message_id,
+        FrameFormat::Extended => message.message_id | EXTENDED_ID_FLAG,
+    }
+}
+
+fn multiplexer_indicator(indicator: &MultiplexIndicator) -> String {
+    match indicator {
+        MultiplexIndicator::Plain => String::new(),
+        MultiplexIndicator::Multiplexor => " M".to_string(),
+        MultiplexIndicator::MultiplexedSignal(value) => format!(" m{value}"),
+        MultiplexIndicator::MultiplexorAndMultiplexedSignal(value) => format!(" m{value}M"),
+    }
+}
+
+fn node_or_vector_xxx(node: &str) -> &str {
+    if node.is_empty() { VECTOR_XXX } else { node }
+}
+
+// can-dbc 解析字符串时不处理转义，读取时由 unescape 还原
+// 反斜杠写成 \\，引号写成 \"
+// can-dbc 的语法中紧跟在反斜杠后的引号总是被当作转义的引号，所以结尾的反斜杠写成 \x5C
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            c => escaped.push(c),
+        }
+    }
+    if text.ends_with('\\') {
+        escaped.truncate(escaped.len() - 2);
+        escaped.push_str("\\x5C");
+    }
+    escaped
+}
+
+// escape 的逆操作，其他工具写出的 \n 等序列保留原样
+pub(super) fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find('\\') {
+        unescaped.push_str(&rest[..index]);
+        rest = &rest[index..];
+        let (c, len) = match rest.as_bytes().get(1) {
+            Some(b'\\') => ('\\', 2),
+            Some(b'"') => ('"', 2),
+            _ if rest[1..].starts_with("x5C") || rest[1..].starts_with("x5c") => ('\\', 4),
+            _ => ('\\', 1),
+        };
+        unescaped.push(c);
+        rest = &rest[len..];
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+// 逐个段落比较两个 DBC，返回内容不同的段落，只有 NS_ 不比较
+// 段落内的顺序可以不同，Message 和 Signal 的顺序必须相同
+pub(super) fn dbc_differences(expected: &Dbc, actual: &Dbc) -> Vec<&'static str> {
+    fn same_items<T: PartialEq>(expected: &[T], actual: &[T]) -> bool {
+        expected.len() == actual.len() && expected.iter().all(|item| actual.contains(item))
+    }
+
+    let mut differences = Vec::new();
+    if expected.version != actual.version {
+        differences.push("VERSION");
+    }
+    // 总是写出 BS_:，没有 BS_ 行和空的 BS_ 等价
+    if expected.bit_timing.as_deref().unwrap_or_default()
+        != actual.bit_timing.as_deref().unwrap_or_default()
+    {
+        differences.push("BS_");
+    }
+    if expected.nodes != actual.nodes {
+        differences.push("BU_");
+    }
+    if expected.messages != actual.messages {
+        differences.push("BO_");
+    }
+    macro_rules! compare_section {
+        ($section:literal, $field:ident) => {
+            if !same_items(&expected.$field, &actual.$field) {
+                differences.push($section);
+            }
+        };
+    }
+    compare_section!("VAL_TABLE_", value_tables);
+    compare_section!("BO_TX_BU_", message_transmitters);
+    compare_section!("EV_", environment_variables);
+    compare_section!("ENVVAR_DATA_", environment_variable_data);
+    compare_section!("SGTYPE_", signal_types);
+    compare_section!("CM_", comments);
+    compare_section!("BA_DEF_", attribute_definitions);
+    compare_section!("BA_DEF_REL_", relation_attribute_definitions);
+    compare_section!("BA_DEF_DEF_", attribute_defaults);
+    compare_section!("BA_DEF_DEF_REL_", relation_attribute_defaults);
+    compare_section!("BA_REL_", relation_attribute_values);
+    compare_section!("BA_", attribute_values_database);
+    compare_section!("BA_ BU_", attribute_values_node);
+    compare_section!("BA_ BO_", attribute_values_message);
+    compare_section!("BA_ SG_", attribute_values_signal);
+    compare_section!("BA_ EV_", attribute_values_env);
+    compare_section!("VAL_", value_descriptions);
+    compare_section!("SIG_TYPE_REF_", signal_type_refs);
+    compare_section!("SIG_GROUP_", signal_groups);
+    compare_section!("SIG_VALTYPE_", signal_extended_value_type_list);
+    compare_section!("SG_MUL_VAL_", extended_multiplex);
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editable_dbc::{AttributeTarget, FileFormat};
+    use can_dbc::{Comment, MessageId, ValueDescription};
+
+    const SAMPLES: &[(&str, &str)] = &[
+        ("empty.dbc", include_str!("../../dbc-sample/empty.dbc")),
+        ("motbus.dbc", include_str!("../../dbc-sample/motbus.dbc")),
+        (
+            "motbus_ext.dbc",
+            include_str!("../../dbc-sample/motbus_ext.dbc"),
+        ),
+        (
+            "motbus_ext_2.dbc",
+            include_str!("../../dbc-sample/motbus_ext_2.dbc"),
+        ),
+        (
+            "can_data_ev1.dbc",
+            include_str!("../../dbc-sample/can_data_ev1.dbc"),
+        ),
+    ];
+
+    fn parse(text: &str) -> EditableDbc {
+        EditableDbc::from_dbc(&Dbc::try_from(text).unwrap())
+    }
+
+    fn assert_equivalent(expected: &EditableDbc, actual: &EditableDbc) {
+        assert_eq!(expected.version(), actual.version());
+        assert_eq!(expected.nodes(), actual.nodes());
+        assert_eq!(expected.messages(), actual.messages());
+        assert_eq!(expected.value_tables(), actual.value_tables());
+        assert_eq!(expected.environment_variables, actual.environment_variables);
+        assert_eq!(expected.attribute_definitions, actual.attribute_definitions);
+        assert_eq!(expected.network_attributes, actual.network_attributes);
+        assert_eq!(expected.node_attributes, actual.node_attributes);
+        assert_eq!(
+            expected.environment_variable_attributes,
+            actual.environment_variable_attributes
+        );
+    }
+
+    // 包含 SIG_VALTYPE_、BO_TX_BU_ 和各种注释，这些内容在示例文件中没有覆盖
+    const EXTRA_SECTIONS_DBC: &str = r#"VERSION "2.0"
+
+BS_:
+
+BU_: Engine Dash Gateway
+
+BO_ 256 EngineData: 8 Engine
+ SG_ Speed : 0|32@1- (1,0) [0|0] "km/h" Dash
+ SG_ Torque : 32|32@1+ (1,0) [0|0] "Nm" Dash
+
+BO_ 2147484160 Ext: 8 Gateway
+ SG_ Energy : 0|64@1- (1,0) [0|0] "" Dash
+
+BO_TX_BU_ 256 : Engine,Gateway;
+
+EV_ EnvMode: 0 [0|3] "" 1 7 DUMMY_NODE_VECTOR0 Engine;
+
+CM_ "Powertrain network";
+CM_ BU_ Engine "Engine control unit";
+CM_ BU_ Dash "Instrument cluster";
+CM_ BO_ 256 "Engine data";
+CM_ SG_ 256 Speed "Vehicle speed";
+CM_ EV_ EnvMode "Test mode";
+
+SIG_VALTYPE_ 256 Speed : 1;
+SIG_VALTYPE_ 256 Torque : 0;
+SIG_VALTYPE_ 2147484160 Energy : 2;
+"#;
+
+    fn assert_same_dbc(name: &str, expected: &Dbc, actual: &Dbc) {
+        let differences = dbc_differences(expected, actual);
+        assert!(differences.is_empty(), "{name}: {differences:?} differ");
+    }
+
+    // 读取时会丢弃指向不存在的 Message / Signal 的属性值、注释、值描述和值类型，比较前先从原始文件中去掉
+    fn without_dangling_references(dbc: &Dbc) -> Dbc {
+        let mut dbc = dbc.clone();
+        let messages = dbc.messages.clone();
+        let has_message = |id: &MessageId| messages.iter().any(|m| m.id == *id);
+        let has_signal = |id: &MessageId, name: &str| {
+            messages
+                .iter()
+                .any(|m| m.id == *id && m.signals.iter().any(|s| s.name == name))
+        };
+        dbc.attribute_values_message
+            .retain(|v| has_message(&v.message_id));
+        dbc.attribute_values_signal
+            .retain(|v| has_signal(&v.message_id, &v.signal_name));
+        dbc.comments.retain(|c| match c {
+            Comment::Message { id, .. } => has_message(id),
+            Comment::Signal {
+                message_id, name, ..
+            } => has_signal(message_id, name),
+            _ => true,
+        });
+        dbc.value_descriptions.retain(|v| match v {
+            ValueDescription::Signal {
+                message_id, name, ..
+            } => has_signal(message_id, name),
+            ValueDescription::EnvironmentVariable { .. } => true,
+        });
+        dbc.signal_extended_value_type_list
+            .retain(|v| has_signal(&v.message_id, &v.signal_name));
+        dbc
+    }
+
+    #[test]
+    fn test_round_trip_samples() {
+        let samples = SAMPLES
+            .iter()
+            .chain([&("extra_sections", EXTRA_SECTIONS_DBC)]);
+        for (name, text) in samples {
+            let original = Dbc::try_from(*text).unwrap();
+            let written = EditableDbc::from_dbc(&original).to_string();
+            let reparsed = Dbc::try_from(written.as_str())
+                .unwrap_or_else(|e| panic!("{name} failed to re-parse: {e:?}\n{written}"));
+            assert_same_dbc(name, &without_dangling_references(&original), &reparsed);
+
+            // 第二次输出应当与第一次完全一致
+            assert_eq!(written, EditableDbc::from_dbc(&reparsed).to_string());
+        }
+    }
+
+    #[test]
+    fn test_write_extra_sections() {
+        let dbc = parse(EXTRA_SECTIONS_DBC);
+        assert_eq!(dbc.comment(), "Powertrain network");
+        assert_eq!(dbc.node_comment("Dash"), Some("Instrument cluster"));
+        assert_eq!(dbc.node_comment("Gateway"), None);
+        let message = dbc.get_message(256).unwrap();
+        assert_eq!(message.extra_transmitters(), &vec!["Engine", "Gateway"]);
+        assert_eq!(
+            message.signals()[0].extended_value_type(),
+            Some(SignalExtendedValueType::IEEEfloat32Bit)
+        );
+
+        let written = dbc.to_string();
+        assert!(written.contains("BO_TX_BU_ 256 : Engine,Gateway;\n"));
+        assert!(written.contains("CM_ \"Powertrain network\";\n"));
+        assert!(written.contains("CM_ BU_ Engine \"Engine control unit\";\n"));
+        assert!(written.contains("CM_ EV_ EnvMode \"Test mode\";\n"));
+        assert!(written.contains("SIG_VALTYPE_ 256 Speed : 1;\n"));
+        assert!(written.contains("SIG_VALTYPE_ 2147484160 Energy : 2;\n"));
+    }
+
+    // 能被 can-dbc 解析但无法保存的段落
+    const UNSUPPORTED_SECTIONS_DBC: &str = r#"VERSION "1.0"
+
+BS_: 500 : 12,34
+
+BU_: Engine Dash
+
+BO_ 256 EngineData: 8 Engine
+ SG_ Speed : 0|16@1+ (1,0) [0|0] "km/h" Dash
+ SG_ Torque : 16|16@1+ (1,0) [0|0] "Nm" Dash
+
+BO_ 512 Muxed: 8 Engine
+ SG_ Mux M : 0|8@1+ (1,0) [0|0] "" Dash
+ SG_ Sub m1M : 8|8@1+ (1,0) [0|0] "" Dash
+ SG_ Value m1 : 16|8@1+ (1,0) [0|0] "" Dash
+
+EV_ EnvMode: 0 [0|3] "" 1 7 DUMMY_NODE_VECTOR0 Engine;
+
+ENVVAR_DATA_ EnvMode: 4;
+
+CM_ "Network";
+
+BA_DEF_REL_ BU_SG_REL_  "RelAttr" INT 0 10;
+BA_DEF_DEF_REL_ "RelAttr" 0;
+BA_REL_ "RelAttr" BU_SG_REL_ Dash SG_ 256 Speed 3;
+
+VAL_ EnvMode 0 "Off" 1 "On" ;
+
+SIG_GROUP_ 256 Group1 1 : Speed Torque;
+
+SG_MUL_VAL_ 512 Sub Mux 1-1;
+SG_MUL_VAL_ 512 Value Sub 0-3, 5-5;
+"#;
+
+    #[test]
+    fn test_unsupported_sections_warnings() {
+        let (dbc, warnings) = FileFormat::Dbc
+            .parse_with_warnings(UNSUPPORTED_SECTIONS_DBC)
+            .unwrap();
+        assert_eq!(
+            warnings,
+            [
+                "1 signal groups (SIG_GROUP_) are not supported",
+                "2 extended multiplexing entries (SG_MUL_VAL_) are not supported",
+                "1 environment variable data entries (ENVVAR_DATA_) are not supported",
+                "1 environment variable value descriptions (VAL_) are not supported",
+                "3 relation attributes (BA_DEF_REL_ / BA_DEF_DEF_REL_ / BA_REL_) are not supported",
+                "Bit timing parameters (BS_) are not supported",
+            ]
+        );
+        // 保存为任何格式都会再次提示，KCD 还有自己无法表示的内容
+        for format in FileFormat::ALL {
+            let (_, written_warnings) = format.write_with_warnings(&dbc);
+            assert!(written_warnings.starts_with(&warnings), "{format}");
+        }
+
+        // 其余内容照常写出
+        let (written, _) = FileFormat::Dbc.write_with_warnings(&dbc);
+        let mut expected = Dbc::try_from(UNSUPPORTED_SECTIONS_DBC).unwrap();
+        expected.signal_groups.clear();
+        expected.extended_multiplex.clear();
+        expected.environment_variable_data.clear();
+        expected
+            .value_descriptions
+            .retain(|v| matches!(v, ValueDescription::Signal { .. }));
+        expected.relation_attribute_definitions.clear();
+        expected.relation_attribute_defaults.clear();
+        expected.relation_attribute_values.clear();
+        let reparsed = Dbc::try_from(written.as_str()).unwrap();
+        assert_same_dbc("unsupported_sections", &expected, &reparsed);
+        assert!(written.contains("BS_:\n"));
+    }
+
+    #[test]
+    fn test_extra_access_nodes_warning() {
+        let mut dbc = parse(EXTRA_SECTIONS_DBC);
+        dbc.environment_variables[0].access_nodes = vec!["Engine".into(), "Dash".into()];
+        let (written, warnings) = FileFormat::Dbc.write_with_warnings(&dbc);
+        assert!(written.contains("DUMMY_NODE_VECTOR0 Engine;\n"));
+        assert_eq!(
+            warnings,
+            ["Environment variable EnvMode: only the first access node is written"]
+        );
+        assert!(FileFormat::Json.write_with_warnings(&dbc).1.is_empty());
+    }
+
+    #[test]
+    fn test_write_signal_line() {
+        let dbc = parse(
+            r#"VERSION ""
+
+BS_:
+
+BU_: A B
+
+BO_ 2147484672 Ext: 8 A
+ SG_ Mux M : 0|4@1+ (1,0) [0|15] "" B
+ SG_ Speed m1 : 8|16@0- (0.25,-40) [-40|100.5] "km/h" A,B
+ SG_ Nested m2M : 24|8@1+ (1,0) [0|0] "" B
+
+CM_ BO_ 2147484672 "Extended message";
+CM_ SG_ 2147484672 Speed "Vehicle \"speed\"";
+"#,
+        );
+        let written = dbc.to_string();
+
+        assert!(written.contains("BU_: A B\n"));
+        assert!(written.contains("BO_ 2147484672 Ext: 8 A\n"));
+        assert!(written.contains(" SG_ Mux M : 0|4@1+ (1,0) [0|15] \"\" B\n"));
+        assert!(written.contains(" SG_ Speed m1 : 8|16@0- (0.25,-40) [-40|100.5] \"km/h\" A,B\n"));
+        assert!(written.contains(" SG_ Nested m2M : 24|8@1+ (1,0) [0|0] \"\" B\n"));
+        assert!(written.contains("CM_ BO_ 2147484672 \"Extended message\";\n"));
+        assert!(written.contains("CM_ SG_ 2147484672 Speed \"Vehicle \\\"speed\\\"\";\n"));
+
+        assert_equivalent(&dbc, &parse(&written));
+    }
+
+    #[test]
+    fn test_write_value_descriptions() {
+        let dbc = parse(
+            r#"VERSION ""
+
+BS_:
+
+BU_: A
+
+VAL_TABLE_ Gear 0 "P" 1 "R" 2 "N" 3 "D" ;
+
+BO_ 100 Status: 1 A
+ SG_ Gear : 0|4@1+ (1,0) [0|3] "" A
+ SG_ Fault : 4|1@1+ (1,0) [0|1] "" A
+
+VAL_ 100 Gear 0 "P" 1 "R" 2 "N" 3 "D" ;
+VAL_ 100 Fault 1 "Active \"now\"" 0 "Inactive" ;
+"#,
+        );
+        let written = dbc.to_string();
+
+        assert!(written.contains("VAL_TABLE_ Gear 0 \"P\" 1 \"R\" 2 \"N\" 3 \"D\" ;\n"));
+        assert!(written.contains("VAL_ 100 Gear 0 \"P\" 1 \"R\" 2 \"N\" 3 \"D\" ;\n"));
+        assert!(written.contains("VAL_ 100 Fault 1 \"Active \\\"now\\\"\" 0 \"Inactive\" ;\n"));
+
+        let reparsed = parse(&written);
+        assert_equivalent(&dbc, &reparsed);
+        assert_eq!(dbc.value_tables(), reparsed.value_tables());
+    }
+
+    #[test]
+    fn test_write_attributes() {
+        let dbc = parse(
+            r#"VERSION ""
+
+BS_:
+
+BU_: ECU1 ECU2
+
+BO_ 100 Status: 8 ECU1
+ SG_ Speed : 0|16@1+ (0.1,0) [0|6553.5] "km/h" ECU2
+
+EV_ EnvMode: 0 [0|3] "" 1 7 DUMMY_NODE_VECTOR0 ECU1;
+
+BA_DEF_  "BusType" STRING ;
+BA_DEF_ BU_  "NodeLayer" HEX 0 255;
+BA_DEF_ BO_  "GenMsgCycleTime" INT 0 65535;
+BA_DEF_ BO_  "VFrameFormat" ENUM  "StandardCAN","ExtendedCAN","StandardCAN_FD","ExtendedCAN_FD";
+BA_DEF_ SG_  "GenSigStartValue" FLOAT -1.5 1000;
+BA_DEF_ EV_  "EnvLevel" INT 0 10;
+BA_DEF_DEF_  "BusType" "CAN";
+BA_DEF_DEF_  "GenMsgCycleTime" 100;
+BA_DEF_DEF_  "VFrameFormat" "StandardCAN";
+BA_DEF_DEF_  "GenSigStartValue" 0;
+BA_ "BusType" "CAN FD";
+BA_ "NodeLayer" BU_ ECU1 16;
+BA_ "GenMsgCycleTime" BO_ 100 20;
+BA_ "VFrameFormat" BO_ 100 2;
+BA_ "GenSigStartValue" SG_ 100 Speed 12.5;
+BA_ "EnvLevel" EV_ EnvMode 3;
+"#,
+        );
+        assert_eq!(dbc.attribute_definitions().len(), 6);
+        assert_eq!(dbc.environment_variables().len(), 1);
+        assert_eq!(
+            dbc.attribute_value(&AttributeTarget::Message(100), "VFrameFormat"),
+            Some(&AttributeValue::Int(2))
+        );
+        // 默认值是整数，定义是 FLOAT，读取时统一为 Float
+        assert_eq!(
+            dbc.get_attribute_definition("GenSigStartValue")
+                .unwrap()
+                .default(),
+            Some(&AttributeValue::Float(0.0))
+        );
+
+        let written = dbc.to_string();
+        assert!(written.contains("EV_ EnvMode: 0 [0|3] \"\" 1 7 DUMMY_NODE_VECTOR0 ECU1;\n"));
+        assert!(written.contains("BA_DEF_  \"BusType\" STRING ;\n"));
+        assert!(written.contains("BA_DEF_ SG_  \"GenSigStartValue\" FLOAT -1.5 1000;\n"));
+        assert!(written.contains(
+            "BA_DEF_ BO_  \"VFrameFormat\" ENUM  \"StandardCAN\",\"ExtendedCAN\",\"StandardCAN_FD\",\"ExtendedCAN_FD\";\n"
+        ));
+        assert!(written.contains("BA_DEF_DEF_ \"BusType\" \"CAN\";\n"));
+        assert!(written.contains("BA_ \"BusType\" \"CAN FD\";\n"));
+        assert!(written.contains("BA_ \"NodeLayer\" BU_ ECU1 16;\n"));
+        assert!(written.contains("BA_ \"GenSigStartValue\" SG_ 100 Speed 12.5;\n"));
+        assert!(written.contains("BA_ \"EnvLevel\" EV_ EnvMode 3;\n"));
+
+        assert_equivalent(&dbc, &parse(&written));
+    }
+
+    #[test]
+    fn test_escape() {
+        let cases = [
+            ("plain", "plain"),
+            ("a \"b\"", "a \\\"b\\\""),
+            ("C:\\path\\", "C:\\\\path\\x5C"),
+            ("\\\"", "\\\\\\\""),
+            ("x5C\\x5C", "x5C\\\\x5C"),
+        ];
+        for (text, escaped) in cases {
+            assert_eq!(escape(text), escaped);
+            assert_eq!(unescape(escaped), text);
+        }
+        // 其他工具写出的转义序列保留原样
+        assert_eq!(unescape("line\\nbreak \\t"), "line\\nbreak \\t");
+    }
+
+    #[test]
+    fn test_round_trip_backslashes_and_quotes() {
+        let texts = [
+            "C:\\path\\",
+            "\\",
+            "\\\\",
+            "say \"hi\"",
+            "\\\"quoted\\\"",
+            "ends with quote\"",
+            "mixed \\n \\\" \" \\",
+        ];
+        for text in texts {
+            let mut dbc = parse(
+                r#"VERSION ""
+
+BS_:
+
+BU_: A
+
+BO_ 100 Status: 1 A
+ SG_ Flag : 0|1@1+ (1,0) [0|1] "" A
+
+VAL_TABLE_ Table 0 "Off" ;
+"#,
+            );
+            dbc.set_message_comment(100, text);
+            dbc.set_signal_comment(100, "Flag", text);
+            dbc.set_signal_unit(100, "Flag", text);
+            dbc.set_signal_value_descriptions(100, "Flag", vec![(1, text.to_string())]);
+            dbc.set_value_table_descriptions("Table", vec![(0, text.to_string())]);
+
+            let written = dbc.to_string();
+            let reparsed = EditableDbc::from_dbc(
+                &Dbc::try_from(written.as_str())
+                    .unwrap_or_else(|e| panic!("{text:?} failed to re-parse: {e:?}\n{written}")),
+            );
+            let message = reparsed.get_message(100).unwrap();
+            let signal = &message.signals()[0];
+            assert_eq!(message.comment(), text);
+            assert_eq!(signal.comment(), text);
+            assert_eq!(signal.unit(), text);
+            assert_eq!(signal.value_description(1), Some(text));
+            assert_eq!(
+                reparsed.get_value_table("Table").unwrap().descriptions()[0].1,
+                text
+            );
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use std::fmt::Display;
+
+use quick_xml::Reader;
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+
+// KCD 和 ARXML 共用的 XML 读写
+// 文件都不大，整个读成元素树后再转换，比直接处理事件流简单
+// 读取时去掉命名空间前缀，忽略注释、处理指令和 DOCTYPE
+
+/// XML 元素，名称和属性名都不带命名空间前缀
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(super) struct Element {
+    pub(super) name: String,
+    pub(super) attributes: Vec<(String, String)>,
+    pub(super) children: Vec<Element>,
+    // 元素直接包含的文本，去掉了首尾空白
+    pub(super) text: String,
+}
+
+impl Element {
+    pub(super) fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// 添加属性，用于输出
+    pub(super) fn with(mut self, name: &str, value: impl Display) -> Self {
+        self.attributes.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub(super) fn with_text(mut self, text: &str) -> Self {
+        self.text = text.to_string();
+        self
+    }
+
+    pub(super) fn push(&mut self, child: Element) {
+        self.children.push(child);
+    }
+
+    pub(super) fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// 第一个名为 name 的子元素
+    pub(super) fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    /// 第一个名为 name 的子元素的文本
+    pub(super) fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|c| c.text.as_str())
+    }
+
+    /// 深度优先查找第一个名为 name 的后代元素，不包括自己
+    pub(super) fn descendant(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find_map(|c| {
+            if c.name == name {
+                Some(c)
+            } else {
+                c.descendant(name)
+            }
+        })
+    }
+
+    /// 按文档顺序列出所有名为 name 的后代元素，不再进入匹配的元素内部
+    pub(super) fn descendants<'a>(&'a self, name: &str) -> Vec<&'a Element> {
+        let mut found = Vec::new();
+        for child in &self.children {
+            if child.name == name {
+                found.push(child);
+            } else {
+                found.extend(child.descendants(name));
+            }
+        }
+        found
+    }
+
+    /// 所有名为 name 的子元素
+    pub(super) fn children_named<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+}
+
+/// 解析 XML 文档，返回根元素
+pub(super) fn parse(text: &str) -> Result<Element, String> {
+    let mut reader = Reader::from_str(text);
+    reader.config_mut().trim_text(true);
+    let position_error = |position: u64, message: String| {
+        let consumed = &text[..(position as usize).min(text.len())];
+        let line = consumed.matches('\n').count() + 1;
+        let column = consumed.chars().rev().take_while(|c| *c != '\n').count() + 1;
+        format!("Invalid XML at line {line}, column {column}: {message}")
+    };
+
+    // 尚未结束的元素，最后一个是当前元素
+    let mut stack: Vec<Element> = Vec::new();
+    let mut root = None;
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| position_error(reader.error_position(), e.to_string()))?;
+        let closed = match event {
+            Event::Start(start) => {
+                stack.push(
+                    element(&start).map_err(|e| position_error(reader.buffer_position(), e))?,
+                );
+                None
+            }
+            Event::Empty(start) => {
+                Some(element(&start).map_err(|e| position_error(reader.buffer_position(), e))?)
+            }
+            Event::End(_) => stack.pop(),
+            Event::Text(content) => {
+                let content = content
+                    .unescape()
+                    .map_err(|e| position_error(reader.buffer_position(), e.to_string()))?;
+                if let Some(current) = stack.last_mut() {
+                    current.text.push_str(&content);
+                }
+                None
+            }
+            Event::CData(content) => {
+                if let Some(current) = stack.last_mut() {
+                    current
+                        .text
+                        .push_str(&String::from_utf8_lossy(&content.into_inner()));
+                }
+                None
+            }
+            Event::Eof => break,
+            _ => None,
+        };
+        if let Some(closed) = closed {
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(closed),
+                None if root.is_none() => root = Some(closed),
+                None => {
+                    return Err(position_error(
+                        reader.buffer_position(),
+                        "more than one root element".to_string(),
+                    ));
+                }
+            }
+        }
+    }
+    if let Some(open) = stack.last() {
+        return Err(position_error(
+            text.len() as u64,
+            format!("element <{}> is not closed", open.name),
+        ));
+    }
+    root.ok_or_else(|| "Invalid XML: the document has no root element".to_string())
+}
+
+fn element(start: &BytesStart) -> Result<Element, String> {
+    let mut element = Element::new(&String::from_utf8_lossy(start.local_name().as_ref()));
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(|e| e.to_string())?;
+        // 命名空间声明对转换没有意义
+        let key = attribute.key;
+        if key.as_namespace_binding().is_some() {
+            continue;
+        }
+        let value = attribute.unescape_value().map_err(|e| e.to_string())?;
+        element.attributes.push((
+            String::from_utf8_lossy(key.local_name().as_ref()).to_string(),
+            value.to_string(),
+        ));
+    }
+    Ok(element)
+}
+
+/// 输出 XML 文档，缩进两个空格
+pub(super) fn write(root: &Element) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    write_element(&mut out, root, 0);
+    out
+}
+
+fn write_element(out: &mut String, element: &Element, indent: usize) {
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+    out.push('<');
+    out.push_str(&element.name);
+    for (name, value) in &element.attributes {
+        out.push_str(&format!(" {name}=\"{}\"", escape(value)));
+    }
+    if element.children.is_empty() && element.text.is_empty() {
+        out.push_str("/>\n");
+        return;
+    }
+    out.push('>');
+    if element.children.is_empty() {
+        out.push_str(&escape(&element.text));
+    } else {
+        out.push('\n');
+        for child in &element.children {
+            write_element(out, child, indent + 1);
+        }
+        for _ in 0..indent {
+            out.push_str("  ");
+        }
+    }
+    out.push_str(&format!("</{}>\n", element.name));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_write() {
+        let text = r#"<?xml version="1.0"?>
+<!-- comment -->
+<ns:Root xmlns:ns="urn:x" ns:a="1 &amp; 2">
+  <Child b="x"/>
+  <Child>text &lt;here&gt;<![CDATA[ & raw]]></Child>
+</ns:Root>
+"#;
+        let root = parse(text).unwrap();
+        assert_eq!(root.name, "Root");
+        assert_eq!(
+            root.attributes,
+            vec![("a".to_string(), "1 & 2".to_string())]
+        );
+        assert_eq!(root.children_named("Child").count(), 2);
+        assert_eq!(root.child("Child").unwrap().attribute("b"), Some("x"));
+        assert_eq!(root.child_text("Child"), Some(""));
+        assert_eq!(root.children[1].text, "text <here> & raw");
+        let nested = parse("<a><b><c>1</c></b><c>2</c><d><c>3</c></d></a>").unwrap();
+        assert_eq!(nested.descendant("c").unwrap().text, "1");
+        let texts: Vec<&str> = nested
+            .descendants("c")
+            .iter()
+            .map(|c| c.text.as_str())
+            .collect();
+        assert_eq!(texts, ["1", "2", "3"]);
+
+        let written = write(&root);
+        assert!(written.contains("<Root a=\"1 &amp; 2\">\n  <Child b=\"x\"/>\n"));
+        assert_eq!(parse(&written).unwrap(), root);
+
+        let error = parse("<a>\n  <b></c>\n</a>").unwrap_err();
+        assert!(error.contains("line 2"), "{error}");
+        assert!(parse("<a>").unwrap_err().contains("not closed"));
+        assert!(parse("<a/><b/>").is_err());
+        assert!(parse("").is_err());
+    }
+}
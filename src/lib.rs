@@ -1,7 +1,24 @@
 //! Library entry for roxy-dbc to allow integration tests and external usage.
+//!
+//! `editable_dbc` is the pure data/IO core: model, codec, validation and serializers.
+//! `trace` reads recorded CAN logs and decodes them against an `EditableDbc`.
+//! `codegen` generates C and Rust message types with pack/unpack code from a DBC.
+//! `monitor` summarises received frames per ID; `socketcan` talks to Linux CAN interfaces.
+//! The GUI modules are only built with the default `gui` feature; depend on roxy-dbc
+//! with `default-features = false` to use the core without a graphics stack.
+#[cfg(feature = "gui")]
 pub mod app;
+pub mod codegen;
 pub mod editable_dbc;
+pub mod monitor;
+#[cfg(target_os = "linux")]
+pub mod socketcan;
+#[cfg(test)]
+mod test_util;
+pub mod trace;
+#[cfg(feature = "gui")]
 pub mod ui;
 
 // Re-export commonly used types at crate root if desired
+#[cfg(feature = "gui")]
 pub use crate::ui::UiState;
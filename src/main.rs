@@ -1,21 +1,26 @@
-mod app;
-mod editable_dbc;
-mod ui;
+mod cli;
+#[cfg(test)]
+mod test_util;
 
-use app::AppWindow;
+#[cfg(feature = "gui")]
+use roxy_dbc::{app::AppWindow, ui};
+#[cfg(feature = "gui")]
 use std::time::Instant;
+#[cfg(feature = "gui")]
 use winit::{
     application::ApplicationHandler,
     event::{Event, WindowEvent},
     event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
 };
 
+#[cfg(feature = "gui")]
 #[derive(Default)]
 struct App {
     window: Option<AppWindow>,
     ui_state: ui::UiState,
 }
 
+#[cfg(feature = "gui")]
 impl ApplicationHandler for App {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         self.window = Some(AppWindow::new(event_loop));
@@ -47,7 +52,8 @@ impl ApplicationHandler for App {
                     .surface
                     .configure(&window.device, &window.surface_desc);
             }
-            WindowEvent::CloseRequested => event_loop.exit(),
+            // 关闭前需要确认未保存的修改，真正的退出在下面检查 should_exit
+            WindowEvent::CloseRequested => self.ui_state.request_exit(),
             // WindowEvent::KeyboardInput { event, .. } => {
             //     if let Key::Named(NamedKey::Escape) = event.logical_key {
             //         if event.state.is_pressed() {
@@ -84,7 +90,7 @@ impl ApplicationHandler for App {
                 let ui = imgui.context.frame();
 
                 // 使用重构后的 UI 模块渲染界面
-                ui::render_ui(&ui, delta_s, imgui.target_frame_time, &mut self.ui_state);
+                ui::render_ui(ui, delta_s, imgui.target_frame_time, &mut self.ui_state);
 
                 let mut encoder: wgpu::CommandEncoder = window
                     .device
@@ -137,6 +143,10 @@ impl ApplicationHandler for App {
             &window.window,
             &Event::WindowEvent { window_id, event },
         );
+
+        if self.ui_state.should_exit {
+            event_loop.exit();
+        }
     }
 
     fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: ()) {
@@ -177,9 +187,28 @@ impl ApplicationHandler for App {
 }
 
 fn main() {
+    // 只有图形界面输出日志
+    #[cfg(feature = "gui")]
     env_logger::init();
 
+    // 带子命令时作为命令行工具运行，不创建窗口
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+
+    run_gui();
+}
+
+#[cfg(feature = "gui")]
+fn run_gui() {
     let event_loop = EventLoop::new().unwrap();
     event_loop.set_control_flow(ControlFlow::Wait); // 等待模式，降低CPU占用
     event_loop.run_app(&mut App::default()).unwrap();
 }
+
+// 没有启用 gui 特性时只能使用命令行
+#[cfg(not(feature = "gui"))]
+fn run_gui() {
+    std::process::exit(cli::run(&[]));
+}
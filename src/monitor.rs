@@ -0,0 +1,145 @@
+//! 实时监视 - 按 ID 汇总接收到的帧，记录每个 ID 最新的一帧、接收次数和周期
+
+use std::collections::BTreeMap;
+
+use crate::editable_dbc::EditableDbc;
+use crate::trace::{CanFrame, DecodedFrame, FrameKind};
+
+/// 一个 ID 的接收情况
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonitorEntry {
+    /// 最近收到的一帧
+    pub frame: CanFrame,
+    pub count: u64,
+    /// 最近两帧的时间间隔，单位为秒，只收到一帧时为 None
+    pub cycle_time: Option<f64>,
+    /// 按 DBC 解码最近一帧的结果，DBC 中没有的 ID 为 None
+    pub decoded: Option<DecodedFrame>,
+}
+
+/// 按 ID 汇总的接收结果，按 ID 排序，标准帧在扩展帧之前
+#[derive(Clone, Debug, Default)]
+pub struct Monitor {
+    entries: BTreeMap<(bool, u32), MonitorEntry>,
+    frame_count: u64,
+    error_count: u64,
+}
+
+impl Monitor {
+    /// 记录一帧，错误帧只计数
+    pub fn push(&mut self, frame: CanFrame, dbc: Option<&EditableDbc>) {
+        self.frame_count += 1;
+        if frame.kind == FrameKind::Error {
+            self.error_count += 1;
+            return;
+        }
+        let decoded = dbc.and_then(|dbc| frame.decode(dbc));
+        match self.entries.get_mut(&(frame.extended, frame.id)) {
+            Some(entry) => {
+                entry.cycle_time = Some(frame.timestamp - entry.frame.timestamp);
+                entry.count += 1;
+                entry.frame = frame;
+                entry.decoded = decoded;
+            }
+            None => {
+                self.entries.insert(
+                    (frame.extended, frame.id),
+                    MonitorEntry {
+                        frame,
+                        count: 1,
+                        cycle_time: None,
+                        decoded,
+                    },
+                );
+            }
+        }
+    }
+
+    /// DBC 改变后重新解码每个 ID 最近的一帧
+    pub fn redecode(&mut self, dbc: Option<&EditableDbc>) {
+        for entry in self.entries.values_mut() {
+            entry.decoded = dbc.and_then(|dbc| entry.frame.decode(dbc));
+        }
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &MonitorEntry> {
+        self.entries.values()
+    }
+
+    /// 收到的 ID 数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 收到的帧总数，包括错误帧
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn error_count(&self) -> u64 {
+        self.error_count
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use can_dbc::Dbc;
+
+    const DBC: &str = r#"VERSION ""
+
+BU_: ECU
+
+BO_ 256 Engine: 2 ECU
+ SG_ Speed : 0|16@1+ (0.5,0) [0|30000] "rpm" Vector__XXX
+"#;
+
+    fn frame(timestamp: f64, id: u32, data: &[u8]) -> CanFrame {
+        CanFrame {
+            timestamp,
+            id,
+            data: data.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_latest_value_per_id() {
+        let dbc = EditableDbc::from_dbc(&Dbc::try_from(DBC).unwrap());
+        let mut monitor = Monitor::default();
+        monitor.push(frame(1.0, 0x200, &[0]), Some(&dbc));
+        monitor.push(frame(1.0, 0x100, &[0xC8, 0]), Some(&dbc));
+        monitor.push(frame(1.1, 0x100, &[0x90, 1]), Some(&dbc));
+        monitor.push(
+            CanFrame {
+                kind: FrameKind::Error,
+                ..Default::default()
+            },
+            Some(&dbc),
+        );
+
+        assert_eq!(monitor.len(), 2);
+        assert_eq!((monitor.frame_count(), monitor.error_count()), (4, 1));
+        let entries: Vec<_> = monitor.entries().collect();
+        assert_eq!(entries[0].frame.id, 0x100);
+        assert_eq!(entries[0].count, 2);
+        assert!((entries[0].cycle_time.unwrap() - 0.1).abs() < 1e-9);
+        let decoded = entries[0].decoded.as_ref().unwrap();
+        assert_eq!(decoded.signals[0].to_string(), "Speed = 200 rpm");
+        assert_eq!(entries[1].cycle_time, None);
+        assert!(entries[1].decoded.is_none());
+
+        monitor.redecode(None);
+        assert!(monitor.entries().all(|e| e.decoded.is_none()));
+        monitor.clear();
+        assert!(monitor.is_empty());
+    }
+}
@@ -0,0 +1,491 @@
+//! SocketCAN - 在 Linux 上通过 CAN_RAW 套接字收发 CAN / CAN FD 报文
+//!
+//! 收到的帧转换为 `trace::CanFrame`，与日志文件使用同一个模型。
+//! 测试时可以使用虚拟接口：
+//!
+//! ```text
+//! sudo modprobe vcan
+//! sudo ip link add dev vcan0 type vcan
+//! sudo ip link set up vcan0
+//! ```
+
+use std::ffi::CString;
+use std::io;
+use std::mem;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::trace::{CanFrame, FrameKind};
+
+// 接收线程每隔这么长时间检查一次是否需要退出
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// 绑定到一个 CAN 接口的 CAN_RAW 套接字，同时接收 CAN 和 CAN FD 帧
+pub struct CanSocket {
+    fd: OwnedFd,
+    interface: String,
+}
+
+impl CanSocket {
+    /// 打开接口，如 `can0`、`vcan0`
+    pub fn open(interface: &str) -> Result<Self, String> {
+        let name =
+            CString::new(interface).map_err(|_| format!("Invalid interface name: {interface}"))?;
+        let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        if index == 0 {
+            return Err(format!("CAN interface not found: {interface}"));
+        }
+
+        let raw_fd = unsafe { libc::socket(libc::PF_CAN, libc::SOCK_RAW, libc::CAN_RAW) };
+        if raw_fd < 0 {
+            return Err(os_error("Failed to create CAN socket"));
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(raw_fd) };
+
+        // 不支持 CAN FD 的内核上只能收发经典 CAN 帧，忽略错误
+        let enable: libc::c_int = 1;
+        unsafe {
+            libc::setsockopt(
+                fd.as_raw_fd(),
+                libc::SOL_CAN_RAW,
+                libc::CAN_RAW_FD_FRAMES,
+                &enable as *const _ as *const libc::c_void,
+                mem::size_of::<libc::c_int>() as libc::socklen_t,
+            );
+        }
+
+        let mut address: libc::sockaddr_can = unsafe { mem::zeroed() };
+        address.can_family = libc::AF_CAN as libc::sa_family_t;
+        address.can_ifindex = index as libc::c_int;
+        let result = unsafe {
+            libc::bind(
+                fd.as_raw_fd(),
+                &address as *const _ as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_can>() as libc::socklen_t,
+            )
+        };
+        if result < 0 {
+            return Err(os_error(&format!("Failed to bind to {interface}")));
+        }
+        Ok(Self {
+            fd,
+            interface: interface.to_string(),
+        })
+    }
+
+    pub fn interface(&self) -> &str {
+        &self.interface
+    }
+
+    /// 设置 recv 的超时时间，None 表示一直等待
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> Result<(), String> {
+        let timeout = timeout.unwrap_or(Duration::ZERO);
+        let value = libc::timeval {
+            tv_sec: timeout.as_secs() as libc::time_t,
+            tv_usec: timeout.subsec_micros() as libc::suseconds_t,
+        };
+        let result = unsafe {
+            libc::setsockopt(
+                self.fd.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_RCVTIMEO,
+                &value as *const _ as *const libc::c_void,
+                mem::size_of::<libc::timeval>() as libc::socklen_t,
+            )
+        };
+        if result < 0 {
+            return Err(os_error("Failed to set read timeout"));
+        }
+        Ok(())
+    }
+
+    /// 接收一帧，超时返回 None
+    ///
+    /// 时间戳为接收时的系统时间（Unix 时间，单位为秒），与 candump -l 的日志一致
+    pub fn recv(&self) -> Result<Option<CanFrame>, String> {
+        let mut raw: libc::canfd_frame = unsafe { mem::zeroed() };
+        let size = unsafe {
+            libc::read(
+                self.fd.as_raw_fd(),
+                &mut raw as *mut _ as *mut libc::c_void,
+                libc::CANFD_MTU,
+            )
+        };
+        if size < 0 {
+            let error = io::Error::last_os_error();
+            return match error.kind() {
+                io::ErrorKind::WouldBlock
+                | io::ErrorKind::TimedOut
+                | io::ErrorKind::Interrupted => Ok(None),
+                _ => Err(format!("Failed to read from {}: {}", self.interface, error)),
+            };
+        }
+        let fd = match size as usize {
+            libc::CAN_MTU => false,
+            libc::CANFD_MTU => true,
+            other => return Err(format!("Unexpected CAN frame size: {other}")),
+        };
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |d| d.as_secs_f64());
+        let mut frame = frame_from_raw(&raw, fd);
+        frame.timestamp = timestamp;
+        frame.channel = self.interface.clone();
+        Ok(Some(frame))
+    }
+
+    /// 发送一帧，frame 的时间戳、通道和方向被忽略
+    pub fn send(&self, frame: &CanFrame) -> Result<(), String> {
+        let (raw, size) = frame_to_raw(frame)?;
+        let written = unsafe {
+            libc::write(
+                self.fd.as_raw_fd(),
+                &raw as *const _ as *const libc::c_void,
+                size,
+            )
+        };
+        if written < 0 {
+            return Err(os_error(&format!("Failed to send on {}", self.interface)));
+        }
+        if written as usize != size {
+            return Err(format!("Incomplete write on {}", self.interface));
+        }
+        Ok(())
+    }
+}
+
+fn os_error(context: &str) -> String {
+    format!("{}: {}", context, io::Error::last_os_error())
+}
+
+// 内核中的帧转换为 CanFrame，经典 CAN 帧的 len 字段即 DLC
+fn frame_from_raw(raw: &libc::canfd_frame, fd: bool) -> CanFrame {
+    let id = raw.can_id;
+    let mut frame = CanFrame {
+        fd,
+        ..Default::default()
+    };
+    if id & libc::CAN_ERR_FLAG != 0 {
+        frame.kind = FrameKind::Error;
+        frame.id = id & libc::CAN_ERR_MASK;
+    } else if id & libc::CAN_EFF_FLAG != 0 {
+        frame.extended = true;
+        frame.id = id & libc::CAN_EFF_MASK;
+    } else {
+        frame.id = id & libc::CAN_SFF_MASK;
+    }
+    if !fd && id & libc::CAN_RTR_FLAG != 0 {
+        frame.kind = FrameKind::Remote;
+        return frame;
+    }
+    let max_len = if fd {
+        libc::CANFD_MAX_DLEN
+    } else {
+        libc::CAN_MAX_DLEN
+    };
+    let len = (raw.len as usize).min(max_len);
+    frame.bit_rate_switch = fd && raw.flags as libc::c_int & libc::CANFD_BRS != 0;
+    frame.data = raw.data[..len].to_vec();
+    frame
+}
+
+// CanFrame 转换为内核中的帧，返回需要写入的字节数（CAN_MTU 或 CANFD_MTU）
+fn frame_to_raw(frame: &CanFrame) -> Result<(libc::canfd_frame, usize), String> {
+    let max_len = if frame.fd {
+        libc::CANFD_MAX_DLEN
+    } else {
+        libc::CAN_MAX_DLEN
+    };
+    if frame.data.len() > max_len {
+        return Err(format!(
+            "Payload of {} bytes does not fit in a{} frame",
+            frame.data.len(),
+            if frame.fd { " CAN FD" } else { " classic CAN" }
+        ));
+    }
+    let mut raw: libc::canfd_frame = unsafe { mem::zeroed() };
+    raw.can_id = match frame.kind {
+        FrameKind::Error => return Err("Error frames cannot be sent".to_string()),
+        FrameKind::Remote if frame.fd => {
+            return Err("CAN FD does not support remote frames".to_string());
+        }
+        FrameKind::Remote => libc::CAN_RTR_FLAG,
+        FrameKind::Data => 0,
+    };
+    if frame.extended {
+        if frame.id > libc::CAN_EFF_MASK {
+            return Err(format!("Invalid extended CAN ID: 0x{:X}", frame.id));
+        }
+        raw.can_id |= frame.id | libc::CAN_EFF_FLAG;
+    } else {
+        if frame.id > libc::CAN_SFF_MASK {
+            return Err(format!("Invalid standard CAN ID: 0x{:X}", frame.id));
+        }
+        raw.can_id |= frame.id;
+    }
+    raw.len = frame.data.len() as u8;
+    raw.data[..frame.data.len()].copy_from_slice(&frame.data);
+    if frame.fd {
+        if frame.bit_rate_switch {
+            raw.flags = libc::CANFD_BRS as u8;
+        }
+        Ok((raw, libc::CANFD_MTU))
+    } else {
+        Ok((raw, libc::CAN_MTU))
+    }
+}
+
+/// 在后台线程中接收一个接口上的帧，界面线程每一帧调用 poll 取出新收到的帧
+///
+/// 被 drop 时通知接收线程退出并等待它结束
+pub struct LiveReceiver {
+    interface: String,
+    frames: Receiver<Result<CanFrame, String>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl LiveReceiver {
+    /// 打开接口并开始接收，打开失败时直接返回错误
+    pub fn start(interface: &str) -> Result<Self, String> {
+        let socket = CanSocket::open(interface)?;
+        socket.set_read_timeout(Some(READ_TIMEOUT))?;
+        let (sender, frames) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread = thread::Builder::new()
+            .name(format!("socketcan-{interface}"))
+            .spawn(move || {
+                while !thread_stop.load(Ordering::Relaxed) {
+                    match socket.recv() {
+                        Ok(Some(frame)) => {
+                            if sender.send(Ok(frame)).is_err() {
+                                break;
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            let _ = sender.send(Err(e));
+                            break;
+                        }
+                    }
+                }
+            })
+            .map_err(|e| format!("Failed to start receive thread: {e}"))?;
+        Ok(Self {
+            interface: interface.to_string(),
+            frames,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn interface(&self) -> &str {
+        &self.interface
+    }
+
+    /// 取出目前收到的所有帧，不会阻塞
+    ///
+    /// 接收出错后线程退出，错误在收到的帧之后返回一次，之后返回空列表
+    pub fn poll(&self) -> Result<Vec<CanFrame>, String> {
+        let mut frames = Vec::new();
+        loop {
+            match self.frames.try_recv() {
+                Ok(Ok(frame)) => frames.push(frame),
+                Ok(Err(e)) => return Err(e),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => return Ok(frames),
+            }
+        }
+    }
+
+    /// 接收线程是否还在运行
+    pub fn is_running(&self) -> bool {
+        self.thread.as_ref().is_some_and(|t| !t.is_finished())
+    }
+}
+
+impl Drop for LiveReceiver {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// 在后台线程中按固定周期发送一帧，发送的内容可以随时更新
+///
+/// 按开始时间计算每次发送的时刻，周期不会因为发送耗时而累积误差。
+/// 发送出错时线程退出，错误通过 error 取得。被 drop 时停止发送
+pub struct PeriodicSender {
+    interface: String,
+    frame: Arc<Mutex<CanFrame>>,
+    error: Arc<Mutex<Option<String>>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl PeriodicSender {
+    /// 打开接口并立即发送第一帧，之后每隔 period 发送一次
+    pub fn start(interface: &str, frame: CanFrame, period: Duration) -> Result<Self, String> {
+        if period.is_zero() {
+            return Err("Cycle time must be greater than 0".to_string());
+        }
+        frame_to_raw(&frame)?;
+        let socket = CanSocket::open(interface)?;
+        let frame = Arc::new(Mutex::new(frame));
+        let error = Arc::new(Mutex::new(None));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let (thread_frame, thread_error, thread_stop) =
+            (frame.clone(), error.clone(), stop.clone());
+        let thread = thread::Builder::new()
+            .name(format!("socketcan-tx-{interface}"))
+            .spawn(move || {
+                let start = Instant::now();
+                let mut count: u32 = 0;
+                while !thread_stop.load(Ordering::Relaxed) {
+                    let next = start + period * count;
+                    let now = Instant::now();
+                    if now < next {
+                        // drop 时会 unpark，不需要等完整个周期
+                        thread::park_timeout(next - now);
+                        continue;
+                    }
+                    let frame = thread_frame.lock().unwrap().clone();
+                    if let Err(e) = socket.send(&frame) {
+                        *thread_error.lock().unwrap() = Some(e);
+                        break;
+                    }
+                    count += 1;
+                    // 落后超过一个周期时跳过错过的发送，不连续补发
+                    let elapsed = start.elapsed();
+                    if period * count < elapsed {
+                        count = (elapsed.as_nanos() / period.as_nanos()) as u32 + 1;
+                    }
+                }
+            })
+            .map_err(|e| format!("Failed to start send thread: {e}"))?;
+        Ok(Self {
+            interface: interface.to_string(),
+            frame,
+            error,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn interface(&self) -> &str {
+        &self.interface
+    }
+
+    /// 更新之后发送的帧，无法发送的帧被拒绝
+    pub fn set_frame(&self, frame: CanFrame) -> Result<(), String> {
+        frame_to_raw(&frame)?;
+        *self.frame.lock().unwrap() = frame;
+        Ok(())
+    }
+
+    /// 发送线程因为出错而退出时返回错误
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().unwrap().clone()
+    }
+}
+
+impl Drop for PeriodicSender {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(frame: &CanFrame) -> CanFrame {
+        let (raw, size) = frame_to_raw(frame).unwrap();
+        frame_from_raw(&raw, size == libc::CANFD_MTU)
+    }
+
+    #[test]
+    fn test_frame_conversion() {
+        let standard = CanFrame {
+            id: 0x123,
+            data: vec![1, 2, 3],
+            ..Default::default()
+        };
+        let (raw, size) = frame_to_raw(&standard).unwrap();
+        assert_eq!(size, libc::CAN_MTU);
+        assert_eq!((raw.can_id, raw.len), (0x123, 3));
+        assert_eq!(round_trip(&standard), standard);
+
+        let extended = CanFrame {
+            id: 0x18FEF100,
+            extended: true,
+            data: vec![0xFF; 8],
+            ..Default::default()
+        };
+        let (raw, _) = frame_to_raw(&extended).unwrap();
+        assert_eq!(raw.can_id, 0x18FEF100 | libc::CAN_EFF_FLAG);
+        assert_eq!(round_trip(&extended), extended);
+
+        let fd = CanFrame {
+            id: 0x321,
+            fd: true,
+            bit_rate_switch: true,
+            data: (0..64).collect(),
+            ..Default::default()
+        };
+        assert_eq!(frame_to_raw(&fd).unwrap().1, libc::CANFD_MTU);
+        assert_eq!(round_trip(&fd), fd);
+
+        let remote = CanFrame {
+            id: 0x7FF,
+            kind: FrameKind::Remote,
+            ..Default::default()
+        };
+        assert_eq!(round_trip(&remote), remote);
+    }
+
+    #[test]
+    fn test_frame_conversion_errors() {
+        let mut frame = CanFrame {
+            id: 0x800,
+            ..Default::default()
+        };
+        assert!(frame_to_raw(&frame).is_err());
+        frame.id = 0x100;
+        frame.data = vec![0; 9];
+        assert!(frame_to_raw(&frame).is_err());
+        frame.fd = true;
+        assert!(frame_to_raw(&frame).is_ok());
+        frame.kind = FrameKind::Error;
+        assert!(frame_to_raw(&frame).is_err());
+
+        // 内核中的错误帧带 CAN_ERR_FLAG
+        let mut raw: libc::canfd_frame = unsafe { mem::zeroed() };
+        raw.can_id = libc::CAN_ERR_FLAG | 0x4;
+        raw.len = 8;
+        let error = frame_from_raw(&raw, false);
+        assert_eq!((error.kind, error.id), (FrameKind::Error, 0x4));
+    }
+
+    #[test]
+    fn test_open_missing_interface() {
+        let error = CanSocket::open("roxy-missing0").err().unwrap();
+        assert!(error.contains("roxy-missing0"), "{error}");
+
+        let frame = CanFrame::default();
+        let period = Duration::from_millis(10);
+        assert!(PeriodicSender::start("roxy-missing0", frame.clone(), period).is_err());
+        assert!(PeriodicSender::start("roxy-missing0", frame, Duration::ZERO).is_err());
+    }
+}
@@ -0,0 +1,11 @@
+//! 测试共用的辅助函数，库和命令行程序的测试都包含这个文件
+
+use std::fs;
+use std::path::PathBuf;
+
+/// 创建并返回本进程的临时目录 `roxy-dbc-<pid>-<name>`，每个测试使用不同的 name，测试可以并行执行
+pub(crate) fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("roxy-dbc-{}-{name}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,355 @@
+//! 记录的 CAN 报文（trace）- 读取日志文件并按 DBC 解码
+//!
+//! 不同的日志格式解析为同一个 `Trace` 模型，解码时按 ID 和帧格式在 `EditableDbc` 中查找 Message。
+
+mod asc;
+mod candump;
+
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+use crate::editable_dbc::{EditableDbc, EditableMessage, FrameFormat};
+
+const CAN_ID_MASK: u32 = 0x1FFF_FFFF;
+
+/// 帧的类型
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FrameKind {
+    #[default]
+    Data,
+    Remote,
+    /// 错误帧，id 为错误类别
+    Error,
+}
+
+/// 收发方向，日志中没有记录时为 None
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Rx,
+    Tx,
+}
+
+/// 日志中的一帧
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CanFrame {
+    /// 时间戳，单位为秒
+    pub timestamp: f64,
+    /// 接口或通道名，如 `can0`、`1`
+    pub channel: String,
+    /// 不带扩展帧标志位的 ID
+    pub id: u32,
+    pub extended: bool,
+    pub kind: FrameKind,
+    pub direction: Option<Direction>,
+    pub fd: bool,
+    pub bit_rate_switch: bool,
+    /// 负载，远程帧为空
+    pub data: Vec<u8>,
+}
+
+/// 按 DBC 解码得到的一个信号
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceSignal {
+    pub name: String,
+    pub raw: i64,
+    pub value: f64,
+    pub unit: String,
+    /// 值描述（VAL_）中对应原始值的文本
+    pub description: Option<String>,
+}
+
+/// 按 DBC 解码得到的一帧
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedFrame {
+    /// DBC 中 Message 的 ID，与 `EditableMessage::message_id` 相同
+    pub message_id: u32,
+    pub message_name: String,
+    pub signals: Vec<TraceSignal>,
+}
+
+/// 信号在某一时刻的值
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sample {
+    /// 相对第一帧的时间，单位为秒
+    pub time: f64,
+    pub value: f64,
+    pub raw: i64,
+}
+
+/// 一个日志文件中的所有帧，按文件中的顺序
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Trace {
+    pub frames: Vec<CanFrame>,
+}
+
+/// 支持读取的日志格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// `candump -l` 输出的日志
+    Candump,
+    /// Vector CANoe / CANalyzer 的 ASC 日志
+    Asc,
+}
+
+impl TraceFormat {
+    /// 根据扩展名判断格式，不区分大小写
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("log") => Ok(TraceFormat::Candump),
+            Some("asc") => Ok(TraceFormat::Asc),
+            _ => Err(format!("Unsupported trace format: {}", path.display())),
+        }
+    }
+
+    /// 从字符串解析
+    pub fn parse(&self, contents: &str) -> Result<Trace, String> {
+        let frames = match self {
+            TraceFormat::Candump => candump::parse(contents)?,
+            TraceFormat::Asc => asc::parse(contents)?,
+        };
+        Ok(Trace { frames })
+    }
+}
+
+impl Display for TraceFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceFormat::Candump => write!(f, "candump log"),
+            TraceFormat::Asc => write!(f, "ASC"),
+        }
+    }
+}
+
+/// 读取日志文件，格式由扩展名决定
+pub fn load_trace(path: &Path) -> Result<Trace, String> {
+    let format = TraceFormat::from_path(path)?;
+    let bytes =
+        fs::read(path).map_err(|e| format!("Failed to open file: {}: {}", path.display(), e))?;
+    format
+        .parse(&String::from_utf8_lossy(&bytes))
+        .map_err(|e| format!("Failed to parse {}: {}: {}", format, path.display(), e))
+}
+
+impl CanFrame {
+    /// 按 DBC 中 Message 的 ID、帧格式和 CAN FD 标志构造一帧数据帧
+    pub fn for_message(message: &EditableMessage, data: Vec<u8>) -> Self {
+        CanFrame {
+            id: message.message_id() & CAN_ID_MASK,
+            extended: message.frame_format() == FrameFormat::Extended,
+            fd: message.is_fd(),
+            bit_rate_switch: message.is_fd() && message.bit_rate_switch(),
+            data,
+            ..Default::default()
+        }
+    }
+
+    /// 帧格式与 DBC 中的 Message 对应
+    pub fn frame_format(&self) -> FrameFormat {
+        if self.extended {
+            FrameFormat::Extended
+        } else {
+            FrameFormat::Standard
+        }
+    }
+
+    /// 在 DBC 中查找这一帧对应的 Message，错误帧和未定义的 ID 返回 None
+    pub fn find_message<'a>(&self, dbc: &'a EditableDbc) -> Option<&'a EditableMessage> {
+        if self.kind == FrameKind::Error {
+            return None;
+        }
+        // 从 DBC 读入的扩展帧 ID 带有最高位标记，比较前去掉
+        dbc.messages().iter().find(|m| {
+            m.frame_format() == self.frame_format() && m.message_id() & CAN_ID_MASK == self.id
+        })
+    }
+
+    /// 按 DBC 解码，未定义的 ID 返回 None
+    ///
+    /// 远程帧没有负载，只得到 Message 名称
+    pub fn decode(&self, dbc: &EditableDbc) -> Option<DecodedFrame> {
+        let message = self.find_message(dbc)?;
+        let signals = message
+            .decode(&self.data)
+            .into_iter()
+            .filter_map(|decoded| {
+                let signal = message
+                    .signals()
+                    .iter()
+                    .find(|s| s.name() == decoded.name)?;
+                Some(TraceSignal {
+                    unit: signal.unit().to_string(),
+                    description: signal.value_description(decoded.raw).map(str::to_string),
+                    name: decoded.name,
+                    raw: decoded.raw,
+                    value: decoded.value,
+                })
+            })
+            .collect();
+        Some(DecodedFrame {
+            message_id: message.message_id(),
+            message_name: message.message_name().to_string(),
+            signals,
+        })
+    }
+
+    /// 负载的十六进制文本，字节之间用空格分隔
+    pub fn data_hex(&self) -> String {
+        self.data
+            .iter()
+            .map(|b| format!("{b:02X}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl Display for TraceSignal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.name, self.value)?;
+        if !self.unit.is_empty() {
+            write!(f, " {}", self.unit)?;
+        }
+        if let Some(description) = &self.description {
+            write!(f, " ({description})")?;
+        }
+        Ok(())
+    }
+}
+
+impl Trace {
+    /// 第一帧的时间戳，界面上显示相对时间
+    pub fn start_time(&self) -> f64 {
+        self.frames.first().map_or(0.0, |f| f.timestamp)
+    }
+
+    /// 解码所有帧，结果与 frames 一一对应
+    pub fn decode(&self, dbc: &EditableDbc) -> Vec<Option<DecodedFrame>> {
+        self.frames.iter().map(|f| f.decode(dbc)).collect()
+    }
+
+    /// 从 decode 的结果中取出一个信号的所有值，按时间排序
+    ///
+    /// 被复用的信号只在复用器取对应值的帧中有值
+    pub fn signal_samples(
+        &self,
+        decoded: &[Option<DecodedFrame>],
+        message_id: u32,
+        signal_name: &str,
+    ) -> Vec<Sample> {
+        let start_time = self.start_time();
+        let mut samples: Vec<Sample> = self
+            .frames
+            .iter()
+            .zip(decoded)
+            .filter_map(|(frame, decoded)| {
+                let decoded = decoded.as_ref().filter(|d| d.message_id == message_id)?;
+                let signal = decoded.signals.iter().find(|s| s.name == signal_name)?;
+                Some(Sample {
+                    time: frame.timestamp - start_time,
+                    value: signal.value,
+                    raw: signal.raw,
+                })
+            })
+            .collect();
+        // 多通道合并的日志时间戳可能不是单调的
+        samples.sort_by(|a, b| a.time.total_cmp(&b.time));
+        samples
+    }
+}
+
+/// 某一时刻信号的值，即该时刻之前（含）的最后一个值
+pub fn sample_at(samples: &[Sample], time: f64) -> Option<&Sample> {
+    let count = samples.partition_point(|s| s.time <= time);
+    count.checked_sub(1).map(|i| &samples[i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use can_dbc::Dbc;
+
+    const DBC: &str = r#"VERSION ""
+
+BU_: ECU
+
+BO_ 256 Engine: 4 ECU
+ SG_ Speed : 0|16@1+ (0.5,0) [0|30000] "rpm" Vector__XXX
+ SG_ State : 16|8@1+ (1,0) [0|2] "" Vector__XXX
+
+BO_ 2566844672 Extended: 1 ECU
+ SG_ Flag : 0|1@1+ (1,0) [0|1] "" Vector__XXX
+
+VAL_ 256 State 0 "Off" 1 "Running" 2 "Fault" ;
+"#;
+
+    #[test]
+    fn test_decode_frames() {
+        let dbc = EditableDbc::from_dbc(&Dbc::try_from(DBC).unwrap());
+        let trace = TraceFormat::Candump
+            .parse(
+                "(1.000000) vcan0 100#C8000100\n\
+                 (1.500000) vcan0 18FEF100#01\n\
+                 (2.000000) vcan0 200#00\n\
+                 (2.500000) vcan0 100#R\n",
+            )
+            .unwrap();
+        let decoded = trace.decode(&dbc);
+
+        let engine = decoded[0].as_ref().unwrap();
+        assert_eq!(engine.message_name, "Engine");
+        assert_eq!(engine.signals[0].to_string(), "Speed = 100 rpm");
+        assert_eq!(engine.signals[1].to_string(), "State = 1 (Running)");
+        assert_eq!(decoded[1].as_ref().unwrap().message_name, "Extended");
+        assert!(decoded[2].is_none());
+        // 远程帧没有负载，只能得到 Message
+        let remote = decoded[3].as_ref().unwrap();
+        assert!(remote.signals.is_empty());
+        assert_eq!(trace.start_time(), 1.0);
+
+        // 构造的帧可以按同一个 DBC 解码回来
+        let extended = dbc.get_message(0x8000_0000 | 0x18FEF100).unwrap();
+        let frame = CanFrame::for_message(extended, vec![1]);
+        assert_eq!((frame.id, frame.extended), (0x18FEF100, true));
+        assert_eq!(frame.decode(&dbc).unwrap().signals[0].raw, 1);
+    }
+
+    #[test]
+    fn test_signal_samples() {
+        let dbc = EditableDbc::from_dbc(&Dbc::try_from(DBC).unwrap());
+        let trace = TraceFormat::Candump
+            .parse(
+                "(10.0) vcan0 100#C8000100\n\
+                 (10.5) vcan0 200#00\n\
+                 (11.0) vcan0 100#90010200\n",
+            )
+            .unwrap();
+        let decoded = trace.decode(&dbc);
+        let samples = trace.signal_samples(&decoded, 256, "State");
+        assert_eq!(samples.len(), 2);
+        assert_eq!((samples[0].time, samples[0].raw), (0.0, 1));
+        assert_eq!((samples[1].time, samples[1].value), (1.0, 2.0));
+        assert!(trace.signal_samples(&decoded, 256, "Missing").is_empty());
+
+        assert_eq!(sample_at(&samples, -0.1), None);
+        assert_eq!(sample_at(&samples, 0.0).unwrap().raw, 1);
+        assert_eq!(sample_at(&samples, 0.9).unwrap().raw, 1);
+        assert_eq!(sample_at(&samples, 5.0).unwrap().raw, 2);
+    }
+
+    #[test]
+    fn test_trace_format_from_path() {
+        assert_eq!(
+            TraceFormat::from_path(Path::new("candump-2024-01-01.LOG")),
+            Ok(TraceFormat::Candump)
+        );
+        assert_eq!(
+            TraceFormat::from_path(Path::new("recording.asc")),
+            Ok(TraceFormat::Asc)
+        );
+        assert!(TraceFormat::from_path(Path::new("trace.txt")).is_err());
+    }
+}
@@ -0,0 +1,286 @@
+use super::{CanFrame, Direction, FrameKind};
+
+// Vector ASC 日志（CANoe / CANalyzer 导出），只读取 CAN 和 CAN FD 的报文和错误帧：
+//   date Wed Jan 10 10:12:38.123 am 2024
+//   base hex  timestamps absolute
+//   Begin Triggerblock Wed Jan 10 10:12:38.123 am 2024
+//      0.015991 1  123             Rx   d 8 01 02 03 04 05 06 07 08  Length = 224000 BitCount = 141
+//      0.016000 1  18FEF100x       Tx   d 1 FF
+//      0.017000 1  123             Rx   r 8
+//      0.020000 1  ErrorFrame
+//      0.030000 CANFD   1 Rx        321  EngineData  1 0 9 12 00 01 02 03 04 05 06 07 08 09 0A 0B ...
+//      0.040000 CANFD   1 Rx        ErrorFrame ...
+//   End TriggerBlock
+// "base dec" 时 ID 和负载都是十进制；"timestamps relative" 时每个时间戳是相对上一个事件的增量
+// 其他事件（Start of measurement、Statistic、Chip status、注释等）被忽略
+
+const CAN_EFF_MASK: u32 = 0x1FFF_FFFF;
+const CAN_SFF_MASK: u32 = 0x7FF;
+
+/// 解析文件头中的 base 和 timestamps 设置
+struct Settings {
+    radix: u32,
+    relative: bool,
+}
+
+/// 解析整个日志，出错时报告行号
+pub fn parse(contents: &str) -> Result<Vec<CanFrame>, String> {
+    let mut settings = Settings {
+        radix: 16,
+        relative: false,
+    };
+    let mut frames = Vec::new();
+    // 相对时间戳模式下累加的当前时间
+    let mut time = 0.0;
+
+    for (i, line) in contents.lines().enumerate() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some(first) = tokens.first() else {
+            continue;
+        };
+        if *first == "base" {
+            settings = parse_settings(&tokens).map_err(|e| format!("line {}: {}", i + 1, e))?;
+            continue;
+        }
+        let Ok(timestamp) = first.parse::<f64>() else {
+            // 文件头、Begin/End Triggerblock 和注释
+            continue;
+        };
+        time = if settings.relative {
+            time + timestamp
+        } else {
+            timestamp
+        };
+
+        let frame = if tokens.get(1) == Some(&"CANFD") {
+            parse_fd_event(&tokens[2..], settings.radix)
+        } else {
+            parse_can_event(&tokens[1..], settings.radix)
+        };
+        match frame {
+            Ok(Some(mut frame)) => {
+                frame.timestamp = time;
+                frames.push(frame);
+            }
+            Ok(None) => {}
+            Err(e) => return Err(format!("line {}: {}", i + 1, e)),
+        }
+    }
+    Ok(frames)
+}
+
+// "base hex  timestamps absolute"
+fn parse_settings(tokens: &[&str]) -> Result<Settings, String> {
+    let radix = match tokens.get(1) {
+        Some(&"hex") => 16,
+        Some(&"dec") => 10,
+        _ => return Err(format!("Unsupported number base: {}", tokens.join(" "))),
+    };
+    let relative = match (tokens.get(2), tokens.get(3)) {
+        (Some(&"timestamps"), Some(&"relative")) => true,
+        (Some(&"timestamps"), Some(&"absolute")) | (None, None) => false,
+        _ => return Err(format!("Unsupported timestamp mode: {}", tokens.join(" "))),
+    };
+    Ok(Settings { radix, relative })
+}
+
+// 经典 CAN 事件，tokens 从通道号开始
+// 第二个字段不是 ErrorFrame 或 ID 时不是报文（如 Statistic:），返回 None
+fn parse_can_event(tokens: &[&str], radix: u32) -> Result<Option<CanFrame>, String> {
+    let (Some(channel), Some(second)) = (tokens.first(), tokens.get(1)) else {
+        return Ok(None);
+    };
+    if channel.parse::<u32>().is_err() {
+        return Ok(None);
+    }
+    if *second == "ErrorFrame" {
+        return Ok(Some(CanFrame {
+            channel: channel.to_string(),
+            kind: FrameKind::Error,
+            ..Default::default()
+        }));
+    }
+    let Some((id, extended)) = parse_id(second, radix) else {
+        return Ok(None);
+    };
+
+    let mut frame = CanFrame {
+        channel: channel.to_string(),
+        id,
+        extended,
+        direction: Some(parse_direction(tokens.get(2))?),
+        ..Default::default()
+    };
+    match tokens.get(3) {
+        Some(&"d") => {
+            let length = parse_length(tokens.get(4), 8)?;
+            frame.data = parse_data(&tokens[5..], length, radix)?;
+        }
+        Some(&"r") => frame.kind = FrameKind::Remote,
+        other => return Err(format!("Expected 'd' or 'r', found {:?}", other)),
+    }
+    Ok(Some(frame))
+}
+
+// CAN FD 事件，tokens 从通道号开始：
+// <channel> <dir> <id> [symbolic name] <brs> <esi> <dlc> <data length> <data>...
+fn parse_fd_event(tokens: &[&str], radix: u32) -> Result<Option<CanFrame>, String> {
+    let channel = tokens
+        .first()
+        .ok_or_else(|| "Missing CAN FD channel".to_string())?;
+    let direction = parse_direction(tokens.get(1))?;
+    let id_text = tokens
+        .get(2)
+        .ok_or_else(|| "Missing CAN FD ID".to_string())?;
+    if *id_text == "ErrorFrame" {
+        return Ok(Some(CanFrame {
+            channel: channel.to_string(),
+            kind: FrameKind::Error,
+            direction: Some(direction),
+            fd: true,
+            ..Default::default()
+        }));
+    }
+    let (id, extended) =
+        parse_id(id_text, radix).ok_or_else(|| format!("Invalid CAN ID: {id_text}"))?;
+
+    // 符号名是可选的，BRS 字段只能是 0 或 1
+    let mut rest = &tokens[3..];
+    if rest.first().is_some_and(|t| *t != "0" && *t != "1") {
+        rest = &rest[1..];
+    }
+    let bit_rate_switch = match rest.first() {
+        Some(&"1") => true,
+        Some(&"0") => false,
+        other => return Err(format!("Invalid BRS flag: {:?}", other)),
+    };
+    // rest[1] 是 ESI，rest[2] 是 DLC 编码，rest[3] 是实际的字节数
+    let length = parse_length(rest.get(3), 64)?;
+    let data = parse_data(rest.get(4..).unwrap_or_default(), length, radix)?;
+    Ok(Some(CanFrame {
+        channel: channel.to_string(),
+        id,
+        extended,
+        direction: Some(direction),
+        fd: true,
+        bit_rate_switch,
+        data,
+        ..Default::default()
+    }))
+}
+
+// 扩展帧的 ID 以 x 结尾
+fn parse_id(text: &str, radix: u32) -> Option<(u32, bool)> {
+    let (digits, extended) = match text.strip_suffix(['x', 'X']) {
+        Some(digits) => (digits, true),
+        None => (text, false),
+    };
+    let id = u32::from_str_radix(digits, radix).ok()?;
+    let max = if extended { CAN_EFF_MASK } else { CAN_SFF_MASK };
+    (id <= max).then_some((id, extended))
+}
+
+fn parse_direction(token: Option<&&str>) -> Result<Direction, String> {
+    match token {
+        Some(&"Rx") => Ok(Direction::Rx),
+        Some(&"Tx") | Some(&"TxRq") => Ok(Direction::Tx),
+        other => Err(format!("Expected Rx or Tx, found {:?}", other)),
+    }
+}
+
+// DLC / 数据长度字段总是十进制，经典 CAN 的 DLC 为 0-8
+fn parse_length(token: Option<&&str>, max: usize) -> Result<usize, String> {
+    token
+        .and_then(|t| t.parse::<usize>().ok())
+        .filter(|&length| length <= max)
+        .ok_or_else(|| format!("Invalid data length: {:?}", token))
+}
+
+fn parse_data(tokens: &[&str], length: usize, radix: u32) -> Result<Vec<u8>, String> {
+    if tokens.len() < length {
+        return Err(format!(
+            "Expected {} data bytes, found {}",
+            length,
+            tokens.len()
+        ));
+    }
+    tokens[..length]
+        .iter()
+        .map(|t| u8::from_str_radix(t, radix).map_err(|_| format!("Invalid data byte: {t}")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASC: &str = "\
+date Wed Jan 10 10:12:38.123 am 2024
+base hex  timestamps absolute
+internal events logged
+// version 13.0.0
+Begin Triggerblock Wed Jan 10 10:12:38.123 am 2024
+   0.000000 Start of measurement
+   0.015991 1  123             Rx   d 8 01 02 03 04 05 06 07 08  Length = 224000 BitCount = 141 ID = 291
+   0.016000 2  18FEF100x       Tx   d 1 FF
+   0.017000 1  123             Rx   r 8
+   0.018000 1  Statistic: D 0 R 0 XD 0 XR 0 E 0 O 0 B 0.00%
+   0.020000 1  ErrorFrame
+   0.030000 CANFD   1 Rx        321  EngineData  1 0 9 12 00 01 02 03 04 05 06 07 08 09 0A 0B  130000 130 0 0 0 0 0
+   0.040000 CANFD   1 Tx        1ABx                0 0 8 8 11 22 33 44 55 66 77 88  130000 130 0 0 0 0 0
+End TriggerBlock
+";
+
+    #[test]
+    fn test_parse_events() {
+        let frames = parse(ASC).unwrap();
+        assert_eq!(frames.len(), 6);
+
+        assert_eq!(frames[0].timestamp, 0.015991);
+        assert_eq!((frames[0].id, frames[0].extended), (0x123, false));
+        assert_eq!(frames[0].data, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(frames[0].direction, Some(Direction::Rx));
+
+        assert_eq!(frames[1].channel, "2");
+        assert_eq!((frames[1].id, frames[1].extended), (0x18FEF100, true));
+        assert_eq!(frames[1].direction, Some(Direction::Tx));
+
+        assert_eq!(frames[2].kind, FrameKind::Remote);
+        assert!(frames[2].data.is_empty());
+
+        assert_eq!(frames[3].kind, FrameKind::Error);
+
+        assert!(frames[4].fd && frames[4].bit_rate_switch);
+        assert_eq!(frames[4].id, 0x321);
+        assert_eq!(frames[4].data.len(), 12);
+        assert_eq!(frames[4].data[11], 0x0B);
+
+        assert!(frames[5].fd && !frames[5].bit_rate_switch);
+        assert_eq!((frames[5].id, frames[5].extended), (0x1AB, true));
+        assert_eq!(frames[5].data[0], 0x11);
+    }
+
+    #[test]
+    fn test_decimal_base_and_relative_timestamps() {
+        let frames = parse(
+            "base dec  timestamps relative\n\
+             Begin Triggerblock\n\
+             \x20  1.000000 1  291  Rx   d 2 255 16\n\
+             \x20  0.500000 1  291  Rx   d 2 0 1\n\
+             End TriggerBlock\n",
+        )
+        .unwrap();
+        assert_eq!(frames[0].id, 0x123);
+        assert_eq!(frames[0].data, vec![0xFF, 0x10]);
+        assert_eq!(frames[1].timestamp, 1.5);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = parse("base hex  timestamps absolute\n 0.1 1 123 Rx d 4 01 02\n").unwrap_err();
+        assert!(error.starts_with("line 2:"), "{error}");
+        assert!(parse("base oct  timestamps absolute\n").is_err());
+        assert!(parse(" 0.1 1 123 Xx d 1 01\n").is_err());
+        assert!(parse(" 0.1 CANFD 1 Rx 123 1 0 8 8 00\n").is_err());
+    }
+}
@@ -0,0 +1,165 @@
+use super::{CanFrame, Direction, FrameKind};
+
+// candump -l 的日志格式，每行一帧：
+//   (1436509052.249713) vcan0 123#DEADBEEF
+//   (1436509052.249713) vcan0 12345678#DEADBEEF       扩展帧，ID 固定 8 位十六进制
+//   (1436509052.249713) vcan0 123#R                   远程帧，R 之后可以带长度
+//   (1436509052.249713) vcan0 123##1DEADBEEF          CAN FD，## 后一位是标志（bit0 = BRS, bit1 = ESI）
+//   (1436509052.249713) vcan0 20000080#0000000000000000  错误帧，ID 带 CAN_ERR_FLAG
+// -x 选项会在行尾加上 R / T 表示收发方向
+// 负载中可以有 '.' 分隔字节，空行被忽略
+
+const CAN_ERR_FLAG: u32 = 0x2000_0000;
+const CAN_EFF_MASK: u32 = 0x1FFF_FFFF;
+const CAN_SFF_MASK: u32 = 0x7FF;
+const CANFD_BRS: u8 = 0x01;
+
+/// 解析整个日志，出错时报告行号
+pub fn parse(contents: &str) -> Result<Vec<CanFrame>, String> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse_line(line).map_err(|e| format!("line {}: {}", i + 1, e)))
+        .collect()
+}
+
+fn parse_line(line: &str) -> Result<CanFrame, String> {
+    let mut fields = line.split_whitespace();
+    let (Some(timestamp), Some(channel), Some(frame)) =
+        (fields.next(), fields.next(), fields.next())
+    else {
+        return Err(format!("Expected '(timestamp) interface frame': {line}"));
+    };
+    let timestamp = timestamp
+        .strip_prefix('(')
+        .and_then(|t| t.strip_suffix(')'))
+        .and_then(|t| t.parse::<f64>().ok())
+        .ok_or_else(|| format!("Invalid timestamp: {timestamp}"))?;
+    let direction = match fields.next() {
+        None => None,
+        Some("R") => Some(Direction::Rx),
+        Some("T") => Some(Direction::Tx),
+        Some(other) => return Err(format!("Unexpected field: {other}")),
+    };
+
+    let mut can_frame = parse_frame(frame)?;
+    can_frame.timestamp = timestamp;
+    can_frame.channel = channel.to_string();
+    can_frame.direction = direction;
+    Ok(can_frame)
+}
+
+// 解析 "ID#DATA" 部分
+fn parse_frame(frame: &str) -> Result<CanFrame, String> {
+    let (id_text, rest) = frame
+        .split_once('#')
+        .ok_or_else(|| format!("Missing '#' in frame: {frame}"))?;
+    let raw_id =
+        u32::from_str_radix(id_text, 16).map_err(|_| format!("Invalid CAN ID: {id_text}"))?;
+    let mut can_frame = match id_text.len() {
+        3 if raw_id <= CAN_SFF_MASK => CanFrame {
+            id: raw_id,
+            ..Default::default()
+        },
+        8 if raw_id & CAN_ERR_FLAG != 0 => CanFrame {
+            id: raw_id & CAN_EFF_MASK,
+            kind: FrameKind::Error,
+            ..Default::default()
+        },
+        8 => CanFrame {
+            id: raw_id & CAN_EFF_MASK,
+            extended: true,
+            ..Default::default()
+        },
+        _ => return Err(format!("Invalid CAN ID: {id_text}")),
+    };
+
+    if let Some(fd) = rest.strip_prefix('#') {
+        let mut chars = fd.chars();
+        let flags = chars
+            .next()
+            .and_then(|c| c.to_digit(16))
+            .ok_or_else(|| format!("Missing CAN FD flags: {frame}"))?;
+        can_frame.fd = true;
+        can_frame.bit_rate_switch = flags as u8 & CANFD_BRS != 0;
+        can_frame.data = parse_data(chars.as_str())?;
+    } else if let Some(length) = rest.strip_prefix(['R', 'r']) {
+        // 远程帧只有请求的长度，没有负载
+        if !length.is_empty() && length.parse::<u8>().is_err() {
+            return Err(format!("Invalid remote frame length: {frame}"));
+        }
+        can_frame.kind = FrameKind::Remote;
+    } else {
+        can_frame.data = parse_data(rest)?;
+    }
+
+    let max_len = if can_frame.fd { 64 } else { 8 };
+    if can_frame.data.len() > max_len {
+        return Err(format!("Payload longer than {max_len} bytes: {frame}"));
+    }
+    Ok(can_frame)
+}
+
+fn parse_data(text: &str) -> Result<Vec<u8>, String> {
+    let digits: Vec<char> = text.chars().filter(|&c| c != '.').collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("Odd number of hex digits in payload: {text}"));
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let byte: String = pair.iter().collect();
+            u8::from_str_radix(&byte, 16).map_err(|_| format!("Invalid payload byte: {byte}"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_frames() {
+        let frames = parse(
+            "(1436509052.249713) vcan0 123#DEADBEEF\n\
+             \n\
+             (1436509052.250000) can1 1ABCDEF0#11.22.33\n\
+             (1436509052.260000) vcan0 7FF#R8 T\n\
+             (1436509052.270000) vcan0 321##1000102030405060708\n\
+             (1436509052.280000) vcan0 20000004#0004000000000000 R\n",
+        )
+        .unwrap();
+        assert_eq!(frames.len(), 5);
+
+        assert_eq!(frames[0].timestamp, 1436509052.249713);
+        assert_eq!(frames[0].channel, "vcan0");
+        assert_eq!((frames[0].id, frames[0].extended), (0x123, false));
+        assert_eq!(frames[0].data, vec![0xDE, 0xAD, 0xBE, 0xEF]);
+
+        assert_eq!((frames[1].id, frames[1].extended), (0x1ABCDEF0, true));
+        assert_eq!(frames[1].data, vec![0x11, 0x22, 0x33]);
+
+        assert_eq!(frames[2].kind, FrameKind::Remote);
+        assert_eq!(frames[2].direction, Some(Direction::Tx));
+        assert!(frames[2].data.is_empty());
+
+        assert!(frames[3].fd && frames[3].bit_rate_switch);
+        assert_eq!(frames[3].data.len(), 9);
+
+        assert_eq!(frames[4].kind, FrameKind::Error);
+        assert_eq!(frames[4].id, 0x4);
+        assert_eq!(frames[4].direction, Some(Direction::Rx));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = parse("(1.0) vcan0 123#00\n(2.0) vcan0 123#0\n").unwrap_err();
+        assert!(error.starts_with("line 2:"), "{error}");
+        assert!(parse("1.0 vcan0 123#00").is_err());
+        assert!(parse("(1.0) vcan0 1234#00").is_err());
+        assert!(parse("(1.0) vcan0 800#00").is_err());
+        assert!(parse("(1.0) vcan0 123#000102030405060708").is_err());
+        assert!(parse("(1.0) vcan0").is_err());
+    }
+}
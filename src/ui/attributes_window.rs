@@ -0,0 +1,261 @@
+//! 属性面板 - 显示和编辑一个对象（网络、节点、Message、Signal、环境变量）的属性
+
+use imgui::{TableColumnFlags, TableColumnSetup, TableFlags, Ui};
+
+use crate::editable_dbc::{
+    AttributeDefinition, AttributeTarget, AttributeValue, AttributeValueType, EditableDbc,
+};
+
+/// 属性面板对属性值的修改请求，由上层应用到 EditableDbc
+pub enum AttributeEdit {
+    Set(AttributeTarget, String, AttributeValue),
+    Reset(AttributeTarget, String),
+}
+
+/// 属性面板状态，每个 DBC 窗口一个
+#[derive(Clone, Default)]
+pub struct AttributesPanel {
+    pub is_open: bool,
+    target: AttributeTarget,
+    // 上一帧消息表中选中的 Message，选中变化时面板跟随切换
+    followed_message: Option<u32>,
+}
+
+impl AttributesPanel {
+    /// 渲染属性面板窗口，返回用户的修改请求
+    pub fn render(
+        &mut self,
+        ui: &Ui,
+        window_id: &str,
+        dbc: &EditableDbc,
+        selected_message: Option<u32>,
+    ) -> Option<AttributeEdit> {
+        if selected_message != self.followed_message {
+            self.followed_message = selected_message;
+            if let Some(message_id) = selected_message {
+                self.target = AttributeTarget::Message(message_id);
+            }
+        }
+        // 对象被删除或改名后回到网络属性
+        if dbc.attribute_values(&self.target).is_none() {
+            self.target = AttributeTarget::Network;
+        }
+
+        let mut edit = None;
+        let mut is_open = self.is_open;
+        ui.window(format!("Attributes###attributes_{window_id}"))
+            .size([420.0, 300.0], imgui::Condition::FirstUseEver)
+            .opened(&mut is_open)
+            .build(|| {
+                self.render_target_selector(ui, dbc);
+                ui.separator();
+                edit = render_attributes_table(ui, dbc, &self.target);
+            });
+        self.is_open = is_open;
+        edit
+    }
+
+    fn render_target_selector(&mut self, ui: &Ui, dbc: &EditableDbc) {
+        if let Some(_combo) = ui.begin_combo("Object", target_label(dbc, &self.target)) {
+            let mut targets = vec![AttributeTarget::Network];
+            targets.extend(dbc.nodes().iter().cloned().map(AttributeTarget::Node));
+            targets.extend(
+                dbc.messages()
+                    .iter()
+                    .map(|m| AttributeTarget::Message(m.message_id())),
+            );
+            targets.extend(
+                dbc.environment_variables()
+                    .iter()
+                    .map(|e| AttributeTarget::EnvironmentVariable(e.name().to_string())),
+            );
+            for target in targets {
+                let selected = target == self.target;
+                if ui
+                    .selectable_config(target_label(dbc, &target))
+                    .selected(selected)
+                    .build()
+                {
+                    self.target = target;
+                }
+            }
+        }
+
+        // Message 和 Signal 的属性通过第二个下拉框切换
+        let message_id = match &self.target {
+            AttributeTarget::Message(id) | AttributeTarget::Signal(id, _) => *id,
+            _ => return,
+        };
+        let Some(message) = dbc.get_message(message_id) else {
+            return;
+        };
+        let preview = match &self.target {
+            AttributeTarget::Signal(_, name) => name.as_str(),
+            _ => "(Message)",
+        };
+        if let Some(_combo) = ui.begin_combo("Signal", preview) {
+            if ui.selectable("(Message)") {
+                self.target = AttributeTarget::Message(message_id);
+            }
+            for signal in message.signals() {
+                if ui.selectable(signal.name()) {
+                    self.target = AttributeTarget::Signal(message_id, signal.name().to_string());
+                }
+            }
+        }
+    }
+}
+
+/// 下拉框中显示的对象名称
+fn target_label(dbc: &EditableDbc, target: &AttributeTarget) -> String {
+    match target {
+        AttributeTarget::Network => "Network".to_string(),
+        AttributeTarget::Node(node) => format!("Node: {node}"),
+        AttributeTarget::Message(id) | AttributeTarget::Signal(id, _) => {
+            let name = dbc
+                .get_message(*id)
+                .map(|m| m.message_name())
+                .unwrap_or("?");
+            format!("Message: {name} (0x{id:03X})")
+        }
+        AttributeTarget::EnvironmentVariable(name) => format!("Env Var: {name}"),
+    }
+}
+
+fn render_attributes_table(
+    ui: &Ui,
+    dbc: &EditableDbc,
+    target: &AttributeTarget,
+) -> Option<AttributeEdit> {
+    let definitions: Vec<&AttributeDefinition> = dbc
+        .attribute_definitions_for(target.object_type())
+        .collect();
+    if definitions.is_empty() {
+        ui.text_disabled(format!(
+            "No attributes are defined for {} objects",
+            target.object_type()
+        ));
+        return None;
+    }
+
+    let mut edit = None;
+    if let Some(_table) = ui.begin_table_with_flags(
+        "attributes_table",
+        4,
+        TableFlags::BORDERS | TableFlags::ROW_BG | TableFlags::RESIZABLE,
+    ) {
+        ui.table_setup_column("Name");
+        ui.table_setup_column("Type");
+        ui.table_setup_column_with(TableColumnSetup {
+            name: "Value",
+            flags: TableColumnFlags::WIDTH_STRETCH,
+            init_width_or_weight: 0.0,
+            user_id: ui.new_id_str("value_col"),
+        });
+        ui.table_setup_column("");
+        ui.table_headers_row();
+
+        for definition in definitions {
+            let _id = ui.push_id(definition.name());
+            ui.table_next_row();
+
+            ui.table_set_column_index(0);
+            ui.text(definition.name());
+
+            ui.table_set_column_index(1);
+            ui.text(definition.value_type().type_name());
+
+            ui.table_set_column_index(2);
+            let explicit = dbc.attribute_value(target, definition.name());
+            let value = explicit.or(definition.default());
+            if let Some(new_value) = render_value_editor(ui, definition, value) {
+                edit = Some(AttributeEdit::Set(
+                    target.clone(),
+                    definition.name().to_string(),
+                    new_value,
+                ));
+            }
+            if let Some(value) = value
+                && let Err(e) = definition.validate(value)
+            {
+                ui.text_colored([1.0, 0.4, 0.4, 1.0], e);
+            }
+
+            ui.table_set_column_index(3);
+            if explicit.is_some() {
+                if ui.small_button("Reset") {
+                    edit = Some(AttributeEdit::Reset(
+                        target.clone(),
+                        definition.name().to_string(),
+                    ));
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Use the default value");
+                }
+            } else if let Some(default) = definition.default() {
+                ui.text_disabled(format!("default: {}", definition.display_value(default)));
+            } else {
+                ui.text_disabled("unset");
+            }
+        }
+    }
+    edit
+}
+
+/// 根据属性类型渲染输入控件，输入框按回车后才提交
+fn render_value_editor(
+    ui: &Ui,
+    definition: &AttributeDefinition,
+    value: Option<&AttributeValue>,
+) -> Option<AttributeValue> {
+    ui.set_next_item_width(-1.0);
+    match definition.value_type() {
+        AttributeValueType::Int { .. } | AttributeValueType::Hex { .. } => {
+            let mut v = match value {
+                Some(AttributeValue::Int(v)) => *v,
+                Some(AttributeValue::Float(v)) => *v as i64,
+                _ => 0,
+            };
+            let is_hex = matches!(definition.value_type(), AttributeValueType::Hex { .. });
+            let input = ui.input_scalar("##value", &mut v).enter_returns_true(true);
+            let changed = if is_hex {
+                input.display_format("%llX").chars_hexadecimal(true).build()
+            } else {
+                input.build()
+            };
+            changed.then_some(AttributeValue::Int(v))
+        }
+        AttributeValueType::Float { .. } => {
+            let mut v = match value {
+                Some(AttributeValue::Int(v)) => *v as f64,
+                Some(AttributeValue::Float(v)) => *v,
+                _ => 0.0,
+            };
+            ui.input_scalar("##value", &mut v)
+                .enter_returns_true(true)
+                .build()
+                .then_some(AttributeValue::Float(v))
+        }
+        AttributeValueType::String => {
+            let mut v = match value {
+                Some(AttributeValue::String(v)) => v.clone(),
+                Some(other) => other.to_string(),
+                None => String::new(),
+            };
+            ui.input_text("##value", &mut v)
+                .enter_returns_true(true)
+                .build()
+                .then_some(AttributeValue::String(v))
+        }
+        AttributeValueType::Enum(values) => {
+            let mut index = match value {
+                Some(AttributeValue::Int(i)) => usize::try_from(*i).unwrap_or(0),
+                Some(AttributeValue::String(s)) => values.iter().position(|v| v == s).unwrap_or(0),
+                _ => 0,
+            };
+            // BA_ 中的枚举值按惯例写成序号
+            ui.combo_simple_string("##value", &mut index, values)
+                .then_some(AttributeValue::Int(index as i64))
+        }
+    }
+}
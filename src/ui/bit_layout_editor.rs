@@ -0,0 +1,326 @@
+//! Signal 布局编辑器 - 在字节/位网格中显示信号，拖动信号修改起始位，拖动边缘修改长度
+
+use can_dbc::ByteOrder;
+use imgui::{MouseButton, MouseCursor, Ui};
+
+use crate::editable_dbc::{
+    EditableMessage, EditableSignal, may_overlap, signal_bits, swap_bit_order,
+};
+
+const CELL_WIDTH: f32 = 44.0;
+const CELL_HEIGHT: f32 = 22.0;
+const HEADER_WIDTH: f32 = 56.0;
+// 鼠标距离信号末端外侧多近时开始调整长度
+const RESIZE_HANDLE_WIDTH: f32 = 6.0;
+
+const SIGNAL_COLORS: [[f32; 4]; 8] = [
+    [0.26, 0.59, 0.98, 0.70],
+    [0.98, 0.60, 0.26, 0.70],
+    [0.40, 0.80, 0.40, 0.70],
+    [0.85, 0.40, 0.85, 0.70],
+    [0.95, 0.85, 0.30, 0.70],
+    [0.35, 0.85, 0.85, 0.70],
+    [0.75, 0.55, 0.40, 0.70],
+    [0.60, 0.60, 0.95, 0.70],
+];
+const GRID_COLOR: [f32; 4] = [0.5, 0.5, 0.5, 0.6];
+const TEXT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const OVERLAP_COLOR: [f32; 4] = [1.0, 0.2, 0.2, 1.0];
+const PREVIEW_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.9];
+
+/// 布局编辑器对信号的修改请求
+pub enum LayoutEdit {
+    StartBit(String, u64),
+    Size(String, u64),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DragMode {
+    Move,
+    Resize,
+}
+
+#[derive(Clone)]
+struct LayoutDrag {
+    signal_name: String,
+    mode: DragMode,
+    // 按下鼠标时所在的位号
+    grab_bit: u64,
+}
+
+/// 布局编辑器状态，只需要记录正在进行的拖动
+#[derive(Clone, Default)]
+pub struct BitLayoutEditor {
+    drag: Option<LayoutDrag>,
+}
+
+/// 网格中的位置，行是字节号，列 0 是第 7 位（与 Vector 工具的显示一致）
+/// 因此 Motorola 信号在网格中从左到右、从上到下是连续的
+fn grid_index(bit: u64) -> u64 {
+    swap_bit_order(bit)
+}
+
+fn bit_at_grid_index(index: u64) -> u64 {
+    // grid_index 是自己的逆变换
+    grid_index(index)
+}
+
+/// 信号末端的位，拖动它可以修改长度，起始位保持不变
+/// Intel 的起始位是最低位，末端是最高位；Motorola 的起始位是最高位，末端是最低位
+fn tail_bit(signal: &EditableSignal) -> u64 {
+    let bits = signal.bits();
+    match signal.byte_order() {
+        ByteOrder::LittleEndian => bits.first().copied(),
+        ByteOrder::BigEndian => bits.last().copied(),
+    }
+    .unwrap_or(signal.start_bit())
+}
+
+/// 拖动后的新起始位，平移量在网格中计算，所以两种字节序的拖动方向都与显示一致
+fn moved_start_bit(signal: &EditableSignal, grab_bit: u64, hover_bit: u64) -> Option<u64> {
+    match signal.byte_order() {
+        ByteOrder::LittleEndian => {
+            let start = signal.start_bit() as i64 + hover_bit as i64 - grab_bit as i64;
+            u64::try_from(start).ok()
+        }
+        ByteOrder::BigEndian => {
+            let index = grid_index(signal.start_bit()) as i64 + grid_index(hover_bit) as i64
+                - grid_index(grab_bit) as i64;
+            u64::try_from(index).ok().map(bit_at_grid_index)
+        }
+    }
+}
+
+/// 拖动末端后的新长度
+fn resized_size(signal: &EditableSignal, hover_bit: u64) -> Option<u64> {
+    let size = match signal.byte_order() {
+        ByteOrder::LittleEndian => hover_bit as i64 - signal.start_bit() as i64 + 1,
+        ByteOrder::BigEndian => {
+            grid_index(hover_bit) as i64 - grid_index(signal.start_bit()) as i64 + 1
+        }
+    };
+    u64::try_from(size)
+        .ok()
+        .filter(|size| (1..=64).contains(size))
+}
+
+impl BitLayoutEditor {
+    pub fn render(&mut self, ui: &Ui, message: &EditableMessage) -> Option<LayoutEdit> {
+        let rows = message.message_size().max(1);
+        let payload_bits = message.message_size() * 8;
+        let signals = message.signals();
+
+        // 每一位被哪些信号占用
+        let mut owners: Vec<Vec<usize>> = vec![Vec::new(); payload_bits as usize];
+        for (i, signal) in signals.iter().enumerate() {
+            for bit in signal.bits() {
+                if let Some(cell) = owners.get_mut(bit as usize) {
+                    cell.push(i);
+                }
+            }
+        }
+
+        let origin = ui.cursor_screen_pos();
+        let grid_origin = [origin[0] + HEADER_WIDTH, origin[1] + CELL_HEIGHT];
+        let size = [
+            HEADER_WIDTH + CELL_WIDTH * 8.0,
+            CELL_HEIGHT * (rows as f32 + 1.0),
+        ];
+        ui.invisible_button("bit_layout", size);
+        let hovered = ui.is_item_hovered();
+        let clicked = ui.is_item_clicked();
+
+        let mouse = ui.io().mouse_pos;
+        let hover_bit = {
+            let col = ((mouse[0] - grid_origin[0]) / CELL_WIDTH)
+                .floor()
+                .clamp(0.0, 7.0) as u64;
+            let row = ((mouse[1] - grid_origin[1]) / CELL_HEIGHT)
+                .floor()
+                .clamp(0.0, rows as f32 - 1.0) as u64;
+            bit_at_grid_index(row * 8 + col)
+        };
+        let cell_rect = |bit: u64| {
+            let index = grid_index(bit);
+            let x = grid_origin[0] + (index % 8) as f32 * CELL_WIDTH;
+            let y = grid_origin[1] + (index / 8) as f32 * CELL_HEIGHT;
+            ([x, y], [x + CELL_WIDTH, y + CELL_HEIGHT])
+        };
+        let owner_at = |bit: u64| {
+            owners
+                .get(bit as usize)
+                .and_then(|cell| cell.last())
+                .map(|&i| &signals[i])
+        };
+
+        let draw_list = ui.get_window_draw_list();
+
+        // 表头：位号和字节号
+        for col in 0..8u64 {
+            let x = grid_origin[0] + col as f32 * CELL_WIDTH + CELL_WIDTH / 2.0 - 4.0;
+            draw_list.add_text([x, origin[1] + 3.0], TEXT_COLOR, (7 - col).to_string());
+        }
+        for row in 0..rows {
+            let y = grid_origin[1] + row as f32 * CELL_HEIGHT + 3.0;
+            draw_list.add_text([origin[0] + 4.0, y], TEXT_COLOR, format!("Byte {row}"));
+        }
+
+        // 信号占用的格子
+        for (bit, cell) in owners.iter().enumerate() {
+            let Some(&i) = cell.last() else {
+                continue;
+            };
+            let (p1, p2) = cell_rect(bit as u64);
+            draw_list
+                .add_rect(p1, p2, SIGNAL_COLORS[i % SIGNAL_COLORS.len()])
+                .filled(true)
+                .build();
+            let overlapping = cell
+                .iter()
+                .any(|&j| j != i && may_overlap(&signals[i], &signals[j]));
+            if overlapping {
+                draw_list
+                    .add_rect(p1, p2, OVERLAP_COLOR)
+                    .thickness(2.0)
+                    .build();
+            }
+        }
+
+        // 网格线
+        for row in 0..rows {
+            for col in 0..8u64 {
+                let (p1, p2) = cell_rect(bit_at_grid_index(row * 8 + col));
+                draw_list.add_rect(p1, p2, GRID_COLOR).build();
+            }
+        }
+
+        // 信号名写在最高位所在的格子，末端画出调整长度的把手
+        for signal in signals {
+            let bits = signal.bits();
+            let Some(&msb) = bits.first().filter(|&&b| b < payload_bits) else {
+                continue;
+            };
+            let (p1, p2) = cell_rect(msb);
+            let row_end = [grid_origin[0] + 8.0 * CELL_WIDTH, p2[1]];
+            draw_list.with_clip_rect_intersect(p1, row_end, || {
+                draw_list.add_text([p1[0] + 3.0, p1[1] + 3.0], TEXT_COLOR, signal.name());
+            });
+
+            let tail = tail_bit(signal);
+            if tail < payload_bits {
+                let (t1, t2) = cell_rect(tail);
+                let x = match signal.byte_order() {
+                    ByteOrder::LittleEndian => t1[0] + 1.0,
+                    ByteOrder::BigEndian => t2[0] - 1.0,
+                };
+                draw_list
+                    .add_line([x, t1[1] + 2.0], [x, t2[1] - 2.0], TEXT_COLOR)
+                    .thickness(3.0)
+                    .build();
+            }
+        }
+
+        // 判断按下的位置：末端外侧的把手调整长度，其他位置平移
+        let on_handle = |signal: &EditableSignal| {
+            if tail_bit(signal) != hover_bit {
+                return false;
+            }
+            let (p1, p2) = cell_rect(hover_bit);
+            match signal.byte_order() {
+                ByteOrder::LittleEndian => mouse[0] - p1[0] <= RESIZE_HANDLE_WIDTH,
+                ByteOrder::BigEndian => p2[0] - mouse[0] <= RESIZE_HANDLE_WIDTH,
+            }
+        };
+
+        if clicked && let Some(signal) = owner_at(hover_bit) {
+            self.drag = Some(LayoutDrag {
+                signal_name: signal.name().to_string(),
+                mode: if on_handle(signal) {
+                    DragMode::Resize
+                } else {
+                    DragMode::Move
+                },
+                grab_bit: hover_bit,
+            });
+        }
+
+        if hovered && self.drag.is_none() {
+            if let Some(signal) = owner_at(hover_bit) {
+                if on_handle(signal) {
+                    ui.set_mouse_cursor(Some(MouseCursor::ResizeEW));
+                }
+                ui.tooltip_text(format!(
+                    "{}\nStart: {}  Length: {}  {}",
+                    signal.name(),
+                    signal.start_bit(),
+                    signal.signal_size(),
+                    match signal.byte_order() {
+                        ByteOrder::LittleEndian => "Intel",
+                        ByteOrder::BigEndian => "Motorola",
+                    }
+                ));
+            } else {
+                ui.tooltip_text(format!("Bit {hover_bit}"));
+            }
+        }
+
+        let drag = self.drag.clone()?;
+        let Some(signal) = signals.iter().find(|s| s.name() == drag.signal_name) else {
+            self.drag = None;
+            return None;
+        };
+
+        // 拖动中：画出新位置的预览，超出负载时用红色
+        let (start_bit, size) = match drag.mode {
+            DragMode::Move => (
+                moved_start_bit(signal, drag.grab_bit, hover_bit),
+                Some(signal.signal_size()),
+            ),
+            DragMode::Resize => (Some(signal.start_bit()), resized_size(signal, hover_bit)),
+        };
+        if drag.mode == DragMode::Resize {
+            ui.set_mouse_cursor(Some(MouseCursor::ResizeEW));
+        }
+        let preview = start_bit
+            .zip(size)
+            .map(|(start_bit, size)| {
+                (
+                    start_bit,
+                    size,
+                    signal_bits(start_bit, size, *signal.byte_order()),
+                )
+            })
+            .filter(|(_, _, bits)| bits.iter().all(|&b| b < payload_bits));
+        if let Some((_, _, bits)) = &preview {
+            for &bit in bits {
+                let (p1, p2) = cell_rect(bit);
+                draw_list
+                    .add_rect(p1, p2, PREVIEW_COLOR)
+                    .thickness(2.0)
+                    .build();
+            }
+        } else {
+            let (p1, p2) = cell_rect(hover_bit);
+            draw_list
+                .add_rect(p1, p2, OVERLAP_COLOR)
+                .thickness(2.0)
+                .build();
+        }
+
+        if ui.is_mouse_down(MouseButton::Left) {
+            return None;
+        }
+        // 松开鼠标时提交，位置不合法或没有变化时放弃
+        self.drag = None;
+        let (start_bit, size, _) = preview?;
+        match drag.mode {
+            DragMode::Move if start_bit != signal.start_bit() => {
+                Some(LayoutEdit::StartBit(signal.name().to_string(), start_bit))
+            }
+            DragMode::Resize if size != signal.signal_size() => {
+                Some(LayoutEdit::Size(signal.name().to_string(), size))
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Compare 窗口 - 并排比较两个已打开的 DBC，高亮有差异的 Message
+
+use imgui::{TableBgTarget, TableColumnFlags, TableColumnSetup, TableFlags, Ui};
+
+use crate::editable_dbc::{DbcDiff, DiffKind, EditableDbc, EditableMessage, MessageDiff};
+use crate::ui::dbc_window::DbcWindow;
+use crate::ui::state::UiState;
+
+// 行背景色和详情中的文字颜色
+const ADDED_COLOR: [f32; 4] = [0.2, 0.6, 0.2, 0.35];
+const REMOVED_COLOR: [f32; 4] = [0.7, 0.2, 0.2, 0.35];
+const CHANGED_COLOR: [f32; 4] = [0.7, 0.6, 0.1, 0.35];
+const ADDED_COLOR_TEXT: [f32; 4] = [0.4, 0.9, 0.4, 1.0];
+const REMOVED_COLOR_TEXT: [f32; 4] = [1.0, 0.4, 0.4, 1.0];
+const CHANGED_COLOR_TEXT: [f32; 4] = [1.0, 0.85, 0.3, 1.0];
+
+/// Compare 窗口状态，左边为旧文件，右边为新文件
+#[derive(Default)]
+pub struct CompareWindow {
+    pub is_open: bool,
+    // 用文件路径记录选择，窗口索引在关闭窗口后会变化
+    left: Option<String>,
+    right: Option<String>,
+    show_unchanged: bool,
+    // 选中行的 (旧 ID, 新 ID)
+    selected: Option<(Option<u32>, Option<u32>)>,
+    // 比较结果缓存，两边的文件路径和数据版本号都不变时不重新比较
+    cache_key: Option<(String, u64, String, u64)>,
+    diff: DbcDiff,
+}
+
+/// 表格中的一行，两边的 Message 按比较结果对齐
+struct CompareRow<'a> {
+    old: Option<&'a EditableMessage>,
+    new: Option<&'a EditableMessage>,
+    diff: Option<&'a MessageDiff>,
+}
+
+impl CompareRow<'_> {
+    fn key(&self) -> (Option<u32>, Option<u32>) {
+        (
+            self.old.map(|m| m.message_id()),
+            self.new.map(|m| m.message_id()),
+        )
+    }
+}
+
+fn kind_color(kind: DiffKind) -> [f32; 4] {
+    match kind {
+        DiffKind::Added => ADDED_COLOR,
+        DiffKind::Removed => REMOVED_COLOR,
+        DiffKind::Changed => CHANGED_COLOR,
+    }
+}
+
+impl CompareWindow {
+    pub fn render(&mut self, ui: &Ui, dbc_windows: &[DbcWindow]) {
+        // 默认比较最先打开的两个文件，选中的文件被关闭后重新选择
+        let exists = |path: &Option<String>| {
+            path.as_ref()
+                .is_some_and(|p| dbc_windows.iter().any(|w| &w.file_path == p))
+        };
+        if !exists(&self.left) {
+            self.left = dbc_windows.first().map(|w| w.file_path.clone());
+        }
+        if !exists(&self.right) {
+            self.right = dbc_windows.get(1).map(|w| w.file_path.clone());
+        }
+
+        let mut is_open = self.is_open;
+        ui.window("Compare")
+            .size([900.0, 500.0], imgui::Condition::FirstUseEver)
+            .opened(&mut is_open)
+            .build(|| {
+                if dbc_windows.len() < 2 {
+                    ui.text_disabled("Open two DBC files to compare them");
+                    return;
+                }
+                file_combo(ui, "Old", &mut self.left, dbc_windows);
+                ui.same_line();
+                file_combo(ui, "New", &mut self.right, dbc_windows);
+
+                let find = |path: &Option<String>| {
+                    dbc_windows
+                        .iter()
+                        .find(|w| Some(&w.file_path) == path.as_ref())
+                };
+                let (Some(left), Some(right)) = (find(&self.left), find(&self.right)) else {
+                    return;
+                };
+                self.update_diff(left, right);
+                self.render_summary(ui);
+                ui.separator();
+                self.render_body(ui, &left.dbc, &right.dbc);
+            });
+        self.is_open = is_open;
+    }
+
+    fn update_diff(&mut self, left: &DbcWindow, right: &DbcWindow) {
+        let key = (
+            left.file_path.clone(),
+            left.dbc.revision(),
+            right.file_path.clone(),
+            right.dbc.revision(),
+        );
+        if self.cache_key.as_ref() != Some(&key) {
+            self.diff = left.dbc.diff(&right.dbc);
+            self.cache_key = Some(key);
+        }
+    }
+
+    fn render_summary(&mut self, ui: &Ui) {
+        let count = |kind| self.diff.messages.iter().filter(|m| m.kind == kind).count();
+        ui.text(format!(
+            "Messages: {} added, {} removed, {} changed",
+            count(DiffKind::Added),
+            count(DiffKind::Removed),
+            count(DiffKind::Changed)
+        ));
+        if !self.diff.added_nodes.is_empty() || !self.diff.removed_nodes.is_empty() {
+            ui.same_line();
+            ui.text(format!(
+                "  Nodes: +[{}] -[{}]",
+                self.diff.added_nodes.join(", "),
+                self.diff.removed_nodes.join(", ")
+            ));
+        }
+        // 网络注释和节点注释的修改
+        for change in &self.diff.changes {
+            ui.text(change.to_string());
+        }
+        ui.checkbox("Show unchanged messages", &mut self.show_unchanged);
+    }
+
+    fn render_body(&mut self, ui: &Ui, old: &EditableDbc, new: &EditableDbc) {
+        let rows = compare_rows(&self.diff, self.show_unchanged, old, new);
+        let details_height = 160.0;
+        ui.child_window("compare_rows")
+            .size([0.0, -details_height])
+            .build(|| render_rows(ui, &rows, &mut self.selected));
+
+        ui.separator();
+        ui.child_window("compare_details").build(|| {
+            let diff = rows
+                .iter()
+                .find(|r| Some(r.key()) == self.selected)
+                .and_then(|r| r.diff);
+            match diff {
+                Some(diff) => render_details(ui, diff),
+                None => ui.text_disabled("Select a changed message to see the details"),
+            }
+        });
+    }
+}
+
+/// 按旧文件中的顺序排列，新增的 Message 在最后
+fn compare_rows<'a>(
+    diff: &'a DbcDiff,
+    show_unchanged: bool,
+    old: &'a EditableDbc,
+    new: &'a EditableDbc,
+) -> Vec<CompareRow<'a>> {
+    let mut rows = Vec::new();
+    for message in old.messages() {
+        let id = message.message_id();
+        match diff.for_old_message(id) {
+            Some(message_diff) => rows.push(CompareRow {
+                old: Some(message),
+                new: message_diff.new_id.and_then(|id| new.get_message(id)),
+                diff: Some(message_diff),
+            }),
+            None if show_unchanged => rows.push(CompareRow {
+                old: Some(message),
+                new: new.get_message(id),
+                diff: None,
+            }),
+            None => {}
+        }
+    }
+    for message_diff in diff.messages.iter().filter(|m| m.kind == DiffKind::Added) {
+        rows.push(CompareRow {
+            old: None,
+            new: message_diff.new_id.and_then(|id| new.get_message(id)),
+            diff: Some(message_diff),
+        });
+    }
+    rows
+}
+
+fn render_rows(
+    ui: &Ui,
+    rows: &[CompareRow],
+    selected_key: &mut Option<(Option<u32>, Option<u32>)>,
+) {
+    if rows.is_empty() {
+        ui.text_disabled("The files have no differences");
+        return;
+    }
+    let Some(_table) = ui.begin_table_with_flags(
+        "compare_table",
+        7,
+        TableFlags::BORDERS | TableFlags::RESIZABLE | TableFlags::SCROLL_Y,
+    ) else {
+        return;
+    };
+    ui.table_setup_scroll_freeze(0, 1);
+    for (i, name) in [
+        "Old ID",
+        "Old Name",
+        "Old Length",
+        "",
+        "New ID",
+        "New Name",
+        "New Length",
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let stretch = i == 1 || i == 5;
+        ui.table_setup_column_with(TableColumnSetup {
+            name,
+            flags: if stretch {
+                TableColumnFlags::WIDTH_STRETCH
+            } else {
+                TableColumnFlags::WIDTH_FIXED
+            },
+            init_width_or_weight: 0.0,
+            user_id: ui.new_id(i),
+        });
+    }
+    ui.table_headers_row();
+
+    for (i, row) in rows.iter().enumerate() {
+        let _id = ui.push_id_usize(i);
+        ui.table_next_row();
+        if let Some(diff) = row.diff {
+            ui.table_set_bg_color(TableBgTarget::ROW_BG0, kind_color(diff.kind));
+        }
+
+        ui.table_set_column_index(0);
+        let selected = *selected_key == Some(row.key());
+        let label = row
+            .old
+            .map(|m| format!("0x{:03X}", m.message_id()))
+            .unwrap_or_default();
+        if ui
+            .selectable_config(format!("{label}##row"))
+            .span_all_columns(true)
+            .selected(selected)
+            .build()
+        {
+            *selected_key = Some(row.key());
+        }
+        if let Some(message) = row.old {
+            ui.table_set_column_index(1);
+            ui.text(message.message_name());
+            ui.table_set_column_index(2);
+            ui.text(message.message_size().to_string());
+        }
+
+        ui.table_set_column_index(3);
+        ui.text(row.diff.map(|d| d.kind.symbol()).unwrap_or(' ').to_string());
+
+        if let Some(message) = row.new {
+            ui.table_set_column_index(4);
+            ui.text(format!("0x{:03X}", message.message_id()));
+            ui.table_set_column_index(5);
+            ui.text(message.message_name());
+            ui.table_set_column_index(6);
+            ui.text(message.message_size().to_string());
+        }
+    }
+}
+
+fn file_combo(ui: &Ui, label: &str, selected: &mut Option<String>, dbc_windows: &[DbcWindow]) {
+    let preview = dbc_windows
+        .iter()
+        .find(|w| Some(&w.file_path) == selected.as_ref())
+        .map(|w| w.file_name())
+        .unwrap_or("");
+    ui.set_next_item_width(250.0);
+    if let Some(_combo) = ui.begin_combo(label, preview) {
+        for window in dbc_windows {
+            let is_selected = Some(&window.file_path) == selected.as_ref();
+            if ui
+                .selectable_config(window.file_name())
+                .selected(is_selected)
+                .build()
+            {
+                *selected = Some(window.file_path.clone());
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip_text(&window.file_path);
+            }
+        }
+    }
+}
+
+/// 选中 Message 的字段修改和 Signal 差异
+fn render_details(ui: &Ui, diff: &MessageDiff) {
+    match diff.kind {
+        DiffKind::Added => ui.text_colored(ADDED_COLOR_TEXT, "Message added"),
+        DiffKind::Removed => ui.text_colored(REMOVED_COLOR_TEXT, "Message removed"),
+        DiffKind::Changed => {}
+    }
+    for change in &diff.changes {
+        ui.text(format!(
+            "{}: {} -> {}",
+            change.field, change.old, change.new
+        ));
+    }
+    for signal in &diff.signals {
+        let color = match signal.kind {
+            DiffKind::Added => ADDED_COLOR_TEXT,
+            DiffKind::Removed => REMOVED_COLOR_TEXT,
+            DiffKind::Changed => CHANGED_COLOR_TEXT,
+        };
+        ui.text_colored(
+            color,
+            format!("{} signal {}", signal.kind.symbol(), signal.name),
+        );
+        for change in &signal.changes {
+            ui.text(format!(
+                "    {}: {} -> {}",
+                change.field, change.old, change.new
+            ));
+        }
+    }
+}
+
+/// 渲染 Compare 窗口
+pub fn render_compare_window(ui: &Ui, ui_state: &mut UiState) {
+    if ui_state.compare_window.is_open {
+        ui_state.compare_window.render(ui, &ui_state.dbc_windows);
+    }
+}
@@ -1,17 +1,19 @@
 // //! DBC 窗口渲染模块
 
-use std::fs::File;
-use std::io::Read;
-use std::path::Path;
-use std::usize;
+use std::path::{Path, PathBuf};
 
-use crate::editable_dbc::{EditableDbc, EditableMessage};
-use crate::ui::message_window::MessageWindow;
+use crate::editable_dbc::{
+    EditableDbc, EditableMessage, NOT_UTF8_WARNING, load_file_with_warnings,
+    save_file_with_warnings,
+};
+use crate::ui::attributes_window::{AttributeEdit, AttributesPanel};
+use crate::ui::message_window::{MessageWindow, MessageWindowEvent, format_value_descriptions};
+use crate::ui::plot_window::signal_drag_source;
 use crate::ui::state::UiState;
 use can_dbc::ByteOrder;
 use imgui::{
     Condition, StyleColor, TableBgTarget, TableColumnFlags, TableColumnSetup, TableFlags,
-    TableSortDirection, Ui,
+    TableSortDirection, Ui, WindowFocusedFlags,
 };
 
 /// DBC 窗口状态
@@ -30,13 +32,17 @@ pub struct DbcWindow {
     message_table: MessageTable,
     is_dirty: bool,
 
-    // 待关闭的 Message 窗口 ID
-    // 因为不会一次关闭多个，所以用 Option<usize> 就够了
-    message_window_to_close: Option<usize>,
+    // 文件不是 UTF-8 编码，无法解码的字节在读取时已被替换，不允许覆盖原文件，只能另存为
+    not_utf8: bool,
+
+    // 右键菜单请求编辑的 Message ID，主循环会处理并打开编辑对话框
+    pending_message_edit: Option<u32>,
+
+    // 属性面板，作为单独的可停靠窗口渲染
+    attributes_panel: AttributesPanel,
 
     // Message 窗口必然依附于 DBC 窗口
     // 一旦关闭了 DBC 窗口，所有相关的 Message 窗口也会被关闭
-    // 这个时候就不用管 message_windows_to_close 了
     // 直接干掉整个 Vec 就行
     pub message_windows: Vec<MessageWindow>,
 }
@@ -51,33 +57,91 @@ impl DbcWindow {
             message_table: MessageTable::new(),
             search_bar: DbcSearchBar::default(),
             is_dirty: true,
-            message_window_to_close: None,
+            not_utf8: false,
+            pending_message_edit: None,
+            attributes_panel: AttributesPanel::default(),
             message_windows: Vec::new(),
         }
     }
 
-    /// 从文件路径创建新的 DBC 窗口状态
-    pub fn from_path(file_path: &Path) -> Result<Self, String> {
-        let mut file = File::open(file_path).unwrap();
-        let mut contents = Vec::new();
-        if let Ok(_) = file.read_to_end(&mut contents) {
-            let contents_str = String::from_utf8_lossy(&contents).to_string();
-            if let Ok(original_dbc) = can_dbc::Dbc::try_from(contents_str.as_str()) {
-                let editable_dbc = EditableDbc::from_dbc(&original_dbc);
-                Ok(Self::new(file_path.to_str().unwrap(), editable_dbc))
-            } else {
-                Err(format!("Failed to parse DBC: {}", file_path.display()))
-            }
-        } else {
-            Err(format!("Filed to open file: {}", file_path.display()))
+    /// 从文件路径创建新的 DBC 窗口状态，同时返回格式转换的警告
+    pub fn from_path(file_path: &Path) -> Result<(Self, Vec<String>), String> {
+        let (editable_dbc, warnings) = load_file_with_warnings(file_path)?;
+        let mut window = Self::new(&file_path.to_string_lossy(), editable_dbc);
+        window.not_utf8 = warnings.iter().any(|w| w == NOT_UTF8_WARNING);
+        Ok((window, warnings))
+    }
+
+    /// 将 DBC 写回当前文件，返回格式转换的警告
+    pub fn save(&mut self) -> Result<Vec<String>, String> {
+        let file_path = PathBuf::from(&self.file_path);
+        self.save_as(&file_path)
+    }
+
+    /// 另存为新文件，成功后窗口对应的文件也随之改变，返回格式转换的警告
+    pub fn save_as(&mut self, file_path: &Path) -> Result<Vec<String>, String> {
+        if self.not_utf8 && file_path == Path::new(&self.file_path) {
+            return Err(format!(
+                "Cannot overwrite '{}': the file is not valid UTF-8 and undecodable bytes were \
+                 replaced when it was opened. Use Save As to save it to a new file.",
+                self.file_path
+            ));
         }
+        let warnings = save_file_with_warnings(&self.dbc, file_path)?;
+        self.file_path = file_path.to_string_lossy().to_string();
+        self.not_utf8 = false;
+        self.dbc.mark_saved();
+        Ok(warnings)
+    }
+
+    /// 撤销上一个操作，成功后刷新消息表
+    pub fn undo(&mut self) -> Result<(), String> {
+        self.dbc.undo()?;
+        self.is_dirty = true;
+        Ok(())
+    }
+
+    /// 重做下一个操作，成功后刷新消息表
+    pub fn redo(&mut self) -> Result<(), String> {
+        self.dbc.redo()?;
+        self.is_dirty = true;
+        Ok(())
+    }
+
+    /// 在外部修改了 dbc 之后调用，下一帧刷新消息表
+    pub fn mark_dirty(&mut self) {
+        self.is_dirty = true;
+    }
+
+    /// 文件名，用于窗口标题和对话框
+    pub fn file_name(&self) -> &str {
+        Path::new(&self.file_path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_else(|| panic!("DBC window has empty file path"))
+    }
+
+    /// 窗口标题，有未保存的修改时在文件名后显示 *
+    /// ### 之后的部分作为 imgui 的窗口 ID，保证标题变化时窗口位置不变
+    fn window_title(&self) -> String {
+        let modified_marker = if self.dbc.is_modified() { " *" } else { "" };
+        format!(
+            "DBC - {}{}###{}",
+            self.file_name(),
+            modified_marker,
+            self.file_path
+        )
     }
 
     /// 渲染DBC文件信息区域
-    fn render_file_info(&self, ui: &Ui) {
+    fn render_file_info(&mut self, ui: &Ui) {
         if !self.file_path.is_empty() {
             ui.text(format!("Loaded: {}", self.file_path));
             ui.text(format!("Messages: {}", self.dbc.message_count()));
+            ui.same_line();
+            if ui.small_button("Attributes") {
+                self.attributes_panel.is_open = !self.attributes_panel.is_open;
+            }
         } else {
             ui.text("No DBC file loaded");
         }
@@ -94,34 +158,115 @@ impl DbcWindow {
 
         if pending_filter {
             self.message_table
-                .update_filter(&self.search_bar.query(), self.dbc.messages());
+                .update_filter(self.search_bar.query(), self.dbc.messages());
         }
 
-        let message_table_event = self.message_table.render(ui, self.dbc.messages());
+        let message_table_event =
+            self.message_table
+                .render(ui, self.dbc.messages(), &self.file_path);
 
-        // 处理双击事件，打开消息窗口
+        // 处理双击事件，打开消息窗口，已经打开的窗口不重复打开
         if let Some(idx) = message_table_event.double_clicked_idx {
-            let message = self.dbc.messages()[idx].clone();
-            let message_window = MessageWindow::new(message, idx);
-            self.message_windows.push(message_window);
+            let message_id = self.dbc.messages()[idx].message_id();
+            if !self
+                .message_windows
+                .iter()
+                .any(|w| w.message_id == message_id)
+            {
+                self.message_windows
+                    .push(MessageWindow::new(message_id, idx));
+            }
         }
 
         let message_table_menu_event =
-            render_message_table_menu(ui, &self, &message_table_event.right_clicked_idx);
+            render_message_table_menu(ui, self, &message_table_event.right_clicked_idx);
 
-        handle_message_table_menu_event(message_table_menu_event, &self);
+        handle_message_table_menu_event(message_table_menu_event, self);
+    }
 
+    /// 渲染所有 Message 窗口并应用其中的修改
+    /// Message 被删除（或 ID 被修改）后对应的窗口随之关闭
+    fn render_message_windows(&mut self, ui: &Ui) {
+        let mut events = Vec::new();
         for message_window in &mut self.message_windows {
-            println!(
-                "Rendering message window {}",
-                message_window.message.message_name()
-            );
-            message_window.render(ui);
+            let Some(message) = self.dbc.get_message(message_window.message_id) else {
+                message_window.is_open = false;
+                continue;
+            };
+            if let Some(event) = message_window.render(ui, &self.file_path, message) {
+                events.push((message_window.message_id, event));
+            }
+        }
+        self.message_windows.retain(|w| w.is_open);
+
+        for (message_id, event) in events {
+            match event {
+                MessageWindowEvent::SetSignalStartBit(signal_name, start_bit) => {
+                    self.dbc
+                        .set_signal_start_bit(message_id, &signal_name, start_bit);
+                }
+                MessageWindowEvent::SetSignalSize(signal_name, size) => {
+                    self.dbc.set_signal_size(message_id, &signal_name, size);
+                }
+            }
+            self.is_dirty = true;
+        }
+    }
+
+    /// 消息表中唯一选中的 Message ID，属性面板跟随它切换
+    fn selected_message_id(&self) -> Option<u32> {
+        match self.message_table.selected_indicies.as_slice() {
+            [idx] => self.dbc.messages().get(*idx).map(|m| m.message_id()),
+            _ => None,
         }
+    }
 
-        if let Some(idx) = self.message_window_to_close {
-            self.message_windows.remove(idx);
-            self.message_window_to_close = None;
+    /// 在消息表中选中并滚动到指定的 Message，被搜索条件过滤掉时清空搜索
+    /// 如果该 Message 的窗口已经打开，同时选中其中的 Signal
+    pub fn focus_message(&mut self, message_id: u32, signal_name: Option<&str>) {
+        let Some(idx) = self
+            .dbc
+            .messages()
+            .iter()
+            .position(|m| m.message_id() == message_id)
+        else {
+            return;
+        };
+        if !self.message_table.filtered_indicies().contains(&idx) {
+            self.search_bar.query.clear();
+            self.message_table.update_filter("", self.dbc.messages());
+        }
+        self.message_table.select_index(idx);
+        self.message_table.scroll_request = Some(idx);
+
+        if let Some(signal_name) = signal_name
+            && let Some(window) = self
+                .message_windows
+                .iter_mut()
+                .find(|w| w.message_id == message_id)
+        {
+            window.selected_signal_name = Some(signal_name.to_string());
+        }
+    }
+
+    /// 渲染属性面板并应用其中的修改
+    fn render_attributes_panel(&mut self, ui: &Ui) -> Result<(), String> {
+        if !self.attributes_panel.is_open {
+            return Ok(());
+        }
+        let selected = self.selected_message_id();
+        let edit = self
+            .attributes_panel
+            .render(ui, &self.file_path, &self.dbc, selected);
+        match edit {
+            Some(AttributeEdit::Set(target, name, value)) => {
+                self.dbc.set_attribute_value(&target, &name, value)
+            }
+            Some(AttributeEdit::Reset(target, name)) => {
+                self.dbc.reset_attribute_value(&target, &name);
+                Ok(())
+            }
+            None => Ok(()),
         }
     }
 }
@@ -132,6 +277,8 @@ struct MessageTable {
     sorted_indicies: Vec<usize>,
     filtered_indicies: Vec<usize>,
     selected_indicies: Vec<usize>,
+    // 下一帧需要滚动到的行
+    scroll_request: Option<usize>,
 }
 
 impl MessageTable {
@@ -140,6 +287,7 @@ impl MessageTable {
             sorted_indicies: Vec::new(),
             filtered_indicies: Vec::new(),
             selected_indicies: Vec::new(),
+            scroll_request: None,
         }
     }
 
@@ -216,15 +364,15 @@ impl MessageTable {
         match sort_direction {
             TableSortDirection::Ascending => {
                 self.sorted_indicies
-                    .sort_by(|&a, &b| messages[a].message_name().cmp(&messages[b].message_name()));
+                    .sort_by(|&a, &b| messages[a].message_name().cmp(messages[b].message_name()));
                 self.filtered_indicies
-                    .sort_by(|&a, &b| messages[a].message_name().cmp(&messages[b].message_name()));
+                    .sort_by(|&a, &b| messages[a].message_name().cmp(messages[b].message_name()));
             }
             TableSortDirection::Descending => {
                 self.sorted_indicies
-                    .sort_by(|&a, &b| messages[b].message_name().cmp(&messages[a].message_name()));
+                    .sort_by(|&a, &b| messages[b].message_name().cmp(messages[a].message_name()));
                 self.filtered_indicies
-                    .sort_by(|&a, &b| messages[b].message_name().cmp(&messages[a].message_name()));
+                    .sort_by(|&a, &b| messages[b].message_name().cmp(messages[a].message_name()));
             }
         }
     }
@@ -284,6 +432,8 @@ impl MessageTable {
         &self,
         ui: &Ui,
         messages: &[EditableMessage],
+        file_path: &str,
+        scroll_to: Option<usize>,
     ) -> Option<MessageTableRowsEvent> {
         let mut table_rows_event = None;
         for &idx in self.filtered_indicies() {
@@ -312,6 +462,17 @@ impl MessageTable {
                     table_rows_event = Some(MessageTableRowsEvent::LeftClick(idx));
                 }
             }
+            // 拖到 Plot 窗口中绘制这个 Message 的所有信号
+            signal_drag_source(
+                ui,
+                file_path,
+                message.message_id(),
+                None,
+                message.message_name(),
+            );
+            if scroll_to == Some(idx) {
+                ui.set_scroll_here_y_with_ratio(0.5);
+            }
             if ui.is_item_clicked_with_button(imgui::MouseButton::Right) {
                 // 判断右键点击
                 println!("Right selected 0x{:03X}", message.message_id());
@@ -329,7 +490,11 @@ impl MessageTable {
             ui.table_set_column_index(1);
             ui.text(message.message_name());
             ui.table_set_column_index(2);
-            ui.text(format!("{}", message.message_size()));
+            if message.is_fd() {
+                ui.text(format!("{} (FD)", message.message_size()));
+            } else {
+                ui.text(format!("{}", message.message_size()));
+            }
             ui.table_set_column_index(3);
             ui.text(format!("{}", message.signals_count()));
         }
@@ -337,14 +502,18 @@ impl MessageTable {
         table_rows_event
     }
 
-    fn render(&mut self, ui: &Ui, messages: &[EditableMessage]) -> MessageTableEvent {
+    fn render(
+        &mut self,
+        ui: &Ui,
+        messages: &[EditableMessage],
+        file_path: &str,
+    ) -> MessageTableEvent {
         // 提前创建可变返回事件
         let mut table_event = MessageTableEvent::default();
-        let _ = ui
-            .child_window("messages_table")
+        ui.child_window("messages_table")
             .size([0.0, 0.0])
             .build(|| {
-                if let Some(_) = ui.begin_table_with_flags(
+                if let Some(_table) = ui.begin_table_with_flags(
                     "messages_table",
                     4,
                     TableFlags::RESIZABLE
@@ -370,7 +539,8 @@ impl MessageTable {
                         sort_specs.set_sorted();
                     }
 
-                    let rows_event = self.render_table_rows(ui, messages);
+                    let scroll_to = self.scroll_request.take();
+                    let rows_event = self.render_table_rows(ui, messages, file_path, scroll_to);
                     // 处理点击
                     if let Some(rows_event) = rows_event {
                         match rows_event {
@@ -394,8 +564,6 @@ impl MessageTable {
                             }
                         }
                     }
-                } else {
-                    return;
                 }
             })
             .unwrap();
@@ -405,6 +573,7 @@ impl MessageTable {
     }
 }
 
+#[allow(clippy::enum_variant_names)]
 enum MessageTableRowsEvent {
     LeftClick(usize),
     CtrlLeftClick(usize),
@@ -485,8 +654,8 @@ fn render_message_table_menu(
     right_clicked_idx: &Option<usize>,
 ) -> MessageTableMenuEvent {
     let mut response: MessageTableMenuEvent = MessageTableMenuEvent { action: None };
-    let popup_id = format!("message_context_menu");
-    if let Some(_) = right_clicked_idx {
+    let popup_id = "message_context_menu".to_string();
+    if right_clicked_idx.is_some() {
         ui.open_popup(&popup_id);
     }
     // 如果选择了单个项，允许编辑
@@ -527,14 +696,14 @@ fn render_message_table_menu(
     response
 }
 
-fn handle_message_table_menu_event(response: MessageTableMenuEvent, window_state: &DbcWindow) {
+fn handle_message_table_menu_event(response: MessageTableMenuEvent, window_state: &mut DbcWindow) {
     match response.action {
         None => {}
         Some(MessageTableMenuAction::Edit) => {
-            println!(
-                "Handle edit for message : {:?}",
-                window_state.message_table.selected_indicies
-            );
+            if let Some(&idx) = window_state.message_table.selected_indicies.first() {
+                window_state.pending_message_edit =
+                    Some(window_state.dbc.messages()[idx].message_id());
+            }
         }
         Some(MessageTableMenuAction::Copy) => {
             println!(
@@ -575,7 +744,7 @@ fn render_signals_table_tooltip(ui: &Ui, message: &EditableMessage) {
         // 创建一个非交互的信号表格（tooltip 本身不支持可靠交互，因此不捕获双击）
         if let Some(_table) = ui.begin_table_with_flags(
             "popup_signals_table",
-            6, // 显示更多列：Name, Start Bit, Length, Byte Order, Factor, Unit
+            7, // 显示更多列：Name, Start Bit, Length, Byte Order, Factor, Unit, Values
             TableFlags::BORDERS | TableFlags::SIZING_FIXED_FIT | TableFlags::ROW_BG,
         ) {
             // 设置表格列
@@ -585,6 +754,7 @@ fn render_signals_table_tooltip(ui: &Ui, message: &EditableMessage) {
             ui.table_setup_column("Order");
             ui.table_setup_column("Factor");
             ui.table_setup_column("Unit");
+            ui.table_setup_column("Values");
             ui.table_headers_row();
 
             // 显示前 10 个信号（避免 tooltip 过大）
@@ -593,7 +763,7 @@ fn render_signals_table_tooltip(ui: &Ui, message: &EditableMessage) {
 
                 ui.table_set_column_index(0);
                 // tooltip 内仅显示文本，避免交互
-                ui.text(&signal.name());
+                ui.text(signal.name());
 
                 ui.table_set_column_index(1);
                 ui.text(format!("{}", signal.start_bit()));
@@ -612,7 +782,10 @@ fn render_signals_table_tooltip(ui: &Ui, message: &EditableMessage) {
                 ui.text(format!("{:.2}", signal.factor()));
 
                 ui.table_set_column_index(5);
-                ui.text(&signal.unit());
+                ui.text(signal.unit());
+
+                ui.table_set_column_index(6);
+                ui.text(format_value_descriptions(signal));
             }
 
             if signals.len() > 10 {
@@ -637,23 +810,20 @@ fn request_window_focus() {
 
 /// 渲染所有 DBC 窗口
 pub fn render_dbc_windows(ui: &Ui, ui_state: &mut UiState) {
+    let mut close_requests = Vec::new();
+    let mut errors = Vec::new();
+
     for (window_idx, dbc_window) in &mut ui_state.dbc_windows.iter_mut().enumerate() {
-        if let Some(request_focus_idx) = ui_state.dbc_window_focus_request {
-            if request_focus_idx == window_idx {
-                request_window_focus();
-                ui_state.dbc_window_focus_request = None;
-            }
+        if let Some(request_focus_idx) = ui_state.dbc_window_focus_request
+            && request_focus_idx == window_idx
+        {
+            request_window_focus();
+            ui_state.dbc_window_focus_request = None;
         }
 
-        let window_title = format!(
-            "DBC - {}",
-            std::path::Path::new(&dbc_window.file_path)
-                .file_name()
-                .and_then(|name| name.to_str())
-                .unwrap_or_else(|| panic!("DBC window has empty file path"))
-        );
+        let window_title = dbc_window.window_title();
 
-        // 现在是始终打开的，没有对 is_open 的修改
+        // 点击关闭按钮后 is_open 会变为 false，是否真正关闭由确认流程决定
         let mut is_open = dbc_window.is_open;
 
         let window_ui = ui
@@ -664,13 +834,57 @@ pub fn render_dbc_windows(ui: &Ui, ui_state: &mut UiState) {
         if dbc_window.is_dirty {
             dbc_window
                 .message_table
-                .init_sort_and_filter(&dbc_window.search_bar.query(), dbc_window.dbc.messages());
+                .init_sort_and_filter(dbc_window.search_bar.query(), dbc_window.dbc.messages());
         }
 
         window_ui.build(|| {
+            if ui.is_window_focused_with_flags(WindowFocusedFlags::ROOT_AND_CHILD_WINDOWS) {
+                ui_state.last_focused_dbc_index = Some(window_idx);
+            }
             dbc_window.render(ui);
         });
 
         dbc_window.is_dirty = false;
+
+        dbc_window.render_message_windows(ui);
+
+        if let Err(e) = dbc_window.render_attributes_panel(ui) {
+            errors.push(e);
+        }
+
+        for message_window in &mut dbc_window.message_windows {
+            if let Some(signal_name) = message_window.pending_signal_edit.take()
+                && let Some(message) = dbc_window.dbc.get_message(message_window.message_id)
+                && let Some(signal) = message.signals().iter().find(|s| s.name() == signal_name)
+            {
+                ui_state
+                    .signal_edit_dialog
+                    .open(window_idx, message.message_id(), signal);
+            }
+        }
+
+        if let Some(message_id) = dbc_window.pending_message_edit.take()
+            && let Some(message) = dbc_window.dbc.get_message(message_id)
+        {
+            ui_state.message_edit_dialog.open(window_idx, message);
+        }
+
+        if !is_open {
+            close_requests.push(window_idx);
+        }
+    }
+
+    if let Some(e) = errors.pop() {
+        ui_state.show_error(e);
+    }
+
+    for window_idx in close_requests {
+        ui_state.request_close_dbc_window(window_idx);
+    }
+
+    // 移除已经确认关闭的窗口，索引会发生变化，所以清空聚焦记录
+    if ui_state.dbc_windows.iter().any(|w| !w.is_open) {
+        ui_state.dbc_windows.retain(|w| w.is_open);
+        ui_state.last_focused_dbc_index = None;
     }
 }
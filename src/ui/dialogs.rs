@@ -0,0 +1,449 @@
+//! 对话框渲染模块
+
+use crate::editable_dbc::signal_bits;
+use crate::ui::menu::import_arxml;
+use crate::ui::message_create_window::message_size_hint;
+use crate::ui::signal_edit_window::parse_f64;
+use crate::ui::state::{UiState, UnsavedChangesChoice};
+use imgui::Ui;
+
+const UNSAVED_CHANGES_POPUP: &str = "Unsaved Changes";
+const ERROR_POPUP: &str = "Error";
+const WARNINGS_POPUP: &str = "Conversion Warnings";
+const ARXML_IMPORT_POPUP: &str = "Import ARXML";
+const MESSAGE_EDIT_POPUP: &str = "Edit Message";
+const SIGNAL_EDIT_POPUP: &str = "Edit Signal";
+
+/// 渲染所有模态对话框
+pub fn render_dialogs(ui: &Ui, ui_state: &mut UiState) {
+    render_message_edit_dialog(ui, ui_state);
+    render_signal_edit_dialog(ui, ui_state);
+    render_unsaved_changes_dialog(ui, ui_state);
+    render_error_dialog(ui, ui_state);
+    render_arxml_import_dialog(ui, ui_state);
+    render_warnings_dialog(ui, ui_state);
+}
+
+/// 渲染 Message 编辑对话框
+fn render_message_edit_dialog(ui: &Ui, ui_state: &mut UiState) {
+    if ui_state.message_edit_dialog.show {
+        ui.open_popup(MESSAGE_EDIT_POPUP);
+        ui_state.message_edit_dialog.show = false;
+    }
+
+    let mut apply = false;
+    let dialog = &mut ui_state.message_edit_dialog;
+
+    ui.modal_popup_config(MESSAGE_EDIT_POPUP)
+        .always_auto_resize(true)
+        .build(|| {
+            ui.input_text("Name", &mut dialog.name_buffer).build();
+            ui.input_text("ID", &mut dialog.id_buffer).build();
+            if dialog.parse_id().is_none() {
+                ui.text_colored([1.0, 0.4, 0.4, 1.0], "Invalid ID");
+            }
+            ui.checkbox("CAN FD", &mut dialog.fd);
+            if dialog.fd {
+                ui.same_line();
+                ui.checkbox("Bit Rate Switch", &mut dialog.bit_rate_switch);
+            }
+            ui.input_text("Size", &mut dialog.size_buffer).build();
+            if dialog.parse_size().is_none() {
+                ui.text_colored([1.0, 0.4, 0.4, 1.0], message_size_hint(dialog.fd));
+            }
+            ui.input_text("Transmitter", &mut dialog.transmitter_buffer)
+                .build();
+            ui.input_text_multiline("Comment", &mut dialog.comment_buffer, [300.0, 60.0])
+                .build();
+            ui.separator();
+
+            ui.enabled(dialog.is_valid(), || {
+                if ui.button("Apply") {
+                    apply = true;
+                    ui.close_current_popup();
+                }
+            });
+            ui.same_line();
+            if ui.button("Cancel") {
+                dialog.close();
+                ui.close_current_popup();
+            }
+        });
+
+    if apply && let Err(e) = apply_message_edit(ui_state) {
+        ui_state.show_error(e);
+    }
+}
+
+/// 将编辑对话框中的修改作为一个事务应用到对应的 DBC
+fn apply_message_edit(ui_state: &mut UiState) -> Result<(), String> {
+    let dialog = &ui_state.message_edit_dialog;
+    let (Some(new_id), Some(new_size)) = (dialog.parse_id(), dialog.parse_size()) else {
+        return Err("Invalid message ID or size".into());
+    };
+    let Some(window) = ui_state.dbc_windows.get_mut(dialog.parent_dbc_id) else {
+        return Err("The DBC window of this message has been closed".into());
+    };
+    let message_id = dialog.message_id;
+    let Some(message) = window.dbc.get_message(message_id) else {
+        return Err(format!("Message {} no longer exists", dialog.original_name));
+    };
+
+    let new_name = dialog.name_buffer.trim();
+    let new_transmitter = dialog.transmitter_buffer.trim();
+    let new_comment = dialog.comment_buffer.as_str();
+    // 只记录真正发生变化的字段
+    let name_changed = message.message_name() != new_name;
+    let size_changed = message.message_size() != new_size;
+    let transmitter_changed = message.transmitter() != new_transmitter;
+    let comment_changed = message.comment() != new_comment;
+    let can_fd_changed = message.is_fd() != dialog.fd
+        || (dialog.fd && message.bit_rate_switch() != dialog.bit_rate_switch);
+    // 缩小长度或者关闭 FD 之前先检查信号是否还在负载范围内
+    if size_changed {
+        message.check_signals_placement(new_size)?;
+    }
+
+    let dbc = &mut window.dbc;
+    dbc.begin_transaction(&dialog.transaction_label())?;
+    if name_changed {
+        dbc.set_message_name(message_id, new_name);
+    }
+    if size_changed {
+        dbc.set_message_size(message_id, new_size);
+    }
+    // 长度先修改，关闭 FD 时检查的是新的长度
+    if can_fd_changed
+        && let Err(e) = dbc.set_message_can_fd(message_id, dialog.fd, dialog.bit_rate_switch)
+    {
+        dbc.rollback_transaction()?;
+        return Err(e);
+    }
+    if transmitter_changed {
+        dbc.set_message_transmitter(message_id, new_transmitter);
+    }
+    if comment_changed {
+        dbc.set_message_comment(message_id, new_comment);
+    }
+    // ID 是其他操作的索引，放在最后修改
+    if new_id != message_id {
+        dbc.set_message_id(message_id, new_id);
+    }
+    dbc.commit_transaction()?;
+
+    window.mark_dirty();
+    Ok(())
+}
+
+/// 渲染 Signal 编辑对话框
+fn render_signal_edit_dialog(ui: &Ui, ui_state: &mut UiState) {
+    if ui_state.signal_edit_dialog.show {
+        ui.open_popup(SIGNAL_EDIT_POPUP);
+        ui_state.signal_edit_dialog.show = false;
+    }
+
+    let mut apply = false;
+    let dialog = &mut ui_state.signal_edit_dialog;
+    let invalid = |ui: &Ui, text: &str| ui.text_colored([1.0, 0.4, 0.4, 1.0], text);
+
+    ui.modal_popup_config(SIGNAL_EDIT_POPUP)
+        .always_auto_resize(true)
+        .build(|| {
+            ui.input_text("Name", &mut dialog.name_buffer).build();
+            ui.input_text("Start Bit", &mut dialog.start_bit_buffer)
+                .build();
+            if dialog.parse_start_bit().is_none() {
+                invalid(ui, "Invalid start bit");
+            }
+            ui.input_text("Length", &mut dialog.size_buffer).build();
+            if dialog.parse_size().is_none() {
+                invalid(ui, "Length must be 1-64 bits");
+            }
+            if ui.radio_button_bool("Intel", dialog.byte_order_is_little) {
+                dialog.byte_order_is_little = true;
+            }
+            ui.same_line();
+            if ui.radio_button_bool("Motorola", !dialog.byte_order_is_little) {
+                dialog.byte_order_is_little = false;
+            }
+            ui.same_line();
+            ui.checkbox("Signed", &mut dialog.signed);
+            ui.input_text("Factor", &mut dialog.factor_buffer).build();
+            if dialog.parse_factor().is_none() {
+                invalid(ui, "Factor must be a non-zero number");
+            }
+            ui.input_text("Offset", &mut dialog.offset_buffer).build();
+            ui.input_text("Min", &mut dialog.min_buffer).build();
+            ui.input_text("Max", &mut dialog.max_buffer).build();
+            ui.input_text("Unit", &mut dialog.unit_buffer).build();
+            ui.input_text_multiline("Comment", &mut dialog.comment_buffer, [300.0, 60.0])
+                .build();
+            ui.separator();
+
+            ui.enabled(dialog.is_valid(), || {
+                if ui.button("Apply") {
+                    apply = true;
+                    ui.close_current_popup();
+                }
+            });
+            ui.same_line();
+            if ui.button("Cancel") {
+                dialog.close();
+                ui.close_current_popup();
+            }
+        });
+
+    if apply && let Err(e) = apply_signal_edit(ui_state) {
+        ui_state.show_error(e);
+    }
+}
+
+/// 将 Signal 编辑对话框中的修改作为一个事务应用到对应的 DBC
+fn apply_signal_edit(ui_state: &mut UiState) -> Result<(), String> {
+    let dialog = &ui_state.signal_edit_dialog;
+    let (Some(start_bit), Some(size), Some(factor)) = (
+        dialog.parse_start_bit(),
+        dialog.parse_size(),
+        dialog.parse_factor(),
+    ) else {
+        return Err("Invalid start bit, length or factor".into());
+    };
+    let (Some(offset), Some(min), Some(max)) = (
+        parse_f64(&dialog.offset_buffer),
+        parse_f64(&dialog.min_buffer),
+        parse_f64(&dialog.max_buffer),
+    ) else {
+        return Err("Invalid offset, min or max".into());
+    };
+    let Some(window) = ui_state.dbc_windows.get_mut(dialog.parent_dbc_id) else {
+        return Err("The DBC window of this signal has been closed".into());
+    };
+    let message_id = dialog.message_id;
+    let Some(message) = window.dbc.get_message(message_id) else {
+        return Err(format!("Message 0x{:X} no longer exists", message_id));
+    };
+    let old_name = dialog.original_name.as_str();
+    let Some(signal) = message.signals().iter().find(|s| s.name() == old_name) else {
+        return Err(format!("Signal {} no longer exists", old_name));
+    };
+
+    let new_name = dialog.name_buffer.trim();
+    if new_name != old_name && message.signals().iter().any(|s| s.name() == new_name) {
+        return Err(format!(
+            "Signal {} already exists in {}",
+            new_name,
+            message.message_name()
+        ));
+    }
+    let byte_order = dialog.byte_order();
+    let payload_bits = message.message_size() * 8;
+    if signal_bits(start_bit, size, byte_order)
+        .iter()
+        .any(|&bit| bit >= payload_bits)
+    {
+        return Err(format!(
+            "Signal {} (start bit {}, length {}) does not fit in {} bytes",
+            new_name,
+            start_bit,
+            size,
+            message.message_size()
+        ));
+    }
+
+    // 只记录真正发生变化的字段
+    let value_type = dialog.value_type();
+    let unit = dialog.unit_buffer.as_str();
+    let comment = dialog.comment_buffer.as_str();
+    let start_bit_changed = signal.start_bit() != start_bit;
+    let size_changed = signal.signal_size() != size;
+    let byte_order_changed = *signal.byte_order() != byte_order;
+    let value_type_changed = *signal.value_type() != value_type;
+    let factor_changed = signal.factor() != factor;
+    let offset_changed = signal.offset() != offset;
+    let min_changed = signal.min() != min;
+    let max_changed = signal.max() != max;
+    let unit_changed = signal.unit() != unit;
+    let comment_changed = signal.comment() != comment;
+
+    let dbc = &mut window.dbc;
+    dbc.begin_transaction(&dialog.transaction_label())?;
+    if start_bit_changed {
+        dbc.set_signal_start_bit(message_id, old_name, start_bit);
+    }
+    if size_changed {
+        dbc.set_signal_size(message_id, old_name, size);
+    }
+    if byte_order_changed {
+        dbc.set_signal_byte_order(message_id, old_name, byte_order);
+    }
+    if value_type_changed {
+        dbc.set_signal_value_type(message_id, old_name, value_type);
+    }
+    if factor_changed {
+        dbc.set_signal_factor(message_id, old_name, factor);
+    }
+    if offset_changed {
+        dbc.set_signal_offset(message_id, old_name, offset);
+    }
+    if min_changed {
+        dbc.set_signal_min(message_id, old_name, min);
+    }
+    if max_changed {
+        dbc.set_signal_max(message_id, old_name, max);
+    }
+    if unit_changed {
+        dbc.set_signal_unit(message_id, old_name, unit);
+    }
+    if comment_changed {
+        dbc.set_signal_comment(message_id, old_name, comment);
+    }
+    // 名称是其他操作的索引，放在最后修改
+    if new_name != old_name {
+        dbc.set_signal_name(message_id, old_name, new_name);
+    }
+    dbc.commit_transaction()?;
+
+    window.mark_dirty();
+    Ok(())
+}
+
+/// 渲染未保存修改确认对话框（Save / Discard / Cancel）
+fn render_unsaved_changes_dialog(ui: &Ui, ui_state: &mut UiState) {
+    let Some(&window_idx) = ui_state.unsaved_changes_dialog.pending_windows.first() else {
+        return;
+    };
+
+    if ui_state.unsaved_changes_dialog.show {
+        ui.open_popup(UNSAVED_CHANGES_POPUP);
+        ui_state.unsaved_changes_dialog.show = false;
+    }
+
+    let file_name = ui_state.dbc_windows[window_idx].file_name().to_string();
+    let mut choice = None;
+
+    ui.modal_popup_config(UNSAVED_CHANGES_POPUP)
+        .always_auto_resize(true)
+        .build(|| {
+            ui.text(format!("'{}' has unsaved changes.", file_name));
+            ui.text("Do you want to save them before closing?");
+            ui.separator();
+
+            if ui.button("Save") {
+                choice = Some(UnsavedChangesChoice::Save);
+            }
+            ui.same_line();
+            if ui.button("Discard") {
+                choice = Some(UnsavedChangesChoice::Discard);
+            }
+            ui.same_line();
+            if ui.button("Cancel") {
+                choice = Some(UnsavedChangesChoice::Cancel);
+            }
+
+            if choice.is_some() {
+                ui.close_current_popup();
+            }
+        });
+
+    if let Some(choice) = choice {
+        ui_state.resolve_unsaved_changes(choice);
+    }
+}
+
+/// 渲染错误对话框
+fn render_error_dialog(ui: &Ui, ui_state: &mut UiState) {
+    if ui_state.error_dialog.show {
+        ui.open_popup(ERROR_POPUP);
+        ui_state.error_dialog.show = false;
+    }
+
+    ui.modal_popup_config(ERROR_POPUP)
+        .always_auto_resize(true)
+        .build(|| {
+            ui.text(&ui_state.error_dialog.message);
+            ui.separator();
+            if ui.button("OK") {
+                ui.close_current_popup();
+            }
+        });
+}
+
+/// 渲染格式转换警告对话框，警告可能很多，放在可滚动的区域中
+fn render_warnings_dialog(ui: &Ui, ui_state: &mut UiState) {
+    if ui_state.warnings_dialog.show {
+        ui.open_popup(WARNINGS_POPUP);
+        ui_state.warnings_dialog.show = false;
+    }
+
+    let mut closed = false;
+    let dialog = &ui_state.warnings_dialog;
+    ui.modal_popup_config(WARNINGS_POPUP)
+        .always_auto_resize(true)
+        .build(|| {
+            ui.text(format!("{} ({})", dialog.title, dialog.warnings.len()));
+            ui.separator();
+            ui.child_window("warnings")
+                .size([600.0, 300.0])
+                .horizontal_scrollbar(true)
+                .build(|| {
+                    for warning in &dialog.warnings {
+                        ui.bullet_text(warning);
+                    }
+                });
+            ui.separator();
+            if ui.button("OK") {
+                ui.close_current_popup();
+                closed = true;
+            }
+        });
+
+    if closed && ui_state.warnings_dialog.exit_after {
+        ui_state.should_exit = true;
+    }
+}
+
+/// 渲染 ARXML 的 CAN 集群选择对话框
+fn render_arxml_import_dialog(ui: &Ui, ui_state: &mut UiState) {
+    if ui_state.arxml_import_dialog.show {
+        ui.open_popup(ARXML_IMPORT_POPUP);
+        ui_state.arxml_import_dialog.show = false;
+    }
+
+    let mut import = false;
+    let dialog = &mut ui_state.arxml_import_dialog;
+    ui.modal_popup_config(ARXML_IMPORT_POPUP)
+        .always_auto_resize(true)
+        .build(|| {
+            let file_name = dialog
+                .path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy();
+            ui.text(format!("'{file_name}' contains several CAN clusters."));
+            ui.text("Choose the cluster to import:");
+            ui.separator();
+            for (i, cluster) in dialog.clusters.iter().enumerate() {
+                if ui
+                    .selectable_config(cluster)
+                    .selected(i == dialog.selected)
+                    .build()
+                {
+                    dialog.selected = i;
+                }
+            }
+            ui.separator();
+            if ui.button("Import") {
+                import = true;
+                ui.close_current_popup();
+            }
+            ui.same_line();
+            if ui.button("Cancel") {
+                ui.close_current_popup();
+            }
+        });
+
+    if import && let Some(cluster) = dialog.clusters.get(dialog.selected).cloned() {
+        let path = dialog.path.clone();
+        import_arxml(ui_state, &path, &cluster);
+    }
+}
@@ -1,8 +1,12 @@
 //! 菜单栏渲染模块
 
+use std::path::Path;
+
+use crate::editable_dbc::{ARXML_EXTENSION, FileFormat, load_arxml, load_arxml_clusters};
 use crate::ui::dbc_window::DbcWindow;
-use crate::ui::state::UiState;
-use imgui::Ui;
+use crate::ui::state::{ArxmlImportDialog, UiState};
+use crate::ui::trace_window::TraceWindow;
+use imgui::{Key, Ui};
 
 /// 渲染主菜单栏
 pub fn render_main_menu_bar(ui: &Ui, ui_state: &mut UiState) {
@@ -11,8 +15,8 @@ pub fn render_main_menu_bar(ui: &Ui, ui_state: &mut UiState) {
         // ui.same_line();
 
         render_file_menu(ui, ui_state);
-        // render_edit_menu(ui, ui_state);
-        // render_view_menu(ui, ui_state);
+        render_edit_menu(ui, ui_state);
+        render_view_menu(ui, ui_state);
         // render_help_menu(ui, ui_state);
     });
 }
@@ -75,19 +79,232 @@ fn render_file_menu(ui: &Ui, ui_state: &mut UiState) {
         if ui.menu_item("Load DBC File") {
             handle_load_dbc_file(ui_state);
         }
+        if ui.menu_item("Import ARXML...") {
+            handle_import_arxml(ui_state);
+        }
+        if ui.menu_item("Open Trace...") {
+            handle_open_trace(ui_state);
+        }
+        if ui.menu_item("Merge...") {
+            ui_state.merge_window.is_open = true;
+        }
+        ui.separator();
+
+        let has_focused_window = ui_state.get_focused_dbc_window().is_some();
+        let has_modified_window = ui_state.dbc_windows.iter().any(|w| w.dbc.is_modified());
+
+        if ui
+            .menu_item_config("Save")
+            .shortcut("Ctrl+S")
+            .enabled(has_focused_window)
+            .build()
+        {
+            handle_save(ui_state);
+        }
+        if ui
+            .menu_item_config("Save As...")
+            .shortcut("Ctrl+Shift+S")
+            .enabled(has_focused_window)
+            .build()
+        {
+            handle_save_as(ui_state);
+        }
+        if ui
+            .menu_item_config("Save All")
+            .enabled(has_modified_window)
+            .build()
+        {
+            handle_save_all(ui_state);
+        }
         ui.separator();
         if ui.menu_item("Exit") {
-            std::process::exit(0);
+            ui_state.request_exit();
+        }
+    });
+}
+
+/// 渲染编辑菜单，撤销/重做作用于当前聚焦的 DBC 窗口
+fn render_edit_menu(ui: &Ui, ui_state: &mut UiState) {
+    ui.menu("Edit", || {
+        let (undo_label, redo_label) = match ui_state.get_focused_dbc_window() {
+            Some(window) => (window.dbc.undo_label(), window.dbc.redo_label()),
+            None => (None, None),
+        };
+
+        let undo_text = match &undo_label {
+            Some(label) => format!("Undo {label}"),
+            None => "Undo".to_string(),
+        };
+        if ui
+            .menu_item_config(&undo_text)
+            .shortcut("Ctrl+Z")
+            .enabled(undo_label.is_some())
+            .build()
+        {
+            handle_undo(ui_state);
+        }
+
+        let redo_text = match &redo_label {
+            Some(label) => format!("Redo {label}"),
+            None => "Redo".to_string(),
+        };
+        if ui
+            .menu_item_config(&redo_text)
+            .shortcut("Ctrl+Y")
+            .enabled(redo_label.is_some())
+            .build()
+        {
+            handle_redo(ui_state);
         }
     });
 }
 
+/// 处理全局快捷键（依赖 last_focused_dbc_index）
+pub fn handle_global_shortcuts(ui: &Ui, ui_state: &mut UiState) {
+    let io = ui.io();
+    if !io.key_ctrl {
+        return;
+    }
+
+    // Ctrl+S: 保存，Ctrl+Shift+S: 另存为
+    if ui.is_key_pressed(Key::S) {
+        if io.key_shift {
+            handle_save_as(ui_state);
+        } else {
+            handle_save(ui_state);
+        }
+    }
+
+    // 正在输入文字时 Ctrl+Z 交给输入框自己处理
+    if io.want_text_input {
+        return;
+    }
+
+    // Ctrl+Z: 撤销，Ctrl+Y / Ctrl+Shift+Z: 重做
+    if ui.is_key_pressed(Key::Z) {
+        if io.key_shift {
+            handle_redo(ui_state);
+        } else {
+            handle_undo(ui_state);
+        }
+    }
+    if ui.is_key_pressed(Key::Y) {
+        handle_redo(ui_state);
+    }
+}
+
+/// 撤销当前聚焦的 DBC 窗口的上一个操作
+fn handle_undo(ui_state: &mut UiState) {
+    let Some(window) = ui_state.get_focused_dbc_window() else {
+        return;
+    };
+    if window.dbc.can_undo()
+        && let Err(e) = window.undo()
+    {
+        ui_state.show_error(e);
+    }
+}
+
+/// 重做当前聚焦的 DBC 窗口的下一个操作
+fn handle_redo(ui_state: &mut UiState) {
+    let Some(window) = ui_state.get_focused_dbc_window() else {
+        return;
+    };
+    if window.dbc.can_redo()
+        && let Err(e) = window.redo()
+    {
+        ui_state.show_error(e);
+    }
+}
+
+/// 保存当前聚焦的 DBC 窗口
+fn handle_save(ui_state: &mut UiState) {
+    let Some(window) = ui_state.get_focused_dbc_window() else {
+        return;
+    };
+    match window.save() {
+        Ok(warnings) => ui_state.show_warnings("Saved with warnings", warnings),
+        Err(e) => ui_state.show_error(e),
+    }
+}
+
+/// 将当前聚焦的 DBC 窗口另存为新文件
+fn handle_save_as(ui_state: &mut UiState) {
+    let Some(idx) = ui_state.last_focused_dbc_index else {
+        return;
+    };
+    let Some(window) = ui_state.dbc_windows.get(idx) else {
+        return;
+    };
+
+    let mut file_dialog = file_dialog().set_file_name(window.file_name());
+    if let Some(parent) = Path::new(&window.file_path).parent() {
+        file_dialog = file_dialog.set_directory(parent);
+    }
+    let Some(path) = file_dialog.save_file() else {
+        return;
+    };
+
+    // 一个文件永远只对应一个 DBC 窗口
+    let path_str = path.to_string_lossy().to_string();
+    if ui_state
+        .dbc_windows
+        .iter()
+        .enumerate()
+        .any(|(i, w)| i != idx && w.file_path == path_str)
+    {
+        ui_state.show_error(format!(
+            "Cannot save as '{}': the file is already open in another window.",
+            path.display()
+        ));
+        return;
+    }
+
+    match ui_state.dbc_windows[idx].save_as(&path) {
+        Ok(warnings) => ui_state.show_warnings("Saved with warnings", warnings),
+        Err(e) => ui_state.show_error(e),
+    }
+}
+
+/// 保存所有有修改的 DBC 窗口
+fn handle_save_all(ui_state: &mut UiState) {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    for window in ui_state.dbc_windows.iter_mut() {
+        if !window.dbc.is_modified() {
+            continue;
+        }
+        match window.save() {
+            Ok(saved_warnings) => warnings.extend(
+                saved_warnings
+                    .into_iter()
+                    .map(|w| format!("{}: {w}", window.file_name())),
+            ),
+            Err(e) => errors.push(e),
+        }
+    }
+    // 两个对话框不能同时打开，有错误时警告附在错误信息之后
+    if errors.is_empty() {
+        ui_state.show_warnings("Saved with warnings", warnings);
+    } else {
+        errors.extend(warnings.into_iter().map(|w| format!("warning: {w}")));
+        ui_state.show_error(errors.join("\n"));
+    }
+}
+
+/// 打开和保存 DBC 的文件对话框，第一个过滤器包含所有支持的格式
+pub fn file_dialog() -> rfd::FileDialog {
+    let extensions: Vec<&str> = FileFormat::ALL.iter().map(|f| f.extension()).collect();
+    let mut dialog = rfd::FileDialog::new().add_filter("Supported files", &extensions);
+    for format in FileFormat::ALL {
+        dialog = dialog.add_filter(format!("{format} files"), &[format.extension()]);
+    }
+    dialog
+}
+
 /// 处理加载 DBC 文件
 fn handle_load_dbc_file(ui_state: &mut UiState) {
-    let Some(path) = rfd::FileDialog::new()
-        .add_filter("DBC files", &["dbc"])
-        .pick_file()
-    else {
+    let Some(path) = file_dialog().pick_file() else {
         return;
     };
 
@@ -105,6 +322,100 @@ fn handle_load_dbc_file(ui_state: &mut UiState) {
     }
 }
 
+/// 导入 ARXML，文件中有多个 CAN 集群时先打开选择对话框
+fn handle_import_arxml(ui_state: &mut UiState) {
+    let Some(path) = rfd::FileDialog::new()
+        .add_filter("AUTOSAR system descriptions", &[ARXML_EXTENSION])
+        .pick_file()
+    else {
+        return;
+    };
+
+    match load_arxml_clusters(&path) {
+        Ok(clusters) => match clusters.as_slice() {
+            [] => ui_state.show_error(format!("'{}' contains no CAN cluster.", path.display())),
+            [only] => import_arxml(ui_state, &path, only),
+            _ => {
+                ui_state.arxml_import_dialog = ArxmlImportDialog {
+                    show: true,
+                    path,
+                    clusters,
+                    selected: 0,
+                };
+            }
+        },
+        Err(e) => ui_state.show_error(e),
+    }
+}
+
+/// 导入 ARXML 中的一个 CAN 集群，选择保存位置后在新的 DBC 窗口中打开，并显示转换日志
+pub fn import_arxml(ui_state: &mut UiState, path: &Path, cluster: &str) {
+    let (dbc, mut log) = match load_arxml(path, Some(cluster)) {
+        Ok(result) => result,
+        Err(e) => {
+            ui_state.show_error(e);
+            return;
+        }
+    };
+
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let mut dialog = file_dialog().set_file_name(format!("{stem}.dbc"));
+    if let Some(parent) = path.parent() {
+        dialog = dialog.set_directory(parent);
+    }
+    let Some(output) = dialog.save_file() else {
+        return;
+    };
+
+    // 一个文件永远只对应一个 DBC 窗口
+    let output_str = output.to_string_lossy().to_string();
+    if ui_state
+        .dbc_windows
+        .iter()
+        .any(|w| w.file_path == output_str)
+    {
+        ui_state.show_error(format!(
+            "Cannot save the imported network as '{}': the file is already open in a window.",
+            output.display()
+        ));
+        return;
+    }
+    let mut window = DbcWindow::new(&output_str, dbc);
+    match window.save_as(&output) {
+        Ok(warnings) => {
+            ui_state.dbc_windows.push(window);
+            ui_state.dbc_window_focus_request = Some(ui_state.dbc_windows.len() - 1);
+            log.extend(warnings);
+            ui_state.show_warnings(&format!("Conversion log of CAN cluster {cluster}"), log);
+        }
+        Err(e) => ui_state.show_error(e),
+    }
+}
+
+/// 打开 CAN 日志，默认使用最后聚焦的 DBC 解码
+fn handle_open_trace(ui_state: &mut UiState) {
+    let Some(path) = rfd::FileDialog::new()
+        .add_filter("CAN traces", &["log", "asc"])
+        .add_filter("candump logs", &["log"])
+        .add_filter("Vector ASC", &["asc"])
+        .pick_file()
+    else {
+        return;
+    };
+
+    let dbc_path = ui_state
+        .last_focused_dbc_index
+        .and_then(|idx| ui_state.dbc_windows.get(idx))
+        .map(|w| w.file_path.clone());
+    match TraceWindow::from_path(ui_state.next_trace_id, &path, dbc_path) {
+        Ok(window) => {
+            ui_state.next_trace_id += 1;
+            ui_state.trace_windows.push(window);
+        }
+        Err(e) => ui_state.show_error(e),
+    }
+}
+
 // /// 渲染消息编辑相关菜单项
 // fn render_message_edit_menu_items(ui: &Ui, ui_state: &mut UiState) {
 //     // Add Message 菜单项
@@ -363,13 +674,12 @@ fn focus_existing_dbc_window(ui_state: &mut UiState, window_index: usize) {
 
 /// 加载新的 DBC 文件
 fn load_new_dbc_file(ui_state: &mut UiState, path: &std::path::Path) {
-    match DbcWindow::from_path(&path) {
-        Ok(dbc_window) => {
+    match DbcWindow::from_path(path) {
+        Ok((dbc_window, warnings)) => {
             ui_state.dbc_windows.push(dbc_window);
+            ui_state.show_warnings("Opened with warnings", warnings);
         }
-        Err(e) => {
-            println!("{}", e.as_str())
-        }
+        Err(e) => ui_state.show_error(e),
     }
 }
 
@@ -445,12 +755,19 @@ fn load_new_dbc_file(ui_state: &mut UiState, path: &std::path::Path) {
 //     // }
 // }
 
-// /// 渲染视图菜单
-// fn render_view_menu(ui: &Ui, ui_state: &mut UiState) {
-//     ui.menu("View", || {
-//         ui.checkbox("Performance Window", &mut ui_state.show_performance_window);
-//     });
-// }
+/// 渲染视图菜单
+fn render_view_menu(ui: &Ui, ui_state: &mut UiState) {
+    ui.menu("View", || {
+        ui.checkbox("Problems", &mut ui_state.problems_window.is_open);
+        ui.checkbox("Compare", &mut ui_state.compare_window.is_open);
+        ui.checkbox("Plot", &mut ui_state.plot_window.is_open);
+        #[cfg(target_os = "linux")]
+        ui.checkbox("Monitor", &mut ui_state.monitor_window.is_open);
+        #[cfg(target_os = "linux")]
+        ui.checkbox("Transmit", &mut ui_state.transmit_window.is_open);
+        ui.checkbox("Performance Window", &mut ui_state.show_performance_window);
+    });
+}
 
 // /// 渲染帮助菜单
 // fn render_help_menu(ui: &Ui, ui_state: &mut UiState) {
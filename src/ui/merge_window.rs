@@ -0,0 +1,282 @@
+//! Merge 窗口 - 三方合并 DBC 文件，逐个选择冲突的解决方式
+
+use std::path::{Path, PathBuf};
+
+use imgui::{TableColumnFlags, TableColumnSetup, TableFlags, Ui};
+
+use crate::editable_dbc::{
+    EditableDbc, MergeConflict, MergeResolutions, MergeResult, MergeSide, load_file,
+};
+use crate::ui::dbc_window::DbcWindow;
+use crate::ui::menu::file_dialog;
+use crate::ui::state::UiState;
+
+const ERROR_COLOR: [f32; 4] = [1.0, 0.4, 0.4, 1.0];
+const UNRESOLVED_COLOR: [f32; 4] = [1.0, 0.85, 0.3, 1.0];
+
+// 三个输入文件的标签，顺序与 MergeWindow::inputs 一致
+const INPUT_LABELS: [&str; 3] = ["Base", "Ours", "Theirs"];
+
+/// 输入文件和读取结果
+#[derive(Default)]
+struct MergeInput {
+    path: Option<PathBuf>,
+    dbc: Option<EditableDbc>,
+    error: Option<String>,
+}
+
+/// Merge 窗口状态
+#[derive(Default)]
+pub struct MergeWindow {
+    pub is_open: bool,
+    inputs: [MergeInput; 3],
+    resolutions: MergeResolutions,
+    // 三个文件都读取成功后才有合并结果，输入或选择变化时重新合并
+    result: Option<MergeResult>,
+}
+
+impl MergeWindow {
+    /// 渲染窗口，用户确认保存合并结果时返回结果和保存路径
+    pub fn render(&mut self, ui: &Ui) -> Option<(EditableDbc, PathBuf)> {
+        let mut save_request = None;
+        let mut is_open = self.is_open;
+        ui.window("Merge")
+            .size([900.0, 500.0], imgui::Condition::FirstUseEver)
+            .opened(&mut is_open)
+            .build(|| {
+                self.render_inputs(ui);
+                ui.separator();
+                let Some(result) = &self.result else {
+                    ui.text_disabled("Choose the common ancestor and both changed files");
+                    return;
+                };
+                let unresolved = result.unresolved_count();
+                if result.conflicts.is_empty() {
+                    ui.text("No conflicts, all changes merged automatically");
+                } else if unresolved > 0 {
+                    ui.text_colored(
+                        UNRESOLVED_COLOR,
+                        format!(
+                            "{} conflict(s), {} unresolved",
+                            result.conflicts.len(),
+                            unresolved
+                        ),
+                    );
+                } else {
+                    ui.text(format!(
+                        "{} conflict(s), all resolved",
+                        result.conflicts.len()
+                    ));
+                }
+
+                ui.same_line();
+                let all_ours = ui.button("Use Ours for All");
+                ui.same_line();
+                let all_theirs = ui.button("Use Theirs for All");
+                ui.same_line();
+                ui.disabled(unresolved > 0, || {
+                    if ui.button("Save Merged As...") {
+                        save_request = self.pick_output_path().map(|path| {
+                            let merged = self.result.as_ref().unwrap().merged.clone();
+                            (merged, path)
+                        });
+                    }
+                });
+
+                let mut changed = None;
+                if let Some(result) = &self.result {
+                    ui.child_window("merge_conflicts")
+                        .build(|| changed = render_conflicts(ui, &result.conflicts));
+                }
+                if all_ours || all_theirs {
+                    let side = if all_ours {
+                        MergeSide::Ours
+                    } else {
+                        MergeSide::Theirs
+                    };
+                    let conflicts = &self.result.as_ref().unwrap().conflicts;
+                    for conflict in conflicts {
+                        self.resolutions.insert(conflict.location.clone(), side);
+                    }
+                    self.remerge();
+                } else if let Some((location, side)) = changed {
+                    self.resolutions.insert(location, side);
+                    self.remerge();
+                }
+            });
+        self.is_open = is_open;
+        save_request
+    }
+
+    fn render_inputs(&mut self, ui: &Ui) {
+        let mut changed = false;
+        for (i, label) in INPUT_LABELS.iter().enumerate() {
+            let _id = ui.push_id_usize(i);
+            if ui.button(format!("{label}...")) {
+                changed |= self.pick_input(i);
+            }
+            ui.same_line_with_pos(90.0);
+            let input = &self.inputs[i];
+            match (&input.path, &input.error) {
+                (_, Some(error)) => ui.text_colored(ERROR_COLOR, error),
+                (Some(path), None) => ui.text(path.to_string_lossy()),
+                (None, None) => ui.text_disabled("(not selected)"),
+            }
+        }
+        if changed {
+            // 换了文件之后旧的选择不再有意义
+            self.resolutions.clear();
+            self.remerge();
+        }
+    }
+
+    fn pick_input(&mut self, index: usize) -> bool {
+        let mut dialog = file_dialog();
+        if let Some(parent) = self.last_directory() {
+            dialog = dialog.set_directory(parent);
+        }
+        let Some(path) = dialog.pick_file() else {
+            return false;
+        };
+        let input = &mut self.inputs[index];
+        match load_file(&path) {
+            Ok(dbc) => {
+                input.dbc = Some(dbc);
+                input.error = None;
+            }
+            Err(e) => {
+                input.dbc = None;
+                input.error = Some(e);
+            }
+        }
+        input.path = Some(path);
+        true
+    }
+
+    fn pick_output_path(&self) -> Option<PathBuf> {
+        let mut dialog = file_dialog().set_file_name("merged.dbc");
+        if let Some(parent) = self.last_directory() {
+            dialog = dialog.set_directory(parent);
+        }
+        dialog.save_file()
+    }
+
+    // 文件对话框默认打开最近选择的文件所在的目录
+    fn last_directory(&self) -> Option<&Path> {
+        self.inputs
+            .iter()
+            .rev()
+            .find_map(|input| input.path.as_deref()?.parent())
+    }
+
+    fn remerge(&mut self) {
+        let [base, ours, theirs] = self.inputs.each_ref().map(|input| input.dbc.as_ref());
+        self.result = match (base, ours, theirs) {
+            (Some(base), Some(ours), Some(theirs)) => {
+                Some(EditableDbc::merge(base, ours, theirs, &self.resolutions))
+            }
+            _ => None,
+        };
+    }
+}
+
+/// 冲突表格，返回本帧修改的选择
+fn render_conflicts(ui: &Ui, conflicts: &[MergeConflict]) -> Option<(String, MergeSide)> {
+    if conflicts.is_empty() {
+        return None;
+    }
+    let _table = ui.begin_table_with_flags(
+        "merge_table",
+        5,
+        TableFlags::BORDERS | TableFlags::RESIZABLE | TableFlags::SCROLL_Y,
+    )?;
+    ui.table_setup_scroll_freeze(0, 1);
+    for (i, name) in ["Location", "Base", "Ours", "Theirs", "Use"]
+        .into_iter()
+        .enumerate()
+    {
+        let flags = if i == 4 {
+            TableColumnFlags::WIDTH_FIXED
+        } else {
+            TableColumnFlags::WIDTH_STRETCH
+        };
+        ui.table_setup_column_with(TableColumnSetup {
+            name,
+            flags,
+            init_width_or_weight: 0.0,
+            user_id: ui.new_id(i),
+        });
+    }
+    ui.table_headers_row();
+
+    let mut changed = None;
+    for (i, conflict) in conflicts.iter().enumerate() {
+        let _id = ui.push_id_usize(i);
+        ui.table_next_row();
+        ui.table_set_column_index(0);
+        if conflict.resolution.is_some() {
+            ui.text(&conflict.location);
+        } else {
+            ui.text_colored(UNRESOLVED_COLOR, &conflict.location);
+        }
+        for (column, value) in [&conflict.base, &conflict.ours, &conflict.theirs]
+            .into_iter()
+            .enumerate()
+        {
+            ui.table_set_column_index(column + 1);
+            match value {
+                Some(value) => {
+                    ui.text_wrapped(value);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text(value);
+                    }
+                }
+                None => ui.text_disabled("(deleted)"),
+            }
+        }
+
+        ui.table_set_column_index(4);
+        for side in [MergeSide::Ours, MergeSide::Theirs] {
+            let label = match side {
+                MergeSide::Ours => "Ours",
+                MergeSide::Theirs => "Theirs",
+            };
+            if ui.radio_button_bool(label, conflict.resolution == Some(side)) {
+                changed = Some((conflict.location.clone(), side));
+            }
+            if side == MergeSide::Ours {
+                ui.same_line();
+            }
+        }
+    }
+    changed
+}
+
+/// 渲染 Merge 窗口，保存合并结果后在新的 DBC 窗口中打开
+pub fn render_merge_window(ui: &Ui, ui_state: &mut UiState) {
+    if !ui_state.merge_window.is_open {
+        return;
+    }
+    let Some((merged, path)) = ui_state.merge_window.render(ui) else {
+        return;
+    };
+
+    // 一个文件永远只对应一个 DBC 窗口
+    let path_str = path.to_string_lossy().to_string();
+    if ui_state.dbc_windows.iter().any(|w| w.file_path == path_str) {
+        ui_state.show_error(format!(
+            "Cannot save the merge result as '{}': the file is already open in a window.",
+            path.display()
+        ));
+        return;
+    }
+    let mut window = DbcWindow::new(&path_str, merged);
+    match window.save_as(&path) {
+        Ok(warnings) => {
+            ui_state.dbc_windows.push(window);
+            ui_state.show_warnings("Saved with warnings", warnings);
+            ui_state.dbc_window_focus_request = Some(ui_state.dbc_windows.len() - 1);
+        }
+        Err(e) => ui_state.show_error(e),
+    }
+}
@@ -1,3 +1,5 @@
+use crate::editable_dbc::is_valid_message_size;
+
 /// Message 新建对话框状态
 #[allow(dead_code)]
 pub struct MessageCreateDialog {
@@ -54,36 +56,12 @@ impl MessageCreateDialog {
 
     /// 解析 ID
     pub fn parse_id(&self) -> Option<u32> {
-        let s = self.id_buffer.trim();
-        if s.is_empty() {
-            return None;
-        }
-        // 尝试解析十六进制（0x 或 0X 前缀）
-        if s.starts_with("0x") || s.starts_with("0X") {
-            if let Ok(id) = u32::from_str_radix(&s[2..], 16) {
-                return Some(id);
-            }
-        }
-        // 尝试解析十进制
-        if let Ok(id) = s.parse::<u32>() {
-            return Some(id);
-        }
-        // 尝试直接解析为十六进制（没有 0x 前缀）
-        if let Ok(id) = u32::from_str_radix(s, 16) {
-            return Some(id);
-        }
-        None
+        parse_message_id(&self.id_buffer)
     }
 
     /// 解析 Size
     pub fn parse_size(&self) -> Option<u64> {
-        let s = self.size_buffer.trim();
-        if let Ok(size) = s.parse::<u64>() {
-            if size <= 8 {
-                return Some(size);
-            }
-        }
-        None
+        parse_message_size(&self.size_buffer, false)
     }
 }
 
@@ -92,3 +70,46 @@ impl Default for MessageCreateDialog {
         Self::new()
     }
 }
+
+/// 解析用户输入的 Message ID，支持 0x 前缀的十六进制、十进制和不带前缀的十六进制
+pub fn parse_message_id(input: &str) -> Option<u32> {
+    let s = input.trim();
+    if s.is_empty() {
+        return None;
+    }
+    // 尝试解析十六进制（0x 或 0X 前缀）
+    if (s.starts_with("0x") || s.starts_with("0X"))
+        && let Ok(id) = u32::from_str_radix(&s[2..], 16)
+    {
+        return Some(id);
+    }
+    // 尝试解析十进制
+    if let Ok(id) = s.parse::<u32>() {
+        return Some(id);
+    }
+    // 尝试直接解析为十六进制（没有 0x 前缀）
+    if let Ok(id) = u32::from_str_radix(s, 16) {
+        return Some(id);
+    }
+    None
+}
+
+/// 解析用户输入的 Message Size（字节数），CAN FD 报文允许 12、16、20、24、32、48、64
+pub fn parse_message_size(input: &str, fd: bool) -> Option<u64> {
+    let s = input.trim();
+    if let Ok(size) = s.parse::<u64>()
+        && is_valid_message_size(size, fd)
+    {
+        return Some(size);
+    }
+    None
+}
+
+/// Size 输入不合法时的提示文本
+pub fn message_size_hint(fd: bool) -> &'static str {
+    if fd {
+        "Size must be 0-8, 12, 16, 20, 24, 32, 48 or 64 bytes"
+    } else {
+        "Size must be 0-8 bytes"
+    }
+}
@@ -1,4 +1,5 @@
 use crate::editable_dbc::EditableMessage;
+use crate::ui::message_create_window::{parse_message_id, parse_message_size};
 use crate::ui::signal_edit_window::SignalEditWindowState;
 
 #[allow(dead_code)]
@@ -32,3 +33,90 @@ impl MessageEditWindowState {
         }
     }
 }
+
+/// Message 编辑对话框状态
+///
+/// Apply 时所有修改在一个事务中提交，撤销一次即可恢复整个对话框的修改
+pub struct MessageEditDialog {
+    pub show: bool,
+    pub parent_dbc_id: usize,
+    // 打开对话框时的 Message ID，用于在 EditableDbc 中找到原来的 Message
+    pub message_id: u32,
+    pub original_name: String,
+
+    // 输入缓冲区
+    pub name_buffer: String,
+    pub comment_buffer: String,
+    pub id_buffer: String,
+    pub size_buffer: String,
+    pub transmitter_buffer: String,
+    pub fd: bool,
+    pub bit_rate_switch: bool,
+}
+
+impl MessageEditDialog {
+    pub fn new() -> Self {
+        Self {
+            show: false,
+            parent_dbc_id: 0,
+            message_id: 0,
+            original_name: String::new(),
+            name_buffer: String::new(),
+            comment_buffer: String::new(),
+            id_buffer: String::new(),
+            size_buffer: String::new(),
+            transmitter_buffer: String::new(),
+            fd: false,
+            bit_rate_switch: false,
+        }
+    }
+
+    /// 打开编辑对话框，缓冲区使用 Message 当前的值填充
+    pub fn open(&mut self, parent_dbc_id: usize, message: &EditableMessage) {
+        self.show = true;
+        self.parent_dbc_id = parent_dbc_id;
+        self.message_id = message.message_id();
+        self.original_name = message.message_name().to_string();
+
+        self.name_buffer = message.message_name().to_string();
+        self.comment_buffer = message.comment().to_string();
+        self.id_buffer = format!("0x{:X}", message.message_id());
+        self.size_buffer = message.message_size().to_string();
+        self.transmitter_buffer = message.transmitter().to_string();
+        self.fd = message.is_fd();
+        self.bit_rate_switch = message.bit_rate_switch();
+    }
+
+    /// 关闭对话框
+    pub fn close(&mut self) {
+        self.show = false;
+    }
+
+    /// 检查输入是否有效
+    pub fn is_valid(&self) -> bool {
+        !self.name_buffer.trim().is_empty()
+            && self.parse_id().is_some()
+            && self.parse_size().is_some()
+    }
+
+    /// 解析 ID
+    pub fn parse_id(&self) -> Option<u32> {
+        parse_message_id(&self.id_buffer)
+    }
+
+    /// 解析 Size
+    pub fn parse_size(&self) -> Option<u64> {
+        parse_message_size(&self.size_buffer, self.fd)
+    }
+
+    /// 事务的名称，显示在 Edit 菜单中，如 "Undo Modify Message EngineData"
+    pub fn transaction_label(&self) -> String {
+        format!("Modify Message {}", self.name_buffer.trim())
+    }
+}
+
+impl Default for MessageEditDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,36 +1,332 @@
-use imgui::Ui;
+use std::cmp::Ordering;
 
-use crate::editable_dbc::EditableMessage;
+use can_dbc::{ByteOrder, ValueType};
+use imgui::{
+    Condition, MouseButton, StyleColor, TableBgTarget, TableColumnFlags, TableColumnSetup,
+    TableFlags, TableSortDirection, TreeNodeFlags, Ui,
+};
+
+use crate::editable_dbc::{EditableMessage, EditableSignal};
+use crate::ui::bit_layout_editor::{BitLayoutEditor, LayoutEdit};
+use crate::ui::plot_window::signal_drag_source;
 
 /// Message 详细窗口状态（包含 Signal 表格）
+///
+/// 窗口只记录 Message ID，每一帧从所属 DBC 窗口的 EditableDbc 中取出最新的 Message
+/// 窗口中的修改以事件的形式返回，由 DBC 窗口应用到 EditableDbc 上，保证可以撤销
 #[allow(dead_code)]
 #[derive(Clone, Default)]
 pub struct MessageWindow {
-    pub message: EditableMessage,
+    pub message_id: u32,
     pub is_open: bool,
     pub parent_dbc_id: usize,
     // 临时信号编辑请求（仅在窗口内双击某个信号时设置，主循环会处理并打开编辑对话框）
     pub pending_signal_edit: Option<String>,
     // 选中信号的名称（用于在表格中高亮整行）
     pub selected_signal_name: Option<String>,
+    bit_layout_editor: BitLayoutEditor,
+    signal_table: SignalTable,
+}
+
+/// Message 窗口中产生的修改
+pub enum MessageWindowEvent {
+    SetSignalStartBit(String, u64),
+    SetSignalSize(String, u64),
 }
 
 impl MessageWindow {
-    pub fn new(message: EditableMessage, parent_dbc_id: usize) -> Self {
+    pub fn new(message_id: u32, parent_dbc_id: usize) -> Self {
         Self {
-            message,
+            message_id,
             is_open: true,
             parent_dbc_id,
             pending_signal_edit: None,
             selected_signal_name: None,
+            bit_layout_editor: BitLayoutEditor::default(),
+            signal_table: SignalTable::default(),
+        }
+    }
+
+    /// 渲染 Message 窗口，window_id 为所属 DBC 窗口的文件路径，用于区分不同 DBC 窗口中相同 ID 的 Message
+    pub fn render(
+        &mut self,
+        ui: &Ui,
+        window_id: &str,
+        message: &EditableMessage,
+    ) -> Option<MessageWindowEvent> {
+        let mut event = None;
+        let mut is_open = self.is_open;
+        ui.window(format!(
+            "Message - {} (0x{:03X})###message_{}_{}",
+            message.message_name(),
+            message.message_id(),
+            window_id,
+            self.message_id
+        ))
+        .size([520.0, 480.0], Condition::FirstUseEver)
+        .opened(&mut is_open)
+        .build(|| {
+            if ui.collapsing_header("Layout", TreeNodeFlags::DEFAULT_OPEN) {
+                ui.text_disabled("Drag a signal to move it, drag its end marker to resize it");
+                event = self
+                    .bit_layout_editor
+                    .render(ui, message)
+                    .map(|edit| match edit {
+                        LayoutEdit::StartBit(name, start_bit) => {
+                            MessageWindowEvent::SetSignalStartBit(name, start_bit)
+                        }
+                        LayoutEdit::Size(name, size) => {
+                            MessageWindowEvent::SetSignalSize(name, size)
+                        }
+                    });
+            }
+            if ui.collapsing_header("Signals", TreeNodeFlags::DEFAULT_OPEN) {
+                let table_event = self.signal_table.render(
+                    ui,
+                    window_id,
+                    message,
+                    self.selected_signal_name.as_deref(),
+                );
+                match table_event {
+                    Some(SignalTableEvent::Select(name)) => {
+                        self.selected_signal_name = Some(name);
+                    }
+                    Some(SignalTableEvent::Open(name)) => {
+                        self.selected_signal_name = Some(name.clone());
+                        self.pending_signal_edit = Some(name);
+                    }
+                    None => {}
+                }
+            }
+            ui.separator();
+            render_value_descriptions(ui, message);
+        });
+        self.is_open = is_open;
+        event
+    }
+}
+
+/// Signal 表格的列，顺序与表格中的顺序一致
+const SIGNAL_COLUMNS: [&str; 10] = [
+    "Name",
+    "Type",
+    "Byte Order",
+    "Start",
+    "Length",
+    "Factor",
+    "Offset",
+    "Min",
+    "Max",
+    "Unit",
+];
+
+/// Signal 表格状态：排序列和筛选条件
+///
+/// Signal 数量不多，每一帧根据最新的 Message 重新排序和筛选
+#[derive(Clone)]
+struct SignalTable {
+    query: String,
+    sort_column: usize,
+    sort_direction: TableSortDirection,
+}
+
+impl Default for SignalTable {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            sort_column: 3,
+            sort_direction: TableSortDirection::Ascending,
+        }
+    }
+}
+
+enum SignalTableEvent {
+    Select(String),
+    // 双击打开 Signal 编辑对话框
+    Open(String),
+}
+
+fn value_type_text(signal: &EditableSignal) -> &'static str {
+    match signal.value_type() {
+        ValueType::Signed => "Signed",
+        ValueType::Unsigned => "Unsigned",
+    }
+}
+
+fn byte_order_text(signal: &EditableSignal) -> &'static str {
+    match signal.byte_order() {
+        ByteOrder::LittleEndian => "Intel",
+        ByteOrder::BigEndian => "Motorola",
+    }
+}
+
+fn compare_signals(a: &EditableSignal, b: &EditableSignal, column: usize) -> Ordering {
+    match column {
+        0 => a.name().cmp(b.name()),
+        1 => value_type_text(a).cmp(value_type_text(b)),
+        2 => byte_order_text(a).cmp(byte_order_text(b)),
+        3 => a.start_bit().cmp(&b.start_bit()),
+        4 => a.signal_size().cmp(&b.signal_size()),
+        5 => a.factor().total_cmp(&b.factor()),
+        6 => a.offset().total_cmp(&b.offset()),
+        7 => a.min().total_cmp(&b.min()),
+        8 => a.max().total_cmp(&b.max()),
+        9 => a.unit().cmp(b.unit()),
+        _ => Ordering::Equal,
+    }
+}
+
+impl SignalTable {
+    /// 按名称、单位或注释筛选后排序，返回 Signal 的索引
+    fn visible_indices(&self, signals: &[EditableSignal]) -> Vec<usize> {
+        let query = self.query.to_lowercase();
+        let mut indices: Vec<usize> = (0..signals.len())
+            .filter(|&i| {
+                let signal = &signals[i];
+                query.is_empty()
+                    || signal.name().to_lowercase().contains(&query)
+                    || signal.unit().to_lowercase().contains(&query)
+                    || signal.comment().to_lowercase().contains(&query)
+            })
+            .collect();
+        indices.sort_by(|&a, &b| {
+            let ordering = compare_signals(&signals[a], &signals[b], self.sort_column);
+            match self.sort_direction {
+                TableSortDirection::Ascending => ordering,
+                TableSortDirection::Descending => ordering.reverse(),
+            }
+        });
+        indices
+    }
+
+    fn render(
+        &mut self,
+        ui: &Ui,
+        file_path: &str,
+        message: &EditableMessage,
+        selected: Option<&str>,
+    ) -> Option<SignalTableEvent> {
+        let signals = message.signals();
+        ui.input_text("Filter##signal_filter", &mut self.query)
+            .hint("name, unit or comment")
+            .build();
+
+        let mut event = None;
+        let _table = ui.begin_table_with_flags(
+            "signals_table",
+            SIGNAL_COLUMNS.len(),
+            TableFlags::RESIZABLE
+                | TableFlags::REORDERABLE
+                | TableFlags::SIZING_FIXED_FIT
+                | TableFlags::BORDERS
+                | TableFlags::ROW_BG
+                | TableFlags::SORTABLE,
+        )?;
+
+        for (i, name) in SIGNAL_COLUMNS.iter().enumerate() {
+            ui.table_setup_column_with(TableColumnSetup {
+                name: *name,
+                flags: if i == self.sort_column {
+                    TableColumnFlags::DEFAULT_SORT
+                } else {
+                    TableColumnFlags::default()
+                },
+                init_width_or_weight: 0.0,
+                user_id: ui.new_id(i),
+            });
+        }
+        ui.table_headers_row();
+
+        if let Some(mut sort_specs) = ui.table_sort_specs_mut() {
+            if sort_specs.should_sort()
+                && let Some(spec) = sort_specs.specs().iter().next()
+            {
+                self.sort_column = spec.column_idx();
+                self.sort_direction = spec
+                    .sort_direction()
+                    .unwrap_or(TableSortDirection::Ascending);
+            }
+            sort_specs.set_sorted();
+        }
+
+        for idx in self.visible_indices(signals) {
+            let signal = &signals[idx];
+            ui.table_next_row();
+            if selected == Some(signal.name()) {
+                ui.table_set_bg_color(
+                    TableBgTarget::ROW_BG0,
+                    ui.style_color(StyleColor::TextSelectedBg),
+                );
+            }
+
+            ui.table_set_column_index(0);
+            if ui
+                .selectable_config(signal.name())
+                .span_all_columns(true)
+                .build()
+            {
+                event = Some(SignalTableEvent::Select(signal.name().to_string()));
+            }
+            if ui.is_item_hovered() && ui.is_mouse_double_clicked(MouseButton::Left) {
+                event = Some(SignalTableEvent::Open(signal.name().to_string()));
+            }
+            // 拖到 Plot 窗口中绘制这个信号
+            signal_drag_source(
+                ui,
+                file_path,
+                message.message_id(),
+                Some(idx),
+                signal.name(),
+            );
+
+            let cells = [
+                value_type_text(signal).to_string(),
+                byte_order_text(signal).to_string(),
+                signal.start_bit().to_string(),
+                signal.signal_size().to_string(),
+                signal.factor().to_string(),
+                signal.offset().to_string(),
+                signal.min().to_string(),
+                signal.max().to_string(),
+                signal.unit().to_string(),
+            ];
+            for (column, text) in cells.iter().enumerate() {
+                ui.table_set_column_index(column + 1);
+                ui.text(text);
+            }
         }
+        event
     }
+}
 
-    pub fn render(&mut self, ui: &Ui) {
-        println!(
-            "Rendering MessageWindow for message ID: {}",
-            self.message.message_id()
-        );
-        ui.separator();
+/// 渲染 Message 中所有带有值描述 (VAL_) 的 Signal
+fn render_value_descriptions(ui: &Ui, message: &EditableMessage) {
+    let signals: Vec<&EditableSignal> = message
+        .signals()
+        .iter()
+        .filter(|s| !s.value_descriptions().is_empty())
+        .collect();
+    if signals.is_empty() {
+        return;
     }
+
+    if ui.collapsing_header("Value Descriptions", TreeNodeFlags::DEFAULT_OPEN) {
+        for signal in signals {
+            if let Some(_node) = ui.tree_node(signal.name()) {
+                for (value, description) in signal.value_descriptions() {
+                    ui.text(format!("{value} = {description}"));
+                }
+            }
+        }
+    }
+}
+
+/// 将值描述格式化为一行文本，如 "0=Off, 1=On"，用于表格和 tooltip
+pub fn format_value_descriptions(signal: &EditableSignal) -> String {
+    signal
+        .value_descriptions()
+        .iter()
+        .map(|(value, description)| format!("{value}={description}"))
+        .collect::<Vec<_>>()
+        .join(", ")
 }
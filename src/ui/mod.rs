@@ -4,16 +4,37 @@
 //! - `state`: UI 状态结构和 Undo/Redo 系统
 //! - `dbc_window`: DBC 浏览器窗口渲染
 //! - `message_window`: Message 详细窗口渲染（包含 Signal 表格）
+//! - `bit_layout_editor`: Message 窗口中的 Signal 布局编辑器
+//! - `attributes_window`: 属性面板（BA_DEF_ / BA_ 的查看和编辑）
+//! - `problems_window`: DBC 检查结果窗口
+//! - `compare_window`: 并排比较两个 DBC
+//! - `merge_window`: 三方合并和冲突解决
+//! - `trace_window`: 按 DBC 解码显示记录的 CAN 日志
+//! - `plot_window`: 把 Trace 中的信号画成随时间变化的曲线
+//! - `monitor_window`: 实时接收 SocketCAN 接口上的报文（仅 Linux）
+//! - `transmit_window`: 按信号定义编辑物理值并发送到 SocketCAN 接口（仅 Linux）
 //! - `dialogs`: 各种对话框（错误、关于、编辑等）
 //! - `menu`: 菜单栏和文件操作
 
+mod attributes_window;
+mod bit_layout_editor;
+mod compare_window;
 mod dbc_window;
+mod dialogs;
 mod menu;
+mod merge_window;
 mod message_create_window;
 mod message_edit_window;
 mod message_window;
+#[cfg(target_os = "linux")]
+mod monitor_window;
+mod plot_window;
+mod problems_window;
 mod signal_edit_window;
 pub mod state;
+mod trace_window;
+#[cfg(target_os = "linux")]
+mod transmit_window;
 
 use imgui::Ui;
 use std::time::Duration;
@@ -30,9 +51,18 @@ pub fn render_ui(ui: &Ui, delta_s: Duration, target_frame_time: Duration, ui_sta
 
     menu::render_main_menu_bar(ui, ui_state);
     dbc_window::render_dbc_windows(ui, ui_state);
-    // menu::handle_global_shortcuts(ui, ui_state);
+    problems_window::render_problems_window(ui, ui_state);
+    compare_window::render_compare_window(ui, ui_state);
+    merge_window::render_merge_window(ui, ui_state);
+    trace_window::render_trace_windows(ui, ui_state);
+    plot_window::render_plot_window(ui, ui_state);
+    #[cfg(target_os = "linux")]
+    monitor_window::render_monitor_window(ui, ui_state);
+    #[cfg(target_os = "linux")]
+    transmit_window::render_transmit_window(ui, ui_state);
+    menu::handle_global_shortcuts(ui, ui_state);
     // message_window::render_message_windows(ui, ui_state);
-    // dialogs::render_dialogs(ui, ui_state);
+    dialogs::render_dialogs(ui, ui_state);
 }
 
 /// 设置主dockspace占满整个窗口
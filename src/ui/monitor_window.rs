@@ -0,0 +1,278 @@
+//! Monitor 窗口 - 实时接收 SocketCAN 接口上的报文，按 ID 显示最新的值
+//!
+//! 接收在后台线程中进行，界面每一帧取出新收到的帧，使用最后聚焦的 DBC 窗口解码。
+
+use std::collections::HashSet;
+
+use imgui::{Direction, TableColumnFlags, TableColumnSetup, TableFlags, Ui};
+
+use crate::monitor::{Monitor, MonitorEntry};
+use crate::socketcan::LiveReceiver;
+use crate::trace::DecodedFrame;
+use crate::ui::dbc_window::DbcWindow;
+use crate::ui::state::UiState;
+
+const UNKNOWN_COLOR: [f32; 4] = [1.0, 0.4, 0.4, 1.0];
+const ERROR_COLOR: [f32; 4] = [1.0, 0.4, 0.4, 1.0];
+const RUNNING_COLOR: [f32; 4] = [0.4, 0.9, 0.4, 1.0];
+
+/// Monitor 窗口状态
+pub struct MonitorWindow {
+    pub is_open: bool,
+    interface: String,
+    receiver: Option<LiveReceiver>,
+    monitor: Monitor,
+    error: Option<String>,
+    // 解码使用的 DBC 的路径和数据版本号，改变后重新解码
+    cache_key: Option<(String, u64)>,
+    expanded: HashSet<(bool, u32)>,
+}
+
+impl Default for MonitorWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            interface: "vcan0".to_string(),
+            receiver: None,
+            monitor: Monitor::default(),
+            error: None,
+            cache_key: None,
+            expanded: HashSet::new(),
+        }
+    }
+}
+
+impl MonitorWindow {
+    fn start(&mut self) {
+        self.error = None;
+        match LiveReceiver::start(self.interface.trim()) {
+            Ok(receiver) => {
+                self.monitor.clear();
+                self.receiver = Some(receiver);
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    // 取出后台线程收到的帧，出错时停止接收
+    fn poll(&mut self, dbc_window: Option<&DbcWindow>) {
+        let key = dbc_window.map(|w| (w.file_path.clone(), w.dbc.revision()));
+        let dbc = dbc_window.map(|w| &w.dbc);
+        if key != self.cache_key {
+            self.monitor.redecode(dbc);
+            self.cache_key = key;
+        }
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+        match receiver.poll() {
+            Ok(frames) => {
+                for frame in frames {
+                    self.monitor.push(frame, dbc);
+                }
+            }
+            Err(e) => {
+                self.error = Some(e);
+                self.receiver = None;
+            }
+        }
+    }
+
+    pub fn render(&mut self, ui: &Ui, dbc_window: Option<&DbcWindow>) {
+        self.poll(dbc_window);
+
+        let mut is_open = self.is_open;
+        ui.window("Monitor")
+            .size([760.0, 480.0], imgui::Condition::FirstUseEver)
+            .opened(&mut is_open)
+            .build(|| {
+                self.render_toolbar(ui, dbc_window);
+                ui.separator();
+                self.render_table(ui);
+            });
+        self.is_open = is_open;
+        // 关闭窗口时停止接收
+        if !self.is_open {
+            self.receiver = None;
+        }
+    }
+
+    fn render_toolbar(&mut self, ui: &Ui, dbc_window: Option<&DbcWindow>) {
+        let running = self.receiver.is_some();
+        ui.disabled(running, || {
+            ui.set_next_item_width(120.0);
+            ui.input_text("Interface", &mut self.interface).build();
+        });
+        ui.same_line();
+        if running {
+            if ui.button("Stop") {
+                self.receiver = None;
+            }
+        } else if ui.button("Start") {
+            self.start();
+        }
+        ui.same_line();
+        if ui.button("Clear") {
+            self.monitor.clear();
+        }
+
+        ui.same_line();
+        match (&self.receiver, &self.error) {
+            (Some(receiver), _) => ui.text_colored(
+                RUNNING_COLOR,
+                format!("Receiving on {}", receiver.interface()),
+            ),
+            (None, Some(error)) => ui.text_colored(ERROR_COLOR, error),
+            (None, None) => ui.text_disabled("Stopped"),
+        }
+
+        match dbc_window {
+            Some(window) => ui.text(format!("Decoding with {}", window.file_name())),
+            None => ui.text_disabled("No DBC open, frames are not decoded"),
+        }
+        ui.same_line();
+        ui.text(format!(
+            "| {} IDs, {} frames, {} error frames",
+            self.monitor.len(),
+            self.monitor.frame_count(),
+            self.monitor.error_count()
+        ));
+    }
+
+    fn render_table(&mut self, ui: &Ui) {
+        let Some(_table) = ui.begin_table_with_flags(
+            "monitor_table",
+            6,
+            TableFlags::BORDERS | TableFlags::RESIZABLE | TableFlags::ROW_BG | TableFlags::SCROLL_Y,
+        ) else {
+            return;
+        };
+        ui.table_setup_scroll_freeze(0, 1);
+        for (i, name) in ["ID", "Name", "DLC", "Data / Value", "Count", "Cycle (ms)"]
+            .into_iter()
+            .enumerate()
+        {
+            let flags = if i == 1 || i == 3 {
+                TableColumnFlags::WIDTH_STRETCH
+            } else {
+                TableColumnFlags::WIDTH_FIXED
+            };
+            ui.table_setup_column_with(TableColumnSetup {
+                name,
+                flags,
+                init_width_or_weight: 0.0,
+                user_id: ui.new_id(i),
+            });
+        }
+        ui.table_headers_row();
+
+        let mut toggled = None;
+        for entry in self.monitor.entries() {
+            let key = (entry.frame.extended, entry.frame.id);
+            let _id = ui.push_id(format!("{}_{}", key.0, key.1));
+            ui.table_next_row();
+            let expanded = self.expanded.contains(&key);
+            if render_entry_row(ui, entry, expanded) {
+                toggled = Some(key);
+            }
+            if let Some(decoded) = &entry.decoded
+                && expanded
+            {
+                render_signal_rows(ui, decoded);
+            }
+        }
+
+        if let Some(key) = toggled
+            && !self.expanded.remove(&key)
+        {
+            self.expanded.insert(key);
+        }
+    }
+}
+
+/// 渲染一个 ID 的最新一帧，点击展开按钮时返回 true
+fn render_entry_row(ui: &Ui, entry: &MonitorEntry, expanded: bool) -> bool {
+    let frame = &entry.frame;
+    let mut toggled = false;
+
+    ui.table_set_column_index(0);
+    if entry
+        .decoded
+        .as_ref()
+        .is_some_and(|d| !d.signals.is_empty())
+    {
+        let direction = if expanded {
+            Direction::Down
+        } else {
+            Direction::Right
+        };
+        toggled = ui.arrow_button("##expand", direction);
+        ui.same_line();
+    }
+    if frame.extended {
+        ui.text(format!("0x{:08X}x", frame.id));
+    } else {
+        ui.text(format!("0x{:03X}", frame.id));
+    }
+
+    ui.table_set_column_index(1);
+    match &entry.decoded {
+        Some(decoded) => ui.text(&decoded.message_name),
+        None => ui.text_colored(UNKNOWN_COLOR, "Unknown"),
+    }
+
+    ui.table_set_column_index(2);
+    let mut dlc = frame.data.len().to_string();
+    if frame.fd {
+        dlc += if frame.bit_rate_switch {
+            " FD BRS"
+        } else {
+            " FD"
+        };
+    }
+    ui.text(dlc);
+
+    ui.table_set_column_index(3);
+    ui.text(frame.data_hex());
+
+    ui.table_set_column_index(4);
+    ui.text(entry.count.to_string());
+
+    ui.table_set_column_index(5);
+    match entry.cycle_time {
+        Some(cycle_time) => ui.text(format!("{:.1}", cycle_time * 1000.0)),
+        None => ui.text_disabled("-"),
+    }
+    toggled
+}
+
+fn render_signal_rows(ui: &Ui, decoded: &DecodedFrame) {
+    for signal in &decoded.signals {
+        ui.table_next_row();
+        ui.table_set_column_index(1);
+        ui.text(format!("    {}", signal.name));
+        ui.table_set_column_index(3);
+        let mut value = format!("{}", signal.value);
+        if !signal.unit.is_empty() {
+            value += &format!(" {}", signal.unit);
+        }
+        if let Some(description) = &signal.description {
+            value += &format!(" ({description})");
+        }
+        ui.text(value);
+        if ui.is_item_hovered() {
+            ui.tooltip_text(format!("Raw: {}", signal.raw));
+        }
+    }
+}
+
+/// 渲染 Monitor 窗口，使用最后聚焦的 DBC 窗口解码
+pub fn render_monitor_window(ui: &Ui, ui_state: &mut UiState) {
+    if !ui_state.monitor_window.is_open {
+        return;
+    }
+    let dbc_window = ui_state
+        .last_focused_dbc_index
+        .and_then(|idx| ui_state.dbc_windows.get(idx));
+    ui_state.monitor_window.render(ui, dbc_window);
+}
@@ -0,0 +1,727 @@
+//! Plot 窗口 - 把 Trace 中解码得到的信号画成随时间变化的曲线
+//!
+//! 从 DBC 窗口的消息表或 Message 窗口的 Signal 表中把 Message / Signal 拖进来即可添加曲线。
+//! 滚轮缩放时间轴（在左侧纵轴上滚动则缩放该纵轴），左键拖动平移，
+//! 单击放置光标 1，Shift + 单击放置光标 2，右键清除光标。
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use imgui::{DragDropFlags, MouseButton, TableColumnFlags, TableColumnSetup, TableFlags, Ui};
+
+use crate::trace::{Sample, sample_at};
+use crate::ui::dbc_window::DbcWindow;
+use crate::ui::state::UiState;
+use crate::ui::trace_window::TraceWindow;
+
+/// 拖放的数据类型名称
+const DRAG_DROP_TYPE: &str = "ROXY_SIGNAL";
+
+/// 最多的纵轴数量
+const MAX_AXES: usize = 4;
+const AXIS_WIDTH: f32 = 64.0;
+const TIME_AXIS_HEIGHT: f32 = 22.0;
+
+const SERIES_COLORS: [[f32; 4]; 8] = [
+    [0.35, 0.7, 1.0, 1.0],
+    [1.0, 0.6, 0.2, 1.0],
+    [0.4, 0.9, 0.4, 1.0],
+    [1.0, 0.4, 0.6, 1.0],
+    [0.8, 0.6, 1.0, 1.0],
+    [1.0, 0.9, 0.3, 1.0],
+    [0.3, 0.9, 0.9, 1.0],
+    [0.9, 0.9, 0.9, 1.0],
+];
+const GRID_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.08];
+const AXIS_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.5];
+const BACKGROUND_COLOR: [f32; 4] = [0.08, 0.08, 0.1, 1.0];
+const CURSOR_COLORS: [[f32; 4]; 2] = [[1.0, 1.0, 0.3, 0.9], [0.3, 1.0, 1.0, 0.9]];
+
+/// 拖放时传递的信号，signal_index 为 None 表示整个 Message 的所有信号
+#[derive(Clone, Copy)]
+pub struct SignalDragPayload {
+    // DBC 窗口文件路径的哈希，用来找到来源的 DBC
+    dbc_key: u64,
+    message_id: u32,
+    signal_index: Option<usize>,
+}
+
+fn dbc_key(file_path: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    file_path.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 把上一个控件设为拖放来源，在消息表和 Signal 表中调用
+pub fn signal_drag_source(
+    ui: &Ui,
+    file_path: &str,
+    message_id: u32,
+    signal_index: Option<usize>,
+    label: &str,
+) {
+    let payload = SignalDragPayload {
+        dbc_key: dbc_key(file_path),
+        message_id,
+        signal_index,
+    };
+    if let Some(tooltip) = ui
+        .drag_drop_source_config(DRAG_DROP_TYPE)
+        .begin_payload(payload)
+    {
+        ui.text(format!("Plot {label}"));
+        tooltip.end();
+    }
+}
+
+/// 一条曲线
+struct PlotSeries {
+    message_id: u32,
+    signal_name: String,
+    unit: String,
+    axis: usize,
+    color: [f32; 4],
+    // 有值描述的信号画成阶梯，并在值变化处标出描述文本
+    value_descriptions: Vec<(i64, String)>,
+    samples: Vec<Sample>,
+}
+
+impl PlotSeries {
+    fn is_step(&self) -> bool {
+        !self.value_descriptions.is_empty()
+    }
+
+    fn describe(&self, sample: &Sample) -> String {
+        let mut text = format!("{}", sample.value);
+        if !self.unit.is_empty() {
+            text += &format!(" {}", self.unit);
+        }
+        if let Some((_, description)) = self
+            .value_descriptions
+            .iter()
+            .find(|(raw, _)| *raw == sample.raw)
+        {
+            text += &format!(" ({description})");
+        }
+        text
+    }
+}
+
+/// 可见范围，None 表示自动适应所有数据
+type Range = Option<(f64, f64)>;
+
+/// Plot 窗口状态
+#[derive(Default)]
+pub struct PlotWindow {
+    pub is_open: bool,
+    // 用 Trace 窗口的 id 记录数据来源
+    pub trace_id: Option<usize>,
+    series: Vec<PlotSeries>,
+    // 曲线数据缓存，Trace 窗口和它的解码结果不变时不重新提取
+    cache_key: Option<(usize, u64)>,
+    time_range: Range,
+    axis_ranges: [Range; MAX_AXES],
+    cursors: [Option<f64>; 2],
+    // 这次按下左键后拖动过，松开时不放置光标
+    panned: bool,
+}
+
+/// 绘图区域和各个坐标轴的屏幕位置
+struct PlotLayout {
+    min: [f32; 2],
+    max: [f32; 2],
+    time: (f64, f64),
+    axes: Vec<(usize, (f64, f64))>,
+}
+
+impl PlotLayout {
+    fn x(&self, time: f64) -> f32 {
+        let (t0, t1) = self.time;
+        self.min[0] + ((time - t0) / (t1 - t0)) as f32 * (self.max[0] - self.min[0])
+    }
+
+    fn time_at(&self, x: f32) -> f64 {
+        let (t0, t1) = self.time;
+        t0 + ((x - self.min[0]) / (self.max[0] - self.min[0])) as f64 * (t1 - t0)
+    }
+
+    fn y(&self, range: (f64, f64), value: f64) -> f32 {
+        let (v0, v1) = range;
+        self.max[1] - ((value - v0) / (v1 - v0)) as f32 * (self.max[1] - self.min[1])
+    }
+
+    fn axis_range(&self, axis: usize) -> (f64, f64) {
+        self.axes
+            .iter()
+            .find(|(a, _)| *a == axis)
+            .map_or((0.0, 1.0), |(_, r)| *r)
+    }
+}
+
+impl PlotWindow {
+    /// 渲染 Plot 窗口，拖入的信号在 dbc_windows 中查找
+    pub fn render(&mut self, ui: &Ui, traces: &[TraceWindow], dbc_windows: &[DbcWindow]) {
+        // 选中的 Trace 被关闭后改用第一个打开的 Trace
+        if !traces.iter().any(|t| Some(t.id) == self.trace_id) {
+            self.trace_id = traces.first().map(|t| t.id);
+        }
+        let trace = traces.iter().find(|t| Some(t.id) == self.trace_id);
+        self.update_samples(trace);
+
+        let mut is_open = self.is_open;
+        ui.window("Plot")
+            .size([900.0, 560.0], imgui::Condition::FirstUseEver)
+            .opened(&mut is_open)
+            .build(|| {
+                self.render_toolbar(ui, traces);
+                if trace.is_none() {
+                    ui.text_disabled("Open a trace to plot its signals");
+                }
+                let readout_height = 28.0 + 22.0 * self.series.len().max(1) as f32;
+                ui.child_window("plot_area")
+                    .size([0.0, -readout_height])
+                    .build(|| self.render_plot(ui, dbc_windows));
+                self.render_readout(ui);
+            });
+        self.is_open = is_open;
+    }
+
+    fn update_samples(&mut self, trace: Option<&TraceWindow>) {
+        let key = trace.map(|t| (t.id, t.decode_generation()));
+        if key == self.cache_key {
+            return;
+        }
+        for series in &mut self.series {
+            series.samples = trace
+                .map(|t| {
+                    t.trace()
+                        .signal_samples(t.decoded(), series.message_id, &series.signal_name)
+                })
+                .unwrap_or_default();
+        }
+        self.cache_key = key;
+    }
+
+    fn render_toolbar(&mut self, ui: &Ui, traces: &[TraceWindow]) {
+        let preview = traces
+            .iter()
+            .find(|t| Some(t.id) == self.trace_id)
+            .map(|t| t.file_name())
+            .unwrap_or("(no trace)");
+        ui.set_next_item_width(250.0);
+        if let Some(_combo) = ui.begin_combo("Trace", preview) {
+            for trace in traces {
+                if ui
+                    .selectable_config(format!("{}##{}", trace.file_name(), trace.id))
+                    .selected(Some(trace.id) == self.trace_id)
+                    .build()
+                {
+                    self.trace_id = Some(trace.id);
+                }
+            }
+        }
+        ui.same_line();
+        if ui.button("Fit") {
+            self.time_range = None;
+            self.axis_ranges = [None; MAX_AXES];
+        }
+        ui.same_line();
+        if ui.button("Clear") {
+            self.series.clear();
+            self.cursors = [None; 2];
+        }
+        if let [Some(c1), Some(c2)] = self.cursors {
+            ui.same_line();
+            ui.text(format!("dt = {:.6} s", c2 - c1));
+        }
+    }
+
+    // 添加拖入的信号，已经存在的信号不重复添加
+    fn add_dropped(&mut self, payload: SignalDragPayload, dbc_windows: &[DbcWindow]) {
+        let Some(message) = dbc_windows
+            .iter()
+            .find(|w| dbc_key(&w.file_path) == payload.dbc_key)
+            .and_then(|w| w.dbc.get_message(payload.message_id))
+        else {
+            return;
+        };
+        let signals: Vec<_> = match payload.signal_index {
+            Some(index) => message.signals().get(index).into_iter().collect(),
+            None => message.signals().iter().collect(),
+        };
+        for signal in signals {
+            if self
+                .series
+                .iter()
+                .any(|s| s.message_id == payload.message_id && s.signal_name == signal.name())
+            {
+                continue;
+            }
+            // 同一单位的信号共用一个纵轴，否则使用下一个空闲的纵轴
+            let axis = self
+                .series
+                .iter()
+                .find(|s| s.unit == signal.unit())
+                .map(|s| s.axis)
+                .or_else(|| (0..MAX_AXES).find(|a| !self.series.iter().any(|s| s.axis == *a)))
+                .unwrap_or(MAX_AXES - 1);
+            self.series.push(PlotSeries {
+                message_id: payload.message_id,
+                signal_name: signal.name().to_string(),
+                unit: signal.unit().to_string(),
+                axis,
+                color: SERIES_COLORS[self.series.len() % SERIES_COLORS.len()],
+                value_descriptions: signal.value_descriptions().clone(),
+                samples: Vec::new(),
+            });
+        }
+        // 让新曲线在下一帧提取数据
+        self.cache_key = None;
+    }
+
+    fn data_time_range(&self) -> (f64, f64) {
+        let mut range: Option<(f64, f64)> = None;
+        for sample in self
+            .series
+            .iter()
+            .flat_map(|s| [s.samples.first(), s.samples.last()])
+            .flatten()
+        {
+            range = Some(match range {
+                Some((t0, t1)) => (t0.min(sample.time), t1.max(sample.time)),
+                None => (sample.time, sample.time),
+            });
+        }
+        widen(range.unwrap_or((0.0, 1.0)))
+    }
+
+    fn data_axis_range(&self, axis: usize) -> (f64, f64) {
+        let mut range: Option<(f64, f64)> = None;
+        for series in self.series.iter().filter(|s| s.axis == axis) {
+            for sample in &series.samples {
+                range = Some(match range {
+                    Some((v0, v1)) => (v0.min(sample.value), v1.max(sample.value)),
+                    None => (sample.value, sample.value),
+                });
+            }
+        }
+        // 上下各留 5% 的空白
+        let (v0, v1) = widen(range.unwrap_or((0.0, 1.0)));
+        let margin = (v1 - v0) * 0.05;
+        (v0 - margin, v1 + margin)
+    }
+
+    fn used_axes(&self) -> Vec<usize> {
+        (0..MAX_AXES)
+            .filter(|a| self.series.iter().any(|s| s.axis == *a))
+            .collect()
+    }
+
+    fn render_plot(&mut self, ui: &Ui, dbc_windows: &[DbcWindow]) {
+        let origin = ui.cursor_screen_pos();
+        let size = ui.content_region_avail();
+        if size[0] < 50.0 || size[1] < 50.0 {
+            return;
+        }
+        ui.invisible_button("plot_canvas", size);
+        let hovered = ui.is_item_hovered();
+        if let Some(target) = ui.drag_drop_target() {
+            if let Some(Ok(payload)) = target
+                .accept_payload::<SignalDragPayload, _>(DRAG_DROP_TYPE, DragDropFlags::empty())
+            {
+                self.add_dropped(payload.data, dbc_windows);
+            }
+            target.pop();
+        }
+
+        let used_axes = self.used_axes();
+        let gutter = AXIS_WIDTH * used_axes.len().max(1) as f32;
+        let layout = PlotLayout {
+            min: [origin[0] + gutter, origin[1] + 4.0],
+            max: [
+                origin[0] + size[0] - 8.0,
+                origin[1] + size[1] - TIME_AXIS_HEIGHT,
+            ],
+            time: self.time_range.unwrap_or_else(|| self.data_time_range()),
+            axes: used_axes
+                .iter()
+                .map(|&a| {
+                    (
+                        a,
+                        self.axis_ranges[a].unwrap_or_else(|| self.data_axis_range(a)),
+                    )
+                })
+                .collect(),
+        };
+
+        if hovered {
+            self.handle_input(ui, &layout, origin);
+        }
+        self.draw(ui, &layout, origin);
+        if self.series.is_empty() {
+            let text = "Drag messages or signals here from the DBC and Message windows";
+            let text_size = ui.calc_text_size(text);
+            ui.get_window_draw_list().add_text(
+                [
+                    (layout.min[0] + layout.max[0] - text_size[0]) / 2.0,
+                    (layout.min[1] + layout.max[1] - text_size[1]) / 2.0,
+                ],
+                AXIS_COLOR,
+                text,
+            );
+        }
+    }
+
+    fn handle_input(&mut self, ui: &Ui, layout: &PlotLayout, origin: [f32; 2]) {
+        let io = ui.io();
+        let mouse = io.mouse_pos;
+        // 鼠标在左侧的纵轴区域时操作对应的纵轴
+        let axis_under_mouse = (mouse[0] < layout.min[0])
+            .then(|| ((mouse[0] - origin[0]) / AXIS_WIDTH) as usize)
+            .and_then(|i| layout.axes.get(i).copied());
+
+        if io.mouse_wheel != 0.0 {
+            let factor = 0.85_f64.powf(io.mouse_wheel as f64);
+            match axis_under_mouse {
+                Some((axis, (v0, v1))) => {
+                    let center = v0
+                        + (layout.max[1] - mouse[1]) as f64
+                            / (layout.max[1] - layout.min[1]) as f64
+                            * (v1 - v0);
+                    self.axis_ranges[axis] = Some(zoom((v0, v1), center, factor));
+                }
+                None => {
+                    let center = layout.time_at(mouse[0]);
+                    self.time_range = Some(zoom(layout.time, center, factor));
+                }
+            }
+        }
+
+        if ui.is_mouse_clicked(MouseButton::Left) {
+            self.panned = false;
+        }
+        if ui.is_mouse_dragging(MouseButton::Left) {
+            self.panned = true;
+            let delta = io.mouse_delta;
+            match axis_under_mouse {
+                Some((axis, (v0, v1))) => {
+                    let shift =
+                        delta[1] as f64 / (layout.max[1] - layout.min[1]) as f64 * (v1 - v0);
+                    self.axis_ranges[axis] = Some((v0 + shift, v1 + shift));
+                }
+                None => {
+                    let (t0, t1) = layout.time;
+                    let shift =
+                        delta[0] as f64 / (layout.max[0] - layout.min[0]) as f64 * (t1 - t0);
+                    self.time_range = Some((t0 - shift, t1 - shift));
+                }
+            }
+        } else if ui.is_mouse_released(MouseButton::Left)
+            && axis_under_mouse.is_none()
+            && !self.panned
+        {
+            let cursor = if io.key_shift { 1 } else { 0 };
+            self.cursors[cursor] = Some(layout.time_at(mouse[0]));
+        }
+        if ui.is_mouse_clicked(MouseButton::Right) {
+            self.cursors = [None; 2];
+        }
+    }
+
+    fn draw(&self, ui: &Ui, layout: &PlotLayout, origin: [f32; 2]) {
+        let draw_list = ui.get_window_draw_list();
+        draw_list
+            .add_rect(layout.min, layout.max, BACKGROUND_COLOR)
+            .filled(true)
+            .build();
+
+        // 时间轴和网格
+        let (step, times) = ticks(
+            layout.time,
+            ((layout.max[0] - layout.min[0]) / 90.0) as usize,
+        );
+        for t in times {
+            let x = layout.x(t);
+            draw_list
+                .add_line([x, layout.min[1]], [x, layout.max[1]], GRID_COLOR)
+                .build();
+            draw_list.add_text(
+                [x + 2.0, layout.max[1] + 4.0],
+                AXIS_COLOR,
+                format_tick(t, step),
+            );
+        }
+
+        // 纵轴，每个轴用第一条曲线的颜色
+        for (i, (axis, range)) in layout.axes.iter().enumerate() {
+            let color = self
+                .series
+                .iter()
+                .find(|s| s.axis == *axis)
+                .map_or(AXIS_COLOR, |s| s.color);
+            let x = origin[0] + AXIS_WIDTH * (i + 1) as f32 - 4.0;
+            draw_list
+                .add_line([x, layout.min[1]], [x, layout.max[1]], color)
+                .build();
+            let (step, values) = ticks(*range, ((layout.max[1] - layout.min[1]) / 40.0) as usize);
+            for v in values {
+                let y = layout.y(*range, v);
+                draw_list.add_line([x - 4.0, y], [x, y], color).build();
+                if i == 0 {
+                    draw_list
+                        .add_line([layout.min[0], y], [layout.max[0], y], GRID_COLOR)
+                        .build();
+                }
+                let text = format_tick(v, step);
+                let width = ui.calc_text_size(&text)[0];
+                draw_list.add_text([x - 6.0 - width, y - 7.0], color, text);
+            }
+        }
+
+        draw_list.with_clip_rect_intersect(layout.min, layout.max, || {
+            for series in &self.series {
+                self.draw_series(ui, layout, series);
+            }
+            for (cursor, color) in self.cursors.iter().zip(CURSOR_COLORS) {
+                if let Some(time) = cursor {
+                    let x = layout.x(*time);
+                    draw_list
+                        .add_line([x, layout.min[1]], [x, layout.max[1]], color)
+                        .build();
+                }
+            }
+        });
+    }
+
+    fn draw_series(&self, ui: &Ui, layout: &PlotLayout, series: &PlotSeries) {
+        let samples = &series.samples;
+        if samples.is_empty() {
+            return;
+        }
+        let range = layout.axis_range(series.axis);
+        // 只画可见范围内的点，左右各多取一个点让曲线延伸到边界
+        let (t0, t1) = layout.time;
+        let first = samples.partition_point(|s| s.time < t0).saturating_sub(1);
+        let last = (samples.partition_point(|s| s.time <= t1) + 1).min(samples.len());
+        let visible = &samples[first..last];
+
+        let mut points: Vec<[f32; 2]> = Vec::with_capacity(visible.len() * 2);
+        let mut previous_y = None;
+        for sample in decimate(visible, layout) {
+            let point = [layout.x(sample.time), layout.y(range, sample.value)];
+            if series.is_step()
+                && let Some(y) = previous_y
+            {
+                points.push([point[0], y]);
+            }
+            previous_y = Some(point[1]);
+            points.push(point);
+        }
+        if series.is_step()
+            && let Some(&[_, y]) = points.last()
+        {
+            // 最后一个值保持到可见范围的右边界
+            points.push([layout.max[0], y]);
+        }
+        let draw_list = ui.get_window_draw_list();
+        if points.len() == 1 {
+            draw_list
+                .add_circle(points[0], 2.0, series.color)
+                .filled(true)
+                .build();
+        } else {
+            draw_list.add_polyline(points, series.color).build();
+        }
+
+        // 值变化处标出描述文本，与上一个标签重叠时跳过
+        if series.is_step() {
+            let mut next_free_x = f32::MIN;
+            let mut previous_raw = None;
+            for sample in visible {
+                if previous_raw == Some(sample.raw) {
+                    continue;
+                }
+                previous_raw = Some(sample.raw);
+                let Some((_, text)) = series
+                    .value_descriptions
+                    .iter()
+                    .find(|(raw, _)| *raw == sample.raw)
+                else {
+                    continue;
+                };
+                let x = layout.x(sample.time).max(layout.min[0]);
+                if x < next_free_x {
+                    continue;
+                }
+                let y = layout.y(range, sample.value) - 16.0;
+                draw_list.add_text([x + 3.0, y], series.color, text);
+                next_free_x = x + ui.calc_text_size(text)[0] + 8.0;
+            }
+        }
+    }
+
+    fn render_readout(&mut self, ui: &Ui) {
+        let Some(_table) = ui.begin_table_with_flags(
+            "plot_readout",
+            6,
+            TableFlags::BORDERS | TableFlags::ROW_BG | TableFlags::SIZING_STRETCH_PROP,
+        ) else {
+            return;
+        };
+        for (i, name) in ["Signal", "Axis", "Cursor 1", "Cursor 2", "Delta", ""]
+            .into_iter()
+            .enumerate()
+        {
+            ui.table_setup_column_with(TableColumnSetup {
+                name,
+                flags: if i == 5 {
+                    TableColumnFlags::WIDTH_FIXED
+                } else {
+                    TableColumnFlags::WIDTH_STRETCH
+                },
+                init_width_or_weight: 0.0,
+                user_id: ui.new_id(i),
+            });
+        }
+        ui.table_headers_row();
+
+        let mut removed = None;
+        for (i, series) in self.series.iter_mut().enumerate() {
+            let _id = ui.push_id_usize(i);
+            ui.table_next_row();
+            ui.table_set_column_index(0);
+            ui.text_colored(series.color, &series.signal_name);
+
+            ui.table_set_column_index(1);
+            ui.set_next_item_width(-1.0);
+            let preview = format!("Y{}", series.axis + 1);
+            if let Some(_combo) = ui.begin_combo("##axis", &preview) {
+                for axis in 0..MAX_AXES {
+                    if ui
+                        .selectable_config(format!("Y{}", axis + 1))
+                        .selected(series.axis == axis)
+                        .build()
+                    {
+                        series.axis = axis;
+                    }
+                }
+            }
+
+            let values = self
+                .cursors
+                .map(|c| c.and_then(|t| sample_at(&series.samples, t)));
+            for (column, value) in values.iter().enumerate() {
+                ui.table_set_column_index(2 + column);
+                match value {
+                    Some(sample) => ui.text(series.describe(sample)),
+                    None => ui.text_disabled("-"),
+                }
+            }
+            ui.table_set_column_index(4);
+            if let [Some(a), Some(b)] = values {
+                ui.text(format!("{}", b.value - a.value));
+            }
+
+            ui.table_set_column_index(5);
+            if ui.small_button("Remove") {
+                removed = Some(i);
+            }
+        }
+        if let Some(i) = removed {
+            self.series.remove(i);
+        }
+    }
+}
+
+// 范围为空时扩展成宽度为 1 的区间
+fn widen((a, b): (f64, f64)) -> (f64, f64) {
+    if b - a > f64::EPSILON {
+        (a, b)
+    } else {
+        (a - 0.5, b + 0.5)
+    }
+}
+
+// 缩放后的宽度不小于端点绝对值的 1e-9 倍，否则 f64 精度不足以区分刻度
+fn zoom((a, b): (f64, f64), center: f64, factor: f64) -> (f64, f64) {
+    let min_span = 1e-9 * a.abs().max(b.abs()).max(1.0);
+    let factor = factor.max(min_span / (b - a).max(min_span));
+    (
+        center - (center - a) * factor,
+        center + (b - center) * factor,
+    )
+}
+
+/// 1 / 2 / 5 * 10^n 的刻度间隔，使刻度数量不超过 max_ticks
+fn nice_step(span: f64, max_ticks: usize) -> f64 {
+    let raw = span / max_ticks.max(1) as f64;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|m| m * magnitude)
+        .find(|step| *step >= raw)
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// range 内的刻度间隔和刻度值，按整数序号计算，数量最多为 max_ticks + 1
+fn ticks((a, b): (f64, f64), max_ticks: usize) -> (f64, Vec<f64>) {
+    let step = nice_step(b - a, max_ticks);
+    if !step.is_finite() || step <= 0.0 {
+        return (step, Vec::new());
+    }
+    let first = (a / step).ceil();
+    let count = ((b / step).floor() - first + 1.0).clamp(0.0, max_ticks.max(1) as f64 + 1.0);
+    let values = (0..count as usize)
+        .map(|i| (first + i as f64) * step)
+        .collect();
+    (step, values)
+}
+
+fn format_tick(value: f64, step: f64) -> String {
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    format!("{value:.decimals$}")
+}
+
+// 点数远多于像素时每个像素列只保留最小值和最大值
+fn decimate<'a>(samples: &'a [Sample], layout: &PlotLayout) -> Vec<&'a Sample> {
+    let width = (layout.max[0] - layout.min[0]).max(1.0) as usize;
+    if samples.len() <= width * 4 {
+        return samples.iter().collect();
+    }
+    let mut result = Vec::with_capacity(width * 2);
+    let mut column_start = 0;
+    while column_start < samples.len() {
+        let column = layout.x(samples[column_start].time).floor();
+        let column_end = column_start
+            + samples[column_start..]
+                .iter()
+                .position(|s| layout.x(s.time).floor() != column)
+                .unwrap_or(samples.len() - column_start);
+        let column_samples = &samples[column_start..column_end];
+        let min = column_samples
+            .iter()
+            .min_by(|a, b| a.value.total_cmp(&b.value))
+            .unwrap();
+        let max = column_samples
+            .iter()
+            .max_by(|a, b| a.value.total_cmp(&b.value))
+            .unwrap();
+        if min.time <= max.time {
+            result.extend([min, max]);
+        } else {
+            result.extend([max, min]);
+        }
+        column_start = column_end;
+    }
+    result
+}
+
+/// 渲染 Plot 窗口
+pub fn render_plot_window(ui: &Ui, ui_state: &mut UiState) {
+    if !ui_state.plot_window.is_open {
+        return;
+    }
+    ui_state
+        .plot_window
+        .render(ui, &ui_state.trace_windows, &ui_state.dbc_windows);
+}
@@ -0,0 +1,187 @@
+//! Problems 窗口 - 显示当前 DBC 的检查结果，点击一条诊断跳转到对应的 Message
+
+use imgui::{TableColumnFlags, TableColumnSetup, TableFlags, Ui};
+
+use crate::editable_dbc::{Diagnostic, EditableDbc, Severity};
+use crate::ui::state::UiState;
+
+/// 用户点击了某条诊断，需要在 DBC 窗口中定位对应的 Message
+pub struct ProblemFocus {
+    pub message_id: u32,
+    pub signal_name: Option<String>,
+}
+
+/// Problems 窗口状态，全局只有一个，跟随最后聚焦的 DBC 窗口
+pub struct ProblemsWindow {
+    pub is_open: bool,
+    show_errors: bool,
+    show_warnings: bool,
+    // 检查结果缓存，文件路径和数据版本号不变时不重新检查
+    cache_key: Option<(String, u64)>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Default for ProblemsWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            show_errors: true,
+            show_warnings: true,
+            cache_key: None,
+            diagnostics: Vec::new(),
+        }
+    }
+}
+
+impl ProblemsWindow {
+    /// 渲染 Problems 窗口，dbc 为 None 表示没有打开的 DBC 窗口
+    pub fn render(&mut self, ui: &Ui, dbc: Option<(&str, &EditableDbc)>) -> Option<ProblemFocus> {
+        match dbc {
+            Some((file_path, dbc)) => {
+                let key = (file_path.to_string(), dbc.revision());
+                if self.cache_key.as_ref() != Some(&key) {
+                    self.diagnostics = dbc.validate();
+                    self.cache_key = Some(key);
+                }
+            }
+            None => {
+                self.diagnostics.clear();
+                self.cache_key = None;
+            }
+        }
+
+        let mut focus = None;
+        let mut is_open = self.is_open;
+        ui.window("Problems")
+            .size([600.0, 240.0], imgui::Condition::FirstUseEver)
+            .opened(&mut is_open)
+            .build(|| {
+                let Some((_, dbc)) = dbc else {
+                    ui.text_disabled("No DBC file loaded");
+                    return;
+                };
+                self.render_filter(ui);
+                ui.separator();
+                focus = self.render_table(ui, dbc);
+            });
+        self.is_open = is_open;
+        focus
+    }
+
+    fn render_filter(&mut self, ui: &Ui) {
+        let count = |severity| {
+            self.diagnostics
+                .iter()
+                .filter(|d| d.severity() == severity)
+                .count()
+        };
+        let errors = count(Severity::Error);
+        let warnings = count(Severity::Warning);
+        ui.checkbox(format!("Errors ({errors})"), &mut self.show_errors);
+        ui.same_line();
+        ui.checkbox(format!("Warnings ({warnings})"), &mut self.show_warnings);
+    }
+
+    fn render_table(&self, ui: &Ui, dbc: &EditableDbc) -> Option<ProblemFocus> {
+        let visible: Vec<&Diagnostic> = self
+            .diagnostics
+            .iter()
+            .filter(|d| match d.severity() {
+                Severity::Error => self.show_errors,
+                Severity::Warning => self.show_warnings,
+            })
+            .collect();
+        if visible.is_empty() {
+            ui.text_disabled("No problems found");
+            return None;
+        }
+
+        let mut focus = None;
+        let _table = ui.begin_table_with_flags(
+            "problems_table",
+            4,
+            TableFlags::BORDERS
+                | TableFlags::ROW_BG
+                | TableFlags::RESIZABLE
+                | TableFlags::SCROLL_Y
+                | TableFlags::SIZING_FIXED_FIT,
+        )?;
+        ui.table_setup_scroll_freeze(0, 1);
+        ui.table_setup_column("Code");
+        ui.table_setup_column("Message");
+        ui.table_setup_column("Signal");
+        ui.table_setup_column_with(TableColumnSetup {
+            name: "Description",
+            flags: TableColumnFlags::WIDTH_STRETCH,
+            init_width_or_weight: 0.0,
+            user_id: ui.new_id_str("description_col"),
+        });
+        ui.table_headers_row();
+
+        for (i, diagnostic) in visible.into_iter().enumerate() {
+            let _id = ui.push_id_usize(i);
+            ui.table_next_row();
+
+            ui.table_set_column_index(0);
+            let color = match diagnostic.severity() {
+                Severity::Error => [1.0, 0.4, 0.4, 1.0],
+                Severity::Warning => [1.0, 0.8, 0.3, 1.0],
+            };
+            let _color = ui.push_style_color(imgui::StyleColor::Text, color);
+            let clicked = ui
+                .selectable_config(diagnostic.rule.code())
+                .span_all_columns(true)
+                .build();
+            _color.pop();
+            if ui.is_item_hovered() {
+                ui.tooltip_text(diagnostic.severity().as_str());
+            }
+            if clicked && let Some(message_id) = diagnostic.message_id {
+                focus = Some(ProblemFocus {
+                    message_id,
+                    signal_name: diagnostic.signal_name.clone(),
+                });
+            }
+
+            ui.table_set_column_index(1);
+            if let Some(message_id) = diagnostic.message_id {
+                let name = dbc
+                    .get_message(message_id)
+                    .map(|m| m.message_name())
+                    .unwrap_or("?");
+                ui.text(format!("{name} (0x{message_id:03X})"));
+            }
+
+            ui.table_set_column_index(2);
+            if let Some(signal_name) = &diagnostic.signal_name {
+                ui.text(signal_name);
+            }
+
+            ui.table_set_column_index(3);
+            ui.text(&diagnostic.text);
+        }
+        focus
+    }
+}
+
+/// 渲染 Problems 窗口，点击诊断后选中并聚焦对应的 Message
+pub fn render_problems_window(ui: &Ui, ui_state: &mut UiState) {
+    if !ui_state.problems_window.is_open {
+        return;
+    }
+    let window_idx = ui_state
+        .last_focused_dbc_index
+        .filter(|&idx| idx < ui_state.dbc_windows.len())
+        .or_else(|| ui_state.dbc_windows.len().checked_sub(1));
+    let dbc = window_idx
+        .map(|idx| &ui_state.dbc_windows[idx])
+        .map(|w| (w.file_path.as_str(), &w.dbc));
+    let focus = ui_state.problems_window.render(ui, dbc);
+
+    if let Some(focus) = focus
+        && let Some(idx) = window_idx
+    {
+        ui_state.dbc_windows[idx].focus_message(focus.message_id, focus.signal_name.as_deref());
+        ui_state.dbc_window_focus_request = Some(idx);
+    }
+}
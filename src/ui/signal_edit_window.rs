@@ -1,3 +1,7 @@
+use can_dbc::{ByteOrder, ValueType};
+
+use crate::editable_dbc::EditableSignal;
+
 #[allow(dead_code)]
 #[derive(Clone)]
 pub struct SignalEditWindowState {
@@ -7,8 +11,9 @@ pub struct SignalEditWindowState {
     pub signal_name: String,
 }
 
-#[allow(dead_code)]
 /// Signal 编辑对话框状态
+///
+/// Apply 时所有修改在一个事务中提交，撤销一次即可恢复整个对话框的修改
 pub struct SignalEditDialog {
     pub show: bool,
     pub parent_dbc_id: usize,
@@ -27,11 +32,10 @@ pub struct SignalEditDialog {
     pub unit_buffer: String,
     pub comment_buffer: String,
 
-    // 原始值（用于取消）
+    // 打开对话框时的 Signal 名称，用于在 Message 中找到原来的 Signal
     pub original_name: String,
 }
 
-#[allow(dead_code)]
 impl SignalEditDialog {
     pub fn new() -> Self {
         Self {
@@ -53,16 +57,84 @@ impl SignalEditDialog {
         }
     }
 
-    pub fn open(&mut self, parent_dbc_id: usize, message_id: u32) {
+    /// 打开编辑对话框，缓冲区使用 Signal 当前的值填充
+    pub fn open(&mut self, parent_dbc_id: usize, message_id: u32, signal: &EditableSignal) {
         self.show = true;
         self.parent_dbc_id = parent_dbc_id;
         self.message_id = message_id;
-        // other fields should be initialized by caller using actual signal data
+        self.original_name = signal.name().to_string();
+
+        self.name_buffer = signal.name().to_string();
+        self.start_bit_buffer = signal.start_bit().to_string();
+        self.size_buffer = signal.signal_size().to_string();
+        self.byte_order_is_little = *signal.byte_order() == ByteOrder::LittleEndian;
+        self.signed = *signal.value_type() == ValueType::Signed;
+        self.factor_buffer = signal.factor().to_string();
+        self.offset_buffer = signal.offset().to_string();
+        self.min_buffer = signal.min().to_string();
+        self.max_buffer = signal.max().to_string();
+        self.unit_buffer = signal.unit().to_string();
+        self.comment_buffer = signal.comment().to_string();
     }
 
     pub fn close(&mut self) {
         self.show = false;
     }
+
+    /// 检查输入是否有效，信号是否放得下由 Apply 时根据 Message 长度检查
+    pub fn is_valid(&self) -> bool {
+        !self.name_buffer.trim().is_empty()
+            && self.parse_start_bit().is_some()
+            && self.parse_size().is_some()
+            && self.parse_factor().is_some()
+            && parse_f64(&self.offset_buffer).is_some()
+            && parse_f64(&self.min_buffer).is_some()
+            && parse_f64(&self.max_buffer).is_some()
+    }
+
+    pub fn parse_start_bit(&self) -> Option<u64> {
+        self.start_bit_buffer.trim().parse().ok()
+    }
+
+    /// 长度为 1-64 位
+    pub fn parse_size(&self) -> Option<u64> {
+        self.size_buffer
+            .trim()
+            .parse()
+            .ok()
+            .filter(|size| (1..=64).contains(size))
+    }
+
+    /// factor 不能为 0
+    pub fn parse_factor(&self) -> Option<f64> {
+        parse_f64(&self.factor_buffer).filter(|factor| *factor != 0.0)
+    }
+
+    pub fn byte_order(&self) -> ByteOrder {
+        if self.byte_order_is_little {
+            ByteOrder::LittleEndian
+        } else {
+            ByteOrder::BigEndian
+        }
+    }
+
+    pub fn value_type(&self) -> ValueType {
+        if self.signed {
+            ValueType::Signed
+        } else {
+            ValueType::Unsigned
+        }
+    }
+
+    /// 事务的名称，显示在 Edit 菜单中，如 "Undo Modify Signal EngineSpeed"
+    pub fn transaction_label(&self) -> String {
+        format!("Modify Signal {}", self.name_buffer.trim())
+    }
+}
+
+/// 解析浮点数输入，不接受 NaN 和无穷大
+pub fn parse_f64(input: &str) -> Option<f64> {
+    input.trim().parse::<f64>().ok().filter(|v| v.is_finite())
 }
 
 impl Default for SignalEditDialog {
@@ -1,11 +1,24 @@
 //! UI 状态管理模块
 
+use std::path::PathBuf;
+
 use crate::editable_dbc::EditableMessage;
+use crate::ui::compare_window::CompareWindow;
 use crate::ui::dbc_window::DbcWindow;
+use crate::ui::merge_window::MergeWindow;
+use crate::ui::message_edit_window::MessageEditDialog;
+#[cfg(target_os = "linux")]
+use crate::ui::monitor_window::MonitorWindow;
+use crate::ui::plot_window::PlotWindow;
+use crate::ui::problems_window::ProblemsWindow;
 use crate::ui::signal_edit_window::SignalEditDialog;
+use crate::ui::trace_window::TraceWindow;
+#[cfg(target_os = "linux")]
+use crate::ui::transmit_window::TransmitWindow;
 
 #[allow(dead_code)]
 /// Confirmation dialog state for delete operations
+#[derive(Default)]
 pub struct ConfirmDeleteDialog {
     pub show: bool,
     pub parent_dbc_id: usize,
@@ -13,55 +26,84 @@ pub struct ConfirmDeleteDialog {
     pub display_name: String,
 }
 
-impl Default for ConfirmDeleteDialog {
-    fn default() -> Self {
-        Self {
-            show: false,
-            parent_dbc_id: 0,
-            message_id: 0,
-            display_name: String::new(),
-        }
-    }
-}
-
 #[allow(dead_code)]
 /// 错误对话框状态
+#[derive(Default)]
 pub struct ErrorDialog {
     pub show: bool,
     pub message: String,
 }
 
-impl Default for ErrorDialog {
-    fn default() -> Self {
-        Self {
-            show: false,
-            message: String::new(),
-        }
-    }
+/// 格式转换警告对话框状态，KCD 等格式读写时丢弃的内容
+#[derive(Default)]
+pub struct WarningsDialog {
+    pub show: bool,
+    pub title: String,
+    pub warnings: Vec<String>,
+    // 关闭对话框后退出程序，用于退出前保存时产生的警告
+    pub exit_after: bool,
+}
+
+/// ARXML 导入时选择 CAN 集群的对话框状态，只在文件中有多个集群时显示
+#[derive(Default)]
+pub struct ArxmlImportDialog {
+    pub show: bool,
+    pub path: PathBuf,
+    pub clusters: Vec<String>,
+    pub selected: usize,
+}
+
+/// 未保存修改确认对话框的选项
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnsavedChangesChoice {
+    Save,
+    Discard,
+    Cancel,
+}
+
+/// 未保存修改确认对话框状态
+///
+/// 关闭单个窗口时队列中只有一个窗口；退出程序时所有有修改的窗口依次排队询问。
+#[derive(Default)]
+pub struct UnsavedChangesDialog {
+    // 下一帧需要打开弹窗
+    pub show: bool,
+    // 等待确认的 DBC 窗口索引，总是先处理第一个
+    pub pending_windows: Vec<usize>,
+    // 全部确认完成后退出程序，否则关闭对应的窗口
+    pub exit_after: bool,
+    // 选择 Save 时保存产生的警告，全部确认完成后一起显示
+    pub warnings: Vec<String>,
 }
 
 /// 剪贴板状态（用于复制/粘贴）
+#[derive(Default)]
 pub struct ClipboardState {
     pub copied_message: Option<EditableMessage>,
 }
 
-impl Default for ClipboardState {
-    fn default() -> Self {
-        Self {
-            copied_message: None,
-        }
-    }
-}
-
 #[allow(dead_code)]
 /// 主 UI 状态管理
 pub struct UiState {
     pub show_performance_window: bool,
     pub show_about_dialog: bool,
+    pub problems_window: ProblemsWindow,
+    pub compare_window: CompareWindow,
+    pub merge_window: MergeWindow,
     pub dbc_windows: Vec<DbcWindow>,
     pub next_dbc_id: usize,
+    pub trace_windows: Vec<TraceWindow>,
+    pub next_trace_id: usize,
+    pub plot_window: PlotWindow,
+    #[cfg(target_os = "linux")]
+    pub monitor_window: MonitorWindow,
+    #[cfg(target_os = "linux")]
+    pub transmit_window: TransmitWindow,
     pub error_dialog: ErrorDialog,
+    pub warnings_dialog: WarningsDialog,
+    pub arxml_import_dialog: ArxmlImportDialog,
     pub signal_edit_dialog: SignalEditDialog,
+    pub message_edit_dialog: MessageEditDialog,
     pub last_focused_dbc_index: Option<usize>,
     pub dbc_window_focus_request: Option<usize>,
     pub message_window_focus_request: Option<usize>,
@@ -69,6 +111,9 @@ pub struct UiState {
     pub clipboard: ClipboardState,
     // confirmation dialog state for deletes
     pub confirm_delete_dialog: ConfirmDeleteDialog,
+    pub unsaved_changes_dialog: UnsavedChangesDialog,
+    // 所有未保存的修改都已处理，主循环可以退出
+    pub should_exit: bool,
 }
 
 impl Default for UiState {
@@ -76,25 +121,40 @@ impl Default for UiState {
         Self {
             show_performance_window: false,
             show_about_dialog: false,
+            problems_window: ProblemsWindow::default(),
+            compare_window: CompareWindow::default(),
+            merge_window: MergeWindow::default(),
             dbc_windows: Vec::new(),
             next_dbc_id: 1,
+            trace_windows: Vec::new(),
+            next_trace_id: 1,
+            plot_window: PlotWindow::default(),
+            #[cfg(target_os = "linux")]
+            monitor_window: MonitorWindow::default(),
+            #[cfg(target_os = "linux")]
+            transmit_window: TransmitWindow::default(),
             error_dialog: ErrorDialog::default(),
+            warnings_dialog: WarningsDialog::default(),
+            arxml_import_dialog: ArxmlImportDialog::default(),
             signal_edit_dialog: SignalEditDialog::default(),
+            message_edit_dialog: MessageEditDialog::default(),
             last_focused_dbc_index: None,
             dbc_window_focus_request: None,
             message_window_focus_request: None,
             last_focused_message_window: None,
             clipboard: ClipboardState::default(),
             confirm_delete_dialog: ConfirmDeleteDialog::default(),
+            unsaved_changes_dialog: UnsavedChangesDialog::default(),
+            should_exit: false,
         }
     }
 }
 
 #[allow(dead_code)]
 impl UiState {
-    /// 检查指定 message 是否有对应的 Signal 窗口打开
-    ///
-    /// 若存在打开的信号窗口则弹出错误对话框并返回 Err。
+    // 检查指定 message 是否有对应的 Signal 窗口打开
+    //
+    // 若存在打开的信号窗口则弹出错误对话框并返回 Err。
     // pub fn ensure_message_not_in_open_message_windows(
     //     &mut self,
     //     message_id: u32,
@@ -121,6 +181,112 @@ impl UiState {
         self.dbc_windows.get_mut(idx)
     }
 
+    /// 显示错误对话框
+    pub fn show_error(&mut self, message: String) {
+        self.error_dialog.message = message;
+        self.error_dialog.show = true;
+    }
+
+    /// 显示格式转换的警告，没有警告时不显示
+    pub fn show_warnings(&mut self, title: &str, warnings: Vec<String>) {
+        if warnings.is_empty() {
+            return;
+        }
+        self.warnings_dialog = WarningsDialog {
+            show: true,
+            title: title.to_string(),
+            warnings,
+            exit_after: false,
+        };
+    }
+
+    /// 请求关闭 DBC 窗口，有未保存的修改时先询问
+    pub fn request_close_dbc_window(&mut self, window_index: usize) {
+        let Some(window) = self.dbc_windows.get_mut(window_index) else {
+            return;
+        };
+        if window.dbc.is_modified() {
+            self.unsaved_changes_dialog = UnsavedChangesDialog {
+                show: true,
+                pending_windows: vec![window_index],
+                exit_after: false,
+                warnings: Vec::new(),
+            };
+        } else {
+            window.is_open = false;
+        }
+    }
+
+    /// 请求退出程序，依次询问所有有未保存修改的窗口
+    pub fn request_exit(&mut self) {
+        let pending_windows: Vec<usize> = self
+            .dbc_windows
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, w)| w.dbc.is_modified().then_some(idx))
+            .collect();
+        if pending_windows.is_empty() {
+            self.should_exit = true;
+        } else {
+            self.unsaved_changes_dialog = UnsavedChangesDialog {
+                show: true,
+                pending_windows,
+                exit_after: true,
+                warnings: Vec::new(),
+            };
+        }
+    }
+
+    /// 处理未保存修改对话框中当前窗口的选择
+    pub fn resolve_unsaved_changes(&mut self, choice: UnsavedChangesChoice) {
+        let Some(&window_index) = self.unsaved_changes_dialog.pending_windows.first() else {
+            return;
+        };
+
+        match choice {
+            UnsavedChangesChoice::Save => match self.dbc_windows[window_index].save() {
+                Ok(warnings) => {
+                    let window = &self.dbc_windows[window_index];
+                    self.unsaved_changes_dialog.warnings.extend(
+                        warnings
+                            .into_iter()
+                            .map(|w| format!("{}: {w}", window.file_name())),
+                    );
+                }
+                Err(e) => {
+                    // 保存失败时中止整个流程，避免丢失修改
+                    self.unsaved_changes_dialog = UnsavedChangesDialog::default();
+                    self.show_error(e);
+                    return;
+                }
+            },
+            UnsavedChangesChoice::Discard => {}
+            UnsavedChangesChoice::Cancel => {
+                self.unsaved_changes_dialog = UnsavedChangesDialog::default();
+                return;
+            }
+        }
+
+        let dialog = &mut self.unsaved_changes_dialog;
+        dialog.pending_windows.remove(0);
+        if !dialog.pending_windows.is_empty() {
+            dialog.show = true;
+            return;
+        }
+        if !dialog.exit_after {
+            self.dbc_windows[window_index].is_open = false;
+        }
+        let warnings = std::mem::take(&mut dialog.warnings);
+        let exit_after = dialog.exit_after;
+        if warnings.is_empty() {
+            self.should_exit = exit_after;
+        } else {
+            // 有警告时先显示，关闭对话框后再退出
+            self.show_warnings("Saved with warnings", warnings);
+            self.warnings_dialog.exit_after = exit_after;
+        }
+    }
+
     // copy_message removed; use handle_copy_message in menu.rs which already performs copy and logs
 
     /// 检查剪贴板是否有内容
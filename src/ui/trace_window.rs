@@ -0,0 +1,357 @@
+//! Trace 窗口 - 显示记录的 CAN 日志，按选择的 DBC 解码每一帧
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use imgui::{Direction, ListClipper, TableColumnFlags, TableColumnSetup, TableFlags, Ui};
+
+use crate::trace::{DecodedFrame, FrameKind, Trace, load_trace};
+use crate::ui::dbc_window::DbcWindow;
+use crate::ui::state::UiState;
+
+const UNKNOWN_COLOR: [f32; 4] = [1.0, 0.4, 0.4, 1.0];
+const ERROR_FRAME_COLOR: [f32; 4] = [1.0, 0.6, 0.2, 1.0];
+
+/// 表格中的一行：帧本身，或者展开后的一个信号
+#[derive(Clone, Copy)]
+enum TraceRow {
+    Frame(usize),
+    Signal(usize, usize),
+}
+
+/// 一个日志文件对应一个 Trace 窗口
+pub struct TraceWindow {
+    pub is_open: bool,
+    pub id: usize,
+    pub file_path: String,
+    trace: Trace,
+    // 用文件路径记录解码使用的 DBC，窗口索引在关闭窗口后会变化
+    dbc_path: Option<String>,
+    unknown_only: bool,
+    expanded: HashSet<usize>,
+    // 解码结果缓存，DBC 的路径和数据版本号不变时不重新解码
+    cache_key: Option<(String, u64)>,
+    decoded: Vec<Option<DecodedFrame>>,
+    // 不在 DBC 中的 ID 个数，随解码结果一起更新
+    unknown_id_count: usize,
+    // 每次重新解码后加一，Plot 窗口据此更新曲线
+    decode_generation: u64,
+    // 工具栏中点击了 Plot 按钮
+    plot_requested: bool,
+    rows: Vec<TraceRow>,
+    rows_dirty: bool,
+}
+
+impl TraceWindow {
+    /// 读取日志文件，默认使用 dbc_path 对应的 DBC 解码
+    pub fn from_path(id: usize, path: &Path, dbc_path: Option<String>) -> Result<Self, String> {
+        let trace = load_trace(path)?;
+        Ok(Self {
+            is_open: true,
+            id,
+            file_path: path.to_string_lossy().to_string(),
+            trace,
+            dbc_path,
+            unknown_only: false,
+            expanded: HashSet::new(),
+            cache_key: None,
+            decoded: Vec::new(),
+            unknown_id_count: 0,
+            decode_generation: 0,
+            plot_requested: false,
+            rows: Vec::new(),
+            rows_dirty: true,
+        })
+    }
+
+    pub fn file_name(&self) -> &str {
+        Path::new(&self.file_path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&self.file_path)
+    }
+
+    pub fn trace(&self) -> &Trace {
+        &self.trace
+    }
+
+    /// 当前的解码结果，与 trace().frames 一一对应
+    pub fn decoded(&self) -> &[Option<DecodedFrame>] {
+        &self.decoded
+    }
+
+    /// 解码结果的版本号，每次重新解码后加一
+    pub fn decode_generation(&self) -> u64 {
+        self.decode_generation
+    }
+
+    pub fn render(&mut self, ui: &Ui, dbc_windows: &[DbcWindow]) {
+        // 选中的 DBC 被关闭后改用第一个打开的 DBC
+        let find = |path: &Option<String>| {
+            dbc_windows
+                .iter()
+                .find(|w| Some(&w.file_path) == path.as_ref())
+        };
+        if find(&self.dbc_path).is_none() {
+            self.dbc_path = dbc_windows.first().map(|w| w.file_path.clone());
+        }
+        self.update_decoded(find(&self.dbc_path));
+
+        let mut is_open = self.is_open;
+        ui.window(format!("Trace - {}###trace_{}", self.file_name(), self.id))
+            .size([900.0, 500.0], imgui::Condition::FirstUseEver)
+            .opened(&mut is_open)
+            .build(|| {
+                self.render_toolbar(ui, dbc_windows);
+                ui.separator();
+                self.render_table(ui);
+            });
+        self.is_open = is_open;
+    }
+
+    fn update_decoded(&mut self, dbc_window: Option<&DbcWindow>) {
+        let Some(window) = dbc_window else {
+            if self.cache_key.take().is_some() || self.decoded.len() != self.trace.frames.len() {
+                self.decoded = vec![None; self.trace.frames.len()];
+                self.unknown_id_count = self.count_unknown_ids();
+                self.decode_generation += 1;
+                self.rows_dirty = true;
+            }
+            return;
+        };
+        let key = (window.file_path.clone(), window.dbc.revision());
+        if self.cache_key.as_ref() != Some(&key) {
+            self.decoded = self.trace.decode(&window.dbc);
+            self.unknown_id_count = self.count_unknown_ids();
+            self.cache_key = Some(key);
+            self.decode_generation += 1;
+            self.rows_dirty = true;
+        }
+    }
+
+    fn render_toolbar(&mut self, ui: &Ui, dbc_windows: &[DbcWindow]) {
+        let preview = dbc_windows
+            .iter()
+            .find(|w| Some(&w.file_path) == self.dbc_path.as_ref())
+            .map(|w| w.file_name())
+            .unwrap_or("(no DBC)");
+        ui.set_next_item_width(250.0);
+        if let Some(_combo) = ui.begin_combo("DBC", preview) {
+            for window in dbc_windows {
+                let is_selected = Some(&window.file_path) == self.dbc_path.as_ref();
+                if ui
+                    .selectable_config(window.file_name())
+                    .selected(is_selected)
+                    .build()
+                {
+                    self.dbc_path = Some(window.file_path.clone());
+                }
+            }
+        }
+
+        ui.same_line();
+        if ui.checkbox("Unknown IDs only", &mut self.unknown_only) {
+            self.rows_dirty = true;
+        }
+        ui.same_line();
+        if ui.button("Expand All") {
+            self.expanded = (0..self.trace.frames.len()).collect();
+            self.rows_dirty = true;
+        }
+        ui.same_line();
+        if ui.button("Collapse All") {
+            self.expanded.clear();
+            self.rows_dirty = true;
+        }
+        ui.same_line();
+        if ui.button("Plot") {
+            self.plot_requested = true;
+        }
+
+        ui.text(format!(
+            "{} frames, {:.3} s",
+            self.trace.frames.len(),
+            self.trace.frames.last().map_or(0.0, |f| f.timestamp) - self.trace.start_time()
+        ));
+        if self.unknown_id_count > 0 {
+            ui.same_line();
+            ui.text_colored(
+                UNKNOWN_COLOR,
+                format!("{} ID(s) not in the DBC", self.unknown_id_count),
+            );
+        }
+    }
+
+    // 不是错误帧、但在 DBC 中找不到的帧有多少个不同的 ID
+    fn count_unknown_ids(&self) -> usize {
+        self.trace
+            .frames
+            .iter()
+            .zip(&self.decoded)
+            .filter(|(frame, decoded)| frame.kind != FrameKind::Error && decoded.is_none())
+            .map(|(frame, _)| (frame.id, frame.extended))
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    fn rebuild_rows(&mut self) {
+        self.rows.clear();
+        for (i, frame) in self.trace.frames.iter().enumerate() {
+            let decoded = self.decoded.get(i).and_then(|d| d.as_ref());
+            if self.unknown_only && (decoded.is_some() || frame.kind == FrameKind::Error) {
+                continue;
+            }
+            self.rows.push(TraceRow::Frame(i));
+            if let Some(decoded) = decoded
+                && self.expanded.contains(&i)
+            {
+                self.rows
+                    .extend((0..decoded.signals.len()).map(|s| TraceRow::Signal(i, s)));
+            }
+        }
+        self.rows_dirty = false;
+    }
+
+    fn render_table(&mut self, ui: &Ui) {
+        if self.rows_dirty {
+            self.rebuild_rows();
+        }
+        let Some(_table) = ui.begin_table_with_flags(
+            "trace_table",
+            6,
+            TableFlags::BORDERS | TableFlags::RESIZABLE | TableFlags::ROW_BG | TableFlags::SCROLL_Y,
+        ) else {
+            return;
+        };
+        ui.table_setup_scroll_freeze(0, 1);
+        for (i, name) in ["Time", "Channel", "ID", "Name", "DLC", "Data / Value"]
+            .into_iter()
+            .enumerate()
+        {
+            let flags = if i == 3 || i == 5 {
+                TableColumnFlags::WIDTH_STRETCH
+            } else {
+                TableColumnFlags::WIDTH_FIXED
+            };
+            ui.table_setup_column_with(TableColumnSetup {
+                name,
+                flags,
+                init_width_or_weight: 0.0,
+                user_id: ui.new_id(i),
+            });
+        }
+        ui.table_headers_row();
+
+        // 日志可能有几十万帧，只渲染可见的行
+        let mut toggled = None;
+        let clipper = ListClipper::new(self.rows.len() as i32).begin(ui);
+        for row_index in clipper.iter() {
+            let _id = ui.push_id_usize(row_index as usize);
+            ui.table_next_row();
+            match self.rows[row_index as usize] {
+                TraceRow::Frame(i) => {
+                    if self.render_frame_row(ui, i) {
+                        toggled = Some(i);
+                    }
+                }
+                TraceRow::Signal(i, s) => {
+                    let Some(decoded) = &self.decoded[i] else {
+                        continue;
+                    };
+                    let signal = &decoded.signals[s];
+                    ui.table_set_column_index(3);
+                    ui.text(format!("    {}", signal.name));
+                    ui.table_set_column_index(5);
+                    let mut value = format!("{}", signal.value);
+                    if !signal.unit.is_empty() {
+                        value += &format!(" {}", signal.unit);
+                    }
+                    if let Some(description) = &signal.description {
+                        value += &format!(" ({description})");
+                    }
+                    ui.text(value);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text(format!("Raw: {}", signal.raw));
+                    }
+                }
+            }
+        }
+
+        if let Some(i) = toggled {
+            if !self.expanded.remove(&i) {
+                self.expanded.insert(i);
+            }
+            self.rows_dirty = true;
+        }
+    }
+
+    /// 渲染一帧，点击展开按钮时返回 true
+    fn render_frame_row(&self, ui: &Ui, index: usize) -> bool {
+        let frame = &self.trace.frames[index];
+        let decoded = self.decoded[index].as_ref();
+        let mut toggled = false;
+
+        ui.table_set_column_index(0);
+        if decoded.is_some_and(|d| !d.signals.is_empty()) {
+            let direction = if self.expanded.contains(&index) {
+                Direction::Down
+            } else {
+                Direction::Right
+            };
+            toggled = ui.arrow_button("##expand", direction);
+            ui.same_line();
+        }
+        ui.text(format!("{:.6}", frame.timestamp - self.trace.start_time()));
+
+        ui.table_set_column_index(1);
+        ui.text(&frame.channel);
+
+        ui.table_set_column_index(2);
+        let id = if frame.extended {
+            format!("0x{:08X}x", frame.id)
+        } else {
+            format!("0x{:03X}", frame.id)
+        };
+        ui.text(id);
+
+        ui.table_set_column_index(3);
+        match (frame.kind, decoded) {
+            (FrameKind::Error, _) => ui.text_colored(ERROR_FRAME_COLOR, "Error frame"),
+            (_, Some(decoded)) => ui.text(&decoded.message_name),
+            (_, None) => ui.text_colored(UNKNOWN_COLOR, "Unknown"),
+        }
+
+        ui.table_set_column_index(4);
+        let mut dlc = frame.data.len().to_string();
+        if frame.fd {
+            dlc += if frame.bit_rate_switch {
+                " FD BRS"
+            } else {
+                " FD"
+            };
+        }
+        ui.text(dlc);
+
+        ui.table_set_column_index(5);
+        if frame.kind == FrameKind::Remote {
+            ui.text_disabled("Remote request");
+        } else {
+            ui.text(frame.data_hex());
+        }
+        toggled
+    }
+}
+
+/// 渲染所有 Trace 窗口，关闭的窗口被移除
+///
+/// 点击 Plot 按钮时打开 Plot 窗口并绘制该 Trace
+pub fn render_trace_windows(ui: &Ui, ui_state: &mut UiState) {
+    for window in ui_state.trace_windows.iter_mut() {
+        window.render(ui, &ui_state.dbc_windows);
+        if std::mem::take(&mut window.plot_requested) {
+            ui_state.plot_window.is_open = true;
+            ui_state.plot_window.trace_id = Some(window.id);
+        }
+    }
+    ui_state.trace_windows.retain(|w| w.is_open);
+}
@@ -0,0 +1,376 @@
+//! Transmit 窗口 - 按 DBC 中的信号定义编辑物理值，编码后发送到 SocketCAN 接口
+//!
+//! 有值描述的信号用下拉框选择，min < max 的信号用滑块编辑，其他信号直接输入。
+//! 可以发送一次，也可以按 Message 的 GenMsgCycleTime 周期发送，周期发送时修改的值立即生效。
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use can_dbc::MultiplexIndicator;
+use imgui::Ui;
+
+use crate::editable_dbc::{EditableMessage, EditableSignal};
+use crate::socketcan::{CanSocket, PeriodicSender};
+use crate::trace::CanFrame;
+use crate::ui::dbc_window::DbcWindow;
+use crate::ui::state::UiState;
+
+const ERROR_COLOR: [f32; 4] = [1.0, 0.4, 0.4, 1.0];
+const RUNNING_COLOR: [f32; 4] = [0.4, 0.9, 0.4, 1.0];
+// Message 没有 GenMsgCycleTime 时的默认周期
+const DEFAULT_CYCLE_TIME_MS: i32 = 100;
+
+/// Transmit 窗口状态
+pub struct TransmitWindow {
+    pub is_open: bool,
+    interface: String,
+    // 发送一次时使用的套接字，接口名改变后重新打开
+    socket: Option<CanSocket>,
+    // 用文件路径记录 Message 所在的 DBC，窗口索引在关闭窗口后会变化
+    dbc_path: Option<String>,
+    message_id: Option<u32>,
+    // 信号名到物理值
+    values: HashMap<String, f64>,
+    cycle_time_ms: i32,
+    sender: Option<PeriodicSender>,
+    // 最近一次操作的结果
+    status: Option<Result<String, String>>,
+}
+
+impl Default for TransmitWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            interface: "vcan0".to_string(),
+            socket: None,
+            dbc_path: None,
+            message_id: None,
+            values: HashMap::new(),
+            cycle_time_ms: DEFAULT_CYCLE_TIME_MS,
+            sender: None,
+            status: None,
+        }
+    }
+}
+
+/// 信号的初始值：原始值 0 对应的物理值，限制在 [min, max] 内
+fn initial_value(signal: &EditableSignal) -> f64 {
+    let value = signal.offset();
+    if signal.min() < signal.max() {
+        value.clamp(signal.min(), signal.max())
+    } else {
+        value
+    }
+}
+
+impl TransmitWindow {
+    // 切换 Message 时重置信号值和周期，并停止周期发送
+    fn select_message(&mut self, window: &DbcWindow, message: &EditableMessage) {
+        self.message_id = Some(message.message_id());
+        self.values = message
+            .signals()
+            .iter()
+            .map(|s| (s.name().to_string(), initial_value(s)))
+            .collect();
+        self.cycle_time_ms = window
+            .dbc
+            .message_cycle_time(message.message_id())
+            .map_or(DEFAULT_CYCLE_TIME_MS, |t| t.min(i32::MAX as u64) as i32);
+        self.sender = None;
+        self.status = None;
+    }
+
+    /// 当前复用器值下有效的信号
+    fn active_signals<'a>(&self, message: &'a EditableMessage) -> Vec<&'a EditableSignal> {
+        let multiplexor_value = message.multiplexor().and_then(|m| {
+            let value = self.values.get(m.name()).copied().unwrap_or_default();
+            m.physical_to_raw(value).ok()
+        });
+        message
+            .signals()
+            .iter()
+            .filter(|s| match s.multiplexer_indicator() {
+                MultiplexIndicator::MultiplexedSignal(value) => {
+                    multiplexor_value == Some(*value as i64)
+                }
+                _ => true,
+            })
+            .collect()
+    }
+
+    fn encode(&self, message: &EditableMessage) -> Result<CanFrame, String> {
+        let values: Vec<(&str, f64)> = self
+            .active_signals(message)
+            .into_iter()
+            .map(|s| {
+                let value = self.values.get(s.name()).copied().unwrap_or_default();
+                (s.name(), value)
+            })
+            .collect();
+        let data = message.encode(&values)?;
+        Ok(CanFrame::for_message(message, data))
+    }
+
+    fn send_once(&mut self, frame: &CanFrame) -> Result<(), String> {
+        let interface = self.interface.trim();
+        if self.socket.as_ref().map(|s| s.interface()) != Some(interface) {
+            self.socket = None;
+            self.socket = Some(CanSocket::open(interface)?);
+        }
+        self.socket.as_ref().unwrap().send(frame)
+    }
+
+    pub fn render(&mut self, ui: &Ui, dbc_windows: &[DbcWindow], focused_dbc: Option<usize>) {
+        // 选中的 DBC 被关闭后改用最后聚焦的 DBC
+        if !dbc_windows
+            .iter()
+            .any(|w| Some(&w.file_path) == self.dbc_path.as_ref())
+        {
+            let window = focused_dbc
+                .and_then(|idx| dbc_windows.get(idx))
+                .or(dbc_windows.first());
+            self.dbc_path = window.map(|w| w.file_path.clone());
+            self.message_id = None;
+            self.sender = None;
+        }
+        let window = dbc_windows
+            .iter()
+            .find(|w| Some(&w.file_path) == self.dbc_path.as_ref());
+        let message = window
+            .zip(self.message_id)
+            .and_then(|(w, id)| w.dbc.get_message(id));
+        // Message 被删除后停止发送
+        if message.is_none() && self.sender.is_some() {
+            self.sender = None;
+        }
+
+        let mut is_open = self.is_open;
+        ui.window("Transmit")
+            .size([520.0, 520.0], imgui::Condition::FirstUseEver)
+            .opened(&mut is_open)
+            .build(|| {
+                self.render_selection(ui, dbc_windows, window);
+                ui.separator();
+                match message {
+                    Some(message) => {
+                        self.render_signals(ui, message);
+                        ui.separator();
+                        let frame = self.encode(message);
+                        self.render_preview(ui, &frame);
+                        ui.separator();
+                        self.render_send_controls(ui, &frame);
+                    }
+                    None => ui.text_disabled("Select a message to transmit"),
+                }
+            });
+        self.is_open = is_open;
+        // 关闭窗口时停止周期发送
+        if !self.is_open {
+            self.sender = None;
+        }
+    }
+
+    fn render_selection(&mut self, ui: &Ui, dbc_windows: &[DbcWindow], window: Option<&DbcWindow>) {
+        let preview = window.map(|w| w.file_name()).unwrap_or("(no DBC)");
+        ui.set_next_item_width(250.0);
+        if let Some(_combo) = ui.begin_combo("DBC", preview) {
+            for candidate in dbc_windows {
+                let is_selected = Some(&candidate.file_path) == self.dbc_path.as_ref();
+                if ui
+                    .selectable_config(candidate.file_name())
+                    .selected(is_selected)
+                    .build()
+                    && !is_selected
+                {
+                    self.dbc_path = Some(candidate.file_path.clone());
+                    self.message_id = None;
+                    self.sender = None;
+                }
+            }
+        }
+
+        let Some(window) = window else {
+            return;
+        };
+        let preview = self
+            .message_id
+            .and_then(|id| window.dbc.get_message(id))
+            .map_or("(no message)".to_string(), message_label);
+        ui.set_next_item_width(250.0);
+        if let Some(_combo) = ui.begin_combo("Message", preview) {
+            for message in window.dbc.messages() {
+                let is_selected = Some(message.message_id()) == self.message_id;
+                if ui
+                    .selectable_config(message_label(message))
+                    .selected(is_selected)
+                    .build()
+                    && !is_selected
+                {
+                    self.select_message(window, message);
+                }
+            }
+        }
+    }
+
+    fn render_signals(&mut self, ui: &Ui, message: &EditableMessage) {
+        for signal in self.active_signals(message) {
+            let _id = ui.push_id(signal.name());
+            let mut label = signal.name().to_string();
+            if message.multiplexor().map(|m| m.name()) == Some(signal.name()) {
+                label += " (M)";
+            }
+            if !signal.unit().is_empty() {
+                label += &format!(" [{}]", signal.unit());
+            }
+            let value = self
+                .values
+                .entry(signal.name().to_string())
+                .or_insert_with(|| initial_value(signal));
+
+            ui.set_next_item_width(-200.0);
+            if !signal.value_descriptions().is_empty() {
+                // 值描述以原始值给出，选中后换算为物理值
+                let current = signal.physical_to_raw(*value).ok();
+                let preview = signal
+                    .value_descriptions()
+                    .iter()
+                    .find(|(raw, _)| Some(*raw) == current)
+                    .map_or_else(
+                        || format!("{}", value),
+                        |(raw, text)| format!("{raw}: {text}"),
+                    );
+                if let Some(_combo) = ui.begin_combo(&label, preview) {
+                    for (raw, text) in signal.value_descriptions() {
+                        if ui
+                            .selectable_config(format!("{raw}: {text}"))
+                            .selected(Some(*raw) == current)
+                            .build()
+                        {
+                            *value = *raw as f64 * signal.factor() + signal.offset();
+                        }
+                    }
+                }
+            } else if signal.min() < signal.max() {
+                ui.slider_config(&label, signal.min(), signal.max())
+                    .display_format("%.6g")
+                    .build(value);
+            } else {
+                ui.input_scalar(&label, value).build();
+            }
+        }
+    }
+
+    fn render_preview(&self, ui: &Ui, frame: &Result<CanFrame, String>) {
+        match frame {
+            Ok(frame) => {
+                let id = if frame.extended {
+                    format!("0x{:08X}x", frame.id)
+                } else {
+                    format!("0x{:03X}", frame.id)
+                };
+                ui.text(format!("ID {}  DLC {}", id, frame.data.len()));
+                // 每行 8 字节
+                for (row, chunk) in frame.data.chunks(8).enumerate() {
+                    let bytes: Vec<String> = chunk.iter().map(|b| format!("{b:02X}")).collect();
+                    ui.text(format!("{:02}: {}", row * 8, bytes.join(" ")));
+                }
+            }
+            Err(e) => ui.text_colored(ERROR_COLOR, e),
+        }
+    }
+
+    fn render_send_controls(&mut self, ui: &Ui, frame: &Result<CanFrame, String>) {
+        let running = self.sender.is_some();
+        ui.disabled(running, || {
+            ui.set_next_item_width(120.0);
+            ui.input_text("Interface", &mut self.interface).build();
+        });
+
+        // 周期发送时持续更新发送的内容
+        if let (Some(sender), Ok(frame)) = (&self.sender, frame) {
+            if let Err(e) = sender.set_frame(frame.clone()) {
+                self.status = Some(Err(e));
+            }
+            if let Some(e) = sender.error() {
+                self.status = Some(Err(e));
+                self.sender = None;
+            }
+        }
+
+        ui.disabled(frame.is_err(), || {
+            if ui.button("Send Once")
+                && let Ok(frame) = frame
+            {
+                self.status = Some(
+                    self.send_once(frame)
+                        .map(|_| format!("Sent {} bytes", frame.data.len())),
+                );
+            }
+        });
+
+        ui.same_line();
+        ui.disabled(running, || {
+            ui.set_next_item_width(100.0);
+            ui.input_int("Cycle (ms)", &mut self.cycle_time_ms).build();
+        });
+        self.cycle_time_ms = self.cycle_time_ms.max(1);
+
+        ui.same_line();
+        if running {
+            if ui.button("Stop") {
+                self.sender = None;
+                self.status = None;
+            }
+        } else {
+            ui.disabled(frame.is_err(), || {
+                if ui.button("Start Periodic")
+                    && let Ok(frame) = frame
+                {
+                    let period = Duration::from_millis(self.cycle_time_ms as u64);
+                    match PeriodicSender::start(self.interface.trim(), frame.clone(), period) {
+                        Ok(sender) => {
+                            self.sender = Some(sender);
+                            self.status = None;
+                        }
+                        Err(e) => self.status = Some(Err(e)),
+                    }
+                }
+            });
+        }
+
+        if let Some(sender) = &self.sender {
+            ui.text_colored(
+                RUNNING_COLOR,
+                format!(
+                    "Sending on {} every {} ms",
+                    sender.interface(),
+                    self.cycle_time_ms
+                ),
+            );
+        } else {
+            match &self.status {
+                Some(Ok(text)) => ui.text(text),
+                Some(Err(e)) => ui.text_colored(ERROR_COLOR, e),
+                None => {}
+            }
+        }
+    }
+}
+
+fn message_label(message: &EditableMessage) -> String {
+    format!(
+        "{} (0x{:03X})",
+        message.message_name(),
+        message.message_id()
+    )
+}
+
+/// 渲染 Transmit 窗口，默认使用最后聚焦的 DBC
+pub fn render_transmit_window(ui: &Ui, ui_state: &mut UiState) {
+    if !ui_state.transmit_window.is_open {
+        return;
+    }
+    ui_state
+        .transmit_window
+        .render(ui, &ui_state.dbc_windows, ui_state.last_focused_dbc_index);
+}